@@ -0,0 +1,183 @@
+use std::io::Write;
+
+use ansi_term::{Color, Style};
+
+use super::iterator::{AnsiElementIterator, Element};
+use super::line_buffered::LineBufferedWriter;
+use crate::color;
+use crate::config::Config;
+
+/// Return a writer which repaints text whose foreground color has too little contrast with its
+/// background color in everything written to it, line by line (used with --high-contrast). Text
+/// without a foreground or background color is taken to be in the default colors of a typical
+/// terminal.
+pub fn contrast_writer<'a>(
+    writer: &'a mut dyn Write,
+    config: &Config,
+) -> LineBufferedWriter<'a, impl FnMut(&str) -> String> {
+    let contrast = Contrast::from_config(config);
+    LineBufferedWriter::new(writer, move |line| contrast.adjust_line(line))
+}
+
+struct Contrast {
+    min_ratio: f64,
+    true_color: bool,
+    // The colors of text without a foreground or background color.
+    default_foreground: (u8, u8, u8),
+    default_background: (u8, u8, u8),
+}
+
+impl Contrast {
+    fn from_config(config: &Config) -> Self {
+        Self {
+            min_ratio: config.high_contrast_ratio,
+            true_color: config.true_color || config.color_dither,
+            default_foreground: config.high_contrast_default_colors.0,
+            default_background: config.high_contrast_default_colors.1,
+        }
+    }
+
+    // Repaint the text of `line` whose colors have too little contrast in a foreground color
+    // which has enough, restoring the foreground color in effect after it.
+    fn adjust_line(&self, line: &str) -> String {
+        let mut result = String::with_capacity(line.len());
+        // The style in effect, accumulated from the SGR sequences since the last reset.
+        let mut style = Style::default();
+        for element in AnsiElementIterator::new(line) {
+            match element {
+                Element::Sgr(sgr_style, i, j) => {
+                    style = if sgr_style.is_plain() {
+                        sgr_style
+                    } else {
+                        accumulate(style, sgr_style)
+                    };
+                    result.push_str(&line[i..j]);
+                }
+                Element::Text(i, j) => match self.adjusted_foreground(style) {
+                    Some(foreground) => {
+                        let text_style = if style.is_reverse {
+                            Style::new().on(foreground)
+                        } else {
+                            Style::new().fg(foreground)
+                        };
+                        result.push_str(&text_style.prefix().to_string());
+                        result.push_str(&line[i..j]);
+                        result.push_str(&restore(style));
+                    }
+                    None => result.push_str(&line[i..j]),
+                },
+                Element::Csi(i, j) | Element::Esc(i, j) | Element::Osc(i, j) => {
+                    result.push_str(&line[i..j])
+                }
+            }
+        }
+        result
+    }
+
+    // The color to show the text of `style` in instead of its own, if it has too little contrast.
+    fn adjusted_foreground(&self, style: Style) -> Option<Color> {
+        if style.foreground.is_none() && style.background.is_none() {
+            // The terminal's own colors are left alone.
+            return None;
+        }
+        let foreground = style
+            .foreground
+            .map_or(self.default_foreground, color::to_rgb);
+        let background = style
+            .background
+            .map_or(self.default_background, color::to_rgb);
+        // With reverse video, the text is shown in the background color.
+        let (text, back) = if style.is_reverse {
+            (background, foreground)
+        } else {
+            (foreground, background)
+        };
+        color::adjust_for_contrast(text, back, self.min_ratio, self.true_color)
+    }
+}
+
+// Apply the colors and attributes set by an SGR sequence to the style in effect.
+fn accumulate(style: Style, sgr_style: Style) -> Style {
+    Style {
+        foreground: sgr_style.foreground.or(style.foreground),
+        background: sgr_style.background.or(style.background),
+        is_bold: style.is_bold || sgr_style.is_bold,
+        is_dimmed: style.is_dimmed || sgr_style.is_dimmed,
+        is_italic: style.is_italic || sgr_style.is_italic,
+        is_underline: style.is_underline || sgr_style.is_underline,
+        is_blink: style.is_blink || sgr_style.is_blink,
+        is_reverse: style.is_reverse || sgr_style.is_reverse,
+        is_hidden: style.is_hidden || sgr_style.is_hidden,
+        is_strikethrough: style.is_strikethrough || sgr_style.is_strikethrough,
+    }
+}
+
+// The SGR sequence restoring the text color of `style` after adjusted text.
+fn restore(style: Style) -> String {
+    match (style.is_reverse, style.foreground, style.background) {
+        (false, Some(foreground), _) => Style::new().fg(foreground).prefix().to_string(),
+        (true, _, Some(background)) => Style::new().on(background).prefix().to_string(),
+        (false, None, _) => "\x1b[39m".to_string(),
+        (true, _, None) => "\x1b[49m".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::parse_style_sections;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    // The style of the first section of `line` with text `text`.
+    fn style_of(line: &str, text: &str) -> Style {
+        parse_style_sections(line)
+            .into_iter()
+            .find(|(_, section)| *section == text)
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn test_adjust_line() {
+        let contrast = Contrast {
+            min_ratio: 4.5,
+            true_color: true,
+            default_foreground: (0xd0, 0xd0, 0xd0),
+            default_background: (0x00, 0x00, 0x00),
+        };
+        let line = contrast
+            .adjust_line("\x1b[48;2;48;48;48ma\x1b[38;2;80;80;80mb\x1b[0mc\x1b[38;2;0;0;0md");
+        // The default foreground color has enough contrast, and is left alone.
+        assert_eq!(style_of(&line, "a").foreground, None);
+        let foreground = color::to_rgb(style_of(&line, "b").foreground.unwrap());
+        assert!(color::contrast_ratio(foreground, (48, 48, 48)) >= 4.5);
+        assert_eq!(style_of(&line, "c"), Style::default());
+        // Black text on the default background is lightened.
+        let foreground = color::to_rgb(style_of(&line, "d").foreground.unwrap());
+        assert!(color::contrast_ratio(foreground, (0, 0, 0)) >= 4.5);
+    }
+
+    #[test]
+    fn test_high_contrast() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++bcd
+";
+        let args = ["--plus-style=#305030 #204020", "--true-color=always"];
+        let output = DeltaTest::with_args(&args).with_input(input).raw_output;
+        let style = style_of(&output, "bcd");
+        assert_eq!(style.foreground, Some(Color::RGB(0x30, 0x50, 0x30)));
+        assert_eq!(style.background, Some(Color::RGB(0x20, 0x40, 0x20)));
+
+        let args = [&args[..], &["--high-contrast"]].concat();
+        let output = DeltaTest::with_args(&args).with_input(input).raw_output;
+        let style = style_of(&output, "bcd");
+        let foreground = color::to_rgb(style.foreground.unwrap());
+        assert!(color::contrast_ratio(foreground, (0x20, 0x40, 0x20)) >= 4.5);
+    }
+}
@@ -0,0 +1,185 @@
+use std::io::Write;
+
+use ansi_term::Color;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::iterator::{AnsiElementIterator, Element};
+use super::line_buffered::LineBufferedWriter;
+use crate::utils::bat::terminal::ansi256_from_rgb_dithered;
+
+/// Return a writer which downsamples the 24-bit colors in everything written to it to 256-color
+/// codes, line by line. Used with --color-dither, in which case styles keep their 24-bit colors
+/// until they are written.
+pub fn dithering_writer<'a>(
+    writer: &'a mut dyn Write,
+) -> LineBufferedWriter<'a, impl FnMut(&str) -> String> {
+    // The row of the output of the next line, by which the dithering pattern is offset.
+    let mut row = 0;
+    LineBufferedWriter::new(writer, move |line| {
+        let downsampled = downsample_line(line, row);
+        if line.ends_with('\n') {
+            row += 1;
+        }
+        downsampled
+    })
+}
+
+/// Replace the 24-bit colors in `line`, the `row`-th line of output, by 256-color codes. Text
+/// with a 24-bit background color is painted cell by cell, choosing each background by ordered
+/// dithering; all other 24-bit colors are replaced by the nearest palette entry.
+pub fn downsample_line(line: &str, row: usize) -> String {
+    let mut result = String::with_capacity(line.len());
+    // The 24-bit background color in effect, if any, and the downsampled style of the last SGR
+    // sequence containing 24-bit colors.
+    let mut rgb_background = None;
+    let mut style = ansi_term::Style::default();
+    let mut column = 0;
+    for element in AnsiElementIterator::new(line) {
+        match element {
+            Element::Sgr(sgr_style, i, j) => {
+                if matches!(sgr_style.foreground, Some(Color::RGB(_, _, _)))
+                    || matches!(sgr_style.background, Some(Color::RGB(_, _, _)))
+                {
+                    rgb_background = match sgr_style.background {
+                        Some(Color::RGB(r, g, b)) => Some((r, g, b)),
+                        _ => None,
+                    };
+                    style = ansi_term::Style {
+                        foreground: sgr_style.foreground.map(nearest_ansi256_color),
+                        background: sgr_style.background.map(nearest_ansi256_color),
+                        ..sgr_style
+                    };
+                    result.push_str(&style.prefix().to_string());
+                } else {
+                    if sgr_style.background.is_some() || sgr_style.is_plain() {
+                        rgb_background = None;
+                    }
+                    result.push_str(&line[i..j]);
+                }
+            }
+            Element::Text(i, j) => {
+                let text = &line[i..j];
+                if let Some(rgb) = rgb_background {
+                    let mut cell_background = None;
+                    for grapheme in text.graphemes(true) {
+                        let n = ansi256_from_rgb_dithered(rgb, column, row);
+                        if cell_background != Some(n) {
+                            let cell_style = ansi_term::Style {
+                                background: Some(Color::Fixed(n)),
+                                ..style
+                            };
+                            result.push_str(&cell_style.prefix().to_string());
+                            cell_background = Some(n);
+                        }
+                        result.push_str(grapheme);
+                        column += grapheme.width();
+                    }
+                    // Restore the nearest background color, e.g. for a following ANSI sequence
+                    // which fills the rest of the line.
+                    result.push_str(&style.prefix().to_string());
+                } else {
+                    result.push_str(text);
+                    column += text.width();
+                }
+            }
+            Element::Csi(i, j) | Element::Esc(i, j) | Element::Osc(i, j) => {
+                result.push_str(&line[i..j])
+            }
+        }
+    }
+    result
+}
+
+fn nearest_ansi256_color(color: Color) -> Color {
+    match color {
+        Color::RGB(r, g, b) => Color::Fixed(ansi_colours::ansi256_from_rgb((r, g, b))),
+        color => color,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::parse_style_sections;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    fn backgrounds(line: &str) -> Vec<Option<Color>> {
+        parse_style_sections(line)
+            .into_iter()
+            .flat_map(|(style, text)| text.chars().map(move |_| style.background))
+            .collect()
+    }
+
+    fn gradient() -> String {
+        (0..32u8)
+            .map(|i| {
+                ansi_term::Style::new()
+                    .on(Color::RGB(i * 4, 0x40, 0x40))
+                    .paint(" ")
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_downsample_line_dithers_gradient() {
+        let flat = backgrounds(&gradient())
+            .into_iter()
+            .map(|color| color.map(nearest_ansi256_color))
+            .collect::<Vec<_>>();
+        let dithered = backgrounds(&downsample_line(&gradient(), 0));
+        assert_eq!(flat.len(), dithered.len());
+        assert!(dithered
+            .iter()
+            .all(|color| matches!(color, Some(Color::Fixed(_)))));
+        assert_ne!(flat, dithered);
+    }
+
+    #[test]
+    fn test_downsample_line_dithers_uniform_fill() {
+        let line = ansi_term::Style::new()
+            .on(Color::RGB(0x3f, 0x1f, 0x1f))
+            .paint(" ".repeat(16))
+            .to_string();
+        let mut dithered = backgrounds(&downsample_line(&line, 0));
+        dithered.dedup();
+        assert!(dithered.len() > 1);
+        // Rows are offset differently.
+        assert_ne!(downsample_line(&line, 0), downsample_line(&line, 1));
+    }
+
+    #[test]
+    fn test_downsample_line_keeps_other_colors() {
+        let line = "\x1b[31mred\x1b[0m \x1b[38;5;10mgreen\x1b[0m\x1b[0K";
+        assert_eq!(downsample_line(line, 0), line);
+        let line = "\x1b[38;2;255;0;0mred\x1b[0m";
+        assert_eq!(downsample_line(line, 0), "\x1b[38;5;196mred\x1b[0m");
+    }
+
+    #[test]
+    fn test_color_dither_option() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++b
+";
+        let args = [
+            "--true-color=never",
+            "--minus-style",
+            "normal #3f1f1f",
+            "--line-fill-method=spaces",
+            "--width=40",
+        ];
+        let flat = DeltaTest::with_args(&args).with_input(input);
+        let dithered =
+            DeltaTest::with_args(&[&args[..], &["--color-dither"]].concat()).with_input(input);
+        assert!(!dithered.raw_output.contains("\x1b[48;2;"));
+        assert_ne!(flat.raw_output, dithered.raw_output);
+        assert_eq!(flat.output, dithered.output);
+    }
+}
@@ -0,0 +1,118 @@
+use std::io::Write;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::iterator::{AnsiElementIterator, Element};
+use super::line_buffered::LineBufferedWriter;
+
+// The mark shown in place of a space at a column of the grid, and the SGR sequences dimming it.
+const GRID_MARK: &str = "┊";
+const DIM: &str = "\x1b[2m";
+const NORMAL_INTENSITY: &str = "\x1b[22m";
+
+/// Return a writer which marks every `interval`-th column of everything written to it, line by
+/// line, so that misaligned output is easy to see (used with --debug-grid). Spaces at those
+/// columns are replaced by a dim mark; other characters are left as they are.
+pub fn grid_writer(
+    writer: &mut dyn Write,
+    interval: usize,
+) -> LineBufferedWriter<'_, impl FnMut(&str) -> String> {
+    LineBufferedWriter::new(writer, move |line| mark_grid(line, interval))
+}
+
+/// Replace the spaces of `line` whose (1-based) column is a multiple of `interval` by a dim mark.
+pub fn mark_grid(line: &str, interval: usize) -> String {
+    let mut result = String::with_capacity(line.len());
+    // The intensity attributes in effect, which are restored after each mark.
+    let (mut is_bold, mut is_dimmed) = (false, false);
+    let mut column = 0;
+    for element in AnsiElementIterator::new(line) {
+        match element {
+            Element::Sgr(style, i, j) => {
+                if style.is_plain() {
+                    (is_bold, is_dimmed) = (false, false);
+                } else {
+                    is_bold |= style.is_bold;
+                    is_dimmed |= style.is_dimmed;
+                }
+                result.push_str(&line[i..j]);
+            }
+            Element::Text(i, j) => {
+                for grapheme in line[i..j].graphemes(true) {
+                    column += grapheme.width();
+                    if grapheme == " " && column % interval == 0 {
+                        result.push_str(DIM);
+                        result.push_str(GRID_MARK);
+                        result.push_str(NORMAL_INTENSITY);
+                        if is_bold {
+                            result.push_str("\x1b[1m");
+                        }
+                        if is_dimmed {
+                            result.push_str(DIM);
+                        }
+                    } else {
+                        result.push_str(grapheme);
+                    }
+                }
+            }
+            Element::Csi(i, j) | Element::Esc(i, j) | Element::Osc(i, j) => {
+                result.push_str(&line[i..j])
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_mark_grid() {
+        assert_eq!(
+            strip_ansi_codes(&mark_grid("ab      x   y\n", 4)),
+            "ab ┊   ┊x  ┊y\n"
+        );
+        // Intensity is restored after a mark.
+        assert_eq!(
+            mark_grid("\x1b[1ma b\n", 2),
+            "\x1b[1ma\x1b[2m┊\x1b[22m\x1b[1mb\n"
+        );
+    }
+
+    #[test]
+    fn test_debug_grid() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ a
+-b
++c
+";
+        let output = DeltaTest::with_args(&["--side-by-side", "--width=40", "--debug-grid=5"])
+            .with_input(input)
+            .output;
+        let mut n_marks = 0;
+        for line in output.lines() {
+            for (column, c) in line.chars().enumerate() {
+                if c == '┊' {
+                    assert_eq!((column + 1) % 5, 0, "{line}");
+                    n_marks += 1;
+                }
+            }
+        }
+        // The padding of the panes is marked.
+        assert!(n_marks >= 3 * 5);
+
+        let output = DeltaTest::with_args(&["--side-by-side", "--width=40"])
+            .with_input(input)
+            .output;
+        assert!(!output.contains('┊'));
+    }
+}
@@ -0,0 +1,258 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use super::iterator::{AnsiElementIterator, Element};
+use super::svg::{escape, hex};
+use super::ANSI_CSI_CLEAR_TO_EOL;
+use crate::color::to_rgb;
+use crate::config::Config;
+use crate::style::Style;
+
+/// A writer which collects everything written to it and renders it as a HTML fragment (used with
+/// --output=html). Text is given the CSS class of the delta style it was painted with, which is
+/// recognized by its ANSI escape sequences; the classes are defined by --html-stylesheet.
+pub struct HtmlWriter<'a> {
+    writer: &'a mut dyn Write,
+    output: Vec<u8>,
+    classes: Vec<(&'static str, Style)>,
+}
+
+impl<'a> HtmlWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write, config: &Config) -> Self {
+        Self {
+            writer,
+            output: Vec::new(),
+            // Unstyled text is not given a class.
+            classes: style_classes(config)
+                .into_iter()
+                .filter(|(_, style)| {
+                    style.is_syntax_highlighted || !style.ansi_term_style.is_plain()
+                })
+                .collect(),
+        }
+    }
+
+    /// Write the HTML fragment of everything written so far.
+    pub fn finish(self) -> io::Result<()> {
+        let output = String::from_utf8_lossy(&self.output);
+        let mut html = String::from(r#"<pre class="delta">"#);
+        for line in output.lines() {
+            self.write_line(&mut html, line);
+            html.push('\n');
+        }
+        html.push_str("</pre>\n");
+        self.writer.write_all(html.as_bytes())?;
+        self.writer.flush()
+    }
+
+    fn write_line(&self, html: &mut String, line: &str) {
+        // Consecutive text of the same class, e.g. differently syntax highlighted, is joined.
+        let mut spans: Vec<(Option<&str>, String)> = Vec::new();
+        let mut fill = None;
+        let mut style = ansi_term::Style::default();
+        for element in AnsiElementIterator::new(line) {
+            match element {
+                Element::Sgr(sgr_style, _, _) => style = sgr_style,
+                Element::Text(start, end) => {
+                    let class = self.class(style);
+                    match spans.last_mut() {
+                        Some((last_class, text)) if *last_class == class => {
+                            text.push_str(&line[start..end])
+                        }
+                        _ => spans.push((class, line[start..end].to_string())),
+                    }
+                }
+                // The fill is painted with the background color only.
+                Element::Csi(start, end)
+                    if &line[start..end] == ANSI_CSI_CLEAR_TO_EOL && style.background.is_some() =>
+                {
+                    fill = self
+                        .classes
+                        .iter()
+                        .find(|(_, s)| s.ansi_term_style.background == style.background)
+                        .map(|(class, _)| *class);
+                }
+                _ => {}
+            }
+        }
+        // A background color extending to the end of the line extends to the width of the block.
+        if let Some(class) = fill {
+            let _ = write!(html, r#"<span class="delta-fill {class}">"#);
+        }
+        for (class, text) in spans {
+            match class {
+                Some(class) => {
+                    let _ = write!(html, r#"<span class="{class}">{}</span>"#, escape(&text));
+                }
+                None => html.push_str(&escape(&text)),
+            }
+        }
+        if fill.is_some() {
+            html.push_str("</span>");
+        }
+    }
+
+    // The class of the first style the text could have been painted with. The foreground color of
+    // syntax highlighted styles varies, so it is not compared, and not carried to the HTML.
+    fn class(&self, ansi_term_style: ansi_term::Style) -> Option<&'static str> {
+        self.classes
+            .iter()
+            .find(|(_, style)| {
+                // Unstyled text is not taken to be syntax highlighted.
+                if style.is_syntax_highlighted {
+                    (ansi_term_style.foreground.is_some() || !style.ansi_term_style.is_plain())
+                        && ansi_term::Style {
+                            foreground: ansi_term_style.foreground,
+                            ..style.ansi_term_style
+                        } == ansi_term_style
+                } else {
+                    style.ansi_term_style == ansi_term_style
+                }
+            })
+            .map(|(class, _)| *class)
+    }
+}
+
+impl Write for HtmlWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The CSS classes of the delta styles, in the order in which they are recognized: styles which
+/// cannot be told apart in the output are given the class of the first of them.
+pub fn style_classes(config: &Config) -> Vec<(&'static str, Style)> {
+    vec![
+        (
+            "delta-line-numbers-minus",
+            config.line_numbers_style_minusplus.minus,
+        ),
+        (
+            "delta-line-numbers-plus",
+            config.line_numbers_style_minusplus.plus,
+        ),
+        ("delta-line-numbers-zero", config.line_numbers_zero_style),
+        (
+            "delta-line-numbers-left",
+            config.line_numbers_style_leftright.minus,
+        ),
+        (
+            "delta-line-numbers-right",
+            config.line_numbers_style_leftright.plus,
+        ),
+        ("delta-minus-emph", config.minus_emph_style),
+        ("delta-plus-emph", config.plus_emph_style),
+        ("delta-minus", config.minus_style),
+        ("delta-plus", config.plus_style),
+        ("delta-minus-non-emph", config.minus_non_emph_style),
+        ("delta-plus-non-emph", config.plus_non_emph_style),
+        ("delta-whitespace-error", config.whitespace_error_style),
+        ("delta-commit", config.commit_style),
+        ("delta-file", config.file_style),
+        ("delta-hunk-header-file", config.hunk_header_file_style),
+        (
+            "delta-hunk-header-line-number",
+            config.hunk_header_line_number_style,
+        ),
+        ("delta-inline-hint", config.inline_hint_style),
+        ("delta-zero", config.zero_style),
+        ("delta-hunk-header", config.hunk_header_style),
+    ]
+}
+
+/// Return a stylesheet defining the CSS classes of --output=html by the delta styles.
+pub fn stylesheet(config: &Config) -> String {
+    let mut css = String::new();
+    if let Some(settings) = config.syntax_theme.as_ref().map(|theme| &theme.settings) {
+        css.push_str(".delta {");
+        if let Some(color) = settings.foreground {
+            let _ = write!(css, " color: {};", hex((color.r, color.g, color.b)));
+        }
+        if let Some(color) = settings.background {
+            let _ = write!(
+                css,
+                " background-color: {};",
+                hex((color.r, color.g, color.b))
+            );
+        }
+        css.push_str(" }\n");
+    }
+    css.push_str(".delta-fill { display: inline-block; width: 100%; }\n");
+    for (class, style) in style_classes(config) {
+        let style = style.ansi_term_style;
+        let mut declarations = String::new();
+        let (foreground, background) = if style.is_reverse {
+            (style.background, style.foreground)
+        } else {
+            (style.foreground, style.background)
+        };
+        if let Some(color) = foreground {
+            let _ = write!(declarations, " color: {};", hex(to_rgb(color)));
+        }
+        if let Some(color) = background {
+            let _ = write!(declarations, " background-color: {};", hex(to_rgb(color)));
+        }
+        if style.is_bold {
+            declarations.push_str(" font-weight: bold;");
+        }
+        if style.is_dimmed {
+            declarations.push_str(" opacity: 0.5;");
+        }
+        if style.is_italic {
+            declarations.push_str(" font-style: italic;");
+        }
+        if style.is_underline {
+            declarations.push_str(" text-decoration: underline;");
+        }
+        if !declarations.is_empty() {
+            let _ = writeln!(css, ".{class} {{{declarations} }}");
+        }
+    }
+    css
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_output_html() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+ a
+-b < c
++b < d & e
+-f
+";
+        let html = DeltaTest::with_args(&["--output=html"])
+            .with_input(input)
+            .output;
+        assert!(html.starts_with(r#"<pre class="delta">"#));
+        assert!(html.ends_with("</pre>\n"));
+        assert!(!html.contains('\x1b'));
+        assert!(html.contains("\n<span class=\"delta-zero\">a</span>\n"));
+        assert!(html.contains(concat!(
+            r#"<span class="delta-fill delta-minus">"#,
+            r#"<span class="delta-minus">b &lt; </span>"#,
+            r#"<span class="delta-minus-emph">c</span></span>"#,
+        )));
+        assert!(html.contains(concat!(
+            r#"<span class="delta-fill delta-plus">"#,
+            r#"<span class="delta-plus">b &lt; </span>"#,
+            r#"<span class="delta-plus-emph">d &amp; e</span></span>"#,
+        )));
+        assert!(html.contains(concat!(
+            r#"<span class="delta-fill delta-minus">"#,
+            r#"<span class="delta-minus">f</span></span>"#,
+        )));
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::{self, Write};
+
+/// A writer which transforms everything written to it line by line: each line, ending with its
+/// newline, is passed to `transform` once it is complete, and the result is written. The last
+/// line, if it has no newline, is transformed when the writer is flushed. A line which is not
+/// valid UTF-8 is written unchanged.
+pub struct LineBufferedWriter<'a, F: FnMut(&str) -> String> {
+    writer: &'a mut dyn Write,
+    line: Vec<u8>,
+    transform: F,
+}
+
+impl<'a, F: FnMut(&str) -> String> LineBufferedWriter<'a, F> {
+    pub fn new(writer: &'a mut dyn Write, transform: F) -> Self {
+        Self {
+            writer,
+            line: Vec::new(),
+            transform,
+        }
+    }
+
+    fn write_line(&mut self) -> io::Result<()> {
+        if self.line.is_empty() {
+            return Ok(());
+        }
+        match std::str::from_utf8(&self.line) {
+            Ok(line) => self.writer.write_all((self.transform)(line).as_bytes())?,
+            Err(_) => self.writer.write_all(&self.line)?,
+        }
+        self.line.clear();
+        Ok(())
+    }
+}
+
+impl<F: FnMut(&str) -> String> Write for LineBufferedWriter<'_, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for &byte in buf {
+            self.line.push(byte);
+            if byte == b'\n' {
+                self.write_line()?;
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_line()?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_buffered_writer() {
+        let mut output = Vec::new();
+        let mut lines = Vec::new();
+        let mut writer = LineBufferedWriter::new(&mut output, |line: &str| {
+            lines.push(line.to_string());
+            line.to_uppercase()
+        });
+        write!(writer, "a\nb").unwrap();
+        write!(writer, "c\n\nd").unwrap();
+        writer.write_all(b"\xff\n").unwrap();
+        writer.flush().unwrap();
+        drop(writer);
+        assert_eq!(output, b"A\nBC\n\nd\xff\n");
+        assert_eq!(lines, ["a\n", "bc\n", "\n"]);
+    }
+}
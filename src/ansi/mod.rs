@@ -1,5 +1,13 @@
 mod console_tests;
+pub mod contrast;
+pub mod dither;
+pub mod grid;
+pub mod html;
 mod iterator;
+pub mod line_buffered;
+pub mod output_end;
+pub mod plain;
+pub mod svg;
 
 use std::borrow::Cow;
 
@@ -104,6 +112,27 @@ pub fn truncate_str_short(s: &str, display_width: usize) -> Cow<str> {
     truncate_str_impl(s, display_width, "", None)
 }
 
+/// Return `s` with the escape characters of its OSC 8 hyperlinks replaced by visible symbols, so
+/// that a terminal displays the sequences rather than acting on them.
+pub fn escape_osc8_hyperlinks(s: &str) -> Cow<'_, str> {
+    if !s.contains("\x1b]8;") {
+        return Cow::from(s);
+    }
+    AnsiElementIterator::new(s)
+        .map(|element| match element {
+            Element::Osc(i, j) if s[i..j].starts_with("\x1b]8;") => {
+                Cow::from(s[i..j].replace('\x1b', "␛").replace('\x07', "␇"))
+            }
+            Element::Sgr(_, i, j)
+            | Element::Csi(i, j)
+            | Element::Esc(i, j)
+            | Element::Osc(i, j)
+            | Element::Text(i, j) => Cow::from(&s[i..j]),
+        })
+        .join("")
+        .into()
+}
+
 pub fn parse_style_sections(s: &str) -> Vec<(ansi_term::Style, &str)> {
     let mut sections = Vec::new();
     let mut curr_style = Style::default();
@@ -221,8 +250,9 @@ mod tests {
 
     // Note that src/ansi/console_tests.rs contains additional test coverage for this module.
     use super::{
-        ansi_preserving_index, ansi_preserving_slice, measure_text_width, parse_first_style,
-        string_starts_with_ansi_style_sequence, strip_ansi_codes, truncate_str, truncate_str_short,
+        ansi_preserving_index, ansi_preserving_slice, escape_osc8_hyperlinks, measure_text_width,
+        parse_first_style, string_starts_with_ansi_style_sequence, strip_ansi_codes, truncate_str,
+        truncate_str_short,
     };
 
     #[test]
@@ -248,6 +278,21 @@ mod tests {
                    "src/ansi/mod.rs\n");
     }
 
+    #[test]
+    fn test_escape_osc8_hyperlinks() {
+        assert_eq!(
+            escape_osc8_hyperlinks(
+                "\x1b[31ma \x1b]8;;https://example.com\x1b\\link\x1b]8;;\x1b\\ b\x1b[0m"
+            ),
+            "\x1b[31ma ␛]8;;https://example.com␛\\link␛]8;;␛\\ b\x1b[0m"
+        );
+        assert_eq!(
+            escape_osc8_hyperlinks("\x1b]8;;https://example.com\x07link\x1b]8;;\x07"),
+            "␛]8;;https://example.com␇link␛]8;;␇"
+        );
+        assert_eq!(escape_osc8_hyperlinks("\x1b[31mtext"), "\x1b[31mtext");
+    }
+
     #[test]
     fn test_measure_text_width_osc_hyperlink() {
         assert_eq!(measure_text_width("\x1b[38;5;4m\x1b]8;;file:///Users/dan/src/delta/src/ansi/mod.rs\x1b\\src/ansi/mod.rs\x1b]8;;\x1b\\\x1b[0m"),
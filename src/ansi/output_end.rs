@@ -0,0 +1,146 @@
+use std::io::{self, Write};
+
+use crate::config::Config;
+
+/// A writer which controls how the output ends (used by --final-newline and --final-reset). The
+/// newline ending the output so far is held back, so that the end can be written by `finish`.
+pub struct OutputEndWriter<'a> {
+    writer: &'a mut dyn Write,
+    final_newline: Option<bool>,
+    final_reset: bool,
+    is_empty: bool,
+    pending_newline: bool,
+}
+
+impl<'a> OutputEndWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write, config: &Config) -> Self {
+        Self {
+            writer,
+            final_newline: config.final_newline,
+            // Without color, there is no graphic rendition to reset.
+            final_reset: config.final_reset && !config.no_color,
+            is_empty: true,
+            pending_newline: false,
+        }
+    }
+
+    /// Write the end of the output: a reset of the graphic rendition with --final-reset, followed
+    /// by the newline held back, with it removed or added as requested by --final-newline.
+    pub fn finish(self) -> io::Result<()> {
+        if self.final_reset {
+            self.writer.write_all(b"\x1b[0m")?;
+        }
+        let final_newline = match self.final_newline {
+            Some(final_newline) => final_newline && !self.is_empty,
+            None => self.pending_newline,
+        };
+        if final_newline {
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.flush()
+    }
+}
+
+impl Write for OutputEndWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        if self.pending_newline {
+            self.writer.write_all(b"\n")?;
+        }
+        self.is_empty = false;
+        self.pending_newline = buf.ends_with(b"\n");
+        self.writer
+            .write_all(&buf[..buf.len() - usize::from(self.pending_newline)])?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::{make_config_from_args, DeltaTest};
+
+    const DIFF: &str = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++b
+";
+
+    #[test]
+    fn test_final_newline() {
+        let output = DeltaTest::with_args(&[]).with_input(DIFF).raw_output;
+        assert!(output.ends_with("\x1b[0m\n"));
+
+        let output = DeltaTest::with_args(&["--final-newline=false"])
+            .with_input(DIFF)
+            .raw_output;
+        assert!(output.ends_with("\x1b[0m"));
+        assert_eq!(output.lines().count(), DIFF.lines().count() + 2);
+
+        let output = DeltaTest::with_args(&["--final-newline=true"])
+            .with_input(DIFF)
+            .raw_output;
+        assert!(output.ends_with("\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_output_end_writer() {
+        let write = |args: &[&str], parts: &[&str]| {
+            let config = make_config_from_args(args);
+            let mut output = Vec::new();
+            let mut writer = OutputEndWriter::new(&mut output, &config);
+            for part in parts {
+                writer.write_all(part.as_bytes()).unwrap();
+            }
+            writer.finish().unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        assert_eq!(write(&[], &["a\n", "", "b\n\n"]), "a\nb\n\n");
+        assert_eq!(write(&[], &["a\nb"]), "a\nb");
+        // Only the last newline is removed, and one is added to output which lacks one.
+        assert_eq!(
+            write(&["--final-newline=false"], &["a\n", "b\n\n"]),
+            "a\nb\n"
+        );
+        assert_eq!(write(&["--final-newline=true"], &["a\nb"]), "a\nb\n");
+        // Empty output stays empty, but for the reset.
+        assert_eq!(write(&["--final-newline=true"], &[]), "");
+        assert_eq!(write(&["--final-reset"], &[]), "\x1b[0m");
+    }
+
+    #[test]
+    fn test_final_reset() {
+        // Lines which are not part of a diff are written as they are received.
+        let output = DeltaTest::with_args(&[]).with_input("text\n").raw_output;
+        assert_eq!(output, "text\n");
+
+        let output = DeltaTest::with_args(&["--final-reset"])
+            .with_input("text\n")
+            .raw_output;
+        assert_eq!(output, "text\x1b[0m\n");
+
+        let output = DeltaTest::with_args(&["--final-reset", "--final-newline=false"])
+            .with_input("text\n")
+            .raw_output;
+        assert_eq!(output, "text\x1b[0m");
+    }
+
+    #[test]
+    fn test_final_reset_without_color() {
+        let output = DeltaTest::with_args(&["--final-reset"])
+            .set_config(|config| config.no_color = true)
+            .with_input("text\n")
+            .raw_output;
+        assert_eq!(output, "text\n");
+    }
+}
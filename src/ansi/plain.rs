@@ -0,0 +1,123 @@
+use std::io::{self, Write};
+
+/// A writer which removes the SGR escape sequences, which set colors and other graphic
+/// renditions, of everything written to it, together with the erasures of the rest of the line by
+/// which the background color is extended (used when color is disabled by NO_COLOR). Other escape
+/// sequences, such as OSC 8 hyperlinks, are kept. Output is passed on a line at a time, so that an
+/// escape sequence split across writes is still removed.
+pub struct PlainWriter<'a> {
+    writer: &'a mut dyn Write,
+    partial_line: Vec<u8>,
+}
+
+impl<'a> PlainWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write) -> Self {
+        Self {
+            writer,
+            partial_line: Vec::new(),
+        }
+    }
+
+    /// Write the rest of the output, which does not end with a newline.
+    pub fn finish(mut self) -> io::Result<()> {
+        let rest = std::mem::take(&mut self.partial_line);
+        self.write_plain(&rest)?;
+        self.writer.flush()
+    }
+
+    fn write_plain(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.write_all(&strip_sgr_sequences(bytes))
+    }
+}
+
+// Remove the SGR sequences and the erase-in-line sequences, i.e. the CSI sequences ending with 'm'
+// or 'K', of `bytes`.
+fn strip_sgr_sequences(bytes: &[u8]) -> Vec<u8> {
+    let mut plain = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"\x1b[") {
+            let n_params = bytes[i + 2..]
+                .iter()
+                .take_while(|&&byte| byte.is_ascii_digit() || byte == b';' || byte == b':')
+                .count();
+            if matches!(bytes.get(i + 2 + n_params), Some(b'm' | b'K')) {
+                i += n_params + 3;
+                continue;
+            }
+        }
+        plain.push(bytes[i]);
+        i += 1;
+    }
+    plain
+}
+
+impl Write for PlainWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.partial_line.extend_from_slice(buf);
+        if let Some(end) = self.partial_line.iter().rposition(|&byte| byte == b'\n') {
+            let rest = self.partial_line.split_off(end + 1);
+            let lines = std::mem::replace(&mut self.partial_line, rest);
+            self.write_plain(&lines)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_plain_writer() {
+        let mut output = Vec::new();
+        let mut writer = PlainWriter::new(&mut output);
+        for part in ["\x1b[31ma\x1b", "[0m\nb\x1b[1", "m\nc"] {
+            writer.write_all(part.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a\nb\nc");
+    }
+
+    #[test]
+    fn test_strip_sgr_sequences() {
+        let hyperlink = "\x1b]8;;file:///a.txt\x1b\\a.txt\x1b]8;;\x1b\\";
+        assert_eq!(
+            strip_sgr_sequences(format!("\x1b[1;38;5;4m{hyperlink}\x1b[0m").as_bytes()),
+            hyperlink.as_bytes()
+        );
+        // Bytes which are not UTF-8 are kept.
+        assert_eq!(
+            strip_sgr_sequences(b"\x1b[31m\xe9t\xe9\x1b[m"),
+            b"\xe9t\xe9"
+        );
+        assert_eq!(
+            strip_sgr_sequences(b"a\x1b[0K\x1b[2J\x1b["),
+            b"a\x1b[2J\x1b["
+        );
+    }
+
+    #[test]
+    fn test_no_color_output() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++b
+";
+        let output = DeltaTest::with_args(&[])
+            .set_config(|config| config.no_color = true)
+            .with_input(input)
+            .raw_output;
+        assert!(!output.contains('\x1b'));
+        assert!(output.ends_with("\na\nb\n"));
+    }
+}
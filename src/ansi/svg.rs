@@ -0,0 +1,231 @@
+use std::fmt::Write as _;
+use std::io::{self, Write};
+
+use ansi_term::{Color, Style};
+use unicode_width::UnicodeWidthStr;
+
+use super::iterator::{AnsiElementIterator, Element};
+use super::ANSI_CSI_CLEAR_TO_EOL;
+use crate::color::to_rgb;
+use crate::config::Config;
+
+// Dimensions of the image, in pixels: a cell is 0.6em wide for typical monospace fonts.
+const FONT_SIZE: f64 = 14.0;
+const CELL_WIDTH: f64 = FONT_SIZE * 0.6;
+const LINE_HEIGHT: f64 = FONT_SIZE * 1.3;
+const BASELINE_OFFSET: f64 = FONT_SIZE;
+
+/// A writer which collects everything written to it and renders it as an SVG image (used with
+/// --output=svg). Text styles are taken from the ANSI escape sequences of the output, so the
+/// image shows the styles computed by the painter.
+pub struct SvgWriter<'a> {
+    writer: &'a mut dyn Write,
+    output: Vec<u8>,
+    background: (u8, u8, u8),
+    foreground: (u8, u8, u8),
+}
+
+impl<'a> SvgWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write, config: &Config) -> Self {
+        let theme_color = |color: Option<syntect::highlighting::Color>| {
+            color.map(|color| (color.r, color.g, color.b))
+        };
+        let settings = config.syntax_theme.as_ref().map(|theme| &theme.settings);
+        Self {
+            writer,
+            output: Vec::new(),
+            background: theme_color(settings.and_then(|s| s.background))
+                .unwrap_or((0x00, 0x00, 0x00)),
+            foreground: theme_color(settings.and_then(|s| s.foreground))
+                .unwrap_or((0xd0, 0xd0, 0xd0)),
+        }
+    }
+
+    /// Write the SVG image of everything written so far.
+    pub fn finish(self) -> io::Result<()> {
+        let output = String::from_utf8_lossy(&self.output);
+        let rows: Vec<Row> = output.lines().map(Row::parse).collect();
+        let columns = rows.iter().map(|row| row.width).max().unwrap_or(0);
+        let width = columns as f64 * CELL_WIDTH;
+        let height = rows.len() as f64 * LINE_HEIGHT;
+
+        let mut svg = String::new();
+        let _ = writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width:.1}" height="{height:.1}" viewBox="0 0 {width:.1} {height:.1}" font-family="monospace" font-size="{FONT_SIZE}" xml:space="preserve">"#
+        );
+        let _ = writeln!(
+            svg,
+            r#"<rect width="100%" height="100%" fill="{}"/>"#,
+            hex(self.background)
+        );
+        let _ = writeln!(svg, r#"<g fill="{}">"#, hex(self.foreground));
+        for (i, row) in rows.iter().enumerate() {
+            row.write_svg(&mut svg, i, columns, self.background, self.foreground);
+        }
+        svg.push_str("</g>\n</svg>\n");
+        self.writer.write_all(svg.as_bytes())?;
+        self.writer.flush()
+    }
+}
+
+impl Write for SvgWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A line of output: its styled text at their columns, and the background color its remainder
+/// is filled with, if it is cleared to the end of the line.
+struct Row<'a> {
+    spans: Vec<(usize, Style, &'a str)>,
+    fill: Option<(usize, Color)>,
+    width: usize,
+}
+
+impl<'a> Row<'a> {
+    fn parse(line: &'a str) -> Self {
+        let mut row = Row {
+            spans: Vec::new(),
+            fill: None,
+            width: 0,
+        };
+        let mut style = Style::default();
+        for element in AnsiElementIterator::new(line) {
+            match element {
+                Element::Sgr(sgr_style, _, _) => style = sgr_style,
+                Element::Text(start, end) => {
+                    let text = &line[start..end];
+                    row.spans.push((row.width, style, text));
+                    row.width += text.width();
+                }
+                Element::Csi(start, end) if &line[start..end] == ANSI_CSI_CLEAR_TO_EOL => {
+                    row.fill = style.background.map(|color| (row.width, color));
+                }
+                _ => {}
+            }
+        }
+        row
+    }
+
+    fn write_svg(
+        &self,
+        svg: &mut String,
+        index: usize,
+        columns: usize,
+        background: (u8, u8, u8),
+        foreground: (u8, u8, u8),
+    ) {
+        let top = index as f64 * LINE_HEIGHT;
+        let rect = |svg: &mut String, column: usize, width: usize, color: (u8, u8, u8)| {
+            let _ = writeln!(
+                svg,
+                r#"<rect x="{:.1}" y="{top:.1}" width="{:.1}" height="{LINE_HEIGHT:.1}" fill="{}"/>"#,
+                column as f64 * CELL_WIDTH,
+                width as f64 * CELL_WIDTH,
+                hex(color),
+            );
+        };
+        if let Some((column, color)) = self.fill {
+            rect(svg, column, columns.saturating_sub(column), to_rgb(color));
+        }
+        let mut text = String::new();
+        for &(column, style, span) in &self.spans {
+            let (fg, bg) = if style.is_reverse {
+                (
+                    Some(style.background.map_or(background, to_rgb)),
+                    Some(style.foreground.map_or(foreground, to_rgb)),
+                )
+            } else {
+                (style.foreground.map(to_rgb), style.background.map(to_rgb))
+            };
+            if let Some(bg) = bg {
+                rect(svg, column, span.width(), bg);
+            }
+            if span.trim().is_empty() {
+                continue;
+            }
+            let _ = write!(text, r#"<tspan x="{:.1}""#, column as f64 * CELL_WIDTH);
+            if let Some(fg) = fg {
+                let _ = write!(text, r#" fill="{}""#, hex(fg));
+            }
+            if style.is_bold {
+                text.push_str(r#" font-weight="bold""#);
+            }
+            if style.is_dimmed {
+                text.push_str(r#" fill-opacity="0.5""#);
+            }
+            if style.is_italic {
+                text.push_str(r#" font-style="italic""#);
+            }
+            if style.is_underline {
+                text.push_str(r#" text-decoration="underline""#);
+            }
+            let _ = write!(text, ">{}</tspan>", escape(span));
+        }
+        if !text.is_empty() {
+            let _ = writeln!(
+                svg,
+                r#"<text y="{:.1}">{text}</text>"#,
+                top + BASELINE_OFFSET
+            );
+        }
+    }
+}
+
+pub(super) fn hex((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+pub(super) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_output_svg() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ a
+-b < c
++d & e
+";
+        let svg = DeltaTest::with_args(&[
+            "--output=svg",
+            "--width=20",
+            "--syntax-theme=none",
+            "--minus-style=#ff0000 #3f0000",
+            "--plus-style=bold #00ff00 #003f00",
+        ])
+        .with_input(input)
+        .output;
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(!svg.contains('\x1b'));
+        assert!(svg.contains(r##"<tspan x="0.0" fill="#ff0000">b &lt; c</tspan>"##));
+        assert!(
+            svg.contains(r##"<tspan x="0.0" fill="#00ff00" font-weight="bold">d &amp; e</tspan>"##)
+        );
+        // The background of the changed lines extends to the width of the image.
+        assert!(svg.contains(r##"<svg xmlns="http://www.w3.org/2000/svg" width="168.0""##));
+        assert!(svg.contains(
+            r##"<rect x="42.0" y="145.6" width="126.0" height="18.2" fill="#3f0000"/>"##
+        ));
+        assert!(svg.contains(
+            r##"<rect x="42.0" y="163.8" width="126.0" height="18.2" fill="#003f00"/>"##
+        ));
+    }
+}
@@ -4,7 +4,10 @@ use std::path::{Path, PathBuf};
 
 use bat::assets::HighlightingAssets;
 use clap::error::Error;
-use clap::{ArgMatches, ColorChoice, CommandFactory, FromArgMatches, Parser, ValueEnum, ValueHint};
+use clap::{
+    ArgAction, ArgMatches, ColorChoice, CommandFactory, FromArgMatches, Parser, ValueEnum,
+    ValueHint,
+};
 use clap_complete::Shell;
 use console::Term;
 use lazy_static::lazy_static;
@@ -34,6 +37,62 @@ const TERM_FALLBACK_WIDTH: usize = 79;
     max_term_width = usize::MAX,
 )]
 pub struct Opt {
+    #[arg(
+        long = "add-p-mode",
+        default_value = "auto",
+        value_name = "auto|always|never",
+        value_parser = ["auto", "always", "never"],
+    )]
+    /// How to handle the prompts of interactive commands such as `git add -p`.
+    ///
+    /// Prompts such as "(1/2) Stage this hunk [y,n,q,a,d,e,?]? " are written uncolored and
+    /// without a trailing newline, and written out immediately, so that the interactive session
+    /// can continue. With "auto" (the default), a line is taken to be a prompt only when it
+    /// follows a hunk; with "always", also elsewhere in the input. With "never", prompts are
+    /// treated like any other line.
+    pub add_p_mode: String,
+
+    #[arg(long = "align-edits")]
+    /// Align the edits of consecutive changed lines which start at nearly the same column.
+    ///
+    /// In a block of removed or added lines, if the first highlighted edit of each line starts
+    /// within a few columns of the others, spaces are inserted before the edits of the lines so
+    /// that they all start at the same column, making it easier to follow the changing column down
+    /// the block. Only the layout of the output is changed, by the padding; it applies to the
+    /// unified layout only.
+    pub align_edits: bool,
+
+    #[arg(long = "annotate-age")]
+    /// Show the age of unchanged lines in a gutter.
+    ///
+    /// The age of each unchanged line of a hunk is taken from `git blame` of the working tree
+    /// version of the file, so this is most useful with `git diff`. Running git blame is
+    /// expensive: it is done once per file, and only when delta is running in a repository.
+    pub annotate_age: bool,
+
+    #[arg(long = "annotate-lines")]
+    /// Prefix each line of a hunk with a machine-parsable tag.
+    ///
+    /// The tag gives the kind of line and its line number, e.g. `[+][L42]` for an added line which
+    /// is line 42 of the new file, `[-][L41]` for a removed line which was line 41 of the old file,
+    /// and `[ ][L40]` for an unchanged line (numbered as in the new file). This is intended for
+    /// exporting diffs as text, e.g. together with --strip-ansi; it is not supported in
+    /// side-by-side mode.
+    pub annotate_lines: bool,
+
+    #[arg(
+        long = "assume-encoding",
+        value_name = "ENCODING",
+        value_parser = ["auto", "utf-8", "latin-1", "utf-16le", "utf-16be"]
+    )]
+    /// Encoding of input lines which are not valid UTF-8.
+    ///
+    /// Such lines are transcoded to UTF-8 for display, and a notice is shown after the file.
+    /// Can be auto (default), utf-8, latin-1, utf-16le or utf-16be. "auto" decodes UTF-16 if the
+    /// line starts with a byte order mark, and Latin-1 otherwise. "utf-8" does not transcode, but
+    /// replaces invalid byte sequences with the replacement character.
+    pub assume_encoding: Option<String>,
+
     #[arg(long = "blame-code-style", value_name = "STYLE")]
     /// Style string for the code section of a git blame line.
     ///
@@ -42,6 +101,22 @@ pub struct Opt {
     /// setting this option to 'syntax' will syntax-highlight the code with no background color.
     pub blame_code_style: Option<String>,
 
+    #[arg(
+        long = "blame-color-by",
+        default_value = "commit",
+        value_name = "commit|domain",
+        value_parser = ["commit", "domain"],
+    )]
+    /// What determines the color of a git blame line.
+    ///
+    /// With "commit" (the default), consecutive lines of different commits get different colors
+    /// of blame-palette. With "domain", lines are colored by the domain of the author's email
+    /// address, so that lines by authors of the same organization share a color. The color of a
+    /// domain is derived from the domain itself, and is the same in every run of delta. This
+    /// requires `git blame -e` (or blame.showEmail), so that the email addresses are shown; lines
+    /// without one are colored by commit.
+    pub blame_color_by: String,
+
     #[arg(
         long = "blame-format",
         default_value = "{timestamp:<15} {author:<15.14} {commit:<8}",
@@ -59,6 +134,16 @@ pub struct Opt {
     /// needed.
     pub blame_palette: Option<String>,
 
+    #[arg(long = "blame-removed")]
+    /// Show the author of removed lines in a gutter.
+    ///
+    /// The author of each removed line of a hunk is taken from `git blame` of the old version of
+    /// the file: that of the parent of the commit in `git log -p` and `git show` output, that of
+    /// the old side of the revisions compared by `git diff` (e.g. A in `git diff A B`), and that of
+    /// HEAD otherwise. Running git blame is expensive: it is done once per file, and only when
+    /// delta is running in a repository.
+    pub blame_removed: bool,
+
     #[arg(
         long = "blame-separator-format",
         default_value = "│{n:^4}│",
@@ -96,6 +181,97 @@ pub struct Opt {
     /// See: <https://docs.rs/chrono/latest/chrono/format/strftime/index.html>
     pub blame_timestamp_output_format: Option<String>,
 
+    #[arg(long = "buffer-limit-bytes", value_name = "N")]
+    /// Maximum number of bytes to buffer for features which need all of the diff at once.
+    ///
+    /// --toc, --line-numbers-zero-pad, --sort-files, --group-by-category,
+    /// --hunk-header-show-position, --collapse-eol-only and --syntax-highlight-max-file-bytes read
+    /// all of the input, --log-show-files reads each commit, --minimap collects all of the output,
+    /// --stat-group-by-dir collects the lines of a diff stat, and --grep-show-counts the lines of
+    /// each file of grep output, before writing it.
+    /// When more than N bytes would be buffered for one of them, delta writes a notice to standard
+    /// error, and writes what it has buffered and the rest of the diff without that feature. By
+    /// default there is no limit.
+    pub buffer_limit_bytes: Option<usize>,
+
+    #[arg(long = "change-heat")]
+    /// Tint each pair of changed lines by how much of it changed.
+    ///
+    /// The unchanged parts of a removed line and of the added line it is paired with are painted
+    /// with a background between the background of the terminal (or of the syntax theme) and the
+    /// background of --minus-emph-style or --plus-emph-style: a line in which a single character
+    /// changed gets a subtle tint, and a line which changed almost entirely a strong one. Lines
+    /// which are not paired are painted as usual.
+    pub change_heat: bool,
+
+    #[arg(
+        long = "change-symbol-style",
+        default_value = "normal",
+        value_name = "STYLE"
+    )]
+    /// Style string for the change symbols of --change-symbols.
+    ///
+    /// See STYLES section.
+    pub change_symbol_style: String,
+
+    #[arg(long = "change-symbols")]
+    /// Show a column with a symbol for the type of each line of a hunk.
+    ///
+    /// The column is in front of the line numbers, and shows '-' for removed lines, '+' for added
+    /// lines, and a space for unchanged lines, so that the type of a line does not depend on its
+    /// color alone. Applies to the unified layout only.
+    pub change_symbols: bool,
+
+    #[arg(long = "check")]
+    /// Highlight the whitespace errors of added lines as `git diff --check` reports them.
+    ///
+    /// Trailing whitespace, spaces before a tab in the indentation (space-before-tab), and
+    /// indentation by 8 or more spaces where tabs could be used (indent-with-non-tab) are painted
+    /// with whitespace-error-style. Without this option, only trailing whitespace is highlighted,
+    /// and only where it was added.
+    pub check: bool,
+
+    #[arg(long = "collapse-blank-additions")]
+    /// Show each run of consecutive blank added lines as a single line.
+    ///
+    /// The line reads "+ N blank lines", and is styled using --inline-hint-style. Line numbers are
+    /// those of the first blank line, and the following lines are numbered as usual. Applies to
+    /// the unified layout only.
+    pub collapse_blank_additions: bool,
+
+    #[arg(long = "collapse-eol-only")]
+    /// Show a file whose changes are all changes of line endings as a single notice.
+    ///
+    /// When every removed line of a file is added back with only its line ending changed, as
+    /// happens when a file is converted between LF and CRLF line endings, its hunks are replaced
+    /// by a line such as "(line endings changed: LF → CRLF)". The direction of the change is
+    /// given when the input shows the carriage returns, as colored git output does. Since the
+    /// hunks of a file must be seen before it is displayed, all of the input is read before
+    /// anything is written (see --buffer-limit-bytes). Has no effect with --color-only.
+    pub collapse_eol_only: bool,
+
+    #[arg(
+        long = "collapse-identical-paths",
+        default_value = "true",
+        value_name = "true|false",
+        value_parser = ["true", "false"],
+    )]
+    /// Whether to show the path of a file only once when its old and new paths are the same.
+    ///
+    /// By default, the header of a modified file shows its path, and that of a renamed or copied
+    /// file shows 'old ⟶ new'. Set this to "false" to show 'old ⟶ new' for every file with both
+    /// an old and a new path, e.g. to see the path on each side of a diff of two files.
+    pub collapse_identical_paths: String,
+
+    #[arg(long = "color-dither")]
+    /// Use ordered dithering for background colors when emitting 256-color codes.
+    ///
+    /// Without 24-bit color support (see --true-color), delta approximates each color by the
+    /// nearest entry of the 256-color palette. With this option, background colors are instead
+    /// approximated by a pattern of neighboring palette entries, so that gradients and colors
+    /// between palette entries look smoother. Has no effect when 24-bit colors are emitted.
+    pub color_dither: bool,
+
     #[arg(long = "color-only")]
     /// Do not alter the input structurally in any way.
     ///
@@ -107,6 +283,14 @@ pub struct Opt {
     /// Load the config file at PATH instead of ~/.gitconfig.
     pub config: String,
 
+    #[arg(long = "context", value_name = "N")]
+    /// Number of context lines to request when using delta to diff two files.
+    ///
+    /// E.g. `delta --context=10 file_1 file_2` passes -U10 to the underlying `git diff` (or
+    /// `diff`). Arguments given with --diff-args come after it, so a context set there takes
+    /// precedence. This has no effect when delta reads a diff from its standard input.
+    pub context: Option<usize>,
+
     #[arg(
         long = "commit-decoration-style",
         default_value = "",
@@ -118,6 +302,75 @@ pub struct Opt {
     /// 'ul' (underline), 'ol' (overline), or the combination 'ul ol'.
     pub commit_decoration_style: String,
 
+    #[arg(long = "commit-merge-style", value_name = "STYLE")]
+    /// Style string for the "Merge:" line of a merge commit.
+    ///
+    /// This styles the line listing the parents of a merge commit, as shown by `git show` and `git
+    /// log`, however many parents it has. With --hyperlinks, each parent hash is linked as the
+    /// commit hash is. See STYLES section. Defaults to commit-style. The style 'omit' can be used
+    /// to remove the line from the output.
+    pub commit_merge_style: Option<String>,
+
+    #[arg(
+        long = "commit-meta-overflow",
+        default_value = "truncate",
+        value_name = "truncate|wrap",
+        value_parser = ["truncate", "wrap"],
+    )]
+    /// How commit metadata lines wider than --commit-meta-width are displayed.
+    ///
+    /// "truncate" cuts the line off, ending it with '…'. "wrap" continues it on following lines,
+    /// aligned with the value of the field.
+    pub commit_meta_overflow: String,
+
+    #[arg(long = "commit-meta-width", default_value = "0", value_name = "N")]
+    /// Maximum width of the fields of commit metadata, such as the Author: and Date: lines.
+    ///
+    /// Lines which are wider are truncated or wrapped, according to --commit-meta-overflow. Use 0
+    /// (the default) to display them in full.
+    pub commit_meta_width: usize,
+
+    #[arg(
+        long = "commit-subject-truncation",
+        default_value = "none",
+        value_name = "none|right|middle",
+        value_parser = ["none", "right", "middle"],
+    )]
+    /// How to shorten the subject of `git log --oneline` commit lines wider than the output.
+    ///
+    /// "right" cuts the end of the subject off, and "middle" replaces its middle, with '…', so
+    /// that the line fits the width of the output (see --width). The commit hash is always shown
+    /// in full. With "none" (the default), lines are shown in full.
+    pub commit_subject_truncation: String,
+
+    #[arg(long = "commit-ref-branch-style", value_name = "STYLE")]
+    /// Style string for the local branches in the decoration of a commit hash line.
+    ///
+    /// See STYLES section. Defaults to commit-style. The decoration is the list of refs which
+    /// `git log --decorate` shows after the commit hash, e.g. "(HEAD -> main, tag: v1.0,
+    /// origin/main)". A style of 'raw' keeps the colors which git gave the ref.
+    pub commit_ref_branch_style: Option<String>,
+
+    #[arg(long = "commit-ref-head-style", value_name = "STYLE")]
+    /// Style string for HEAD in the decoration of a commit hash line.
+    ///
+    /// See STYLES section and --commit-ref-branch-style. Defaults to commit-style.
+    pub commit_ref_head_style: Option<String>,
+
+    #[arg(long = "commit-ref-remote-style", value_name = "STYLE")]
+    /// Style string for the remote-tracking branches in the decoration of a commit hash line.
+    ///
+    /// See STYLES section and --commit-ref-branch-style. Defaults to commit-style. A ref is
+    /// taken to be remote-tracking if it is given in full, as with `git log --decorate=full`, or
+    /// if it starts with the name of a remote configured in git.
+    pub commit_ref_remote_style: Option<String>,
+
+    #[arg(long = "commit-ref-tag-style", value_name = "STYLE")]
+    /// Style string for the tags in the decoration of a commit hash line.
+    ///
+    /// See STYLES section and --commit-ref-branch-style. Defaults to commit-style.
+    pub commit_ref_tag_style: Option<String>,
+
     #[arg(
         long = "commit-regex",
         default_value = r"^commit ",
@@ -133,12 +386,45 @@ pub struct Opt {
     /// output.
     pub commit_style: String,
 
+    #[arg(long = "compact-renames")]
+    /// List files that were renamed without changes in a compact table.
+    ///
+    /// Consecutive pure renames (100% similarity, no hunks) are shown as rows of `old ⟶ new`
+    /// with the old paths aligned, instead of each getting its own decorated file header.
+    pub compact_renames: bool,
+
+    #[arg(long = "compute-function-context")]
+    /// Find the function context of hunks for which git gives none.
+    ///
+    /// Git shows the function context of a hunk in its header by searching the lines preceding it
+    /// for one matching the `xfuncname` regex of the diff driver assigned to the file by
+    /// .gitattributes, and otherwise for a line starting with a letter, which misses e.g. the
+    /// methods of a Python class. With this option, when git gives no function context, delta
+    /// searches the file with the regex of the file's diff driver, if it has one, and otherwise
+    /// with a built-in regex for its language (Bash, Elixir, Go, JavaScript, Lua, Markdown,
+    /// Python, Ruby, Rust and TypeScript). The new version of the file is read from the
+    /// repository, by the blob id of its "index" line, or else from the working tree if the file
+    /// there is that version; the function context is not searched for otherwise.
+    pub compute_function_context: bool,
+
     #[arg(long = "dark")]
     /// Use default colors appropriate for a dark terminal background.
     ///
     /// For more control, see the style options and --syntax-theme.
     pub dark: bool,
 
+    #[arg(
+        long = "debug-grid",
+        default_value = "0",
+        value_name = "N",
+        hide = true
+    )]
+    /// Mark every Nth column of the output, for debugging alignment.
+    ///
+    /// Spaces at those columns are replaced by a dim mark, so that output which is not aligned
+    /// with the grid, e.g. in test fixtures, is easy to see. A value of 0 disables the grid.
+    pub debug_grid: usize,
+
     #[arg(long = "default-language", value_name = "LANG", default_value = "txt")]
     /// Default language used for syntax highlighting.
     ///
@@ -196,12 +482,45 @@ pub struct Opt {
     /// <https://github.com/so-fancy/diff-so-fancy>
     pub diff_so_fancy: bool,
 
+    #[arg(long = "digest")]
+    /// Write a hash of the content of the diff to standard error.
+    ///
+    /// The hash, 16 hexadecimal digits on a line of their own, is computed from the paths of the
+    /// files, the hunk headers and lines, and the header lines of changes without hunks (renames,
+    /// copies, mode changes, added or deleted empty files and binary files) only. It does not
+    /// depend on how delta or git style the diff, so it can be used to tell whether a diff has
+    /// changed between runs.
+    pub digest: bool,
+
     #[arg(long = "diff-stat-align-width", default_value = "48", value_name = "N")]
     /// Width allocated for file paths in a diff stat section.
     ///
     /// If a relativized file path exceeds this width then the diff stat will be misaligned.
     pub diff_stat_align_width: usize,
 
+    #[arg(
+        long = "emph-extend",
+        default_value = "span",
+        value_name = "span|line",
+        value_parser = ["span", "line"],
+    )]
+    /// How far the emph background of a changed line extends.
+    ///
+    /// "span" paints only the changed spans of a line in minus-emph-style or plus-emph-style.
+    /// "line" paints the rest of the line from its first changed span in the emph style too, with
+    /// the background extending to the end of the line, which makes a small change at the start
+    /// of a long line easier to spot.
+    pub emph_extend: String,
+
+    #[arg(long = "exit-code")]
+    /// Exit with status 1 if the input contained changes, and 0 otherwise.
+    ///
+    /// This is like `git diff --exit-code`: a diff is considered to contain changes if it has at
+    /// least one added or removed line, or reports a rename, a copy, a change of mode, the creation
+    /// or deletion of a file, or a change of a binary file. When delta runs a git (or diff) process
+    /// itself, a non-zero exit status of that process is passed through unaltered.
+    pub exit_code: bool,
+
     #[arg(long = "features", value_name = "FEATURES")]
     /// Names of delta features to activate (space-separated).
     ///
@@ -222,6 +541,16 @@ pub struct Opt {
     /// Used in the default value of navigate-regex.
     pub file_added_label: String,
 
+    #[arg(
+        long = "file-category-style",
+        default_value = "bold",
+        value_name = "STYLE"
+    )]
+    /// Style string for the headers of the sections of files written with --group-by-category.
+    ///
+    /// See STYLES section.
+    pub file_category_style: String,
+
     #[arg(
         long = "file-copied-label",
         default_value = "copied:",
@@ -230,6 +559,13 @@ pub struct Opt {
     /// Text to display before a copied file path.
     pub file_copied_label: String,
 
+    #[arg(long = "file-copied-style", value_name = "STYLE")]
+    /// Style string for the file section of a copied file.
+    ///
+    /// See STYLES section. Defaults to file-style. The decoration is given by
+    /// file-decoration-style.
+    pub file_copied_style: Option<String>,
+
     #[arg(
         long = "file-decoration-style",
         default_value = "blue ul",
@@ -241,6 +577,14 @@ pub struct Opt {
     /// 'ul' (underline), 'ol' (overline), or the combination 'ul ol'.
     pub file_decoration_style: String,
 
+    #[arg(long = "file-footer")]
+    /// Write a summary line after the hunks of each file.
+    ///
+    /// The line gives the path of the file, its numbers of added and removed lines, and its
+    /// number of hunks, e.g. "── src/foo.rs: +12 −3 across 2 hunks ──", and is styled using
+    /// --file-style. Has no effect with --color-only.
+    pub file_footer: bool,
+
     #[arg(
         long = "file-modified-label",
         default_value = "",
@@ -281,6 +625,79 @@ pub struct Opt {
     /// Sed-style command transforming file paths for display.
     pub file_regex_replacement: Option<String>,
 
+    #[arg(
+        long = "final-newline",
+        value_name = "true|false",
+        value_parser = ["true", "false"],
+    )]
+    /// Whether the output ends with a newline.
+    ///
+    /// By default, the output ends as the input does, which is usually with a newline. "true"
+    /// adds a newline to output which lacks one, and "false" removes the newline ending the
+    /// output, so that the end of the output is the same whatever the input, e.g. for tools
+    /// capturing it. Empty output is left empty. Only applies with --output=ansi.
+    pub final_newline: Option<String>,
+
+    #[arg(long = "final-reset")]
+    /// End the output with an escape sequence resetting all colors and attributes.
+    ///
+    /// The sequence (ESC[0m) is written after the last line, before its newline, even if the
+    /// output already resets them, so that tools capturing delta's output can rely on it. It is
+    /// not written when color is disabled by NO_COLOR. Only applies with --output=ansi.
+    pub final_reset: bool,
+
+    #[arg(long = "focus-file", value_name = "PATH")]
+    /// Show only the given file in full, and each other file as a one-line header.
+    ///
+    /// PATH is compared with the old and the new path of each file of a git diff, as shown in its
+    /// `diff --git` line, e.g. 'src/main.rs'. The other files are reduced to their path followed
+    /// by "(collapsed)", so that the files around the one being reviewed can still be seen. Has
+    /// no effect with --color-only.
+    pub focus_file: Option<String>,
+
+    #[arg(long = "focus-line", value_name = "old:N|new:N")]
+    /// Line to paint in the focus style.
+    ///
+    /// The line is given by its line number in the old or new version of the file, e.g.
+    /// 'new:42'. Both its line number gutter and its content are painted using
+    /// --focus-line-style, so that tools showing delta output can highlight a line of interest.
+    /// Not supported in side-by-side mode.
+    pub focus_line: Option<String>,
+
+    #[arg(
+        long = "focus-line-style",
+        default_value = "reverse",
+        value_name = "STYLE"
+    )]
+    /// Style string for the line given by --focus-line.
+    ///
+    /// See STYLES section.
+    pub focus_line_style: String,
+
+    #[arg(long = "force-text")]
+    /// Show the diff of files which git considers binary as text.
+    ///
+    /// When git reports "Binary files ... differ" for a file, e.g. because it misdetects a text
+    /// file as binary, the diff of the two versions of the file is computed again with `git diff
+    /// --text`, and its hunks are shown instead. This only applies when delta is run in the
+    /// repository, and both versions of the file are in it; otherwise the file is shown as a
+    /// binary file as usual.
+    pub force_text: bool,
+
+    #[arg(long = "format-command", value_name = "COMMANDS")]
+    /// Commands formatting the files of a language before two files are diffed.
+    ///
+    /// A semicolon-separated list of entries of the form LANG=COMMAND, for example
+    /// --format-command='rust=rustfmt --emit stdout; python=black -q -'. LANG is the name or a
+    /// file extension of a language, as listed by --list-languages. When two files are given on
+    /// the command line, each file of a language with a command is written to the standard input
+    /// of the command, and the diff is computed between the standard outputs, so that formatting
+    /// noise is not shown; the diff is then computed within delta (see --two-file-backend). So
+    /// that the lines shown still correspond to those of the file, the output is only used if the
+    /// command succeeds and its output has as many lines as the file. The command cannot contain
+    /// ';'.
+    pub format_command: Option<String>,
+
     #[arg(long = "generate-completion")]
     /// Print completion file for the given shell.
     pub generate_completion: Option<Shell>,
@@ -355,6 +772,112 @@ pub struct Opt {
     /// lines: set this option to "keep" to keep the original separator symbols.
     pub grep_separator_symbol: String,
 
+    #[arg(
+        long = "grep-separator-tab-gap",
+        default_value = "",
+        value_name = "STRING"
+    )]
+    /// Text written in place of the tabs read as field separators of grep output.
+    ///
+    /// See --grep-separator-tabs. The text is written between the line number (or file path) and
+    /// the line contents.
+    pub grep_separator_tab_gap: String,
+
+    #[arg(long = "grep-separator-tabs", default_value = "0", value_name = "N")]
+    /// The number of tabs at the start of each line of grep output which separate its fields.
+    ///
+    /// Some grep formats separate the file path, line number, and line contents with tabs, e.g.
+    /// `rg --field-match-separator=$'\t'` (a tab in place of each separator symbol), or `grep -T`
+    /// (a tab after the line number, which is padded with spaces). The first N tabs of each line
+    /// are read as field separators instead of being expanded as tabs of the line contents (see
+    /// --tabs), and are written as --grep-separator-tab-gap. A tab following a separator symbol
+    /// pads the line contents and is removed; any other tab is read as the separator symbol ":" of
+    /// a matching line.
+    pub grep_separator_tabs: usize,
+
+    #[arg(long = "grep-show-counts")]
+    /// Show the number of matches in each file of grep output.
+    ///
+    /// The count is added to the file path header of ripgrep-style output, and written in a
+    /// header line of its own before the lines of each file in classic output. The lines of each
+    /// file are held back until the count is known (see --buffer-limit-bytes).
+    pub grep_show_counts: bool,
+
+    #[arg(
+        long = "grep-strip-input-ansi",
+        default_value = "true",
+        value_name = "true|false",
+        value_parser = ["true", "false"],
+    )]
+    /// Whether to discard the colors of the line contents of pre-colored grep output.
+    ///
+    /// By default, the contents of each line of grep output received with colors (e.g. from `rg
+    /// --color=always`) are stripped of them and styled by delta, using the colors only to find the
+    /// matches. Set this to "false" to write the line contents with the colors they were received
+    /// with instead; the file paths and line numbers are styled by delta in either case.
+    pub grep_strip_input_ansi: String,
+
+    #[arg(long = "group-by-category")]
+    /// Group the files of a diff by the kind of change made to them.
+    ///
+    /// The files of each commit are shown in sections with the headers "Added files", "Deleted
+    /// files", "Modified files" and "Renamed files", in that order. Copied files are shown with
+    /// the added files, and files whose mode only changed with the modified files. Within a
+    /// section, files keep their order, or the order given by --sort-files. The headers are styled
+    /// by --file-category-style. Since the files must be seen before any of them is displayed, all
+    /// of the input is read before anything is written (see --buffer-limit-bytes). Has no effect
+    /// with --color-only.
+    pub group_by_category: bool,
+
+    #[arg(
+        long = "group-changes",
+        default_value = "interleaved",
+        value_name = "interleaved|grouped",
+        value_parser = ["interleaved", "grouped"],
+    )]
+    /// How to order the removed and added lines of a change block.
+    ///
+    /// A change block is a run of removed and added lines between unchanged lines. By default,
+    /// each run of removed lines followed by added lines is shown, and paired for highlighting,
+    /// before the lines after it, keeping the interleaving of the diff. Set this to "grouped" to
+    /// show all removed lines of the block followed by all added lines, as in a classic diff.
+    pub group_changes: String,
+
+    #[arg(long = "guard-nested")]
+    /// Pass the input through unchanged when delta was started by delta.
+    ///
+    /// Nested delta processes are detected by looking for a delta process among the parents of
+    /// this one in the process tree, skipping wrappers such as sudo. Reading the process tree is
+    /// not possible on all platforms, in which case the input is always processed.
+    pub guard_nested: bool,
+
+    #[arg(long = "high-contrast")]
+    /// Adjust text colors that are hard to read against their background.
+    ///
+    /// Every foreground color in the output which has a contrast ratio of less than
+    /// high-contrast-ratio with its background color is lightened or darkened until it has. Text
+    /// without a foreground or background color is assumed to be in the default colors of a
+    /// terminal with a dark or light background, according to --dark or --light.
+    pub high_contrast: bool,
+
+    #[arg(
+        long = "high-contrast-ratio",
+        default_value = "4.5",
+        value_name = "RATIO"
+    )]
+    /// Minimum contrast ratio of text colors with --high-contrast.
+    ///
+    /// The ratio is that defined by WCAG 2, from 1 for colors of the same luminance to 21 for
+    /// black on white. The default is the minimum recommended for normal text.
+    pub high_contrast_ratio: f64,
+
+    #[arg(long = "html-stylesheet")]
+    /// Display a stylesheet for --output=html.
+    ///
+    /// The stylesheet defines the CSS classes of the HTML output by the active delta styles and
+    /// syntax theme.
+    pub html_stylesheet: bool,
+
     #[arg(
         long = "hunk-header-decoration-style",
         default_value = "blue box",
@@ -366,6 +889,19 @@ pub struct Opt {
     /// 'ul' (underline), 'ol' (overline), or the combination 'ul ol'.
     pub hunk_header_decoration_style: String,
 
+    #[arg(
+        long = "hunk-header-empty-range",
+        default_value = "show",
+        value_name = "hide|show",
+        value_parser = ["hide", "show"],
+    )]
+    /// Whether to show the line number of an empty hunk range in the hunk header.
+    ///
+    /// A hunk which only removes lines, e.g. `@@ -1,2 +0,0 @@`, has an empty range in the new
+    /// file, and the hunk header line number is then the line preceding the removed lines, which
+    /// is 0 at the start of the file. Set this to "hide" to omit the line number in that case.
+    pub hunk_header_empty_range: String,
+
     #[arg(
         long = "hunk-header-file-style",
         default_value = "blue",
@@ -388,6 +924,15 @@ pub struct Opt {
     /// 'line-number' special attribute.
     pub hunk_header_line_number_style: String,
 
+    #[arg(long = "hunk-header-show-position")]
+    /// Show the position of each hunk among the hunks of its file in the hunk header.
+    ///
+    /// E.g. "[hunk 2/7]" is written before the file path and line number of the second of seven
+    /// hunks of a file. Since the number of hunks of a file is known only once all of it has been
+    /// read, all of the input is read before anything is written (see --buffer-limit-bytes). Has
+    /// no effect with hunk-header-style raw or omit.
+    pub hunk_header_show_position: bool,
+
     #[arg(
         long = "hunk-header-style",
         default_value = "line-number syntax",
@@ -406,7 +951,23 @@ pub struct Opt {
     /// Used in the default value of navigate-regex.
     pub hunk_label: String,
 
-    #[arg(long = "hyperlinks")]
+    #[arg(long = "hunk-spacing", default_value = "0", value_name = "N")]
+    /// Number of blank lines to write before each hunk of a file but the first.
+    ///
+    /// This separates hunks which follow one another closely, e.g. when hunk headers are omitted
+    /// or subtle. The blank lines are not part of the diff, and do not affect line numbers. Has
+    /// no effect with --color-only.
+    pub hunk_spacing: usize,
+
+    #[arg(
+        long = "hyperlinks",
+        default_value = "false",
+        default_missing_value = "true",
+        num_args = 0..=1,
+        require_equals = true,
+        value_name = "true|false|auto",
+        value_parser = ["true", "false", "auto"],
+    )]
     /// Render commit hashes, file names, and line numbers as hyperlinks.
     ///
     /// Following the hyperlink spec for terminal emulators:
@@ -417,8 +978,10 @@ pub struct Opt {
     /// supported by several common terminal emulators. To make them work, you must use less
     /// version >= 581 with the -R flag (or use -r with older less versions, but this will break
     /// e.g. --navigate). If you use tmux, then you will also need a patched fork of tmux (see
-    /// <https://github.com/dandavison/tmux>).
-    pub hyperlinks: bool,
+    /// <https://github.com/dandavison/tmux>). Set this to "auto" to render hyperlinks only if the
+    /// terminal is known to support them, judging by the TERM_PROGRAM and TERM environment
+    /// variables, so that other terminals do not show stray escape sequences.
+    pub hyperlinks: String,
 
     #[arg(long = "hyperlinks-commit-link-format", value_name = "FMT")]
     /// Format string for commit hyperlinks (requires --hyperlinks).
@@ -440,8 +1003,74 @@ pub struct Opt {
     /// OS should handle. You can specify any scheme, such as "file-line://{path}:{line}" and
     /// register an application to handle it. See
     /// <https://dandavison.github.io/delta/hyperlinks.html> for details.
+    ///
+    /// With "auto", the format is chosen by the editor of the VISUAL or EDITOR environment
+    /// variable: "code" gives "vscode://file/{path}:{line}", "subl" gives
+    /// "subl://open?url=file://{path}&line={line}", and so on for codium, code-insiders, cursor,
+    /// idea, pycharm and zed. Other editors, including vim and nvim which have no URL scheme, give
+    /// the default "file://{path}".
     pub hyperlinks_file_link_format: String,
 
+    #[arg(long = "hyperlinks-hunk-header-range")]
+    /// Render the range of the new file in raw hunk headers as a hyperlink (requires --hyperlinks).
+    ///
+    /// In a hunk header such as `@@ -10,7 +12,8 @@` shown with hunk-header-style raw, or with
+    /// --color-only, the text `+12,8` links to line 12 of the file, according to
+    /// --hyperlinks-file-link-format.
+    pub hyperlinks_hunk_header_range: bool,
+
+    #[arg(long = "ignore-files", action = ArgAction::Append, value_name = "GLOB")]
+    /// Omit the files whose paths match GLOB from the output.
+    ///
+    /// Can be given more than once. The whole of a matching file is omitted: its header and its
+    /// hunks. A file which is renamed is omitted if either of its paths matches. As in .gitignore,
+    /// a GLOB without a slash is matched against the file name, so that for example
+    /// --ignore-files='*.lock' omits lock files in every directory, while a GLOB containing a
+    /// slash is matched against the whole path, e.g. --ignore-files='vendor/**'. A `*` does not
+    /// match a slash. In git config, each of several `ignore-files` entries gives a GLOB.
+    pub ignore_files: Vec<String>,
+
+    #[arg(long = "indent", default_value = "0", value_name = "N")]
+    /// Indent every line of output by N spaces.
+    ///
+    /// This is useful when embedding delta's output in other indented text. The indentation is
+    /// not styled, and it applies to all lines, including decorations and line numbers. It is
+    /// included in the width given by --width, or taken from the terminal.
+    pub indent: usize,
+
+    #[arg(
+        long = "index-mode-change-style",
+        default_value = "bold yellow",
+        value_name = "STYLE"
+    )]
+    /// Style string for a changed file mode in the "index" line.
+    ///
+    /// See --show-index and the STYLES section.
+    pub index_mode_change_style: String,
+
+    #[arg(
+        long = "index-style",
+        default_value = "inline-hint-style",
+        value_name = "STYLE"
+    )]
+    /// Style string for the "index" line.
+    ///
+    /// See --show-index and the STYLES section.
+    pub index_style: String,
+
+    #[arg(
+        long = "inline-highlight-budget",
+        default_value = "0",
+        value_name = "N"
+    )]
+    /// Maximum number of characters of a line to highlight as changed.
+    ///
+    /// Once N characters of a changed line have been painted in minus-emph-style or
+    /// plus-emph-style, the rest of the changes in the line are painted like its unchanged parts
+    /// (minus-non-emph-style or plus-non-emph-style), which bounds the highlighting of very long
+    /// lines. To highlight all changes, set to zero.
+    pub inline_highlight_budget: usize,
+
     #[arg(
         long = "inline-hint-style",
         default_value = "blue",
@@ -453,6 +1082,35 @@ pub struct Opt {
     /// to highlight tabs, and the symbols used to indicate wrapped lines. See STYLES section.
     pub inline_hint_style: String,
 
+    #[arg(long = "inline-only")]
+    /// Show each pair of changed lines as a single line with the edits marked inline.
+    ///
+    /// When a removed line and an added line are inferred to be homologous, delta shows one line
+    /// in which the deleted sections are struck through and painted in minus-emph-style, and the
+    /// inserted sections are painted in plus-emph-style. Removed and added lines without a
+    /// homolog are shown as usual. Not supported in side-by-side mode.
+    pub inline_only: bool,
+
+    #[arg(long = "inline-single-word")]
+    /// Show a pair of changed lines differing in a single word as one line.
+    ///
+    /// When each pair of homologous removed and added lines of a change block differs only in
+    /// one word being replaced by another, each pair is shown as a single line, in which the word
+    /// is shown as 'old→new': the removed word painted in minus-emph-style, the arrow in
+    /// inline-hint-style and the added word in plus-emph-style. The rest of the line is painted
+    /// as context. Other change blocks are shown as usual. Not supported in side-by-side mode.
+    pub inline_single_word: bool,
+
+    #[arg(
+        long = "insertion-point-style",
+        default_value = "inline-hint-style",
+        value_name = "STYLE"
+    )]
+    /// Style string for the insertion point marker.
+    ///
+    /// See --show-insertion-point and the STYLES section.
+    pub insertion_point_style: String,
+
     #[arg(
         long = "inspect-raw-lines",
         default_value = "true",
@@ -466,6 +1124,18 @@ pub struct Opt {
     /// Git's --color-moved feature. Set this to "false" to disable this behavior.
     pub inspect_raw_lines: String,
 
+    #[arg(long = "issue-link-format", action = ArgAction::Append, value_name = "[REGEX => ]FMT")]
+    /// Render references to issues in commit messages as hyperlinks (requires --hyperlinks).
+    ///
+    /// Each match of REGEX in the message of a commit, e.g. "#123" or "JIRA-456", links to the URL
+    /// given by FMT, in which the placeholder "{id}" is replaced by the first capture group of
+    /// REGEX if it has one, and by the whole match otherwise. REGEX defaults to `#(\d+)`, so that
+    /// --issue-link-format='https://github.com/dandavison/delta/issues/{id}' links GitHub issues.
+    /// Can be given more than once, e.g. with --issue-link-format='[A-Z]+-\d+ =>
+    /// https://example.atlassian.net/browse/{id}' as well. In git config, each of several
+    /// `issue-link-format` entries gives a format.
+    pub issue_link_format: Vec<String>,
+
     #[arg(long = "keep-plus-minus-markers")]
     /// Prefix added/removed lines with a +/- character, as git does.
     ///
@@ -500,7 +1170,9 @@ pub struct Opt {
     #[arg(short = 'n', long = "line-numbers")]
     /// Display line numbers next to the diff.
     ///
-    /// See LINE NUMBERS section.
+    /// See LINE NUMBERS section. On an output so narrow that the line numbers would leave no width
+    /// for the content of lines, they are not displayed; if even that leaves none, the file,
+    /// commit and hunk header decorations are dropped too.
     pub line_numbers: bool,
 
     #[arg(
@@ -525,6 +1197,13 @@ pub struct Opt {
     /// See STYLES and LINE NUMBERS sections.
     pub line_numbers_left_style: String,
 
+    #[arg(long = "line-numbers-min-width", default_value = "0", value_name = "N")]
+    /// Minimum width of line number fields.
+    ///
+    /// Line number fields are N characters wide, or as wide as the largest line number of the hunk
+    /// if that is wider. See the LINE NUMBERS section.
+    pub line_numbers_min_width: usize,
+
     #[arg(
         long = "line-numbers-minus-style",
         default_value = "auto",
@@ -567,6 +1246,14 @@ pub struct Opt {
     /// See STYLES and LINE NUMBERS sections.
     pub line_numbers_right_style: String,
 
+    #[arg(long = "line-numbers-zero-pad")]
+    /// Pad line numbers with leading zeros.
+    ///
+    /// Line numbers are zero-padded to the width of the largest line number in their file, or to
+    /// --line-numbers-min-width if that is wider. Finding the largest line number requires
+    /// reading all of the input before the output is shown.
+    pub line_numbers_zero_pad: bool,
+
     #[arg(
         long = "line-numbers-zero-style",
         default_value = "auto",
@@ -577,6 +1264,14 @@ pub struct Opt {
     /// See STYLES and LINE NUMBERS sections.
     pub line_numbers_zero_style: String,
 
+    #[arg(long = "line-prefixes", value_name = "PLUS,MINUS,ZERO")]
+    /// Prefixes for added, removed, and unchanged lines, separated by commas.
+    ///
+    /// E.g. --line-prefixes='+ ,- ,  '. The prefixes are written in front of hunk lines instead of
+    /// the markers of --keep-plus-minus-markers, so that changes can be told apart without colors,
+    /// e.g. in diffs exported as text with --strip-ansi. Applies to the unified layout only.
+    pub line_prefixes: Option<String>,
+
     #[arg(long = "list-languages")]
     /// List supported languages and associated file extensions.
     pub list_languages: bool,
@@ -585,18 +1280,44 @@ pub struct Opt {
     /// List available syntax-highlighting color themes.
     pub list_syntax_themes: bool,
 
+    #[arg(long = "literal-content-escapes")]
+    /// Display the OSC 8 hyperlinks found in the input literally.
+    ///
+    /// A file may itself contain the escape sequences of terminal hyperlinks. By default they are
+    /// removed from the lines of the diff, and passed through to the terminal in the lines which
+    /// are written as they are, such as those of commit messages with --preserve-commit-ansi. With
+    /// this option, their escape characters are shown as ␛ (and a BEL ending one as ␇), so that the
+    /// sequences are displayed as text. The hyperlinks written by delta itself (see --hyperlinks)
+    /// are not affected.
+    pub literal_content_escapes: bool,
+
+    #[arg(long = "log-show-files")]
+    /// List the files changed by each commit between its message and its diff.
+    ///
+    /// Applies to the output of commands such as `git log -p` and `git show`. Since the list of a
+    /// commit is taken from its diff, each commit is read up to the next one before it is
+    /// displayed.
+    pub log_show_files: bool,
+
     #[arg(long = "map-styles", value_name = "STYLES_MAP")]
     /// Map styles encountered in raw input to desired output styles.
     ///
     /// An example is --map-styles='bold purple => red "#eeeeee", bold cyan => syntax "#eeeeee"'
     pub map_styles: Option<String>,
 
+    #[arg(long = "max-files", default_value = "0", value_name = "N")]
+    /// Stop displaying the diff after this many files.
+    ///
+    /// The remaining files are summarized by a line giving their number. To display all files,
+    /// set to zero.
+    pub max_files: usize,
+
     #[arg(long = "max-line-distance", default_value = "0.6", value_name = "DIST")]
     /// Maximum line pair distance parameter in within-line diff algorithm.
     ///
     /// This parameter is the maximum distance (0.0 - 1.0) between two lines for them to be inferred
     /// to be homologous. Homologous line pairs are highlighted according to the deletion and
-    /// insertion operations transforming one into the other.
+    /// insertion operations transforming one into the other. See also --min-line-similarity.
     pub max_line_distance: f64,
 
     #[arg(
@@ -682,6 +1403,29 @@ pub struct Opt {
     /// STYLES section.
     pub merge_conflict_theirs_diff_header_style: String,
 
+    #[arg(
+        long = "min-line-similarity",
+        value_name = "SIMILARITY",
+        value_parser = parse_fraction
+    )]
+    /// Minimum similarity (0.0 - 1.0) of two lines for them to be inferred to be homologous.
+    ///
+    /// A removed line and an added line which are less similar than this are treated as
+    /// unrelated: they are shown as a whole removed line and a whole added line, without
+    /// highlighting the edits between them. 0.0 pairs all lines, and 1.0 only identical lines.
+    /// When given, this overrides --max-line-distance, of which it is the complement: a
+    /// similarity of 0.4 is a distance of 0.6.
+    pub min_line_similarity: Option<f64>,
+
+    #[arg(long = "minimap")]
+    /// Show an overview of the whole diff in a narrow column on the right.
+    ///
+    /// Each cell of the column summarizes several lines of output, and is colored by the kind of
+    /// line that predominates among them: removed, added, or other. The column is as tall as the
+    /// terminal, so that it can be read like a scrollbar. The output is buffered until the input
+    /// is exhausted, and two columns are taken from the width of the diff.
+    pub minimap: bool,
+
     #[arg(
         long = "minus-empty-line-marker-style",
         default_value = "normal auto",
@@ -729,24 +1473,115 @@ pub struct Opt {
     /// --file-added-label, --file-copied-label, --file-modified-label, --file-removed-label, --file-renamed-label.
     pub navigate: bool,
 
+    #[arg(long = "navigate-prompt-format", value_name = "FMT")]
+    /// Format string for the prompt shown at the bottom of less with --navigate.
+    ///
+    /// The placeholders {current} and {total} are replaced by the position of the current file
+    /// among the files less is showing, and their number; {line} by the number of the line at the
+    /// top of the screen, {lines} by the number of lines, and {percent} by the position at the top
+    /// of the screen as a percentage of the output. Delta's output is a single file for less, and
+    /// the number of lines and percentage are only known once it has been read to the end. Other
+    /// text is shown as it is. By default, the prompt of less is not changed.
+    pub navigate_prompt_format: Option<String>,
+
     #[arg(long = "navigate-regex", value_name = "REGEX")]
     /// Regular expression defining navigation stop points.
     pub navigate_regex: Option<String>,
 
+    #[arg(long = "new-fd", value_name = "FD")]
+    /// File descriptor to read the new version of a file from (see --old-fd).
+    pub new_fd: Option<u32>,
+
     #[arg(long = "no-gitconfig")]
     /// Do not read any settings from git config.
     ///
     /// See GIT CONFIG section.
     pub no_gitconfig: bool,
 
+    #[arg(long = "null-input")]
+    /// Read the input as NUL-delimited records, as written by git commands given -z.
+    ///
+    /// Each record of `git diff --name-status -z` or `git diff --raw -z` output, a status
+    /// followed by its paths, is handled as a line in which they are separated by tabs, as without
+    /// -z, so that paths containing newlines are kept whole. Any other field is handled as a line
+    /// by itself. Without this option, input is read this way if it starts with the NUL-terminated
+    /// status of such a record.
+    pub null_input: bool,
+
+    #[arg(long = "notes-style", default_value = "yellow", value_name = "STYLE")]
+    /// Style string for the notes of a commit.
+    ///
+    /// This styles the "Notes:" section which `git log --notes` appends to a commit message,
+    /// including sections for other notes refs, e.g. "Notes (review):". See STYLES section. The
+    /// style 'omit' can be used to remove notes from the output.
+    pub notes_style: String,
+
+    #[arg(long = "old-fd", value_name = "FD")]
+    /// File descriptor to read the old version of a file from, to diff it without git.
+    ///
+    /// Given with --new-fd, e.g. `delta --old-fd=3 --new-fd=4 3<old.txt 4<new.txt`, delta reads
+    /// the whole contents of both file descriptors, computes the diff of their lines itself, and
+    /// displays it instead of reading a diff from its standard input. This lets a program which
+    /// holds both versions of a file in memory show their diff without writing them to files or
+    /// depending on git or diff. The diff has --context lines of context, 3 by default.
+    pub old_fd: Option<u32>,
+
+    #[arg(
+        long = "output",
+        default_value = "ansi",
+        value_name = "ansi|svg|html",
+        value_parser = ["ansi", "svg", "html"],
+    )]
+    /// Format of delta's output.
+    ///
+    /// "ansi" writes text with ANSI escape sequences for a terminal. "svg" renders the same
+    /// styled text as an SVG image in a monospace font, for embedding in documents. With "svg",
+    /// --true-color=auto uses 24-bit colors, and the background of the image is taken from the
+    /// syntax theme. "html" writes a <pre> fragment in which text is marked with the CSS class of
+    /// the delta style it is painted with, e.g. "delta-minus-emph" for minus-emph-style; see
+    /// --html-stylesheet. The colors of syntax highlighting are not included in "html" output.
+    pub output: String,
+
     #[arg(long = "pager", value_name = "CMD")]
     /// Which pager to use.
     ///
     /// The default pager is `less`. You can also change pager by setting the
     /// environment variable DELTA_PAGER, or PAGER. This option overrides these
     /// environment variables.
+    ///
+    /// The environment variables are consulted in the order DELTA_PAGER, BAT_PAGER, PAGER, and
+    /// the first one which is set is used. To change this order, or to restrict which variables
+    /// are consulted, set DELTA_PAGER_PRECEDENCE to a comma-separated list of variable names,
+    /// e.g. DELTA_PAGER_PRECEDENCE=PAGER,DELTA_PAGER. If none of them is set, `less` is used.
     pub pager: Option<String>,
 
+    #[arg(
+        long = "pager-quit-if-one-screen",
+        default_value = "true",
+        value_name = "true|false",
+        value_parser = ["true", "false"],
+    )]
+    /// Whether less should exit immediately if the output fits on one screen.
+    ///
+    /// With --paging=auto, delta passes --quit-if-one-screen (-F) to less. Set this to "false" to
+    /// always keep less open instead. This only affects the arguments delta gives to less: the
+    /// LESS environment variable is never modified, so options set there still apply.
+    pub pager_quit_if_one_screen: String,
+
+    #[arg(
+        long = "pager-without-color",
+        default_value = "true",
+        value_name = "true|false",
+        value_parser = ["true", "false"],
+    )]
+    /// Whether to use a pager when the output is not colored.
+    ///
+    /// The output is not colored when the NO_COLOR environment variable is set to a non-empty
+    /// value: delta then writes it without ANSI escape sequences, and does not pass
+    /// --RAW-CONTROL-CHARS (-R) to less. Set this to "false" to write uncolored output directly to
+    /// the terminal instead of starting a pager, whatever the value of --paging.
+    pub pager_without_color: String,
+
     #[arg(
         long = "paging",
         default_value = "auto",
@@ -758,6 +1593,22 @@ pub struct Opt {
     /// Options are: auto, always, and never.
     pub paging_mode: String,
 
+    #[arg(
+        long = "pairing-heuristic",
+        default_value = "positional",
+        value_name = "positional|similarity",
+        value_parser = ["positional", "similarity"],
+    )]
+    /// How removed lines are paired with added lines for within-line highlighting.
+    ///
+    /// "positional" pairs each removed line with the first following added line which is similar
+    /// enough to it (see --max-line-distance). "similarity" instead chooses the pairs which are
+    /// most similar overall, so that a removed line is paired with the added line it most closely
+    /// resembles; this gives better highlights when lines have been reordered, or when there are
+    /// more removed than added lines. Very large blocks of changed lines are always paired
+    /// positionally.
+    pub pairing_heuristic: String,
+
     #[arg(long = "parse-ansi")]
     /// Display ANSI color escape sequences in human-readable form.
     ///
@@ -765,6 +1616,22 @@ pub struct Opt {
     /// This can be used to help identify input style strings to use with map-styles.
     pub parse_ansi: bool,
 
+    #[arg(long = "parse-style", value_name = "STYLE")]
+    /// Display how a style string is parsed, and exit.
+    ///
+    /// Example usage: delta --parse-style "bold red ul"
+    /// This writes the foreground and background colors and the attributes of the style, and a
+    /// sample of text painted in it, to help with writing style strings for config. An invalid
+    /// style string is reported as an error. See STYLES section.
+    pub parse_style: Option<String>,
+
+    #[arg(long = "path-compress-dirs")]
+    /// Shorten the directories of file paths in file headers to their first letter.
+    ///
+    /// E.g. src/handlers/foo/bar.rs is displayed as s/h/f/bar.rs. The file name is kept whole, and
+    /// the targets of --hyperlinks are the full paths.
+    pub path_compress_dirs: bool,
+
     #[arg(
         long = "plus-emph-style",
         default_value = "syntax auto",
@@ -805,24 +1672,75 @@ pub struct Opt {
     /// See STYLES section.
     pub plus_style: String,
 
+    #[arg(long = "preserve-commit-ansi")]
+    /// Keep the ANSI escape sequences of commit messages.
+    ///
+    /// Commit messages sometimes contain escape sequences, e.g. colors, written by the tools which
+    /// made them. By default they are removed from the lines of the commit messages of `git log`
+    /// output, so that they cannot leak into the rest of the output. The colors which git adds to
+    /// the commit header line are not affected.
+    pub preserve_commit_ansi: bool,
+
+    #[arg(long = "query-terminal-background")]
+    /// Detect whether the terminal is dark or light from its background color alone.
+    ///
+    /// When the terminal is queried for its colors (see --detect-dark-light), only its
+    /// background color is queried, with an OSC 11 escape sequence, and the terminal is taken to
+    /// be light if the color is lighter than middle grey. The query times out after 100ms,
+    /// rather than 1s. If the terminal does not answer, the COLORFGBG environment variable, set
+    /// by some terminals, is used instead: a background color of 7 or 9 to 15 is taken to be
+    /// light, and any other dark.
+    pub query_terminal_background: bool,
+
     #[arg(long = "raw")]
     /// Do not alter the input in any way.
     ///
     /// This is mainly intended for testing delta.
     pub raw: bool,
 
+    #[arg(long = "reflow-prose")]
+    /// Compare the paragraphs of prose files rather than their lines (experimental).
+    ///
+    /// In Markdown, plain text, reStructuredText, AsciiDoc, Org and Textile files, the words which
+    /// changed in a block of removed and added lines are found by comparing paragraphs, i.e. runs
+    /// of non-blank lines, as if the lines of each were joined, rather than comparing lines. A
+    /// line starting a heading, list item, quote or table row starts a paragraph. So when a
+    /// hard-wrapped paragraph is edited and rewrapped, only the words which changed are
+    /// emphasized, rather than every line whose line break moved. The lines are shown as they
+    /// are, each split back at its own line breaks.
+    pub reflow_prose: bool,
+
     #[arg(long = "relative-paths")]
     /// Output all file paths relative to the current directory.
     ///
     /// This means that they will resolve correctly when clicked on or used in shell commands.
     pub relative_paths: bool,
 
+    #[arg(long = "reverse")]
+    /// Display the diff as if it were applied in reverse.
+    ///
+    /// Removed lines are shown as added lines and vice versa, with the styles and line numbers of
+    /// the other side, and the old and new file paths are swapped. This does not re-run git, so
+    /// it works on any unified diff input. Combined (merge) diffs are not reversed.
+    pub reverse: bool,
+
     #[arg(long = "right-arrow", default_value = "⟶  ", value_name = "STRING")]
     /// Text to display with a changed file path.
     ///
     /// For example, a unified diff heading, a rename, or a chmod.
     pub right_arrow: String,
 
+    #[arg(long = "semantic-highlight", value_name = "RULES")]
+    /// Styles for tokens matched by regexes, applied on top of syntax highlighting.
+    ///
+    /// A semicolon-separated list of rules of the form NAME:/REGEX/:STYLE, for example
+    /// --semantic-highlight='keyword:/\b(TODO|FIXME)\b/:red bold; number:/\d+/:cyan'. In
+    /// removed, added and unchanged lines of a hunk, the text matched by the regex of a rule is
+    /// painted in its style: the foreground and background colors of the style, if any, replace
+    /// those of the text, and its attributes are added to those of the text. A later rule is
+    /// applied on top of an earlier one. The regex of a rule cannot contain "/:".
+    pub semantic_highlight: Option<String>,
+
     #[arg(long = "show-colors")]
     /// Show available named colors.
     ///
@@ -838,6 +1756,42 @@ pub struct Opt {
     /// --zero-style, --plus-style, --light, --dark, etc.
     pub show_config: bool,
 
+    #[arg(long = "show-index")]
+    /// Show the "index" line of each file below its file header.
+    ///
+    /// The line gives the abbreviated blob ids of the file before and after the change, and its
+    /// mode, painted in --index-style. If the mode changed, whether given in the "index" line or in
+    /// "old mode" and "new mode" lines, the old and new modes are shown instead, painted in
+    /// --index-mode-change-style.
+    pub show_index: bool,
+
+    #[arg(long = "show-insertion-point")]
+    /// Mark where text was inserted into a line from which nothing was removed.
+    ///
+    /// When a removed line and an added line are inferred to be homologous, and text was inserted
+    /// without any text being removed at that point, a '‸' marker is shown at the insertion point
+    /// in the removed line. The marker is painted using --insertion-point-style.
+    pub show_insertion_point: bool,
+
+    #[arg(long = "show-legend")]
+    /// Write a legend of the colors of added, removed and unchanged lines before the output.
+    ///
+    /// The legend is a line of the form '■ added  ■ removed  ■ context', in which each '■' is
+    /// painted in the background color of --plus-style, --minus-style and --zero-style
+    /// respectively, or in its foreground color if it has no background color. Nothing is written
+    /// if the input is empty, or with --color-only.
+    pub show_legend: bool,
+
+    #[arg(long = "show-moves-paired")]
+    /// Show each block of lines moved within a file in two panes, after the hunks of the file.
+    ///
+    /// A block of consecutive removed lines is moved if the same lines were added as a block
+    /// elsewhere in the file, and if it has at least 20 alphanumeric characters, as for git's
+    /// --color-moved. The removed block is shown in the left pane, painted in --minus-style, and
+    /// the added block in the right pane, painted in --plus-style, under a header giving the line
+    /// numbers of both, so that they can be compared at a glance.
+    pub show_moves_paired: bool,
+
     #[arg(long = "show-syntax-themes")]
     /// Show example diff for available syntax-highlighting themes.
     ///
@@ -856,16 +1810,153 @@ pub struct Opt {
     /// shown, use --dark or --light, or both, on the command line together with this option.
     pub show_themes: bool,
 
-    #[arg(short = 's', long = "side-by-side")]
+    #[arg(long = "show-trailing-context", default_value = "0", value_name = "N")]
+    /// Show N lines following the last hunk of each file.
+    ///
+    /// The lines are read from the working tree version of the file, and shown as unchanged lines.
+    /// This is useful for diffs which were made with little or no context. It only applies when
+    /// delta is run in a git repository, and to files which can be found in it. Use 0 (the
+    /// default) to show only the context lines present in the diff.
+    pub show_trailing_context: usize,
+
+    #[arg(
+        short = 's',
+        long = "side-by-side",
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        value_enum,
+        default_value_t = SideBySide::default(),
+        default_missing_value = "true",
+        value_name = "true|false|auto",
+    )]
     /// Display diffs in side-by-side layout.
-    pub side_by_side: bool,
+    ///
+    /// With "auto", side-by-side layout falls back to the unified layout when the output is
+    /// narrower than --side-by-side-min-width.
+    pub side_by_side: SideBySide,
+
+    #[arg(long = "side-by-side-gap", default_value = "0", value_name = "N")]
+    /// Number of columns between the left and right panels in side-by-side layout.
+    ///
+    /// The columns are taken from the width of the panels, and filled using
+    /// --side-by-side-gap-style.
+    pub side_by_side_gap: usize,
+
+    #[arg(
+        long = "side-by-side-gap-style",
+        default_value = "normal",
+        value_name = "STYLE"
+    )]
+    /// Style string for the columns between the panels in side-by-side layout.
+    ///
+    /// See STYLES section and --side-by-side-gap.
+    pub side_by_side_gap_style: String,
+
+    #[arg(long = "side-by-side-min-width", default_value = "0", value_name = "N")]
+    /// Minimum width of the output for side-by-side layout.
+    ///
+    /// If --side-by-side=auto is given but the output width (as given by --width, or taken from
+    /// the terminal) is less than N, the diff is displayed in the unified layout instead. The
+    /// default of 0 always uses side-by-side layout.
+    pub side_by_side_min_width: usize,
+
+    #[arg(long = "side-by-side-shared-gutter")]
+    /// Show one column of line numbers between the panels in side-by-side layout.
+    ///
+    /// Instead of a column of line numbers at the start of each panel, the left panel has none,
+    /// and the right panel starts with a column giving the line numbers of the new file. The
+    /// column is blank for a removed line which has no added line next to it. Its format is that
+    /// of --line-numbers-right-format, which in side-by-side layout starts with a dividing
+    /// character separating it from the left panel.
+    pub side_by_side_shared_gutter: bool,
+
+    #[arg(
+        long = "sort-files",
+        default_value = "none",
+        value_name = "path|changes|none",
+        value_parser = ["path", "changes", "none"],
+    )]
+    /// Order in which to show the files of a diff.
+    ///
+    /// "path" shows the files in the alphabetical order of their paths, and "changes" shows the
+    /// files with the most removed and added lines first. "none" shows them in the order of the
+    /// input. In the output of e.g. `git log -p`, the files of each commit are sorted separately.
+    /// Sorting the files requires all of the input to be read and held in memory before any of it
+    /// is written, so that memory use grows with the size of the diff, and nothing is shown until
+    /// the input ends; see --buffer-limit-bytes.
+    pub sort_files: String,
+
+    #[arg(long = "stat-group-by-dir")]
+    /// Group the lines of a diff stat by top-level directory.
+    ///
+    /// In `git diff --stat` or `git log --stat` output, the files in each top-level directory are
+    /// listed under a line giving the directory and its total number of changed lines. Files at
+    /// the top level of the repository are listed as usual.
+    pub stat_group_by_dir: bool,
+
+    #[arg(long = "stateful-highlighting")]
+    /// Highlight the old and the new version of each file as continuous text (experimental).
+    ///
+    /// By default, the syntax highlighting of the lines of a hunk starts afresh at each hunk, and
+    /// continues from removed into added lines. With this option the removed and unchanged lines
+    /// are highlighted as a continuation of one another, and the added and unchanged lines too, so
+    /// that a multi-line construct such as a string or a comment is highlighted consistently. If
+    /// the file can be read from the working tree, its lines preceding each hunk are highlighted
+    /// first, so that a hunk starting inside such a construct is highlighted correctly; these are
+    /// the lines of the new version of the file, and are used for the old version too.
+    pub stateful_highlighting: bool,
+
+    #[arg(long = "status-map", value_name = "STATUS_MAP")]
+    /// Labels and styles for the status letters of --name-status and --raw output.
+    ///
+    /// A comma-separated list of entries of the form LETTER=LABEL or LETTER=LABEL:STYLE, for
+    /// example --status-map='M=modified:yellow, A=added:green, D=deleted:red'. In lines of
+    /// `git diff --name-status` or `git diff --raw` output, a status letter which has an entry is
+    /// replaced by its label, painted in its style. Other status letters are left unchanged.
+    pub status_map: Option<String>,
+
+    #[arg(long = "strip-ansi")]
+    /// Remove ANSI escape sequences from standard input.
+    ///
+    /// Example usage: delta --strip-ansi < colored.txt > plain.txt
+    /// This writes the input with all color and hyperlink escape sequences removed, e.g. to
+    /// get plain text from delta output that was copied from a pager.
+    pub strip_ansi: bool,
+
+    #[arg(
+        long = "syntax-highlight-context",
+        default_value = "true",
+        value_name = "true|false",
+        value_parser = ["true", "false"],
+    )]
+    /// Whether to syntax-highlight unchanged (context) lines.
+    ///
+    /// Set this to "false" to show context lines without syntax highlighting, so that only the
+    /// removed and added lines are highlighted, regardless of zero-style. Context lines are still
+    /// read by the highlighter, so that the lines following them are highlighted correctly.
+    pub syntax_highlight_context: String,
+
+    #[arg(
+        long = "syntax-highlight-max-file-bytes",
+        default_value = "0",
+        value_name = "N"
+    )]
+    /// Do not syntax-highlight files whose diff is larger than N bytes.
+    ///
+    /// The lines of such a file keep the colors of removed and added lines, but not those of
+    /// syntax highlighting, which is slow on large files. The size of a file is taken to be that of
+    /// its hunks in the input, since the input does not give the size of the whole file. The whole
+    /// input is read before anything is written (see --buffer-limit-bytes). Use 0 (the default)
+    /// to highlight files of any size.
+    pub syntax_highlight_max_file_bytes: usize,
 
     #[arg(long = "syntax-theme", value_name = "SYNTAX_THEME")]
     /// The syntax-highlighting theme to use.
     ///
     /// Use --show-syntax-themes to demo available themes. Defaults to the value of the BAT_THEME
-    /// environment variable, if that contains a valid theme name. --syntax-theme=none disables all
-    /// syntax highlighting.
+    /// environment variable, if that contains a valid theme name. --syntax-theme=none (or an empty
+    /// value) disables all syntax highlighting, while retaining the diff colors.
     pub syntax_theme: Option<String>,
 
     #[arg(long = "tabs", default_value = "8", value_name = "N")]
@@ -875,8 +1966,20 @@ pub struct Opt {
     /// calculate line widths assuming tabs occupy one character's width on the screen: if your
     /// terminal renders tabs as more than one character wide then delta's output will look
     /// incorrect.
+    ///
+    /// Tabs in the text of decorated headers, such as a file name or the function context of a hunk
+    /// header, and in the line-number formats are replaced in the same way, or by a single space
+    /// with --tabs=0, so that decorations are never misaligned.
     pub tab_width: usize,
 
+    #[arg(long = "toc")]
+    /// List the changed files before the diff.
+    ///
+    /// Each file in the numbered list is an OSC 8 hyperlink to the file (see
+    /// --hyperlinks-file-link-format). Since the list must be complete before the diff is shown,
+    /// all of the input is read before anything is displayed.
+    pub toc: bool,
+
     #[arg(
         long = "true-color",
         default_value = "auto",
@@ -889,9 +1992,33 @@ pub struct Opt {
     /// the environment variable COLORTERM has the value "truecolor" or "24bit". If your terminal
     /// application (the application you use to enter commands at a shell prompt) supports 24 bit
     /// colors, then it probably already sets this environment variable, in which case you don't
-    /// need to do anything.
+    /// need to do anything. It does not if the environment variable TERM is that of a terminal
+    /// known not to render 24-bit color whatever COLORTERM says ("dumb", "eterm", "eterm-color",
+    /// and "linux"). Use "never" if your terminal claims 24-bit color and doesn't
+    /// render it.
     pub true_color: String,
 
+    #[arg(
+        long = "two-file-backend",
+        default_value = "git",
+        value_name = "git|internal",
+        value_parser = ["git", "internal"],
+    )]
+    /// How the diff of two files given on the command line is computed.
+    ///
+    /// "git" (the default) runs `git diff --no-index`, or `diff` for files given by process
+    /// substitution to a git which cannot read them. "internal" computes the diff within delta
+    /// without running any command; --diff-args is then ignored.
+    pub two_file_backend: String,
+
+    #[arg(long = "validate-line-numbers")]
+    /// Warn on standard error when a hunk has more or fewer lines than its header declares.
+    ///
+    /// A debugging aid: after each hunk, the line numbers delta has given the lines of the new side
+    /// of the file are checked against the start and length declared by the hunk header, and a
+    /// mismatch is reported with the file, the number of the hunk in the file, and its header.
+    pub validate_line_numbers: bool,
+
     #[arg(
         long = "whitespace-error-style",
         default_value = "auto auto",
@@ -962,6 +2089,28 @@ pub struct Opt {
     /// line and continues right-aligned.
     pub wrap_right_symbol: String,
 
+    #[arg(
+        long = "wrap-symbol-minus-style",
+        default_value = "inline-hint-style",
+        value_name = "STYLE"
+    )]
+    /// Style string for the wrap symbols of removed lines in side-by-side mode.
+    ///
+    /// The symbols are painted on the background of the line unless the style has a background of
+    /// its own. See --wrap-left-symbol, --wrap-right-symbol, --wrap-right-prefix-symbol and the
+    /// STYLES section.
+    pub wrap_symbol_minus_style: String,
+
+    #[arg(
+        long = "wrap-symbol-plus-style",
+        default_value = "inline-hint-style",
+        value_name = "STYLE"
+    )]
+    /// Style string for the wrap symbols of added lines in side-by-side mode.
+    ///
+    /// See --wrap-symbol-minus-style.
+    pub wrap_symbol_plus_style: String,
+
     #[arg(
         long = "zero-style",
         default_value = "syntax normal",
@@ -1177,6 +2326,7 @@ For a short help summary, please use delta -h.
 #[derive(Default, Clone, Debug)]
 pub struct ComputedValues {
     pub available_terminal_width: usize,
+    pub available_terminal_height: usize,
     pub stdout_is_term: bool,
     pub background_color_extends_to_terminal_width: bool,
     pub decorations_width: Width,
@@ -1186,6 +2336,15 @@ pub struct ComputedValues {
     pub syntax_set: SyntaxSet,
     pub syntax_theme: Option<SyntaxTheme>,
     pub true_color: bool,
+    pub color_dither: bool,
+}
+
+// Parse a number from 0.0 to 1.0.
+fn parse_fraction(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(x) if (0.0..=1.0).contains(&x) => Ok(x),
+        _ => Err("expected a number from 0.0 to 1.0".to_string()),
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -1195,6 +2354,16 @@ pub enum Width {
     Variable,
 }
 
+impl Width {
+    /// Return the fixed width, or `available_terminal_width` if the width is variable.
+    pub fn or_available(&self, available_terminal_width: usize) -> usize {
+        match self {
+            Width::Fixed(width) => *width,
+            Width::Variable => available_terminal_width,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum InspectRawLines {
     True,
@@ -1213,6 +2382,17 @@ pub enum DetectDarkLight {
     Never,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum SideBySide {
+    /// Always use side-by-side layout.
+    True,
+    /// Never use side-by-side layout.
+    #[default]
+    False,
+    /// Use side-by-side layout if the output is at least --side-by-side-min-width wide.
+    Auto,
+}
+
 // Which call path to take
 #[derive(Debug)]
 pub enum Call<T> {
@@ -1400,6 +2580,7 @@ impl Opt {
 lazy_static! {
     static ref IGNORED_OPTION_NAMES: HashSet<&'static str> = vec![
         "generate-completion",
+        "html-stylesheet",
         "list-languages",
         "list-syntax-themes",
         "show-config",
@@ -106,6 +106,86 @@ fn ansi_16_color_number_to_name(n: u8) -> Option<&'static str> {
     None
 }
 
+/// Return the RGB components of `color`, taking those of the standard 256-color palette for
+/// palette colors.
+pub fn to_rgb(color: Color) -> (u8, u8, u8) {
+    match color {
+        Color::RGB(r, g, b) => (r, g, b),
+        Color::Fixed(n) => ansi_colours::rgb_from_ansi256(n),
+        Color::Black => ansi_colours::rgb_from_ansi256(0),
+        Color::Red => ansi_colours::rgb_from_ansi256(1),
+        Color::Green => ansi_colours::rgb_from_ansi256(2),
+        Color::Yellow => ansi_colours::rgb_from_ansi256(3),
+        Color::Blue => ansi_colours::rgb_from_ansi256(4),
+        Color::Purple => ansi_colours::rgb_from_ansi256(5),
+        Color::Cyan => ansi_colours::rgb_from_ansi256(6),
+        Color::White => ansi_colours::rgb_from_ansi256(7),
+    }
+}
+
+// The relative luminance of a color, as defined by WCAG 2.
+fn relative_luminance((r, g, b): (u8, u8, u8)) -> f64 {
+    let linear = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// The WCAG 2 contrast ratio of two colors, from 1.0 for colors of the same luminance to 21.0 for
+/// black and white.
+pub fn contrast_ratio(a: (u8, u8, u8), b: (u8, u8, u8)) -> f64 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// If `foreground` has a contrast ratio of less than `min_ratio` with `background`, return the
+/// least lightened or darkened version of it which has at least that ratio, or else white or
+/// black (used by --high-contrast). The color is a 256-color palette entry unless `true_color`.
+pub fn adjust_for_contrast(
+    foreground: (u8, u8, u8),
+    background: (u8, u8, u8),
+    min_ratio: f64,
+    true_color: bool,
+) -> Option<Color> {
+    if contrast_ratio(foreground, background) >= min_ratio {
+        return None;
+    }
+    let to_color = |(r, g, b): (u8, u8, u8)| {
+        if true_color {
+            Color::RGB(r, g, b)
+        } else {
+            Color::Fixed(ansi_colours::ansi256_from_rgb((r, g, b)))
+        }
+    };
+    // Lighten or darken, whichever can give the greater contrast.
+    let target = if contrast_ratio((0xff, 0xff, 0xff), background)
+        >= contrast_ratio((0x00, 0x00, 0x00), background)
+    {
+        (0xff, 0xff, 0xff)
+    } else {
+        (0x00, 0x00, 0x00)
+    };
+    let mix =
+        |c: u8, t: u8, fraction: f64| (c as f64 + (t as f64 - c as f64) * fraction).round() as u8;
+    const N_STEPS: u32 = 20;
+    (1..=N_STEPS)
+        .map(|step| {
+            let fraction = step as f64 / N_STEPS as f64;
+            to_color((
+                mix(foreground.0, target.0, fraction),
+                mix(foreground.1, target.1, fraction),
+                mix(foreground.2, target.2, fraction),
+            ))
+        })
+        .find(|&color| contrast_ratio(to_rgb(color), background) >= min_ratio)
+        .or(Some(to_color(target)))
+}
+
 /// The color mode determines some default color choices
 /// such as the diff background color or the palette used for blame.
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
@@ -190,3 +270,36 @@ const DARK_THEME_PLUS_EMPH_COLOR_256: Color = Color::Fixed(28);
 pub const LIGHT_THEME_BLAME_PALETTE: &[&str] = &["#FFFFFF", "#DDDDDD", "#BBBBBB"];
 
 pub const DARK_THEME_BLAME_PALETTE: &[&str] = &["#000000", "#222222", "#444444"];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contrast_ratio() {
+        let ratio = contrast_ratio((0xff, 0xff, 0xff), (0x00, 0x00, 0x00));
+        assert!((ratio - 21.0).abs() < 1e-9);
+        assert_eq!(contrast_ratio((0x12, 0x34, 0x56), (0x12, 0x34, 0x56)), 1.0);
+    }
+
+    #[test]
+    fn test_adjust_for_contrast() {
+        // Dark gray on black is lightened just enough.
+        let adjusted = adjust_for_contrast((0x30, 0x30, 0x30), (0x00, 0x00, 0x00), 4.5, true);
+        let (r, g, b) = to_rgb(adjusted.unwrap());
+        assert!(contrast_ratio((r, g, b), (0x00, 0x00, 0x00)) >= 4.5);
+        assert!(r < 0xff && r == g && g == b);
+        // Light gray on white is darkened.
+        let adjusted = adjust_for_contrast((0xe0, 0xe0, 0xe0), (0xff, 0xff, 0xff), 4.5, false);
+        let Some(Color::Fixed(n)) = adjusted else {
+            panic!("{:?}", adjusted);
+        };
+        let rgb = ansi_colours::rgb_from_ansi256(n);
+        assert!(rgb.0 < 0xe0 && contrast_ratio(rgb, (0xff, 0xff, 0xff)) >= 4.5);
+        // A pair with enough contrast is left alone.
+        assert_eq!(
+            adjust_for_contrast((0xff, 0xff, 0xff), (0x00, 0x00, 0x00), 4.5, true),
+            None
+        );
+    }
+}
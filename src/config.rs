@@ -1,24 +1,31 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+#[cfg(test)]
+use std::ffi::OsString;
 use std::path::PathBuf;
 
 use clap::parser::ValueSource;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
 use regex::Regex;
 use syntect::highlighting::Style as SyntectStyle;
 use syntect::highlighting::Theme as SyntaxTheme;
 use syntect::parsing::SyntaxSet;
 
 use crate::ansi;
-use crate::cli;
+use crate::cli::{self, SideBySide};
 use crate::color::{self, ColorMode};
 use crate::delta::State;
+#[cfg(test)]
+use crate::env::DeltaEnv;
 use crate::fatal;
+use crate::features::hyperlinks;
 use crate::features::navigate;
 use crate::features::side_by_side::{self, ansifill, LeftRight};
 use crate::git_config::GitConfig;
 use crate::handlers;
 use crate::handlers::blame::parse_blame_line_numbers;
 use crate::handlers::blame::BlameLineNumbers;
-use crate::minusplus::MinusPlus;
+use crate::minusplus::{MinusPlus, MinusPlusIndex};
 use crate::paint::BgFillMethod;
 use crate::parse_styles;
 use crate::style;
@@ -26,6 +33,8 @@ use crate::style::Style;
 use crate::tests::TESTING;
 use crate::utils;
 use crate::utils::bat::output::PagingMode;
+use crate::utils::encoding::Encoding;
+use crate::utils::format_command::{self, FormatCommand};
 use crate::utils::regex_replacement::RegexReplacement;
 use crate::wrapping::WrapConfig;
 
@@ -36,34 +45,78 @@ pub const SYNTAX_FALLBACK_LANG: &str = "txt";
 
 #[cfg_attr(test, derive(Clone))]
 pub struct Config {
+    pub add_p_mode: AddPMode,
+    pub align_edits: bool,
+    pub annotate_age: bool,
+    pub annotate_lines: bool,
+    pub assume_encoding: Encoding,
     pub available_terminal_width: usize,
+    pub available_terminal_height: usize,
     pub background_color_extends_to_terminal_width: bool,
     pub blame_code_style: Option<Style>,
+    pub blame_color_by: BlameColorBy,
     pub blame_format: String,
     pub blame_separator_format: BlameLineNumbers,
     pub blame_palette: Vec<String>,
+    pub blame_removed: bool,
     pub blame_separator_style: Option<Style>,
     pub blame_timestamp_format: String,
     pub blame_timestamp_output_format: Option<String>,
+    pub buffer_limit_bytes: Option<usize>,
+    pub change_heat: bool,
+    pub change_heat_base_color: (u8, u8, u8),
+    pub change_symbol_style: Style,
+    pub change_symbols: bool,
+    pub check: bool,
+    pub collapse_blank_additions: bool,
+    pub collapse_eol_only: bool,
+    pub collapse_identical_paths: bool,
+    pub color_dither: bool,
     pub color_only: bool,
+    pub commit_merge_style: Style,
+    pub commit_meta_overflow: CommitMetaOverflow,
+    pub commit_meta_width: usize,
     pub commit_regex: Regex,
+    pub commit_ref_branch_style: Style,
+    pub commit_ref_head_style: Style,
+    pub commit_ref_remote_style: Style,
+    pub commit_ref_tag_style: Style,
     pub commit_style: Style,
+    pub commit_subject_truncation: CommitSubjectTruncation,
+    pub compact_renames: bool,
+    pub compute_function_context: bool,
+    pub context: Option<usize>,
     pub cwd_of_delta_process: Option<PathBuf>,
     pub cwd_of_user_shell_process: Option<PathBuf>,
     pub cwd_relative_to_repo_root: Option<String>,
     pub decorations_width: cli::Width,
+    pub debug_grid: usize,
     pub default_language: String,
     pub diff_args: String,
+    pub diff_fds: Option<(u32, u32)>,
     pub diff_stat_align_width: usize,
+    pub digest: bool,
+    pub emph_extend: EmphExtend,
     pub error_exit_code: i32,
+    pub exit_code: bool,
     pub file_added_label: String,
+    pub file_category_style: Style,
     pub file_copied_label: String,
+    pub file_copied_style: Style,
+    pub file_footer: bool,
     pub file_modified_label: String,
     pub file_removed_label: String,
     pub file_renamed_label: String,
     pub file_regex_replacement: Option<RegexReplacement>,
     pub right_arrow: String,
     pub file_style: Style,
+    pub final_newline: Option<bool>,
+    pub final_reset: bool,
+    pub focus_file: Option<String>,
+    pub focus_line: Option<(MinusPlusIndex, usize)>,
+    pub focus_line_style: Style,
+    pub force_text: bool,
+    pub format_commands: Vec<FormatCommand>,
     pub git_config: Option<GitConfig>,
     pub git_minus_style: Style,
     pub git_plus_style: Style,
@@ -77,29 +130,61 @@ pub struct Config {
     pub grep_match_word_style: Style,
     pub grep_output_type: Option<GrepType>,
     pub grep_separator_symbol: String,
+    pub grep_separator_tab_gap: String,
+    pub grep_separator_tabs: usize,
+    pub grep_show_counts: bool,
+    pub grep_strip_input_ansi: bool,
+    pub group_by_category: bool,
+    pub group_changes: GroupChanges,
+    pub guard_nested: bool,
     pub handle_merge_conflicts: bool,
+    pub high_contrast: bool,
+    pub high_contrast_default_colors: ((u8, u8, u8), (u8, u8, u8)),
+    pub high_contrast_ratio: f64,
     pub hostname: Option<String>,
+    pub hunk_header_empty_range: HunkHeaderEmptyRange,
     pub hunk_header_file_style: Style,
     pub hunk_header_line_number_style: Style,
+    pub hunk_header_show_position: bool,
     pub hunk_header_style_include_file_path: HunkHeaderIncludeFilePath,
     pub hunk_header_style_include_line_number: HunkHeaderIncludeLineNumber,
     pub hunk_header_style_include_code_fragment: HunkHeaderIncludeCodeFragment,
     pub hunk_header_style: Style,
     pub hunk_label: String,
+    pub hunk_spacing: usize,
     pub hyperlinks_commit_link_format: Option<String>,
     pub hyperlinks_file_link_format: String,
+    pub hyperlinks_hunk_header_range: bool,
     pub hyperlinks: bool,
+    pub ignore_files: GlobSet,
+    pub indent: usize,
+    pub index_mode_change_style: Style,
+    pub index_style: Style,
+    pub inline_highlight_budget: usize,
     pub inline_hint_style: Style,
+    pub inline_only: bool,
+    pub inline_single_word: bool,
+    pub insertion_point_style: Style,
     pub inspect_raw_lines: cli::InspectRawLines,
+    pub issue_links: Vec<(Regex, String)>,
     pub keep_plus_minus_markers: bool,
     pub line_buffer_size: usize,
     pub line_fill_method: BgFillMethod,
     pub line_numbers_format: LeftRight<String>,
+    pub line_numbers_min_width: usize,
     pub line_numbers_style_leftright: LeftRight<Style>,
     pub line_numbers_style_minusplus: MinusPlus<Style>,
+    pub line_numbers_zero_pad: bool,
     pub line_numbers_zero_style: Style,
+    // The prefixes of added, removed, and unchanged lines.
+    pub line_prefixes: Option<(String, String, String)>,
+    pub literal_content_escapes: bool,
+    pub log_show_files: bool,
     pub line_numbers: bool,
+    pub stat_group_by_dir: bool,
+    pub status_map: HashMap<char, (String, Style)>,
     pub styles_map: Option<HashMap<style::AnsiTermStyleEqualityKey, Style>>,
+    pub max_files: usize,
     pub max_line_distance_for_naively_paired_lines: f64,
     pub max_line_distance: f64,
     pub max_line_length: usize,
@@ -108,34 +193,63 @@ pub struct Config {
     pub merge_conflict_ours_diff_header_style: Style,
     pub merge_conflict_theirs_diff_header_style: Style,
     pub merge_conflict_end_symbol: String,
+    pub minimap: bool,
     pub minus_emph_style: Style,
     pub minus_empty_line_marker_style: Style,
     pub minus_file: Option<PathBuf>,
     pub minus_non_emph_style: Style,
     pub minus_style: Style,
+    pub navigate_prompt: Option<String>,
     pub navigate_regex: Option<String>,
     pub navigate: bool,
+    pub no_color: bool,
+    pub notes_style: Style,
+    pub null_input: bool,
     pub null_style: Style,
     pub null_syntect_style: SyntectStyle,
+    pub output_format: OutputFormat,
     pub pager: Option<String>,
+    pub pager_quit_if_one_screen: bool,
     pub paging_mode: PagingMode,
+    pub pairing_heuristic: PairingHeuristic,
+    pub path_compress_dirs: bool,
     pub plus_emph_style: Style,
     pub plus_empty_line_marker_style: Style,
     pub plus_file: Option<PathBuf>,
     pub plus_non_emph_style: Style,
     pub plus_style: Style,
+    pub preserve_commit_ansi: bool,
+    pub reflow_prose: bool,
     pub relative_paths: bool,
+    pub repo_root: Option<PathBuf>,
+    pub reverse: bool,
+    pub semantic_highlight: Vec<(String, Regex, Style)>,
+    pub show_index: bool,
+    pub show_insertion_point: bool,
+    pub show_legend: bool,
+    pub show_moves_paired: bool,
+    pub show_trailing_context: usize,
     pub show_themes: bool,
     pub side_by_side_data: side_by_side::SideBySideData,
     pub side_by_side: bool,
+    pub side_by_side_gap: usize,
+    pub side_by_side_gap_style: Style,
+    pub sort_files: SortFiles,
+    pub stateful_highlighting: bool,
+    pub syntax_highlight_context: bool,
+    pub syntax_highlight_max_file_bytes: usize,
     pub syntax_set: SyntaxSet,
     pub syntax_theme: Option<SyntaxTheme>,
     pub tab_cfg: utils::tabs::TabCfg,
+    pub toc: bool,
     pub tokenization_regex: Regex,
     pub true_color: bool,
     pub truncation_symbol: String,
+    pub two_file_backend: TwoFileBackend,
+    pub validate_line_numbers: bool,
     pub whitespace_error_style: Style,
     pub wrap_config: WrapConfig,
+    pub wrap_symbol_style: MinusPlus<Style>,
     pub zero_style: Style,
 }
 
@@ -163,7 +277,89 @@ pub enum HunkHeaderIncludeCodeFragment {
     No,
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AddPMode {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlameColorBy {
+    Commit,
+    Domain,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitMetaOverflow {
+    Truncate,
+    Wrap,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitSubjectTruncation {
+    None,
+    Right,
+    Middle,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GroupChanges {
+    Interleaved,
+    Grouped,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HunkHeaderEmptyRange {
+    Hide,
+    Show,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    Ansi,
+    Svg,
+    Html,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PairingHeuristic {
+    Positional,
+    Similarity,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortFiles {
+    Path,
+    Changes,
+    None,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TwoFileBackend {
+    Git,
+    Internal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmphExtend {
+    Span,
+    Line,
+}
+
 impl Config {
+    /// Build a config from command line arguments alone (not including the program name), using
+    /// `env` in place of the process environment. Git config is not read.
+    #[cfg(test)]
+    pub fn from_iter<I>(env: &DeltaEnv, iter: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString> + Clone,
+    {
+        let args = std::iter::once(OsString::from("delta")).chain(iter.into_iter().map(Into::into));
+        Self::from(cli::Opt::from_iter_and_git_config(env, args, None))
+    }
+
     pub fn get_style(&self, state: &State) -> &Style {
         match state {
             State::HunkMinus(_, _) => &self.minus_style,
@@ -181,20 +377,45 @@ impl Config {
     pub fn git_config(&self) -> Option<&GitConfig> {
         self.git_config.as_ref()
     }
+
+    /// The exit code of a run which did not encounter an error. If exit-code is in effect then
+    /// this is 1 when changes were found, as with `git diff --exit-code`.
+    pub fn success_exit_code(&self, changes_found: bool) -> i32 {
+        if self.exit_code && changes_found {
+            1
+        } else {
+            0
+        }
+    }
 }
 
 impl From<cli::Opt> for Config {
     fn from(opt: cli::Opt) -> Self {
         let mut styles = parse_styles::parse_styles(&opt);
         let styles_map = parse_styles::parse_styles_map(&opt);
+        let status_map = parse_styles::parse_status_map(&opt);
+        let semantic_highlight = parse_styles::parse_semantic_highlight(&opt);
+        let format_commands = format_command::parse_format_commands(
+            opt.format_command.as_deref(),
+            &opt.computed.syntax_set,
+        );
+        let hyperlinks = match opt.hyperlinks.as_ref() {
+            "true" => true,
+            "false" => false,
+            "auto" => hyperlinks::terminal_supports_hyperlinks(&opt.env),
+            _ => fatal("Invalid option for hyperlinks: Expected \"true\", \"false\" or \"auto\"."),
+        };
+        let hyperlinks_file_link_format = if opt.hyperlinks_file_link_format == "auto" {
+            hyperlinks::file_link_format_from_env(&opt.env).to_string()
+        } else {
+            opt.hyperlinks_file_link_format.clone()
+        };
 
         let wrap_config = WrapConfig::from_opt(&opt, styles["inline-hint-style"]);
 
         let max_line_distance_for_naively_paired_lines = opt
             .env
             .experimental_max_line_distance_for_naively_paired_lines
-            .as_ref()
-            .map(|s| s.parse::<f64>().unwrap_or(0.0))
             .unwrap_or(0.0);
 
         let commit_regex = Regex::new(&opt.commit_regex).unwrap_or_else(|_| {
@@ -216,6 +437,10 @@ impl From<cli::Opt> for Config {
         });
 
         let blame_palette = make_blame_palette(opt.blame_palette, opt.computed.color_mode);
+        let change_heat_base_color = make_change_heat_base_color(
+            opt.computed.syntax_theme.as_ref(),
+            opt.computed.color_mode,
+        );
 
         if blame_palette.is_empty() {
             fatal("Option 'blame-palette' must not be empty.")
@@ -239,13 +464,19 @@ impl From<cli::Opt> for Config {
         let side_by_side_data = side_by_side::SideBySideData::new_sbs(
             &opt.computed.decorations_width,
             &opt.computed.available_terminal_width,
+            opt.side_by_side_gap,
         );
         let side_by_side_data = ansifill::UseFullPanelWidth::sbs_odd_fix(
             &opt.computed.decorations_width,
+            opt.side_by_side_gap,
             &line_fill_method,
             side_by_side_data,
         );
 
+        let gutter_tab_cfg = utils::tabs::TabCfg::new(opt.tab_width).for_decorations();
+        let side_by_side_shared_gutter =
+            opt.side_by_side_shared_gutter && opt.side_by_side == SideBySide::True;
+
         let navigate_regex = if (opt.navigate || opt.show_themes)
             && (opt.navigate_regex.is_none() || opt.navigate_regex == Some("".to_string()))
         {
@@ -261,6 +492,25 @@ impl From<cli::Opt> for Config {
             opt.navigate_regex
         };
 
+        let navigate_prompt = if opt.navigate {
+            opt.navigate_prompt_format
+                .as_deref()
+                .map(navigate::make_navigate_less_prompt)
+        } else {
+            None
+        };
+
+        let max_line_distance = match opt.min_line_similarity {
+            Some(similarity) => 1.0 - similarity,
+            None => opt.max_line_distance,
+        };
+
+        let diff_fds = match (opt.old_fd, opt.new_fd) {
+            (Some(old_fd), Some(new_fd)) => Some((old_fd, new_fd)),
+            (None, None) => None,
+            _ => fatal("Options --old-fd and --new-fd must be given together."),
+        };
+
         let grep_output_type = match opt.grep_output_type.as_deref() {
             Some("ripgrep") => Some(GrepType::Ripgrep),
             Some("classic") => Some(GrepType::Classic),
@@ -281,30 +531,95 @@ impl From<cli::Opt> for Config {
         );
 
         Self {
+            add_p_mode: match opt.add_p_mode.as_ref() {
+                "auto" => AddPMode::Auto,
+                "always" => AddPMode::Always,
+                "never" => AddPMode::Never,
+                _ => fatal(
+                    "Invalid option for add-p-mode: Expected \"auto\", \"always\" or \"never\".",
+                ),
+            },
+            align_edits: opt.align_edits,
+            annotate_age: opt.annotate_age,
+            annotate_lines: opt.annotate_lines,
+            assume_encoding: Encoding::from_option(opt.assume_encoding.as_deref()),
             available_terminal_width: opt.computed.available_terminal_width,
+            available_terminal_height: opt.computed.available_terminal_height,
             background_color_extends_to_terminal_width: opt
                 .computed
                 .background_color_extends_to_terminal_width,
             blame_format: opt.blame_format,
             blame_code_style: styles.remove("blame-code-style"),
+            blame_color_by: match opt.blame_color_by.as_ref() {
+                "commit" => BlameColorBy::Commit,
+                "domain" => BlameColorBy::Domain,
+                _ => fatal("Invalid option for blame-color-by: Expected \"commit\" or \"domain\"."),
+            },
             blame_palette,
+            blame_removed: opt.blame_removed,
             blame_separator_format: parse_blame_line_numbers(&opt.blame_separator_format),
             blame_separator_style: styles.remove("blame-separator-style"),
             blame_timestamp_format: opt.blame_timestamp_format,
             blame_timestamp_output_format: opt.blame_timestamp_output_format,
+            buffer_limit_bytes: opt.buffer_limit_bytes,
+            change_heat: opt.change_heat,
+            change_heat_base_color,
+            change_symbol_style: styles["change-symbol-style"],
+            change_symbols: opt.change_symbols && opt.side_by_side != SideBySide::True,
+            check: opt.check,
+            commit_merge_style: styles["commit-merge-style"],
+            commit_meta_overflow: match opt.commit_meta_overflow.as_ref() {
+                "truncate" => CommitMetaOverflow::Truncate,
+                "wrap" => CommitMetaOverflow::Wrap,
+                _ => fatal(
+                    "Invalid option for commit-meta-overflow: Expected \"truncate\" or \"wrap\".",
+                ),
+            },
+            commit_meta_width: opt.commit_meta_width,
+            commit_ref_branch_style: styles["commit-ref-branch-style"],
+            commit_ref_head_style: styles["commit-ref-head-style"],
+            commit_ref_remote_style: styles["commit-ref-remote-style"],
+            commit_ref_tag_style: styles["commit-ref-tag-style"],
             commit_style: styles["commit-style"],
+            commit_subject_truncation: match opt.commit_subject_truncation.as_ref() {
+                "none" => CommitSubjectTruncation::None,
+                "right" => CommitSubjectTruncation::Right,
+                "middle" => CommitSubjectTruncation::Middle,
+                _ => fatal(
+                    "Invalid option for commit-subject-truncation: Expected \"none\", \"right\" or \"middle\".",
+                ),
+            },
+            collapse_blank_additions: opt.collapse_blank_additions,
+            collapse_eol_only: opt.collapse_eol_only && !opt.color_only,
+            collapse_identical_paths: opt.collapse_identical_paths == "true",
+            color_dither: opt.computed.color_dither,
             color_only: opt.color_only,
+            compact_renames: opt.compact_renames,
+            compute_function_context: opt.compute_function_context,
+            context: opt.context,
             commit_regex,
             cwd_of_delta_process,
             cwd_of_user_shell_process,
             cwd_relative_to_repo_root,
             decorations_width: opt.computed.decorations_width,
+            debug_grid: opt.debug_grid,
             default_language: opt.default_language,
             diff_args: opt.diff_args,
+            diff_fds,
             diff_stat_align_width: opt.diff_stat_align_width,
+            digest: opt.digest,
+            emph_extend: match opt.emph_extend.as_ref() {
+                "span" => EmphExtend::Span,
+                "line" => EmphExtend::Line,
+                _ => fatal("Invalid option for emph-extend: Expected \"span\" or \"line\"."),
+            },
             error_exit_code: 2, // Use 2 for error because diff uses 0 and 1 for non-error.
+            exit_code: opt.exit_code,
             file_added_label,
+            file_category_style: styles["file-category-style"],
             file_copied_label,
+            file_copied_style: styles["file-copied-style"],
+            file_footer: opt.file_footer && !opt.color_only,
             file_modified_label,
             file_removed_label,
             file_renamed_label,
@@ -315,6 +630,18 @@ impl From<cli::Opt> for Config {
             right_arrow,
             hunk_label,
             file_style: styles["file-style"],
+            final_newline: opt.final_newline.as_deref().map(|value| value == "true"),
+            final_reset: opt.final_reset,
+            focus_file: if opt.color_only {
+                None
+            } else {
+                opt.focus_file
+                    .map(|path| path.trim_start_matches("./").to_string())
+            },
+            focus_line: opt.focus_line.as_deref().map(parse_focus_line),
+            focus_line_style: styles["focus-line-style"],
+            force_text: opt.force_text,
+            format_commands,
             git_config: opt.git_config,
             grep_context_line_style: styles["grep-context-line-style"],
             grep_file_style: styles["grep-file-style"],
@@ -326,11 +653,36 @@ impl From<cli::Opt> for Config {
             grep_match_word_style: styles["grep-match-word-style"],
             grep_output_type,
             grep_separator_symbol: opt.grep_separator_symbol,
+            grep_separator_tab_gap: opt.grep_separator_tab_gap,
+            grep_separator_tabs: opt.grep_separator_tabs,
+            grep_show_counts: opt.grep_show_counts,
+            grep_strip_input_ansi: opt.grep_strip_input_ansi == "true",
+            group_by_category: opt.group_by_category && !opt.color_only,
+            group_changes: match opt.group_changes.as_ref() {
+                "interleaved" => GroupChanges::Interleaved,
+                "grouped" => GroupChanges::Grouped,
+                _ => fatal(
+                    "Invalid option for group-changes: Expected \"interleaved\" or \"grouped\".",
+                ),
+            },
+            guard_nested: opt.guard_nested,
             handle_merge_conflicts: !opt.raw,
+            high_contrast: opt.high_contrast,
+            high_contrast_default_colors: make_high_contrast_default_colors(opt.computed.color_mode),
+            high_contrast_ratio: opt.high_contrast_ratio,
             hostname: opt.env.hostname,
+            hunk_header_empty_range: match opt.hunk_header_empty_range.as_ref() {
+                "hide" => HunkHeaderEmptyRange::Hide,
+                "show" => HunkHeaderEmptyRange::Show,
+                _ => fatal(
+                    "Invalid option for hunk-header-empty-range: Expected \"hide\" or \"show\".",
+                ),
+            },
             hunk_header_file_style: styles["hunk-header-file-style"],
             hunk_header_line_number_style: styles["hunk-header-line-number-style"],
+            hunk_header_show_position: opt.hunk_header_show_position,
             hunk_header_style: styles["hunk-header-style"],
+            hunk_spacing: if opt.color_only { 0 } else { opt.hunk_spacing },
             hunk_header_style_include_file_path: if opt
                 .hunk_header_style
                 .split(' ')
@@ -358,11 +710,25 @@ impl From<cli::Opt> for Config {
             } else {
                 HunkHeaderIncludeCodeFragment::Yes
             },
-            hyperlinks: opt.hyperlinks,
+            hyperlinks,
             hyperlinks_commit_link_format: opt.hyperlinks_commit_link_format,
-            hyperlinks_file_link_format: opt.hyperlinks_file_link_format,
+            hyperlinks_file_link_format,
+            hyperlinks_hunk_header_range: opt.hyperlinks_hunk_header_range,
+            ignore_files: parse_ignore_files(&opt.ignore_files),
+            indent: opt.indent,
             inspect_raw_lines: opt.computed.inspect_raw_lines,
+            issue_links: opt
+                .issue_link_format
+                .iter()
+                .map(|issue_link_format| parse_issue_link_format(issue_link_format))
+                .collect(),
+            index_mode_change_style: styles["index-mode-change-style"],
+            index_style: styles["index-style"],
+            inline_highlight_budget: opt.inline_highlight_budget,
             inline_hint_style: styles["inline-hint-style"],
+            inline_only: opt.inline_only,
+            inline_single_word: opt.inline_single_word,
+            insertion_point_style: styles["insertion-point-style"],
             keep_plus_minus_markers: opt.keep_plus_minus_markers,
             line_fill_method: if !opt.computed.stdout_is_term && !TESTING {
                 // Don't write ANSI sequences (which rely on the width of the
@@ -373,10 +739,19 @@ impl From<cli::Opt> for Config {
                 line_fill_method
             },
             line_numbers: opt.line_numbers && !handlers::hunk::is_word_diff(),
-            line_numbers_format: LeftRight::new(
-                opt.line_numbers_left_format,
-                opt.line_numbers_right_format,
-            ),
+            line_numbers_format: if side_by_side_shared_gutter {
+                // The right column of line numbers, between the panels, is the only one.
+                LeftRight::new(
+                    String::new(),
+                    utils::tabs::expand(&opt.line_numbers_right_format, &gutter_tab_cfg),
+                )
+            } else {
+                LeftRight::new(
+                    utils::tabs::expand(&opt.line_numbers_left_format, &gutter_tab_cfg),
+                    utils::tabs::expand(&opt.line_numbers_right_format, &gutter_tab_cfg),
+                )
+            },
+            line_numbers_min_width: opt.line_numbers_min_width,
             line_numbers_style_leftright: LeftRight::new(
                 styles["line-numbers-left-style"],
                 styles["line-numbers-right-style"],
@@ -385,11 +760,16 @@ impl From<cli::Opt> for Config {
                 styles["line-numbers-minus-style"],
                 styles["line-numbers-plus-style"],
             ),
+            line_numbers_zero_pad: opt.line_numbers_zero_pad,
             line_numbers_zero_style: styles["line-numbers-zero-style"],
+            line_prefixes: opt.line_prefixes.as_deref().map(parse_line_prefixes),
             line_buffer_size: opt.line_buffer_size,
-            max_line_distance: opt.max_line_distance,
+            literal_content_escapes: opt.literal_content_escapes,
+            log_show_files: opt.log_show_files,
+            max_files: opt.max_files,
+            max_line_distance,
             max_line_distance_for_naively_paired_lines,
-            max_line_length: if opt.side_by_side {
+            max_line_length: if opt.side_by_side == SideBySide::True {
                 wrap_config.config_max_line_length(
                     opt.max_line_length,
                     opt.computed.available_terminal_width,
@@ -403,42 +783,126 @@ impl From<cli::Opt> for Config {
             merge_conflict_theirs_diff_header_style: styles
                 ["merge-conflict-theirs-diff-header-style"],
             merge_conflict_end_symbol: opt.merge_conflict_end_symbol,
+            minimap: opt.minimap,
             minus_emph_style: styles["minus-emph-style"],
             minus_empty_line_marker_style: styles["minus-empty-line-marker-style"],
             minus_file: opt.minus_file,
             minus_non_emph_style: styles["minus-non-emph-style"],
             minus_style: styles["minus-style"],
             navigate: opt.navigate,
+            navigate_prompt,
             navigate_regex,
+            no_color: opt.env.no_color,
+            notes_style: styles["notes-style"],
+            null_input: opt.null_input,
             null_style: Style::new(),
             null_syntect_style: SyntectStyle::default(),
+            output_format: match opt.output.as_ref() {
+                "ansi" => OutputFormat::Ansi,
+                "svg" => OutputFormat::Svg,
+                "html" => OutputFormat::Html,
+                _ => fatal("Invalid option for output: Expected \"ansi\", \"svg\" or \"html\"."),
+            },
             pager: opt.pager,
-            paging_mode: opt.computed.paging_mode,
+            pager_quit_if_one_screen: opt.pager_quit_if_one_screen == "true",
+            paging_mode: if opt.env.no_color && opt.pager_without_color == "false" {
+                PagingMode::Never
+            } else {
+                opt.computed.paging_mode
+            },
+            pairing_heuristic: match opt.pairing_heuristic.as_ref() {
+                "positional" => PairingHeuristic::Positional,
+                "similarity" => PairingHeuristic::Similarity,
+                _ => fatal(
+                    "Invalid option for pairing-heuristic: Expected \"positional\" or \"similarity\".",
+                ),
+            },
+            path_compress_dirs: opt.path_compress_dirs,
             plus_emph_style: styles["plus-emph-style"],
             plus_empty_line_marker_style: styles["plus-empty-line-marker-style"],
             plus_file: opt.plus_file,
             plus_non_emph_style: styles["plus-non-emph-style"],
             plus_style: styles["plus-style"],
+            preserve_commit_ansi: opt.preserve_commit_ansi,
             git_minus_style: styles["git-minus-style"],
             git_plus_style: styles["git-plus-style"],
+            reflow_prose: opt.reflow_prose,
             relative_paths: opt.relative_paths,
+            repo_root: opt.env.repo_root,
+            reverse: opt.reverse,
+            semantic_highlight,
+            show_index: opt.show_index,
+            show_insertion_point: opt.show_insertion_point,
+            show_legend: opt.show_legend,
+            show_moves_paired: opt.show_moves_paired,
+            show_trailing_context: opt.show_trailing_context,
             show_themes: opt.show_themes,
-            side_by_side: opt.side_by_side && !handlers::hunk::is_word_diff(),
+            side_by_side: opt.side_by_side == SideBySide::True && !handlers::hunk::is_word_diff(),
             side_by_side_data,
+            side_by_side_gap: opt.side_by_side_gap,
+            side_by_side_gap_style: styles["side-by-side-gap-style"],
+            sort_files: match opt.sort_files.as_ref() {
+                "path" => SortFiles::Path,
+                "changes" => SortFiles::Changes,
+                "none" => SortFiles::None,
+                _ => fatal(
+                    "Invalid option for sort-files: Expected \"path\", \"changes\" or \"none\".",
+                ),
+            },
+            stateful_highlighting: opt.stateful_highlighting,
+            stat_group_by_dir: opt.stat_group_by_dir,
+            status_map,
             styles_map,
+            syntax_highlight_context: opt.syntax_highlight_context == "true",
+            syntax_highlight_max_file_bytes: opt.syntax_highlight_max_file_bytes,
             syntax_set: opt.computed.syntax_set,
             syntax_theme: opt.computed.syntax_theme,
             tab_cfg: utils::tabs::TabCfg::new(opt.tab_width),
+            toc: opt.toc,
             tokenization_regex,
             true_color: opt.computed.true_color,
             truncation_symbol: format!("{}→{}", ansi::ANSI_SGR_REVERSE, ansi::ANSI_SGR_RESET),
+            two_file_backend: match opt.two_file_backend.as_ref() {
+                "git" => TwoFileBackend::Git,
+                "internal" => TwoFileBackend::Internal,
+                _ => fatal(
+                    "Invalid option for two-file-backend: Expected \"git\" or \"internal\".",
+                ),
+            },
+            validate_line_numbers: opt.validate_line_numbers,
             wrap_config,
+            wrap_symbol_style: MinusPlus::new(
+                styles["wrap-symbol-minus-style"],
+                styles["wrap-symbol-plus-style"],
+            ),
             whitespace_error_style: styles["whitespace-error-style"],
             zero_style: styles["zero-style"],
         }
     }
 }
 
+// The background which lines are tinted from with --change-heat: that of the syntax theme, or else
+// that of a typical terminal.
+fn make_change_heat_base_color(
+    syntax_theme: Option<&SyntaxTheme>,
+    mode: ColorMode,
+) -> (u8, u8, u8) {
+    match syntax_theme.and_then(|theme| theme.settings.background) {
+        Some(color) => (color.r, color.g, color.b),
+        None if mode == ColorMode::Light => (0xff, 0xff, 0xff),
+        None => (0x00, 0x00, 0x00),
+    }
+}
+
+// The foreground and background colors assumed by --high-contrast for text without them: those of
+// a typical terminal.
+fn make_high_contrast_default_colors(mode: ColorMode) -> ((u8, u8, u8), (u8, u8, u8)) {
+    match mode {
+        ColorMode::Light => ((0x00, 0x00, 0x00), (0xff, 0xff, 0xff)),
+        ColorMode::Dark => ((0xd0, 0xd0, 0xd0), (0x00, 0x00, 0x00)),
+    }
+}
+
 fn make_blame_palette(blame_palette: Option<String>, mode: ColorMode) -> Vec<String> {
     match (blame_palette, mode) {
         (Some(string), _) => string
@@ -456,6 +920,66 @@ fn make_blame_palette(blame_palette: Option<String>, mode: ColorMode) -> Vec<Str
     }
 }
 
+/// Parse the value of --focus-line, e.g. "new:42", into the side of the diff and the line number.
+fn parse_focus_line(focus_line: &str) -> (MinusPlusIndex, usize) {
+    let parsed = match focus_line.split_once(':') {
+        Some(("old", n)) => n.parse().ok().map(|n| (MinusPlusIndex::Minus, n)),
+        Some(("new", n)) => n.parse().ok().map(|n| (MinusPlusIndex::Plus, n)),
+        _ => None,
+    };
+    parsed.unwrap_or_else(|| {
+        fatal(format!(
+            "Invalid value for focus-line: {focus_line}. Expected \"old:N\" or \"new:N\"."
+        ))
+    })
+}
+
+/// Parse the globs given by --ignore-files into the set of paths to omit. As in .gitignore, a glob
+/// without a slash matches the file name in any directory, and `*` does not match a slash.
+fn parse_ignore_files(globs: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs {
+        let glob = if glob.contains('/') {
+            Cow::from(glob.trim_start_matches('/'))
+        } else {
+            Cow::from(format!("**/{glob}"))
+        };
+        match GlobBuilder::new(&glob).literal_separator(true).build() {
+            Ok(glob) => builder.add(glob),
+            Err(err) => fatal(format!("Invalid value for ignore-files: {err}.")),
+        };
+    }
+    builder
+        .build()
+        .unwrap_or_else(|err| fatal(format!("Invalid value for ignore-files: {err}.")))
+}
+
+/// Parse a value of --issue-link-format, e.g. "[A-Z]+-\d+ => https://example.com/browse/{id}", into
+/// the regex matching references to issues and the format of their URLs. The regex defaults to
+/// one matching GitHub issue references, e.g. "#123".
+fn parse_issue_link_format(issue_link_format: &str) -> (Regex, String) {
+    let (regex, format) = match issue_link_format.rsplit_once(" => ") {
+        Some((regex, format)) => (regex.trim(), format.trim()),
+        None => (r"#(\d+)", issue_link_format.trim()),
+    };
+    match Regex::new(regex) {
+        Ok(regex) => (regex, format.to_string()),
+        Err(err) => fatal(format!("Invalid value for issue-link-format: {err}.")),
+    }
+}
+
+/// Parse the value of --line-prefixes, e.g. "+ ,- ,  ", into the prefixes of added, removed, and
+/// unchanged lines.
+fn parse_line_prefixes(line_prefixes: &str) -> (String, String, String) {
+    match line_prefixes.split(',').collect::<Vec<_>>()[..] {
+        [plus, minus, zero] => (plus.to_string(), minus.to_string(), zero.to_string()),
+        _ => fatal(format!(
+            "Invalid value for line-prefixes: {line_prefixes}. \
+             Expected three prefixes separated by commas, e.g. \"+ ,- ,  \"."
+        )),
+    }
+}
+
 /// Did the user supply `option` on the command line?
 pub fn user_supplied_option(option: &str, arg_matches: &clap::ArgMatches) -> bool {
     arg_matches.value_source(option) == Some(ValueSource::CommandLine)
@@ -474,7 +998,10 @@ pub const HEADER_LEN: usize = 7;
 
 #[cfg(test)]
 pub mod tests {
+    use super::{parse_ignore_files, Config};
     use crate::cli;
+    use crate::env::DeltaEnv;
+    use crate::features::side_by_side::{Left, Right};
     use crate::tests::integration_test_utils;
     use crate::utils::bat::output::PagingMode;
     use std::fs::remove_file;
@@ -504,4 +1031,96 @@ pub mod tests {
         // syntax_set doesn't depend on gitconfig.
         remove_file(git_config_path).unwrap();
     }
+
+    #[test]
+    fn test_config_from_iter() {
+        let config = Config::from_iter(&DeltaEnv::default(), ["--side-by-side", "--width", "80"]);
+        assert!(config.side_by_side);
+        assert_eq!(config.decorations_width, cli::Width::Fixed(80));
+        assert_eq!(config.side_by_side_data[Left].width, 40);
+        assert_eq!(config.side_by_side_data[Right].width, 40);
+        assert!(config.git_config().is_none());
+    }
+
+    #[test]
+    fn test_paging_without_color() {
+        for (no_color, args, expected) in [
+            (
+                false,
+                &["--pager-without-color=false"][..],
+                PagingMode::QuitIfOneScreen,
+            ),
+            (true, &[][..], PagingMode::QuitIfOneScreen),
+            (
+                true,
+                &["--pager-without-color=false"][..],
+                PagingMode::Never,
+            ),
+            (
+                true,
+                &["--pager-without-color=false", "--paging=always"][..],
+                PagingMode::Never,
+            ),
+        ] {
+            let env = DeltaEnv {
+                no_color,
+                ..DeltaEnv::default()
+            };
+            let config = Config::from_iter(&env, args.iter().copied());
+            assert_eq!(config.no_color, no_color);
+            assert_eq!(config.paging_mode, expected, "{no_color} {args:?}");
+        }
+    }
+
+    #[test]
+    fn test_ignore_files_are_matched_like_gitignore() {
+        let ignore_files = |globs: &[&str]| {
+            parse_ignore_files(
+                &globs
+                    .iter()
+                    .map(|glob| glob.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        };
+        // A glob without a slash matches the file name, in any directory.
+        let globs = ignore_files(&["*.lock", "b.txt"]);
+        assert!(globs.is_match("Cargo.lock"));
+        assert!(globs.is_match("web/yarn.lock"));
+        assert!(globs.is_match("src/deep/b.txt"));
+        assert!(!globs.is_match("src/a.txt"));
+        assert!(!globs.is_match("src/b.txt.orig"));
+        // A glob with a slash matches the whole path, and `*` does not match a slash.
+        let globs = ignore_files(&["src/*.rs", "/vendor/**"]);
+        assert!(globs.is_match("src/a.rs"));
+        assert!(!globs.is_match("src/deep/a.rs"));
+        assert!(!globs.is_match("lib/src/a.rs"));
+        assert!(globs.is_match("vendor/x/y.c"));
+    }
+
+    #[test]
+    fn test_ignore_files_in_git_config() {
+        let git_config_contents = b"
+[delta]
+    ignore-files = *.lock
+    ignore-files = vendor/**
+";
+        let git_config_path = "delta__test_ignore_files_in_git_config.gitconfig";
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &[],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        assert!(config.ignore_files.is_match("Cargo.lock"));
+        assert!(config.ignore_files.is_match("vendor/x.c"));
+        assert!(!config.ignore_files.is_match("src/a.rs"));
+        // The command line takes precedence.
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &["--ignore-files", "*.md"],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        assert!(config.ignore_files.is_match("README.md"));
+        assert!(!config.ignore_files.is_match("Cargo.lock"));
+        remove_file(git_config_path).unwrap();
+    }
 }
@@ -1,6 +1,7 @@
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::PathBuf;
 
 use bytelines::ByteLines;
 
@@ -8,13 +9,27 @@ use crate::ansi;
 use crate::config::delta_unreachable;
 use crate::config::Config;
 use crate::config::GrepType;
+use crate::config::OutputFormat;
+use crate::config::SortFiles;
 use crate::features;
 use crate::handlers::grep;
 use crate::handlers::hunk_header::{AmbiguousDiffMinusCounter, ParsedHunkHeader};
 use crate::handlers::{self, merge_conflict};
+use crate::minusplus::MinusPlusIndex::Plus;
 use crate::paint::Painter;
 use crate::style::DecorationStyle;
 use crate::utils;
+use crate::utils::blame::{AgeAnnotator, RemovedLineBlamer};
+use crate::utils::buffer_limit::BufferLimit;
+use crate::utils::digest::Digest;
+use crate::utils::encoding::Encoding;
+use crate::utils::function_context::FunctionContextFinder;
+use crate::utils::highlighting_context::HighlightingContext;
+use crate::utils::line_number_validator::LineNumberValidator;
+use crate::utils::moved_blocks::MovedBlocks;
+use crate::utils::null_input::{NullDelimitedRecords, NullInput};
+use crate::utils::text_diff::{self, TextDiffSource};
+use crate::utils::trailing_context::{TrailingContext, WorkingTree};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum State {
@@ -112,13 +127,237 @@ pub struct StateMachine<'a> {
     pub handled_diff_header_header_line_file_pair: Option<(String, String)>,
     pub blame_key_colors: HashMap<String, String>,
     pub minus_line_counter: AmbiguousDiffMinusCounter,
+    // Whether any added or removed hunk line has been seen (used by --exit-code).
+    pub changes_found: bool,
+    // Whether the current diff header declared 100% similarity, and the pure renames that are
+    // waiting to be written as a table (used by --compact-renames).
+    pub pure_rename: bool,
+    pub pending_compact_renames: Vec<(String, String)>,
+    // The diff stat lines waiting to be written grouped by directory (used by
+    // --stat-group-by-dir).
+    pub pending_diff_stat: Vec<handlers::diff_stat::DiffStatEntry>,
+    // The limit on the bytes of the pending diff stat lines (see --buffer-limit-bytes).
+    pub diff_stat_buffer_limit: BufferLimit,
+    // The encoding that lines of the current file were transcoded from, if any.
+    pub transcoded_from: Option<Encoding>,
+    // The encoding of the lines of content of the current file, decided at the first of them from
+    // the byte order mark it starts with, if any (with --assume-encoding=auto).
+    pub file_encoding: Option<Encoding>,
+    // The number of sections of the current `GIT binary patch` seen so far, if in one.
+    pub binary_patch_sections: Option<usize>,
+    // The source of the ages shown by --annotate-age, when git blame is available.
+    pub age_annotator: Option<AgeAnnotator>,
+    // The source of the authors shown by --blame-removed, when git blame is available.
+    pub removed_line_blamer: Option<RemovedLineBlamer>,
+    // Supplies hunk-header function context from .gitattributes diff drivers when git omits it.
+    pub function_context_finder: FunctionContextFinder,
+    // The grep output of the current file, held back until its number of matches is known, and
+    // that number while the held back lines are written (used by --grep-show-counts).
+    pub pending_grep_file: Option<grep::PendingGrepFile>,
+    pub grep_match_count: Option<usize>,
+    pub grep_buffer_limit: BufferLimit,
+    // Whether the current line is in the notes of a commit, following a "Notes:" line.
+    pub in_commit_notes: bool,
+    // The number of files seen so far (used by --max-files).
+    pub n_files: usize,
+    // Whether the current file is omitted because its path matches --ignore-files.
+    pub ignoring_file: bool,
+    // Whether the current file is shown as a one-line header because it is not --focus-file.
+    pub collapsing_file: bool,
+    // The source of the lines shown after the last hunk of a file by --show-trailing-context,
+    // and the file and line number at which the lines following the current hunk start.
+    pub trailing_context: Option<TrailingContext>,
+    pub pending_trailing_context: Option<(PathBuf, usize)>,
+    // The removed and added lines of the current file (used by --show-moves-paired).
+    pub moved_blocks: Option<MovedBlocks>,
+    // The counts of the hunks and lines of the current file (used by --file-footer).
+    pub file_footer: Option<handlers::hunk::FileFooter>,
+    // The source of text diffs of files which git considers binary (used by --force-text), and
+    // the abbreviated blob ids of the current file, from its "index" line (also used by
+    // --compute-function-context).
+    pub text_diff_source: Option<Box<dyn TextDiffSource>>,
+    pub blob_ids: Option<(String, String)>,
+    // The old and new modes of the current file from its "old mode" and "new mode" lines, and its
+    // painted "index" line, waiting to be written below its file header (used by --show-index).
+    pub mode_change: Option<(String, String)>,
+    pub pending_index_line: Option<String>,
+    // The width of the largest line number of the file of each hunk (used by
+    // --line-numbers-zero-pad), and the number of hunk headers of the input seen so far, which
+    // indexes the values read ahead for each hunk.
+    pub hunk_line_number_widths: Vec<usize>,
+    pub n_hunks: usize,
+    // The position of each hunk among the hunks of its file, and the number of hunks of the file
+    // (used by --hunk-header-show-position).
+    pub hunk_positions: Vec<(usize, usize)>,
+    // The number of bytes of the hunks of the file of each hunk, read ahead with
+    // --syntax-highlight-max-file-bytes.
+    pub hunk_file_sizes: Vec<usize>,
+    // The change of line endings of the file of each hunk, if it changes nothing else, and the
+    // number of removed and added lines left in the current hunk of such a file, which are
+    // skipped (used by --collapse-eol-only).
+    pub hunk_eol_changes: Vec<Option<handlers::eol_change::EolChange>>,
+    pub eol_only_hunk_remaining: Option<(usize, usize)>,
+    // The category of each file in the input which is yet to be reached, that of the current
+    // file, and that whose header was written last (used by --group-by-category).
+    pub file_categories: VecDeque<handlers::file_category::FileCategory>,
+    pub file_category: Option<handlers::file_category::FileCategory>,
+    pub shown_file_category: Option<handlers::file_category::FileCategory>,
+    // The changed files of each commit in the input which is yet to be reached, and those of the
+    // current commit, waiting to be written before its diff (used by --log-show-files).
+    pub commit_files: VecDeque<Vec<String>>,
+    pub pending_commit_files: Option<Vec<String>>,
+    // The source of the lines preceding each hunk (used by --stateful-highlighting).
+    pub highlighting_context: Option<HighlightingContext>,
+    pub digest: Option<Digest>,
+    pub line_number_validator: Option<LineNumberValidator>,
+    // Whether the input is NUL-delimited, and its pending records if so (see --null-input).
+    pub null_input: NullInput,
+    // Whether the legend of --show-legend is yet to be written.
+    pub pending_legend: bool,
 }
 
-pub fn delta<I>(lines: ByteLines<I>, writer: &mut dyn Write, config: &Config) -> std::io::Result<()>
+/// Process the input and write the output. An Ok result indicates whether the input contained
+/// changes, i.e. added or removed lines.
+pub fn delta<I>(
+    lines: ByteLines<I>,
+    writer: &mut dyn Write,
+    config: &Config,
+) -> std::io::Result<bool>
 where
     I: BufRead,
 {
-    StateMachine::new(writer, config).consume(lines)
+    consume_through_layers(&output_layers(config), lines, writer, config)
+}
+
+/// A writer transforming the output on its way to the writer given to delta.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputLayer {
+    Svg,
+    Html,
+    OutputEnd,
+    Plain,
+    Minimap,
+    Grid,
+    Indent,
+    Dithering,
+    Contrast,
+}
+
+/// The layers enabled by `config`, outermost first: the output of each layer is written to the
+/// layer preceding it.
+fn output_layers(config: &Config) -> Vec<OutputLayer> {
+    let mut layers = Vec::new();
+    match config.output_format {
+        OutputFormat::Svg => layers.push(OutputLayer::Svg),
+        OutputFormat::Html => layers.push(OutputLayer::Html),
+        OutputFormat::Ansi => {
+            if config.final_newline.is_some() || config.final_reset {
+                layers.push(OutputLayer::OutputEnd);
+            }
+            if config.no_color {
+                layers.push(OutputLayer::Plain);
+            }
+        }
+    }
+    for (layer, enabled) in [
+        (OutputLayer::Minimap, config.minimap),
+        (OutputLayer::Grid, config.debug_grid > 0),
+        (OutputLayer::Indent, config.indent > 0),
+        (OutputLayer::Dithering, config.color_dither),
+        (OutputLayer::Contrast, config.high_contrast),
+    ] {
+        if enabled {
+            layers.push(layer);
+        }
+    }
+    layers
+}
+
+impl OutputLayer {
+    fn wrap<'w>(self, writer: &'w mut dyn Write, config: &Config) -> Box<dyn LayerWriter + 'w> {
+        match self {
+            OutputLayer::Svg => Box::new(ansi::svg::SvgWriter::new(writer, config)),
+            OutputLayer::Html => Box::new(ansi::html::HtmlWriter::new(writer, config)),
+            OutputLayer::OutputEnd => {
+                Box::new(ansi::output_end::OutputEndWriter::new(writer, config))
+            }
+            OutputLayer::Plain => Box::new(ansi::plain::PlainWriter::new(writer)),
+            OutputLayer::Minimap => Box::new(utils::minimap::MinimapWriter::new(writer, config)),
+            OutputLayer::Grid => Box::new(ansi::grid::grid_writer(writer, config.debug_grid)),
+            OutputLayer::Indent => {
+                Box::new(utils::indent::IndentingWriter::new(writer, config.indent))
+            }
+            OutputLayer::Dithering => Box::new(ansi::dither::dithering_writer(writer)),
+            OutputLayer::Contrast => Box::new(ansi::contrast::contrast_writer(writer, config)),
+        }
+    }
+}
+
+// Process the input, writing the output through `layers` to `writer`.
+fn consume_through_layers<I>(
+    layers: &[OutputLayer],
+    lines: ByteLines<I>,
+    writer: &mut dyn Write,
+    config: &Config,
+) -> std::io::Result<bool>
+where
+    I: BufRead,
+{
+    let Some((layer, inner_layers)) = layers.split_first() else {
+        return StateMachine::new(writer, config).consume(lines);
+    };
+    let mut writer = layer.wrap(writer, config);
+    let changes_found = consume_through_layers(inner_layers, lines, &mut writer, config)?;
+    writer.finish()?;
+    Ok(changes_found)
+}
+
+/// The writer of an output layer.
+trait LayerWriter: Write {
+    /// Write whatever is held back, once all the output has been written to the layer.
+    fn finish(self: Box<Self>) -> std::io::Result<()>;
+}
+
+impl LayerWriter for ansi::svg::SvgWriter<'_> {
+    fn finish(self: Box<Self>) -> std::io::Result<()> {
+        ansi::svg::SvgWriter::finish(*self)
+    }
+}
+
+impl LayerWriter for ansi::html::HtmlWriter<'_> {
+    fn finish(self: Box<Self>) -> std::io::Result<()> {
+        ansi::html::HtmlWriter::finish(*self)
+    }
+}
+
+impl LayerWriter for ansi::output_end::OutputEndWriter<'_> {
+    fn finish(self: Box<Self>) -> std::io::Result<()> {
+        ansi::output_end::OutputEndWriter::finish(*self)
+    }
+}
+
+impl LayerWriter for ansi::plain::PlainWriter<'_> {
+    fn finish(self: Box<Self>) -> std::io::Result<()> {
+        ansi::plain::PlainWriter::finish(*self)
+    }
+}
+
+impl LayerWriter for utils::minimap::MinimapWriter<'_> {
+    fn finish(self: Box<Self>) -> std::io::Result<()> {
+        utils::minimap::MinimapWriter::finish(*self)
+    }
+}
+
+impl LayerWriter for utils::indent::IndentingWriter<'_> {
+    fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+        self.flush()
+    }
+}
+
+impl<F: FnMut(&str) -> String> LayerWriter for ansi::line_buffered::LineBufferedWriter<'_, F> {
+    fn finish(mut self: Box<Self>) -> std::io::Result<()> {
+        self.flush()
+    }
 }
 
 impl<'a> StateMachine<'a> {
@@ -140,60 +379,345 @@ impl<'a> StateMachine<'a> {
             config,
             blame_key_colors: HashMap::new(),
             minus_line_counter: AmbiguousDiffMinusCounter::not_needed(),
+            changes_found: false,
+            pure_rename: false,
+            pending_compact_renames: Vec::new(),
+            pending_diff_stat: Vec::new(),
+            diff_stat_buffer_limit: BufferLimit::new("stat-group-by-dir", config),
+            transcoded_from: None,
+            file_encoding: None,
+            binary_patch_sections: None,
+            age_annotator: AgeAnnotator::from_config(config),
+            removed_line_blamer: RemovedLineBlamer::from_config(config),
+            function_context_finder: FunctionContextFinder::default(),
+            pending_grep_file: None,
+            grep_match_count: None,
+            grep_buffer_limit: BufferLimit::new("grep-show-counts", config),
+            in_commit_notes: false,
+            n_files: 0,
+            ignoring_file: false,
+            collapsing_file: false,
+            trailing_context: TrailingContext::from_config(config),
+            pending_trailing_context: None,
+            moved_blocks: config.show_moves_paired.then(MovedBlocks::default),
+            file_footer: None,
+            text_diff_source: text_diff::from_config(config),
+            blob_ids: None,
+            mode_change: None,
+            pending_index_line: None,
+            hunk_line_number_widths: Vec::new(),
+            n_hunks: 0,
+            hunk_positions: Vec::new(),
+            hunk_file_sizes: Vec::new(),
+            hunk_eol_changes: Vec::new(),
+            eol_only_hunk_remaining: None,
+            file_categories: VecDeque::new(),
+            file_category: None,
+            shown_file_category: None,
+            commit_files: VecDeque::new(),
+            pending_commit_files: None,
+            highlighting_context: if config.stateful_highlighting {
+                Some(HighlightingContext::new(Box::new(WorkingTree)))
+            } else {
+                None
+            },
+            digest: config.digest.then(Digest::default),
+            line_number_validator: config
+                .validate_line_numbers
+                .then(LineNumberValidator::default),
+            null_input: if config.null_input {
+                NullInput::Yes(NullDelimitedRecords::default())
+            } else {
+                NullInput::Undecided
+            },
+            pending_legend: config.show_legend && !config.color_only,
         }
     }
 
-    fn consume<I>(&mut self, mut lines: ByteLines<I>) -> std::io::Result<()>
+    pub fn consume<I>(&mut self, mut lines: ByteLines<I>) -> std::io::Result<bool>
     where
         I: BufRead,
     {
-        while let Some(Ok(raw_line_bytes)) = lines.next() {
-            self.ingest_line(raw_line_bytes);
-
-            if self.source == Source::Unknown {
-                self.source = detect_source(&self.line);
-                // Handle (rare) plain `diff -u file1 file2` header. Done here to avoid having
-                // to introduce and handle a Source::DiffUnifiedAmbiguous variant everywhere.
-                if self.line.starts_with("--- ") {
-                    self.minus_line_counter = AmbiguousDiffMinusCounter::prepare_to_count();
+        let sort_files = self.config.sort_files != SortFiles::None;
+        if self.config.toc
+            || self.config.line_numbers_zero_pad
+            || sort_files
+            || self.config.group_by_category
+            || self.config.hunk_header_show_position
+            || self.config.collapse_eol_only
+            || self.config.syntax_highlight_max_file_bytes > 0
+        {
+            let feature = if self.config.toc {
+                "toc"
+            } else if self.config.line_numbers_zero_pad {
+                "line-numbers-zero-pad"
+            } else if sort_files {
+                "sort-files"
+            } else if self.config.group_by_category {
+                "group-by-category"
+            } else if self.config.hunk_header_show_position {
+                "hunk-header-show-position"
+            } else if self.config.collapse_eol_only {
+                "collapse-eol-only"
+            } else {
+                "syntax-highlight-max-file-bytes"
+            };
+            let mut limit = BufferLimit::new(feature, self.config);
+            let input = utils::buffer_limit::read_input(&mut lines, &mut limit);
+            if limit.is_exceeded() {
+                // Write the diff as if the input had not been read ahead.
+                let mut input_lines = ByteLines::new(&input[..]);
+                while let Some(Ok(raw_line_bytes)) = input_lines.next() {
+                    self.consume_input_line(raw_line_bytes)?;
+                }
+                return self.consume_lines(lines);
+            }
+            let input = if sort_files || self.config.group_by_category {
+                utils::sort_files::sort_files(&input, self.config)
+            } else {
+                input
+            };
+            if self.config.toc && !input.is_empty() {
+                self.emit_pending_legend()?;
+                self.emit_toc(&input)?;
+            }
+            if self.config.line_numbers_zero_pad {
+                self.hunk_line_number_widths =
+                    features::line_numbers::get_hunk_line_number_widths(&input);
+            }
+            if self.config.group_by_category {
+                self.file_categories = handlers::file_category::get_file_categories(&input);
+            }
+            if self.config.log_show_files {
+                self.commit_files = handlers::commit_meta::get_commit_files(&input, self.config);
+            }
+            if self.config.hunk_header_show_position {
+                self.hunk_positions = handlers::hunk_header::get_hunk_positions(&input);
+            }
+            if self.config.collapse_eol_only {
+                self.hunk_eol_changes = handlers::eol_change::get_hunk_eol_changes(&input);
+            }
+            if self.config.syntax_highlight_max_file_bytes > 0 {
+                self.hunk_file_sizes = handlers::hunk_header::get_hunk_file_sizes(&input);
+            }
+            return self.consume_lines(ByteLines::new(&input[..]));
+        }
+        if self.config.log_show_files {
+            return self.consume_commits(lines);
+        }
+        self.consume_lines(lines)
+    }
+
+    // Process the input one commit at a time, reading each commit up to the next `commit` line
+    // before writing it, so that its changed files can be listed before its diff (used by
+    // --log-show-files).
+    fn consume_commits<I>(&mut self, mut lines: ByteLines<I>) -> std::io::Result<bool>
+    where
+        I: BufRead,
+    {
+        let mut limit = BufferLimit::new("log-show-files", self.config);
+        let mut commit = Vec::new();
+        while let Some(Ok(line)) = lines.next() {
+            let text = ansi::strip_ansi_codes(&String::from_utf8_lossy(line));
+            if self.config.commit_regex.is_match(&text) {
+                self.consume_commit(&commit)?;
+                commit.clear();
+                limit.reset();
+            }
+            commit.extend_from_slice(line);
+            commit.push(b'\n');
+            if limit.add(line.len() + 1) {
+                // Write the commit as if it had not been read ahead.
+                let mut commit_lines = ByteLines::new(&commit[..]);
+                while let Some(Ok(raw_line_bytes)) = commit_lines.next() {
+                    self.consume_input_line(raw_line_bytes)?;
                 }
+                return self.consume_lines(lines);
             }
+        }
+        self.consume_commit(&commit)?;
+        self.consume_lines(ByteLines::new(&[][..]))
+    }
 
-            // Every method named handle_* must return std::io::Result<bool>.
-            // The bool indicates whether the line has been handled by that
-            // method (in which case no subsequent handlers are permitted to
-            // handle it).
-            let _ = self.handle_commit_meta_header_line()?
-                || self.handle_diff_stat_line()?
-                || self.handle_diff_header_diff_line()?
-                || self.handle_diff_header_file_operation_line()?
-                || self.handle_diff_header_minus_line()?
-                || self.handle_diff_header_plus_line()?
-                || self.handle_hunk_header_line()?
-                || self.handle_diff_header_mode_line()?
-                || self.handle_diff_header_misc_line()?
-                || self.handle_submodule_log_line()?
-                || self.handle_submodule_short_line()?
-                || self.handle_merge_conflict_line()?
-                || self.handle_hunk_line()?
-                || self.handle_git_show_file_line()?
-                || self.handle_blame_line()?
-                || self.handle_grep_line()?
-                || self.should_skip_line()
-                || self.emit_line_unchanged()?;
+    // Process the lines of a commit read ahead by `consume_commits`.
+    fn consume_commit(&mut self, commit: &[u8]) -> std::io::Result<()> {
+        self.commit_files = handlers::commit_meta::get_commit_files(commit, self.config);
+        let mut commit_lines = ByteLines::new(commit);
+        while let Some(Ok(raw_line_bytes)) = commit_lines.next() {
+            self.consume_input_line(raw_line_bytes)?;
+        }
+        Ok(())
+    }
+
+    fn consume_lines<I>(&mut self, mut lines: ByteLines<I>) -> std::io::Result<bool>
+    where
+        I: BufRead,
+    {
+        while let Some(Ok(raw_line_bytes)) = lines.next() {
+            self.consume_input_line(raw_line_bytes)?;
+        }
+        if let NullInput::Yes(records) = &mut self.null_input {
+            if let Some(line) = records.finish() {
+                self.consume_line(&line)?;
+            }
         }
 
+        self.emit_compact_renames()?;
+        self.emit_grouped_diff_stat()?;
+        self.emit_pending_grep_file()?;
+        self.emit_trailing_context()?;
+        self.emit_moved_blocks()?;
+        self.emit_file_footer()?;
         self.handle_pending_line_with_diff_name()?;
         self.painter.paint_buffered_minus_and_plus_lines();
         self.painter.emit()?;
+        self.emit_transcoding_notice()?;
+        self.emit_max_files_summary()?;
+        if let Some(validator) = self.line_number_validator.as_mut() {
+            if let Some(mismatch) = validator.finish(self.painter.hunk_line_numbers[Plus]) {
+                eprintln!("{mismatch}");
+            }
+        }
+        if let Some(digest) = self.digest.as_mut() {
+            digest.finish()?;
+        }
+        Ok(self.changes_found)
+    }
+
+    // Process a line of the input, which holds any number of records if it is NUL-delimited.
+    fn consume_input_line(&mut self, raw_line_bytes: &[u8]) -> std::io::Result<()> {
+        self.null_input.decide(raw_line_bytes);
+        match &mut self.null_input {
+            NullInput::Yes(records) => {
+                for line in records.push(raw_line_bytes) {
+                    self.consume_line(&line)?;
+                }
+                Ok(())
+            }
+            _ => self.consume_line(raw_line_bytes),
+        }
+    }
+
+    // Process a line of the input.
+    fn consume_line(&mut self, raw_line_bytes: &[u8]) -> std::io::Result<()> {
+        self.emit_pending_legend()?;
+        self.ingest_line(raw_line_bytes);
+
+        if self.source == Source::Unknown {
+            self.source = detect_source(&self.line);
+            // Handle (rare) plain `diff -u file1 file2` header. Done here to avoid having
+            // to introduce and handle a Source::DiffUnifiedAmbiguous variant everywhere.
+            if self.line.starts_with("--- ") {
+                self.minus_line_counter = AmbiguousDiffMinusCounter::prepare_to_count();
+            }
+        }
+
+        if !self.pending_compact_renames.is_empty() && !self.test_compact_renames_continue() {
+            self.emit_compact_renames()?;
+        }
+        if !self.pending_diff_stat.is_empty() && !self.test_diff_stat_continue() {
+            self.emit_grouped_diff_stat()?;
+        }
+        if self.pending_grep_file.is_some() && !self.test_pending_grep_file_continue() {
+            self.emit_pending_grep_file()?;
+        }
+        if self.pending_trailing_context.is_some() && !self.test_hunks_continue() {
+            self.emit_trailing_context()?;
+        }
+        if self.moved_blocks.as_ref().is_some_and(|m| !m.is_empty()) && !self.test_hunks_continue()
+        {
+            self.emit_moved_blocks()?;
+        }
+        if self.file_footer.is_some() && !self.test_hunks_continue() {
+            self.emit_file_footer()?;
+        }
+
+        self.handle_line()?;
+        if self.digest.is_some() {
+            let is_change_header_line = self.test_diff_header_change_line();
+            if let Some(digest) = self.digest.as_mut() {
+                digest.add_line(
+                    &self.state,
+                    &self.minus_file,
+                    &self.plus_file,
+                    &self.line,
+                    is_change_header_line,
+                );
+            }
+        }
+        if let Some(validator) = self.line_number_validator.as_mut() {
+            if let Some(mismatch) = validator.add_line(
+                &self.state,
+                &self.minus_file,
+                &self.plus_file,
+                self.painter.hunk_line_numbers[Plus],
+            ) {
+                eprintln!("{mismatch}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Handle the current line.
+    pub fn handle_line(&mut self) -> std::io::Result<()> {
+        // Every method named handle_* must return std::io::Result<bool>.
+        // The bool indicates whether the line has been handled by that
+        // method (in which case no subsequent handlers are permitted to
+        // handle it).
+        let _ = self.handle_hunk_count_line()?
+            || self.handle_file_category_line()?
+            || self.handle_diff_header_change_line()?
+            || self.handle_ignored_file_line()?
+            || self.handle_max_files_line()?
+            || self.handle_unfocused_file_line()?
+            || self.handle_add_p_prompt_line()?
+            || self.handle_commit_meta_header_line()?
+            || self.handle_commit_notes_line()?
+            || self.handle_commit_merge_line()?
+            || self.handle_commit_meta_field_line()?
+            || self.handle_commit_message_line()?
+            || self.handle_oneline_commit_line()?
+            || self.handle_diff_stat_line()?
+            || self.handle_name_status_line()?
+            || self.handle_eol_only_hunk_line()?
+            || self.handle_diff_header_diff_line()?
+            || self.handle_binary_patch_line()?
+            || self.handle_diff_header_file_operation_line()?
+            || self.handle_diff_header_minus_line()?
+            || self.handle_diff_header_plus_line()?
+            || self.handle_hunk_header_line()?
+            || self.handle_diff_header_mode_line()?
+            || self.handle_diff_header_similarity_line()?
+            || self.handle_diff_header_blob_ids_line()?
+            || self.handle_force_text_line()?
+            || self.handle_diff_header_index_line()?
+            || self.handle_diff_header_misc_line()?
+            || self.handle_submodule_log_line()?
+            || self.handle_submodule_short_line()?
+            || self.handle_merge_conflict_line()?
+            || self.handle_hunk_line()?
+            || self.handle_git_show_file_line()?
+            || self.handle_blame_line()?
+            || self.handle_grep_line()?
+            || self.should_skip_line()
+            || self.emit_line_unchanged()?;
         Ok(())
     }
 
     fn ingest_line(&mut self, raw_line_bytes: &[u8]) {
-        match String::from_utf8(raw_line_bytes.to_vec()) {
-            Ok(utf8) => self.ingest_line_utf8(utf8),
-            Err(_) => {
-                let raw_line = String::from_utf8_lossy(raw_line_bytes);
+        // Every line of content of a UTF-16 file is decoded, including those which happen to be
+        // valid UTF-8.
+        let utf16 = self.get_utf16_content_encoding(raw_line_bytes);
+        match (utf16, String::from_utf8(raw_line_bytes.to_vec())) {
+            (None, Ok(utf8)) => self.ingest_line_utf8(utf8),
+            (utf16, _) => {
+                let (raw_line, transcoded_from) = utils::encoding::decode_non_utf8_line(
+                    raw_line_bytes,
+                    utf16.unwrap_or(self.config.assume_encoding),
+                );
+                if transcoded_from.is_some() {
+                    self.transcoded_from = transcoded_from;
+                }
                 let truncated_len = utils::round_char_boundary::floor_char_boundary(
                     &raw_line,
                     self.config.max_line_length,
@@ -204,6 +728,25 @@ impl<'a> StateMachine<'a> {
         }
     }
 
+    // Return the encoding of the line if it is a line of content of a UTF-16 file. The encoding of
+    // the file is decided at its first line of content.
+    fn get_utf16_content_encoding(&mut self, raw_line_bytes: &[u8]) -> Option<Encoding> {
+        let is_content_line = matches!(
+            self.state,
+            State::HunkHeader(..)
+                | State::HunkZero(..)
+                | State::HunkMinus(..)
+                | State::HunkPlus(..)
+        ) && matches!(raw_line_bytes.first(), Some(b' ' | b'+' | b'-'));
+        if !is_content_line || self.config.assume_encoding != Encoding::Auto {
+            return None;
+        }
+        let encoding = *self
+            .file_encoding
+            .get_or_insert_with(|| utils::encoding::detect_encoding(raw_line_bytes));
+        matches!(encoding, Encoding::Utf16Le | Encoding::Utf16Be).then_some(encoding)
+    }
+
     fn ingest_line_utf8(&mut self, raw_line: String) {
         self.raw_line = raw_line;
         // When a file has \r\n line endings, git sometimes adds ANSI escape sequences between the
@@ -218,6 +761,11 @@ impl<'a> StateMachine<'a> {
                 );
             }
         }
+        if self.config.literal_content_escapes {
+            if let Cow::Owned(raw_line) = ansi::escape_osc8_hyperlinks(&self.raw_line) {
+                self.raw_line = raw_line;
+            }
+        }
         if self.config.max_line_length > 0
             && self.raw_line.len() > self.config.max_line_length
             // Do not truncate long hunk headers
@@ -254,6 +802,22 @@ impl<'a> StateMachine<'a> {
         Ok(handled_line)
     }
 
+    /// Emit a notice if lines of the file just processed were transcoded.
+    pub fn emit_transcoding_notice(&mut self) -> std::io::Result<()> {
+        if let Some(encoding) = self.transcoded_from.take() {
+            if !self.config.color_only {
+                writeln!(
+                    self.painter.writer,
+                    "{}",
+                    ansi_term::Style::new()
+                        .dimmed()
+                        .paint(format!("(transcoded from {})", encoding.name()))
+                )?;
+            }
+        }
+        Ok(())
+    }
+
     /// Should a handle_* function be called on this element?
     // TODO: I'm not sure the above description is accurate; I think this
     // function needs a more accurate name.
@@ -1,11 +1,19 @@
+use std::collections::HashMap;
+
+use lazy_static::lazy_static;
 use regex::Regex;
 
 use unicode_segmentation::UnicodeSegmentation;
 use unicode_width::UnicodeWidthStr;
 
 use crate::align;
+use crate::config::PairingHeuristic;
 use crate::minusplus::MinusPlus;
 
+// The largest number of (minus line, plus line) combinations for which the distance is computed
+// when pairing lines by similarity. Larger blocks of changed lines are paired positionally.
+const MAX_LINE_PAIRS_FOR_SIMILARITY_PAIRING: usize = 10_000;
+
 /// Infer the edit operations responsible for the differences between a collection of old and new
 /// lines. A "line" is a string. An annotated line is a Vec of (op, &str) pairs, where the &str
 /// slices are slices of the line, and their concatenation equals the line. Return the input minus
@@ -19,6 +27,9 @@ use crate::minusplus::MinusPlus;
 /// `noop_deletions[i]` is the appropriate deletion operation tag to be used for `minus_lines[i]`;
 /// `noop_deletions` is guaranteed to be the same length as `minus_lines`. The equivalent statements
 /// hold for `plus_insertions` and `plus_lines`.
+///
+/// `pairing_heuristic` determines which homologous minus and plus lines are paired: each minus
+/// line with the first homologous plus line, or the pairs which are most similar in total.
 #[allow(clippy::too_many_arguments)]
 #[allow(clippy::type_complexity)]
 pub fn infer_edits<'a, EditOperation>(
@@ -31,6 +42,7 @@ pub fn infer_edits<'a, EditOperation>(
     tokenization_regex: &Regex,
     max_line_distance: f64,
     max_line_distance_for_naively_paired_lines: f64,
+    pairing_heuristic: PairingHeuristic,
 ) -> (
     Vec<Vec<(EditOperation, &'a str)>>,  // annotated minus lines
     Vec<Vec<(EditOperation, &'a str)>>,  // annotated plus lines
@@ -39,54 +51,81 @@ pub fn infer_edits<'a, EditOperation>(
 where
     EditOperation: Copy + PartialEq + std::fmt::Debug,
 {
+    let is_homologous = |distance: f64| {
+        minus_lines.len() == plus_lines.len()
+            && distance <= max_line_distance_for_naively_paired_lines
+            || distance <= max_line_distance
+    };
+    let annotate_pair = |minus_index: usize, plus_index: usize| {
+        let (minus_line, plus_line) = (minus_lines[minus_index], plus_lines[plus_index]);
+        let alignment = align::Alignment::new(
+            tokenize(minus_line, tokenization_regex),
+            tokenize(plus_line, tokenization_regex),
+        );
+        annotate(
+            alignment,
+            noop_deletions[minus_index],
+            deletion,
+            noop_insertions[plus_index],
+            insertion,
+            minus_line,
+            plus_line,
+        )
+    };
+
+    // The annotations of the homologous pairs of lines, computed while pairing the lines, of
+    // which those of the lines which are paired are used below.
+    let mut annotations = HashMap::new();
+    let mut homologous_distance = |minus_index: usize, plus_index: usize| {
+        let annotation = annotate_pair(minus_index, plus_index);
+        let distance = Some(annotation.2).filter(|d| is_homologous(*d));
+        if distance.is_some() {
+            annotations.insert((minus_index, plus_index), annotation);
+        }
+        distance
+    };
+    let pairs = match pairing_heuristic {
+        PairingHeuristic::Similarity
+            if minus_lines.len() * plus_lines.len() <= MAX_LINE_PAIRS_FOR_SIMILARITY_PAIRING =>
+        {
+            pair_lines_by_similarity(minus_lines.len(), plus_lines.len(), homologous_distance)
+        }
+        _ => pair_lines_positionally(minus_lines.len(), plus_lines.len(), |m, p| {
+            homologous_distance(m, p).is_some()
+        }),
+    };
+
     let mut annotated_minus_lines = Vec::<Vec<(EditOperation, &str)>>::new();
     let mut annotated_plus_lines = Vec::<Vec<(EditOperation, &str)>>::new();
     let mut line_alignment = Vec::<(Option<usize>, Option<usize>)>::new();
 
-    let mut plus_index = 0; // plus lines emitted so far
-
-    'minus_lines_loop: for (minus_index, minus_line) in minus_lines.iter().enumerate() {
-        let mut considered = 0; // plus lines considered so far as match for minus_line
-        for plus_line in &plus_lines[plus_index..] {
-            let alignment = align::Alignment::new(
-                tokenize(minus_line, tokenization_regex),
-                tokenize(plus_line, tokenization_regex),
-            );
-            let (annotated_minus_line, annotated_plus_line, distance) = annotate(
-                alignment,
-                noop_deletions[minus_index],
-                deletion,
-                noop_insertions[plus_index],
-                insertion,
-                minus_line,
-                plus_line,
-            );
-            if minus_lines.len() == plus_lines.len()
-                && distance <= max_line_distance_for_naively_paired_lines
-                || distance <= max_line_distance
-            {
-                // minus_line and plus_line are inferred to be a homologous pair.
-
-                // Emit as unpaired the plus lines already considered and rejected
-                for plus_line in &plus_lines[plus_index..(plus_index + considered)] {
-                    annotated_plus_lines.push(vec![(noop_insertions[plus_index], plus_line)]);
-                    line_alignment.push((None, Some(plus_index)));
-                    plus_index += 1;
-                }
-                annotated_minus_lines.push(annotated_minus_line);
-                annotated_plus_lines.push(annotated_plus_line);
-                line_alignment.push((Some(minus_index), Some(plus_index)));
-                plus_index += 1;
-
-                // Greedy: move on to the next minus line.
-                continue 'minus_lines_loop;
-            } else {
-                considered += 1;
-            }
+    let (mut minus_index, mut plus_index) = (0, 0); // lines emitted so far
+    for (paired_minus_index, paired_plus_index) in pairs {
+        // Emit as unpaired the lines preceding the pair
+        for minus_line in &minus_lines[minus_index..paired_minus_index] {
+            annotated_minus_lines.push(vec![(noop_deletions[minus_index], minus_line)]);
+            line_alignment.push((Some(minus_index), None));
+            minus_index += 1;
+        }
+        for plus_line in &plus_lines[plus_index..paired_plus_index] {
+            annotated_plus_lines.push(vec![(noop_insertions[plus_index], plus_line)]);
+            line_alignment.push((None, Some(plus_index)));
+            plus_index += 1;
         }
-        // No homolog was found for minus i; emit as unpaired.
+        let (annotated_minus_line, annotated_plus_line, _) = annotations
+            .remove(&(minus_index, plus_index))
+            .unwrap_or_else(|| annotate_pair(minus_index, plus_index));
+        annotated_minus_lines.push(annotated_minus_line);
+        annotated_plus_lines.push(annotated_plus_line);
+        line_alignment.push((Some(minus_index), Some(plus_index)));
+        minus_index += 1;
+        plus_index += 1;
+    }
+    // Emit any remaining minus lines
+    for minus_line in &minus_lines[minus_index..] {
         annotated_minus_lines.push(vec![(noop_deletions[minus_index], minus_line)]);
         line_alignment.push((Some(minus_index), None));
+        minus_index += 1;
     }
     // Emit any remaining plus lines
     for plus_line in &plus_lines[plus_index..] {
@@ -105,6 +144,224 @@ where
     (annotated_minus_lines, annotated_plus_lines, line_alignment)
 }
 
+/// Infer the edits between old and new lines of prose as `infer_edits` does, but comparing
+/// paragraphs rather than lines (used by --reflow-prose). A paragraph is a run of non-blank lines,
+/// such as a hard-wrapped paragraph of a Markdown file, which is compared as if its lines were
+/// joined by spaces, so that a paragraph which was rewrapped is highlighted where its words
+/// changed. A line starting a block such as a heading, list item or quote starts a paragraph. The
+/// edits and the alignment are returned for the lines, which keep their line breaks: paired
+/// paragraphs have their lines paired in order.
+#[allow(clippy::too_many_arguments)]
+#[allow(clippy::type_complexity)]
+pub fn infer_edits_of_paragraphs<'a, EditOperation>(
+    minus_lines: Vec<&'a str>,
+    plus_lines: Vec<&'a str>,
+    noop_deletions: Vec<EditOperation>,
+    deletion: EditOperation,
+    noop_insertions: Vec<EditOperation>,
+    insertion: EditOperation,
+    tokenization_regex: &Regex,
+    max_line_distance: f64,
+    max_line_distance_for_naively_paired_lines: f64,
+    pairing_heuristic: PairingHeuristic,
+) -> (
+    Vec<Vec<(EditOperation, &'a str)>>,  // annotated minus lines
+    Vec<Vec<(EditOperation, &'a str)>>,  // annotated plus lines
+    Vec<(Option<usize>, Option<usize>)>, // line alignment
+)
+where
+    EditOperation: Copy + PartialEq + std::fmt::Debug,
+{
+    let minus_paragraphs = Paragraphs::new(&minus_lines);
+    let plus_paragraphs = Paragraphs::new(&plus_lines);
+    let (annotated_minus_paragraphs, annotated_plus_paragraphs, paragraph_alignment) = infer_edits(
+        minus_paragraphs.texts.iter().map(String::as_str).collect(),
+        plus_paragraphs.texts.iter().map(String::as_str).collect(),
+        minus_paragraphs.first_of_each(&noop_deletions),
+        deletion,
+        plus_paragraphs.first_of_each(&noop_insertions),
+        insertion,
+        tokenization_regex,
+        max_line_distance,
+        max_line_distance_for_naively_paired_lines,
+        pairing_heuristic,
+    );
+    let mut line_alignment = Vec::new();
+    for (minus_paragraph, plus_paragraph) in paragraph_alignment {
+        let minus_range = minus_paragraph.map_or(0..0, |i| minus_paragraphs.line_ranges[i].clone());
+        let plus_range = plus_paragraph.map_or(0..0, |i| plus_paragraphs.line_ranges[i].clone());
+        for i in 0..minus_range.len().max(plus_range.len()) {
+            line_alignment.push((
+                Some(minus_range.start + i).filter(|j| minus_range.contains(j)),
+                Some(plus_range.start + i).filter(|j| plus_range.contains(j)),
+            ));
+        }
+    }
+    (
+        minus_paragraphs.split(annotated_minus_paragraphs, &minus_lines, &noop_deletions),
+        plus_paragraphs.split(annotated_plus_paragraphs, &plus_lines, &noop_insertions),
+        line_alignment,
+    )
+}
+
+lazy_static! {
+    // A line starting a block of Markdown or similar markup: a heading, list item, quote or table row.
+    static ref PROSE_BLOCK_START_REGEX: Regex =
+        Regex::new(r"^\s*([#>|]|[-*+]\s|\d+[.)]\s)").unwrap();
+}
+
+// The paragraphs of a block of lines: the text of each, its lines joined by spaces, and the range
+// of its lines.
+struct Paragraphs {
+    texts: Vec<String>,
+    line_ranges: Vec<std::ops::Range<usize>>,
+    // The byte offset of each line in the text of its paragraph.
+    line_offsets: Vec<usize>,
+}
+
+impl Paragraphs {
+    fn new(lines: &[&str]) -> Self {
+        let is_blank = |line: &str| line.trim().is_empty();
+        let mut paragraphs = Self {
+            texts: Vec::new(),
+            line_ranges: Vec::new(),
+            line_offsets: Vec::new(),
+        };
+        for (i, line) in lines.iter().enumerate() {
+            let continues_paragraph = i > 0
+                && lines[i - 1].ends_with('\n')
+                && !is_blank(lines[i - 1])
+                && !is_blank(line)
+                && !PROSE_BLOCK_START_REGEX.is_match(line);
+            match (
+                paragraphs.texts.last_mut(),
+                paragraphs.line_ranges.last_mut(),
+            ) {
+                (Some(text), Some(range)) if continues_paragraph => {
+                    // The newline ending the preceding line is replaced by a space.
+                    text.pop();
+                    text.push(' ');
+                    paragraphs.line_offsets.push(text.len());
+                    text.push_str(line);
+                    range.end = i + 1;
+                }
+                _ => {
+                    paragraphs.texts.push(line.to_string());
+                    paragraphs.line_ranges.push(i..i + 1);
+                    paragraphs.line_offsets.push(0);
+                }
+            }
+        }
+        paragraphs
+    }
+
+    // Return the item of the first line of each paragraph, given an item for each line.
+    fn first_of_each<T: Copy>(&self, items: &[T]) -> Vec<T> {
+        self.line_ranges
+            .iter()
+            .map(|range| items[range.start])
+            .collect()
+    }
+
+    // Split the annotated paragraphs into annotated lines, the sections of which are slices of
+    // `lines`. A line without any section is given the operation of `noop_operations`.
+    fn split<'a, EditOperation: Copy>(
+        &self,
+        annotated_paragraphs: Vec<Vec<(EditOperation, &str)>>,
+        lines: &[&'a str],
+        noop_operations: &[EditOperation],
+    ) -> Vec<Vec<(EditOperation, &'a str)>> {
+        let mut annotated_lines = Vec::with_capacity(lines.len());
+        for (sections, range) in annotated_paragraphs.iter().zip(&self.line_ranges) {
+            for i in range.clone() {
+                let (line, line_start) = (lines[i], self.line_offsets[i]);
+                let line_end = line_start + line.len();
+                let mut annotated_line = Vec::new();
+                let mut section_start = 0;
+                for (operation, section) in sections {
+                    let section_end = section_start + section.len();
+                    let (start, end) = (section_start.max(line_start), section_end.min(line_end));
+                    if start < end {
+                        annotated_line
+                            .push((*operation, &line[start - line_start..end - line_start]));
+                    }
+                    section_start = section_end;
+                }
+                if annotated_line.is_empty() {
+                    annotated_line.push((noop_operations[i], line));
+                }
+                annotated_lines.push(annotated_line);
+            }
+        }
+        annotated_lines
+    }
+}
+
+/// Pair each minus line with the first following plus line that is homologous to it, i.e. pair
+/// lines greedily in the order they appear in. Return the (minus_index, plus_index) pairs.
+fn pair_lines_positionally<F>(
+    n_minus_lines: usize,
+    n_plus_lines: usize,
+    mut is_homologous: F,
+) -> Vec<(usize, usize)>
+where
+    F: FnMut(usize, usize) -> bool,
+{
+    let mut pairs = Vec::new();
+    let mut plus_index = 0; // plus lines paired or skipped so far
+    for minus_index in 0..n_minus_lines {
+        if let Some(paired_plus_index) =
+            (plus_index..n_plus_lines).find(|&p| is_homologous(minus_index, p))
+        {
+            pairs.push((minus_index, paired_plus_index));
+            plus_index = paired_plus_index + 1;
+        }
+    }
+    pairs
+}
+
+/// Pair homologous minus and plus lines such that the total similarity of the pairs is maximal,
+/// where the similarity of a pair is 1 - distance. `distance` returns None for lines which are
+/// not homologous. As with positional pairing, pairs do not cross, i.e. the pairs are in the
+/// order of both their minus and plus lines. Return the (minus_index, plus_index) pairs.
+fn pair_lines_by_similarity<F>(
+    n_minus_lines: usize,
+    n_plus_lines: usize,
+    mut distance: F,
+) -> Vec<(usize, usize)>
+where
+    F: FnMut(usize, usize) -> Option<f64>,
+{
+    let distances: Vec<Vec<Option<f64>>> = (0..n_minus_lines)
+        .map(|m| (0..n_plus_lines).map(|p| distance(m, p)).collect())
+        .collect();
+    // similarity[m][p] is the maximal total similarity of pairs of minus_lines[m..] and
+    // plus_lines[p..].
+    let mut similarity = vec![vec![0.0; n_plus_lines + 1]; n_minus_lines + 1];
+    for m in (0..n_minus_lines).rev() {
+        for p in (0..n_plus_lines).rev() {
+            let paired = distances[m][p].map(|d| 1.0 - d + similarity[m + 1][p + 1]);
+            similarity[m][p] = similarity[m + 1][p]
+                .max(similarity[m][p + 1])
+                .max(paired.unwrap_or(0.0));
+        }
+    }
+    let mut pairs = Vec::new();
+    let (mut m, mut p) = (0, 0);
+    while m < n_minus_lines && p < n_plus_lines {
+        match distances[m][p] {
+            Some(d) if 1.0 - d + similarity[m + 1][p + 1] >= similarity[m][p] => {
+                pairs.push((m, p));
+                m += 1;
+                p += 1;
+            }
+            _ if similarity[m + 1][p] >= similarity[m][p + 1] => m += 1,
+            _ => p += 1,
+        }
+    }
+    pairs
+}
+
 // Return `None` if there is no trailing whitespace.
 // Return `Some(content)` where content is trimmed if there was some trailing whitespace
 fn get_contents_before_trailing_whitespace(line: &str) -> Option<&str> {
@@ -895,6 +1152,132 @@ mod tests {
         );
     }
 
+    fn infer_line_alignment(
+        minus_lines: Vec<&str>,
+        plus_lines: Vec<&str>,
+        pairing_heuristic: PairingHeuristic,
+    ) -> Vec<(Option<usize>, Option<usize>)> {
+        let (n_minus_lines, n_plus_lines) = (minus_lines.len(), plus_lines.len());
+        let (_, _, line_alignment) = infer_edits(
+            minus_lines,
+            plus_lines,
+            vec![MinusNoop; n_minus_lines],
+            Deletion,
+            vec![PlusNoop; n_plus_lines],
+            Insertion,
+            &DEFAULT_TOKENIZATION_REGEXP,
+            0.6,
+            0.0,
+            pairing_heuristic,
+        );
+        line_alignment
+    }
+
+    #[test]
+    fn test_pairing_heuristic_with_more_minus_lines() {
+        // The added line is a modification of the second removed line, but it is also similar
+        // enough to the first removed line to be paired with it positionally.
+        let minus_lines = vec!["let total = sum(a, b);", "let total = sum(a, b, c);"];
+        let plus_lines = vec!["let total = sum(a, b, c, d);"];
+        assert_eq!(
+            infer_line_alignment(
+                minus_lines.clone(),
+                plus_lines.clone(),
+                PairingHeuristic::Positional
+            ),
+            vec![(Some(0), Some(0)), (Some(1), None)]
+        );
+        assert_eq!(
+            infer_line_alignment(minus_lines, plus_lines, PairingHeuristic::Similarity),
+            vec![(Some(0), None), (Some(1), Some(0))]
+        );
+    }
+
+    #[test]
+    fn test_pairing_heuristic_with_reordered_lines() {
+        let minus_lines = vec![
+            "let width = config.width;",
+            "let height = config.height;",
+            "let depth = config.depth;",
+        ];
+        let plus_lines = vec![
+            "let height = config.height();",
+            "let depth = config.depth();",
+        ];
+        // Positionally, the first removed line is paired with the first added line.
+        assert_eq!(
+            infer_line_alignment(
+                minus_lines.clone(),
+                plus_lines.clone(),
+                PairingHeuristic::Positional
+            )[0],
+            (Some(0), Some(0))
+        );
+        assert_eq!(
+            infer_line_alignment(minus_lines, plus_lines, PairingHeuristic::Similarity),
+            vec![(Some(0), None), (Some(1), Some(0)), (Some(2), Some(1))]
+        );
+    }
+
+    #[test]
+    fn test_infer_edits_of_paragraphs() {
+        let minus_lines = vec![
+            "The quick brown fox jumps over\n",
+            "the lazy dog.\n",
+            "\n",
+            "- A list item\n",
+        ];
+        let plus_lines = vec!["The quick brown fox leaps\n", "over the lazy dog.\n", "\n"];
+        let (minus_edits, plus_edits, line_alignment) = infer_edits_of_paragraphs(
+            minus_lines.clone(),
+            plus_lines.clone(),
+            vec![MinusNoop; minus_lines.len()],
+            Deletion,
+            vec![PlusNoop; plus_lines.len()],
+            Insertion,
+            &DEFAULT_TOKENIZATION_REGEXP,
+            0.6,
+            0.0,
+            PairingHeuristic::Positional,
+        );
+        // Only the changed word is an edit, although the line breaks moved.
+        assert_eq!(
+            minus_edits,
+            vec![
+                vec![
+                    (MinusNoop, "The quick brown fox "),
+                    (Deletion, "jumps"),
+                    (MinusNoop, " over\n"),
+                ],
+                vec![(MinusNoop, "the lazy dog.\n")],
+                vec![(MinusNoop, "\n")],
+                vec![(MinusNoop, "- A list item\n")],
+            ]
+        );
+        assert_eq!(
+            plus_edits,
+            vec![
+                vec![
+                    (PlusNoop, "The quick brown fox"),
+                    (PlusNoop, " "),
+                    (Insertion, "leaps"),
+                    (PlusNoop, "\n"),
+                ],
+                vec![(PlusNoop, "over the lazy dog.\n")],
+                vec![(PlusNoop, "\n")],
+            ]
+        );
+        assert_eq!(
+            line_alignment,
+            vec![
+                (Some(0), Some(0)),
+                (Some(1), Some(1)),
+                (Some(2), Some(2)),
+                (Some(3), None)
+            ]
+        );
+    }
+
     fn assert_edits(
         minus_lines: Vec<&str>,
         plus_lines: Vec<&str>,
@@ -915,6 +1298,7 @@ mod tests {
             &DEFAULT_TOKENIZATION_REGEXP,
             max_line_distance,
             0.0,
+            PairingHeuristic::Positional,
         );
         // compare_annotated_lines(actual_edits, expected_edits);
         // TODO: test line alignment
@@ -1,6 +1,7 @@
 use std::env;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+const COLORFGBG: &str = "COLORFGBG";
 const COLORTERM: &str = "COLORTERM";
 const BAT_THEME: &str = "BAT_THEME";
 const GIT_CONFIG_PARAMETERS: &str = "GIT_CONFIG_PARAMETERS";
@@ -10,69 +11,179 @@ const DELTA_NAVIGATE: &str = "DELTA_NAVIGATE";
 const DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES: &str =
     "DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES";
 const DELTA_PAGER: &str = "DELTA_PAGER";
+const DELTA_PAGER_PRECEDENCE: &str = "DELTA_PAGER_PRECEDENCE";
+const EDITOR: &str = "EDITOR";
+const NO_COLOR: &str = "NO_COLOR";
+const TERM: &str = "TERM";
+const TERM_PROGRAM: &str = "TERM_PROGRAM";
+const VISUAL: &str = "VISUAL";
 
 #[derive(Default, Clone)]
 pub struct DeltaEnv {
     pub bat_theme: Option<String>,
+    pub colorfgbg: Option<String>,
     pub colorterm: Option<String>,
     pub current_dir: Option<std::path::PathBuf>,
-    pub experimental_max_line_distance_for_naively_paired_lines: Option<String>,
+    pub editor: Option<String>,
+    pub experimental_max_line_distance_for_naively_paired_lines: Option<f64>,
     pub features: Option<String>,
     pub git_config_parameters: Option<String>,
     pub git_prefix: Option<String>,
     pub hostname: Option<String>,
-    pub navigate: Option<String>,
+    pub navigate: Option<bool>,
+    pub no_color: bool,
     pub pagers: (Option<String>, Option<String>),
+    pub repo_root: Option<PathBuf>,
+    pub term: Option<String>,
+    pub term_program: Option<String>,
+    pub visual: Option<String>,
 }
 
 impl DeltaEnv {
     /// Create a structure with current environment variable
     pub fn init() -> Self {
         let bat_theme = env::var(BAT_THEME).ok();
+        let colorfgbg = env::var(COLORFGBG).ok();
         let colorterm = env::var(COLORTERM).ok();
+        let editor = env::var(EDITOR).ok();
         let experimental_max_line_distance_for_naively_paired_lines =
-            env::var(DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES).ok();
+            env::var(DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES)
+                .ok()
+                .and_then(|value| parse_max_line_distance(&value));
         let features = env::var(DELTA_FEATURES).ok();
         let git_config_parameters = env::var(GIT_CONFIG_PARAMETERS).ok();
         let git_prefix = env::var(GIT_PREFIX).ok();
         let hostname = hostname();
-        let navigate = env::var(DELTA_NAVIGATE).ok();
+        let navigate = env::var(DELTA_NAVIGATE)
+            .ok()
+            .map(|value| parse_navigate(&value));
+        // See https://no-color.org: the variable disables color when set to a non-empty value.
+        let no_color = env::var_os(NO_COLOR).is_some_and(|value| !value.is_empty());
+        let term = env::var(TERM).ok();
+        let term_program = env::var(TERM_PROGRAM).ok();
+        let visual = env::var(VISUAL).ok();
 
         let current_dir = env::current_dir().ok();
+        let repo_root = current_dir.as_deref().and_then(find_repo_root);
+        let pager_precedence = parse_pager_precedence(env::var(DELTA_PAGER_PRECEDENCE).ok());
+        // DELTA_PAGER is only used if no variable before it in the precedence is set.
+        let delta_pager = pager_precedence
+            .iter()
+            .find(|name| env::var(name).is_ok())
+            .filter(|name| *name == DELTA_PAGER)
+            .and_then(|_| env::var(DELTA_PAGER).ok());
         let pagers = (
-            env::var(DELTA_PAGER).ok(),
+            delta_pager,
             // Reimplement bat's pager detection logic to preserve full PAGER commands.
             // This fixes the bug where bat::config::get_pager_executable(None) was stripping
             // arguments from complex PAGER commands like '/bin/sh -c "head -10000 | cat"'.
             // We can't use bat::pager::get_pager directly because the pager module is private.
-            get_pager_from_env(),
+            get_pager_from_env(&pager_precedence),
         );
 
         Self {
             bat_theme,
+            colorfgbg,
             colorterm,
             current_dir,
+            editor,
             experimental_max_line_distance_for_naively_paired_lines,
             features,
             git_config_parameters,
             git_prefix,
             hostname,
             navigate,
+            no_color,
             pagers,
+            repo_root,
+            term,
+            term_program,
+            visual,
         }
     }
 }
 
+/// Interpret the value of DELTA_NAVIGATE. Setting the variable, even to an empty value, turns
+/// navigate on unless its value is one of 0/false/no/off. Values which are neither these nor
+/// 1/true/yes/on are warned about.
+fn parse_navigate(value: &str) -> bool {
+    match value.trim().to_lowercase().as_str() {
+        "" | "1" | "true" | "yes" | "on" => true,
+        "0" | "false" | "no" | "off" => false,
+        _ => {
+            eprintln!(
+                "Invalid value for {DELTA_NAVIGATE} environment variable: {value:?} \
+                 (valid values are 1/true/yes/on and 0/false/no/off). Treating it as set."
+            );
+            true
+        }
+    }
+}
+
+/// Interpret the value of DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES, a
+/// distance between 0 and 1 given either as a ratio, e.g. "0.6", or as a percentage, e.g. "60%".
+/// Other values are warned about and treated as if the variable was unset.
+fn parse_max_line_distance(value: &str) -> Option<f64> {
+    let value = value.trim();
+    let distance = match value.strip_suffix('%') {
+        Some(percentage) => percentage.trim_end().parse::<f64>().map(|p| p / 100.0),
+        None => value.parse::<f64>(),
+    };
+    match distance {
+        Ok(distance) if (0.0..=1.0).contains(&distance) => Some(distance),
+        _ => {
+            eprintln!(
+                "Invalid value for {DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES} \
+                 environment variable: {value:?} (valid values are ratios from 0 to 1, e.g. 0.6, \
+                 and percentages from 0% to 100%, e.g. 60%). Ignoring it."
+            );
+            None
+        }
+    }
+}
+
+/// Interpret the value of DELTA_PAGER_PRECEDENCE, a comma-separated list of the names of the
+/// environment variables consulted for the pager, in order. When it is unset the order is
+/// DELTA_PAGER, BAT_PAGER, PAGER.
+fn parse_pager_precedence(value: Option<String>) -> Vec<String> {
+    match value {
+        Some(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(String::from)
+            .collect(),
+        None => [DELTA_PAGER, "BAT_PAGER", "PAGER"]
+            .map(String::from)
+            .to_vec(),
+    }
+}
+
+/// Return the root of the git working tree containing `dir`, i.e. the nearest ancestor of `dir`
+/// which has a `.git` entry. In the main worktree this is a directory; in a linked worktree (or a
+/// submodule) it is a file pointing to the git directory elsewhere.
+pub fn find_repo_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|dir| is_git_entry(&dir.join(".git")))
+        .map(Path::to_path_buf)
+}
+
+fn is_git_entry(path: &Path) -> bool {
+    path.is_dir()
+        || std::fs::read_to_string(path).is_ok_and(|contents| contents.starts_with("gitdir:"))
+}
+
 fn hostname() -> Option<String> {
     grep_cli::hostname().ok()?.to_str().map(|s| s.to_string())
 }
 
 #[cfg(test)]
 pub mod tests {
-    use super::DeltaEnv;
+    use super::{find_repo_root, DeltaEnv};
     use crate::tests::integration_test_utils::EnvVarGuard;
     use lazy_static::lazy_static;
     use std::env;
+    use std::fs;
     use std::sync::{Arc, Mutex};
 
     lazy_static! {
@@ -89,6 +200,88 @@ pub mod tests {
         assert_eq!(env.features, Some(feature.into()));
         // otherwise `current_dir` is not used in the test cfg:
         assert_eq!(env.current_dir, env::current_dir().ok());
+        assert_eq!(
+            env.repo_root,
+            env.current_dir.as_deref().and_then(find_repo_root)
+        );
+    }
+
+    #[test]
+    fn test_find_repo_root() {
+        let root = env::temp_dir().join("delta__test_find_repo_root");
+        let _ = fs::remove_dir_all(&root);
+        let repo = root.join("repo");
+        let worktree = root.join("worktree");
+        let not_a_worktree = worktree.join("vendored");
+        fs::create_dir_all(repo.join(".git")).unwrap();
+        fs::create_dir_all(repo.join("src/deep")).unwrap();
+        fs::create_dir_all(not_a_worktree.join("src")).unwrap();
+        fs::write(
+            worktree.join(".git"),
+            format!("gitdir: {}\n", repo.join(".git/worktrees/w").display()),
+        )
+        .unwrap();
+        // A `.git` file which is not a pointer to a git directory is ignored.
+        fs::write(not_a_worktree.join(".git"), "not a gitdir\n").unwrap();
+
+        assert_eq!(find_repo_root(&repo), Some(repo.clone()));
+        assert_eq!(find_repo_root(&repo.join("src/deep")), Some(repo.clone()));
+        assert_eq!(find_repo_root(&worktree), Some(worktree.clone()));
+        assert_eq!(
+            find_repo_root(&not_a_worktree.join("src")),
+            Some(worktree.clone())
+        );
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn test_env_parsing_navigate() {
+        for (value, expected) in [
+            ("1", Some(true)),
+            ("true", Some(true)),
+            ("yes", Some(true)),
+            ("on", Some(true)),
+            ("TRUE", Some(true)),
+            ("0", Some(false)),
+            ("false", Some(false)),
+            ("no", Some(false)),
+            ("off", Some(false)),
+            ("Off", Some(false)),
+            ("maybe", Some(true)),
+            ("", Some(true)),
+        ] {
+            let _guard = ENV_ACCESS.lock().unwrap();
+            let _env_guard = EnvVarGuard::new("DELTA_NAVIGATE", value);
+            let env = DeltaEnv::init();
+            assert_eq!(env.navigate, expected, "DELTA_NAVIGATE={value:?}");
+        }
+    }
+
+    #[test]
+    fn test_env_parsing_max_line_distance() {
+        for (value, expected) in [
+            ("0.6", Some(0.6)),
+            ("60%", Some(0.6)),
+            (" 25 % ", Some(0.25)),
+            ("1", Some(1.0)),
+            ("0%", Some(0.0)),
+            ("1.5", None),
+            ("150%", None),
+            ("-0.1", None),
+            ("sixty", None),
+            ("%", None),
+        ] {
+            let _guard = ENV_ACCESS.lock().unwrap();
+            let _env_guard = EnvVarGuard::new(
+                "DELTA_EXPERIMENTAL_MAX_LINE_DISTANCE_FOR_NAIVELY_PAIRED_LINES",
+                value,
+            );
+            let env = DeltaEnv::init();
+            assert_eq!(
+                env.experimental_max_line_distance_for_naively_paired_lines, expected,
+                "{value:?}"
+            );
+        }
     }
 
     #[test]
@@ -184,19 +377,63 @@ pub mod tests {
             "PAGER should also be preserved for fallback"
         );
     }
+
+    #[test]
+    fn test_env_parsing_pager_precedence() {
+        for (precedence, expected) in [
+            (None, (Some("delta-pager"), Some("bat-pager"))),
+            (Some("PAGER,BAT_PAGER"), (None, Some("pager"))),
+            (Some("PAGER, DELTA_PAGER"), (None, Some("pager"))),
+            (Some("BAT_PAGER,DELTA_PAGER"), (None, Some("bat-pager"))),
+            (
+                Some("DELTA_PAGER,PAGER"),
+                (Some("delta-pager"), Some("pager")),
+            ),
+            (Some("BAT_PAGER"), (None, Some("bat-pager"))),
+            (Some("DELTA_PAGER"), (Some("delta-pager"), Some("less"))),
+            (Some("UNSET_PAGER,PAGER"), (None, Some("pager"))),
+            (Some(""), (None, Some("less"))),
+        ] {
+            let _guard = ENV_ACCESS.lock().unwrap();
+            let _delta_pager_guard = EnvVarGuard::new("DELTA_PAGER", "delta-pager");
+            let _bat_pager_guard = EnvVarGuard::new("BAT_PAGER", "bat-pager");
+            let _pager_guard = EnvVarGuard::new("PAGER", "pager");
+            let _precedence_guard =
+                precedence.map(|value| EnvVarGuard::new("DELTA_PAGER_PRECEDENCE", value));
+            let env = DeltaEnv::init();
+            assert_eq!(
+                env.pagers,
+                (expected.0.map(String::from), expected.1.map(String::from)),
+                "DELTA_PAGER_PRECEDENCE={precedence:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_env_parsing_pager_precedence_replaces_problematic_pager() {
+        let _guard = ENV_ACCESS.lock().unwrap();
+        let _bat_pager_guard = EnvVarGuard::new("BAT_PAGER", "more");
+        let _pager_guard = EnvVarGuard::new("PAGER", "more");
+        let _precedence_guard = EnvVarGuard::new("DELTA_PAGER_PRECEDENCE", "PAGER,BAT_PAGER");
+        let env = DeltaEnv::init();
+        // Only a pager taken from PAGER is replaced.
+        assert_eq!(env.pagers.1, Some("less".into()));
+    }
 }
 
-/// Get pager from environment variables using bat's logic.
+/// Get pager from environment variables using bat's logic, consulting the variables other than
+/// DELTA_PAGER in the order of `pager_precedence`.
 /// This reimplements bat's pager::get_pager function to preserve full PAGER commands
 /// including arguments, while still handling problematic pagers properly.
-fn get_pager_from_env() -> Option<String> {
-    let bat_pager = env::var("BAT_PAGER");
-    let pager = env::var("PAGER");
-
-    let (cmd, from_pager_env) = match (&bat_pager, &pager) {
-        (Ok(bat_pager), _) => (bat_pager.as_str(), false),
-        (_, Ok(pager)) => (pager.as_str(), true),
-        _ => ("less", false),
+fn get_pager_from_env(pager_precedence: &[String]) -> Option<String> {
+    let pager = pager_precedence
+        .iter()
+        .filter(|name| *name != DELTA_PAGER)
+        .find_map(|name| env::var(name).ok().map(|value| (value, name == "PAGER")));
+
+    let (cmd, from_pager_env) = match &pager {
+        Some((pager, from_pager_env)) => (pager.as_str(), *from_pager_env),
+        None => ("less", false),
     };
 
     // Parse the command using shell_words to split into binary and arguments
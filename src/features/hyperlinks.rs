@@ -5,6 +5,7 @@ use lazy_static::lazy_static;
 use regex::{Match, Matches, Regex};
 
 use crate::config::Config;
+use crate::env::DeltaEnv;
 use crate::features::OptionValueFunction;
 use crate::git_config::{GitConfig, GitRemoteRepo};
 
@@ -12,9 +13,9 @@ pub fn make_feature() -> Vec<(String, OptionValueFunction)> {
     builtin_feature!([
         (
             "hyperlinks",
-            bool,
+            String,
             None,
-            _opt => true
+            _opt => "true"
         )
     ])
 }
@@ -91,6 +92,93 @@ pub fn format_commit_line_with_osc8_commit_hyperlink<'a>(
     Cow::from(line)
 }
 
+/// Return the line with each reference to an issue matched by the regex of an --issue-link-format
+/// made a hyperlink to the URL given by its format. Where matches of several regexes overlap, the
+/// one starting first is linked.
+pub fn format_line_with_osc8_issue_hyperlinks<'a>(line: &'a str, config: &Config) -> Cow<'a, str> {
+    let mut links: Vec<_> = config
+        .issue_links
+        .iter()
+        .flat_map(|(regex, format)| {
+            regex.captures_iter(line).filter_map(move |caps| {
+                let reference = caps.get(0).filter(|m| !m.is_empty())?;
+                let id = caps.get(1).unwrap_or(reference).as_str();
+                Some((reference.range(), format.replace("{id}", id)))
+            })
+        })
+        .collect();
+    if links.is_empty() {
+        return Cow::from(line);
+    }
+    links.sort_by_key(|(range, _)| range.start);
+    let mut result = String::new();
+    let mut pos = 0;
+    for (range, url) in links {
+        if range.start < pos {
+            continue;
+        }
+        result.push_str(&line[pos..range.start]);
+        result.push_str(&format_osc8_hyperlink(&url, &line[range.clone()]));
+        pos = range.end;
+    }
+    result.push_str(&line[pos..]);
+    Cow::from(result)
+}
+
+// The file hyperlink format used when the editor is not known.
+const DEFAULT_FILE_LINK_FORMAT: &str = "file://{path}";
+
+/// Return the file hyperlink format of --hyperlinks-file-link-format=auto: that of the URL scheme
+/// of the editor of VISUAL or EDITOR, or a file URL if neither is an editor with a known scheme.
+pub fn file_link_format_from_env(env: &DeltaEnv) -> &'static str {
+    [env.visual.as_deref(), env.editor.as_deref()]
+        .iter()
+        .flatten()
+        .find_map(|editor| file_link_format_for_editor(editor))
+        .unwrap_or(DEFAULT_FILE_LINK_FORMAT)
+}
+
+// The file hyperlink format of the URL scheme of an editor command, such as "code --wait".
+fn file_link_format_for_editor(editor: &str) -> Option<&'static str> {
+    let words = shell_words::split(editor).ok()?;
+    let program = Path::new(words.first()?).file_stem()?.to_str()?;
+    match program {
+        "code" => Some("vscode://file/{path}:{line}"),
+        "code-insiders" => Some("vscode-insiders://file/{path}:{line}"),
+        "codium" => Some("vscodium://file/{path}:{line}"),
+        "cursor" => Some("cursor://file/{path}:{line}"),
+        "subl" | "sublime_text" => Some("subl://open?url=file://{path}&line={line}"),
+        "idea" => Some("idea://open?file={path}&line={line}"),
+        "pycharm" => Some("pycharm://open?file={path}&line={line}"),
+        "zed" => Some("zed://file/{path}:{line}"),
+        // Terminal editors such as vim and nvim have no URL scheme.
+        _ => None,
+    }
+}
+
+/// Return whether the terminal is known to support OSC 8 hyperlinks, judging by TERM_PROGRAM and
+/// TERM (used by --hyperlinks=auto).
+pub fn terminal_supports_hyperlinks(env: &DeltaEnv) -> bool {
+    let term_program_supports_hyperlinks = matches!(
+        env.term_program.as_deref(),
+        Some(
+            "iTerm.app"
+                | "WezTerm"
+                | "vscode"
+                | "ghostty"
+                | "Hyper"
+                | "Tabby"
+                | "WarpTerminal"
+                | "rio"
+        )
+    );
+    let term_supports_hyperlinks = matches!(
+        env.term.as_deref(),
+        Some("xterm-kitty" | "xterm-ghostty" | "wezterm" | "alacritty" | "foot" | "contour")
+    );
+    term_program_supports_hyperlinks || term_supports_hyperlinks
+}
+
 /// Create a file hyperlink, displaying `text`.
 pub fn format_osc8_file_hyperlink<'a, P>(
     absolute_path: P,
@@ -138,7 +226,9 @@ pub mod tests {
     use super::*;
 
     use crate::{
-        tests::integration_test_utils::{self, make_config_from_args, DeltaTest},
+        tests::integration_test_utils::{
+            self, make_config_from_args, make_config_from_args_and_git_config, DeltaTest,
+        },
         utils,
     };
 
@@ -190,6 +280,186 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_issue_hyperlinks() {
+        let config = make_config_from_args(&[
+            "--issue-link-format=https://github.com/dandavison/delta/issues/{id}",
+            "--issue-link-format=[A-Z][A-Z0-9]+-\\d+ => https://example.com/browse/{id}",
+        ]);
+        let line = "Fix #123 (JIRA-456)";
+        let result = format_line_with_osc8_issue_hyperlinks(line, &config);
+        assert_eq!(
+            result,
+            "Fix \u{1b}]8;;https://github.com/dandavison/delta/issues/123\u{1b}\\#123\u{1b}]8;;\
+            \u{1b}\\ (\u{1b}]8;;https://example.com/browse/JIRA-456\u{1b}\\JIRA-456\u{1b}]8;;\
+            \u{1b}\\)"
+        );
+        let line = "No issue #x";
+        assert_eq!(format_line_with_osc8_issue_hyperlinks(line, &config), line);
+    }
+
+    #[test]
+    fn test_issue_hyperlinks_in_git_config() {
+        let git_config_contents = br#"
+[delta]
+    issue-link-format = https://github.com/dandavison/delta/issues/{id}
+    issue-link-format = "[A-Z][A-Z0-9]+-\\d+ => https://example.com/browse/{id}"
+"#;
+        let git_config_path = "delta__test_issue_hyperlinks_in_git_config.gitconfig";
+        let config = make_config_from_args_and_git_config(
+            &[],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        let line = "Fix #123 (JIRA-456)";
+        let result = format_line_with_osc8_issue_hyperlinks(line, &config);
+        assert_eq!(
+            result,
+            "Fix \u{1b}]8;;https://github.com/dandavison/delta/issues/123\u{1b}\\#123\u{1b}]8;;\
+            \u{1b}\\ (\u{1b}]8;;https://example.com/browse/JIRA-456\u{1b}\\JIRA-456\u{1b}]8;;\
+            \u{1b}\\)"
+        );
+        std::fs::remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid value for issue-link-format")]
+    fn test_issue_link_format_invalid_regex() {
+        make_config_from_args(&["--issue-link-format=#(\\d+ => https://example.com/{id}"]);
+    }
+
+    #[test]
+    fn test_issue_hyperlinks_in_commit_message() {
+        let input = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: A <a@example.com>
+Date:   Mon Jan 1 00:00:00 2024 +0000
+
+    Fix crash (#123)
+
+    Closes #45.
+";
+        let issue_link_format = "--issue-link-format=https://github.com/o/r/issues/{id}";
+        let result = DeltaTest::with_args(&["--hyperlinks", issue_link_format]).with_input(input);
+        assert!(result.raw_output.contains(
+            "    Fix crash (\u{1b}]8;;https://github.com/o/r/issues/123\u{1b}\\#123\u{1b}]8;;\u{1b}\\)"
+        ));
+        assert!(result.raw_output.contains(
+            "    Closes \u{1b}]8;;https://github.com/o/r/issues/45\u{1b}\\#45\u{1b}]8;;\u{1b}\\."
+        ));
+        // Issues are only linked with --hyperlinks.
+        let result = DeltaTest::with_args(&[issue_link_format]).with_input(input);
+        assert!(!result.raw_output.contains("\u{1b}]8;;"));
+    }
+
+    #[test]
+    fn test_file_link_format_from_env() {
+        for (visual, editor, expected) in [
+            (None, Some("code"), "vscode://file/{path}:{line}"),
+            (
+                None,
+                Some("/usr/bin/code --wait"),
+                "vscode://file/{path}:{line}",
+            ),
+            (
+                Some("subl -w"),
+                Some("code"),
+                "subl://open?url=file://{path}&line={line}",
+            ),
+            // VISUAL is skipped if it has no known scheme.
+            (Some("nvim"), Some("code"), "vscode://file/{path}:{line}"),
+            (Some("nvim"), None, "file://{path}"),
+            (None, None, "file://{path}"),
+        ] {
+            let env = DeltaEnv {
+                visual: visual.map(str::to_string),
+                editor: editor.map(str::to_string),
+                ..DeltaEnv::default()
+            };
+            assert_eq!(
+                file_link_format_from_env(&env),
+                expected,
+                "{visual:?} {editor:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_hyperlinks_file_link_format_auto() {
+        let config = |args: &[&str]| {
+            let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
+                DeltaEnv {
+                    editor: Some("code".to_string()),
+                    ..DeltaEnv::default()
+                },
+                args,
+                None,
+                None,
+            );
+            crate::config::Config::from(opt)
+        };
+        assert_eq!(
+            config(&["--hyperlinks-file-link-format=auto"]).hyperlinks_file_link_format,
+            "vscode://file/{path}:{line}"
+        );
+        assert_eq!(config(&[]).hyperlinks_file_link_format, "file://{path}");
+    }
+
+    #[test]
+    fn test_hyperlinks_auto() {
+        let config = |term: Option<&str>, term_program: Option<&str>, args: &[&str]| {
+            let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
+                DeltaEnv {
+                    term: term.map(str::to_string),
+                    term_program: term_program.map(str::to_string),
+                    ..DeltaEnv::default()
+                },
+                args,
+                None,
+                None,
+            );
+            crate::config::Config::from(opt)
+        };
+        for (term, term_program, expected) in [
+            (Some("xterm-256color"), Some("iTerm.app"), true),
+            (Some("xterm-kitty"), None, true),
+            (Some("xterm-256color"), Some("Apple_Terminal"), false),
+            (Some("linux"), None, false),
+            (None, None, false),
+        ] {
+            assert_eq!(
+                config(term, term_program, &["--hyperlinks=auto"]).hyperlinks,
+                expected,
+                "{term:?} {term_program:?}"
+            );
+        }
+        // An unsupporting terminal gets no hyperlinks with auto, unless they are always enabled.
+        assert!(!config(Some("linux"), None, &[]).hyperlinks);
+        assert!(config(Some("linux"), None, &["--hyperlinks"]).hyperlinks);
+        assert!(config(Some("linux"), None, &["--hyperlinks=true"]).hyperlinks);
+        assert!(!config(Some("linux"), None, &["--hyperlinks=false"]).hyperlinks);
+
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++b
+";
+        let output = |term_program: &str| {
+            let config = config(
+                None,
+                Some(term_program),
+                &["--hyperlinks=auto", "--line-numbers"],
+            );
+            integration_test_utils::run_delta(input, &config)
+        };
+        assert!(output("WezTerm").contains("\x1b]8;;"));
+        assert!(!output("Apple_Terminal").contains("\x1b]8;;"));
+    }
+
     #[test]
     fn test_hyperlinks_to_repo() {
         let mut config = make_config_from_args(&["--hyperlinks"]);
@@ -3,6 +3,7 @@ use std::cmp::max;
 use lazy_static::lazy_static;
 use regex::Regex;
 
+use crate::ansi;
 use crate::color::ColorMode::*;
 use crate::config;
 use crate::delta::State;
@@ -11,6 +12,7 @@ use crate::features::side_by_side::ansifill::{self, ODD_PAD_CHAR};
 use crate::features::side_by_side::{Left, PanelSide, Right};
 use crate::features::OptionValueFunction;
 use crate::format::{self, Align, Placeholder};
+use crate::handlers::hunk_header::parse_hunk_header;
 use crate::minusplus::*;
 use crate::style::Style;
 use crate::utils;
@@ -183,15 +185,20 @@ impl<'a> LineNumbersData<'a> {
         }
     }
 
-    /// Initialize line number data for a hunk.
-    pub fn initialize_hunk(&mut self, line_numbers: &[(usize, usize)], plus_file: String) {
+    /// Initialize line number data for a hunk. Line numbers are given at least `min_width`.
+    pub fn initialize_hunk(
+        &mut self,
+        line_numbers: &[(usize, usize)],
+        plus_file: String,
+        min_width: usize,
+    ) {
         // Typically, line_numbers has length 2: an entry for the minus file, and one for the plus
         // file. In the case of merge commits, it may be longer.
         self.line_number =
             MinusPlus::new(line_numbers[0].0, line_numbers[line_numbers.len() - 1].0);
         let hunk_max_line_number = line_numbers.iter().map(|(n, d)| n + d).max().unwrap();
         self.hunk_max_line_number_width =
-            1 + (hunk_max_line_number as f64).log10().floor() as usize;
+            max(format::log10_plus_1(hunk_max_line_number), min_width);
         self.plus_file = plus_file;
     }
 
@@ -240,6 +247,39 @@ impl<'a> LineNumbersData<'a> {
     }
 }
 
+/// Return the width of the largest line number of the file of each hunk of the input, in the
+/// order of the hunks (used by --line-numbers-zero-pad).
+pub fn get_hunk_line_number_widths(input: &[u8]) -> Vec<usize> {
+    let input = String::from_utf8_lossy(input);
+    let lines: Vec<String> = input.lines().map(ansi::strip_ansi_codes).collect();
+    let mut widths: Vec<usize> = Vec::new();
+    // The index of the first hunk of the current file.
+    let mut file_start = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("diff ")
+            || line.starts_with("--- ")
+                && lines
+                    .get(i + 1)
+                    .is_some_and(|next| next.starts_with("+++ "))
+        {
+            file_start = widths.len();
+        } else if line.starts_with("@@") {
+            if let Some(parsed_hunk_header) = parse_hunk_header(line) {
+                let hunk_max_line_number = parsed_hunk_header
+                    .line_numbers_and_hunk_lengths
+                    .iter()
+                    .map(|(n, d)| n + d)
+                    .max()
+                    .unwrap();
+                widths.push(format::log10_plus_1(hunk_max_line_number));
+                let file_width = widths[file_start..].iter().copied().max().unwrap();
+                widths[file_start..].fill(file_width);
+            }
+        }
+    }
+    widths
+}
+
 #[allow(clippy::too_many_arguments)]
 fn format_and_paint_line_number_field<'a>(
     line_numbers_data: &'a LineNumbersData,
@@ -272,6 +312,7 @@ fn format_and_paint_line_number_field<'a>(
                     line_numbers[Minus],
                     alignment_spec,
                     width,
+                    min_field_width,
                     placeholder.precision,
                     None,
                     config,
@@ -282,6 +323,7 @@ fn format_and_paint_line_number_field<'a>(
                     line_numbers[Plus],
                     alignment_spec,
                     width,
+                    min_field_width,
                     placeholder.precision,
                     Some(plus_file),
                     config,
@@ -296,16 +338,24 @@ fn format_and_paint_line_number_field<'a>(
     ansi_strings
 }
 
-/// Return line number formatted according to `alignment` and `width`.
+/// Return line number formatted according to `alignment` and `width`. With
+/// --line-numbers-zero-pad, the number is first padded with zeros to `zero_pad_width`.
 fn format_line_number(
     line_number: Option<usize>,
     alignment: Align,
     width: usize,
+    zero_pad_width: usize,
     precision: Option<usize>,
     plus_file: Option<&str>,
     config: &config::Config,
 ) -> String {
-    let pad = |n| format::pad(n, width, alignment, precision);
+    let pad = |n: usize| {
+        if config.line_numbers_zero_pad {
+            format::pad(format!("{n:0zero_pad_width$}"), width, alignment, precision)
+        } else {
+            format::pad(n, width, alignment, precision)
+        }
+    };
     match (line_number, config.hyperlinks, plus_file) {
         (None, _, _) => " ".repeat(width),
         (Some(n), true, Some(file)) => match utils::path::absolute_path(file, config) {
@@ -593,31 +643,31 @@ pub mod tests {
         let w = ansifill::UseFullPanelWidth(false);
         let format = MinusPlus::new("".into(), "".into());
         let mut data = LineNumbersData::from_format_strings(&format, w.clone());
-        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into());
+        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into(), 0);
         assert_eq!(data.formatted_width(), MinusPlus::new(0, 0));
 
         let format = MinusPlus::new("│".into(), "│+│".into());
         let mut data = LineNumbersData::from_format_strings(&format, w.clone());
 
-        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into());
+        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into(), 0);
         assert_eq!(data.formatted_width(), MinusPlus::new(1, 3));
 
         let format = MinusPlus::new("│{nm:^3}│".into(), "│{np:^3}│".into());
         let mut data = LineNumbersData::from_format_strings(&format, w.clone());
 
-        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into());
+        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into(), 0);
         assert_eq!(data.formatted_width(), MinusPlus::new(8, 8));
 
         let format = MinusPlus::new("│{nm:^3}│ │{np:<12}│ │{nm}│".into(), "".into());
         let mut data = LineNumbersData::from_format_strings(&format, w.clone());
 
-        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into());
+        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into(), 0);
         assert_eq!(data.formatted_width(), MinusPlus::new(32, 0));
 
         let format = MinusPlus::new("│{np:^3}│ │{nm:<12}│ │{np}│".into(), "".into());
         let mut data = LineNumbersData::from_format_strings(&format, w);
 
-        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into());
+        data.initialize_hunk(&[(10, 11), (10000, 100001)], "a".into(), 0);
         assert_eq!(data.formatted_width(), MinusPlus::new(32, 0));
     }
 
@@ -749,6 +799,65 @@ pub mod tests {
         assert_eq!(lines.next().unwrap(), "     ⋮10000│bb = 2");
     }
 
+    #[test]
+    fn test_line_numbers_zero_pad() {
+        let config = make_config_from_args(&["--line-numbers", "--line-numbers-zero-pad"]);
+        let output = run_delta(TWO_LINE_DIFFS, &config);
+        let output = strip_ansi_codes(&output);
+        let mut lines = output.lines().skip(crate::config::HEADER_LEN);
+        // Both hunks are padded to the width of the largest line number in the file.
+        assert_eq!(lines.next().unwrap(), "001 ⋮001 │a = 1");
+        assert_eq!(lines.next().unwrap(), "002 ⋮    │b = 2");
+        assert_eq!(lines.next().unwrap(), "    ⋮002 │bb = 2");
+        let mut lines = lines.skip_while(|line| !line.starts_with("499 "));
+        assert_eq!(lines.next().unwrap(), "499 ⋮499 │a = 3");
+        assert_eq!(lines.next().unwrap(), "500 ⋮    │b = 4");
+        assert_eq!(lines.next().unwrap(), "    ⋮500 │bb = 4");
+    }
+
+    #[test]
+    fn test_line_numbers_zero_pad_after_ignored_file() {
+        let config = make_config_from_args(&[
+            "--line-numbers",
+            "--line-numbers-zero-pad",
+            "--ignore-files=b.py",
+        ]);
+        let input = format!(
+            "{}{TWO_LINE_DIFFS}",
+            FIVE_DIGIT_LINE_NUMBER_DIFF.replace("a.py", "b.py")
+        );
+        let output = strip_ansi_codes(&run_delta(&input, &config));
+        // The hunk of the ignored file does not shift the widths of the following hunks.
+        assert!(output.contains("\n001 ⋮001 │a = 1\n"));
+        assert!(output.contains("\n499 ⋮499 │a = 3\n"));
+    }
+
+    #[test]
+    fn test_line_numbers_min_width() {
+        let config = make_config_from_args(&[
+            "--line-numbers",
+            "--line-numbers-zero-pad",
+            "--line-numbers-min-width=5",
+        ]);
+        let output = run_delta(TWO_LINE_DIFFS, &config);
+        let output = strip_ansi_codes(&output);
+        let mut lines = output.lines().skip(crate::config::HEADER_LEN);
+        assert_eq!(lines.next().unwrap(), "00001⋮00001│a = 1");
+        assert_eq!(lines.next().unwrap(), "00002⋮     │b = 2");
+
+        let config = make_config_from_args(&["--line-numbers", "--line-numbers-min-width=5"]);
+        let output = run_delta(TWO_LINE_DIFFS, &config);
+        let output = strip_ansi_codes(&output);
+        let mut lines = output.lines().skip(crate::config::HEADER_LEN);
+        assert_eq!(lines.next().unwrap(), "  1  ⋮  1  │a = 1");
+    }
+
+    #[test]
+    fn test_get_hunk_line_number_widths() {
+        let input = format!("{TWO_LINE_DIFFS}{FIVE_DIGIT_LINE_NUMBER_DIFF}");
+        assert_eq!(get_hunk_line_number_widths(input.as_bytes()), [3, 3, 5]);
+    }
+
     #[test]
     fn test_color_only() {
         let config = make_config_from_args(&["--line-numbers", "--color-only"]);
@@ -59,6 +59,38 @@ pub fn make_navigate_regex(
     }
 }
 
+// Construct the prompt of less from --navigate-prompt-format, in which each placeholder is
+// replaced by the corresponding escape sequence of a less prompt, and the characters that are
+// special in a less prompt are escaped elsewhere.
+pub fn make_navigate_less_prompt(format: &str) -> String {
+    const PLACEHOLDERS: [(&str, &str); 5] = [
+        ("{current}", "%i"),
+        ("{total}", "%m"),
+        ("{line}", "%lt"),
+        ("{lines}", "%L"),
+        ("{percent}", "%pt\\%"),
+    ];
+    let mut prompt = String::new();
+    let mut rest = format;
+    while !rest.is_empty() {
+        if let Some((placeholder, escape)) = PLACEHOLDERS
+            .iter()
+            .find(|(placeholder, _)| rest.starts_with(placeholder))
+        {
+            prompt.push_str(escape);
+            rest = &rest[placeholder.len()..];
+        } else {
+            let c = rest.chars().next().unwrap();
+            if matches!(c, '%' | '?' | ':' | '.' | '\\') {
+                prompt.push('\\');
+            }
+            prompt.push(c);
+            rest = &rest[c.len_utf8()..];
+        }
+    }
+    prompt
+}
+
 // Create a less history file to be used by delta's child less process. This file is initialized
 // with the contents of user's real less hist file, to which the navigate regex is appended. This
 // has the effect that 'n' or 'N' in delta's less process will search for the navigate regex,
@@ -145,8 +177,21 @@ fn get_less_hist_file() -> Option<PathBuf> {
 mod tests {
     use std::fs::remove_file;
 
+    use super::make_navigate_less_prompt;
     use crate::tests::integration_test_utils;
 
+    #[test]
+    fn test_make_navigate_less_prompt() {
+        assert_eq!(
+            make_navigate_less_prompt("file {current} of {total}, line {line}/{lines}"),
+            "file %i of %m, line %lt/%L"
+        );
+        assert_eq!(
+            make_navigate_less_prompt("{percent} done? 100% {unknown}."),
+            "%pt\\% done\\? 100\\% {unknown}\\."
+        );
+    }
+
     #[test]
     fn test_navigate_with_overridden_key_in_main_section() {
         let git_config_contents = b"
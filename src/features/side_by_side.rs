@@ -47,10 +47,15 @@ pub type SideBySideData = LeftRight<Panel>;
 
 impl SideBySideData {
     /// Create a [`LeftRight<Panel>`](LeftRight<Panel>) named [`SideBySideData`].
-    pub fn new_sbs(decorations_width: &cli::Width, available_terminal_width: &usize) -> Self {
+    /// The panels share the width which is not taken by the `gap` between them.
+    pub fn new_sbs(
+        decorations_width: &cli::Width,
+        available_terminal_width: &usize,
+        gap: usize,
+    ) -> Self {
         let panel_width = match decorations_width {
-            cli::Width::Fixed(w) => w / 2,
-            _ => available_terminal_width / 2,
+            cli::Width::Fixed(w) => w.saturating_sub(gap) / 2,
+            _ => available_terminal_width.saturating_sub(gap) / 2,
         };
         SideBySideData::new(Panel { width: panel_width }, Panel { width: panel_width })
     }
@@ -186,6 +191,7 @@ pub fn paint_minus_and_plus_lines_side_by_side(
             bg_should_fill[Left],
             config,
         ));
+        push_panel_gap(output_buffer, config);
 
         let right_state = match plus_line_index {
             Some(i) => &line_states[Right][i],
@@ -271,11 +277,57 @@ pub fn paint_zero_lines_side_by_side<'a>(
                 config,
             );
             output_buffer.push_str(&panel_line);
+            if *panel_side == Left {
+                push_panel_gap(output_buffer, config);
+            }
         }
         output_buffer.push('\n');
     }
 }
 
+/// Paint a `\ No newline at end of file` marker line. The marker is placed in the panel of the
+/// side it belongs to, or in both panels if `panel_side` is `None` (i.e. it follows an unchanged
+/// line, so neither side ends with a newline).
+pub fn paint_no_newline_marker_side_by_side(
+    marker: &str,
+    panel_side: Option<PanelSide>,
+    output_buffer: &mut String,
+    config: &Config,
+) {
+    for side in [Left, Right] {
+        let mut panel_line = if panel_side.is_none() || panel_side == Some(side) {
+            marker.to_string()
+        } else {
+            String::new()
+        };
+        let panel_width = config.side_by_side_data[side].width;
+        let text_width = ansi::measure_text_width(&panel_line);
+        if text_width > panel_width {
+            panel_line =
+                ansi::truncate_str(&panel_line, panel_width, &config.truncation_symbol).to_string();
+        } else if side == Left {
+            panel_line.push_str(&" ".repeat(panel_width - text_width));
+        }
+        output_buffer.push_str(&panel_line);
+        if side == Left {
+            push_panel_gap(output_buffer, config);
+        }
+    }
+    output_buffer.push('\n');
+}
+
+/// Write the columns between the left and right panels (see --side-by-side-gap).
+fn push_panel_gap(output_buffer: &mut String, config: &Config) {
+    if config.side_by_side_gap > 0 {
+        output_buffer.push_str(
+            &config
+                .side_by_side_gap_style
+                .paint(" ".repeat(config.side_by_side_gap))
+                .to_string(),
+        );
+    }
+}
+
 #[allow(clippy::too_many_arguments)]
 fn paint_left_panel_minus_line<'a>(
     line_index: Option<usize>,
@@ -560,15 +612,20 @@ pub mod ansifill {
         pub fn new(config: &Config) -> Self {
             Self(
                 config.side_by_side
-                    && Self::is_odd_with_ansi(&config.decorations_width, &config.line_fill_method),
+                    && Self::is_odd_with_ansi(
+                        &config.decorations_width,
+                        config.side_by_side_gap,
+                        &config.line_fill_method,
+                    ),
             )
         }
         pub fn sbs_odd_fix(
             width: &crate::cli::Width,
+            gap: usize,
             method: &BgFillMethod,
             sbs_data: SideBySideData,
         ) -> SideBySideData {
-            if Self::is_odd_with_ansi(width, method) {
+            if Self::is_odd_with_ansi(width, gap, method) {
                 Self::adapt_sbs_data(sbs_data)
             } else {
                 sbs_data
@@ -577,9 +634,9 @@ pub mod ansifill {
         pub fn pad_width(&self) -> bool {
             self.0
         }
-        fn is_odd_with_ansi(width: &crate::cli::Width, method: &BgFillMethod) -> bool {
+        fn is_odd_with_ansi(width: &crate::cli::Width, gap: usize, method: &BgFillMethod) -> bool {
             method == &BgFillMethod::TryAnsiSequence
-                && matches!(&width, crate::cli::Width::Fixed(width) if width % 2 == 1)
+                && matches!(&width, crate::cli::Width::Fixed(width) if width.saturating_sub(gap) % 2 == 1)
         }
         fn adapt_sbs_data(mut sbs_data: SideBySideData) -> SideBySideData {
             sbs_data[super::Right].width += 1;
@@ -595,6 +652,8 @@ pub mod tests {
     use crate::tests::integration_test_utils::{make_config_from_args, run_delta, DeltaTest};
     use insta::assert_snapshot;
 
+    use super::{Left, Right};
+
     #[test]
     fn test_two_fitting_minus_lines() {
         // rustfmt ignores the assert macro arguments, so do the setup outside
@@ -670,6 +729,97 @@ pub mod tests {
         (blue)│(88)    (blue)│(normal)              (blue) │(28)  2 (blue)│(231 22)b (203)=(231) (141)234567(normal)"#);
     }
 
+    #[test]
+    fn test_side_by_side_gap() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--width",
+            "44",
+            "--side-by-side-gap=3",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(TWO_PLUS_LINES_DIFF)
+        .expect_after_header(
+            r#"
+            │    │                 │  1 │a = 1         
+            │    │                 │  2 │b = 234567    "#,
+        );
+        // The gap is taken from the width of the panels.
+        let config = make_config_from_args(&["--side-by-side", "--width=41"]);
+        assert_eq!(config.side_by_side_data[Left].width, 20);
+        let config =
+            make_config_from_args(&["--side-by-side", "--width=41", "--side-by-side-gap=5"]);
+        assert_eq!(config.side_by_side_data[Left].width, 18);
+        assert_eq!(config.side_by_side_data[Right].width, 18);
+    }
+
+    #[test]
+    fn test_side_by_side_shared_gutter() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-shared-gutter",
+            "--width",
+            "40",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(ONE_MINUS_ONE_PLUS_LINE_DIFF)
+        .expect_after_header(
+            r#"
+            a = 1               │  1 │a = 1
+            b = 2               │  2 │bb = 2        "#,
+        );
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-shared-gutter",
+            "--width",
+            "40",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(TWO_MINUS_LINES_DIFF)
+        .expect_after_header(
+            r#"
+            a = 1               │    │
+            b = 23456           │    │"#,
+        );
+        // expect_after_header would strip the leading spaces of these lines.
+        let result = DeltaTest::with_args(&[
+            "--side-by-side",
+            "--side-by-side-shared-gutter",
+            "--width",
+            "40",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(TWO_PLUS_LINES_DIFF);
+        let lines: Vec<&str> = result.output.lines().skip(7).collect();
+        assert_eq!(
+            lines,
+            [
+                "                    │  1 │a = 1         ",
+                "                    │  2 │b = 234567    ",
+            ]
+        );
+        // Without side-by-side layout, the line numbers are as usual.
+        let config = make_config_from_args(&["--line-numbers", "--side-by-side-shared-gutter"]);
+        assert_eq!(config.line_numbers_format[Left], "{nm:^4}⋮");
+    }
+
+    #[test]
+    fn test_side_by_side_gap_style() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--width",
+            "44",
+            "--side-by-side-gap=3",
+            "--side-by-side-gap-style=normal red",
+            "--line-fill-method=spaces",
+        ])
+        .explain_ansi()
+        .with_input(TWO_PLUS_LINES_DIFF)
+        .expect_after_header(r#"
+        (blue)│(88)    (blue)│(normal)              (normal red)   (blue)│(28)  1 (blue)│(231 22)a (203)=(231) (141)1(normal 22)         (normal)
+        (blue)│(88)    (blue)│(normal)              (normal red)   (blue)│(28)  2 (blue)│(231 22)b (203)=(231) (141)234567(normal 22)    (normal)"#);
+    }
+
     #[test]
     fn test_two_plus_lines_truncated() {
         let mut config = make_config_from_args(&[
@@ -717,6 +867,42 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_no_newline_marker_only_on_new_side() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--width",
+            "72",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(NO_NEWLINE_ON_NEW_SIDE_DIFF)
+        .expect_after_header(
+            r#"
+            #indent_mark
+            │  1 │a = 1                         │  1 │a = 1
+            │  2 │b = 2                         │  2 │bb = 2                        
+                                                \ No newline at end of file"#,
+        );
+    }
+
+    #[test]
+    fn test_no_newline_marker_only_on_old_side() {
+        DeltaTest::with_args(&[
+            "--side-by-side",
+            "--width",
+            "72",
+            "--line-fill-method=spaces",
+        ])
+        .with_input(NO_NEWLINE_ON_OLD_SIDE_DIFF)
+        .expect_after_header(
+            r#"
+            │  1 │a = 1                         │  1 │a = 1
+            │  2 │b = 2                         │    │
+            \ No newline at end of file         
+            │    │                              │  2 │bb = 2                        "#,
+        );
+    }
+
     #[test]
     fn test_two_minus_lines_unicode_truncated() {
         DeltaTest::with_args(&[
@@ -770,4 +956,74 @@ pub mod tests {
             │    │三 │    │"#,
         );
     }
+
+    #[test]
+    fn test_side_by_side_mode_change_with_rename() {
+        let args = [
+            "--side-by-side",
+            "--width",
+            "40",
+            "--line-fill-method=spaces",
+        ];
+        // The mode change and the rename are written across both panels, above the hunks.
+        let result = DeltaTest::with_args(&args).with_input(MODE_CHANGE_WITH_RENAME_DIFF);
+        let lines: Vec<&str> = result.output.lines().collect();
+        assert_eq!(lines[1], "renamed: a.py ⟶   b.py (mode +x)");
+        assert_eq!(lines[2], "─".repeat(40));
+        assert_eq!(lines[7], "│  1 │a = 1         │  1 │a = 1");
+
+        // The box around them is as wide as they are.
+        let result = DeltaTest::with_args(&[&args[..], &["--file-decoration-style=box"]].concat())
+            .with_input(MODE_CHANGE_WITH_RENAME_DIFF);
+        let lines: Vec<&str> = result.output.lines().collect();
+        assert_eq!(
+            lines[1..4],
+            [
+                format!("{}┐", "─".repeat(33)).as_str(),
+                "renamed: a.py ⟶   b.py (mode +x) │",
+                format!("{}┘", "─".repeat(33)).as_str(),
+            ]
+        );
+        assert_eq!(lines[8], "│  1 │a = 1         │  1 │a = 1");
+    }
+
+    const MODE_CHANGE_WITH_RENAME_DIFF: &str = "\
+diff --git a/a.py b/b.py
+old mode 100644
+new mode 100755
+similarity index 50%
+rename from a.py
+rename to b.py
+index 223ca50..367a6f6
+--- a/a.py
++++ b/b.py
+@@ -1,2 +1,2 @@
+ a = 1
+-b = 2
++bb = 2
+";
+
+    const NO_NEWLINE_ON_NEW_SIDE_DIFF: &str = "\
+diff --git i/a.py w/a.py
+index 223ca50..367a6f6 100644
+--- i/a.py
++++ w/a.py
+@@ -1,2 +1,2 @@
+ a = 1
+-b = 2
++bb = 2
+\\ No newline at end of file
+";
+
+    const NO_NEWLINE_ON_OLD_SIDE_DIFF: &str = "\
+diff --git i/a.py w/a.py
+index 223ca50..367a6f6 100644
+--- i/a.py
++++ w/a.py
+@@ -1,2 +1,2 @@
+ a = 1
+-b = 2
+\\ No newline at end of file
++bb = 2
+";
 }
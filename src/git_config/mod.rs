@@ -2,6 +2,7 @@ mod remote;
 
 pub use remote::GitRemoteRepo;
 
+use crate::cli::SideBySide;
 use crate::env::DeltaEnv;
 use regex::Regex;
 use std::collections::HashMap;
@@ -214,6 +215,21 @@ impl GitConfigGet for Option<String> {
     }
 }
 
+// All the values of a key which may be given more than once.
+impl GitConfigGet for Vec<String> {
+    fn git_config_get(key: &str, git_config: &GitConfig) -> Option<Self> {
+        if let Some(val) = git_config.config_from_env_var.get(key) {
+            return Some(vec![val.to_string()]);
+        }
+        let mut values = Vec::new();
+        let mut entries = git_config.config.multivar(key, None).ok()?;
+        while let Some(Ok(entry)) = entries.next() {
+            values.extend(entry.value().map(str::to_string));
+        }
+        (!values.is_empty()).then_some(values)
+    }
+}
+
 impl GitConfigGet for bool {
     fn git_config_get(key: &str, git_config: &GitConfig) -> Option<Self> {
         match git_config.config_from_env_var.get(key).map(|s| s.as_str()) {
@@ -224,6 +240,21 @@ impl GitConfigGet for bool {
     }
 }
 
+impl GitConfigGet for SideBySide {
+    fn git_config_get(key: &str, git_config: &GitConfig) -> Option<Self> {
+        match String::git_config_get(key, git_config).as_deref() {
+            Some("auto") => Some(SideBySide::Auto),
+            _ => bool::git_config_get(key, git_config).map(|value| {
+                if value {
+                    SideBySide::True
+                } else {
+                    SideBySide::False
+                }
+            }),
+        }
+    }
+}
+
 impl GitConfigGet for usize {
     fn git_config_get(key: &str, git_config: &GitConfig) -> Option<Self> {
         if let Some(s) = git_config.config_from_env_var.get(key) {
@@ -238,6 +269,12 @@ impl GitConfigGet for usize {
     }
 }
 
+impl GitConfigGet for Option<usize> {
+    fn git_config_get(key: &str, git_config: &GitConfig) -> Option<Self> {
+        usize::git_config_get(key, git_config).map(Some)
+    }
+}
+
 impl GitConfigGet for f64 {
     fn git_config_get(key: &str, git_config: &GitConfig) -> Option<Self> {
         if let Some(s) = git_config.config_from_env_var.get(key) {
@@ -252,6 +289,12 @@ impl GitConfigGet for f64 {
     }
 }
 
+impl GitConfigGet for Option<f64> {
+    fn git_config_get(key: &str, git_config: &GitConfig) -> Option<Self> {
+        f64::git_config_get(key, git_config).map(Some)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
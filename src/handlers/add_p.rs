@@ -0,0 +1,111 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+
+use crate::ansi;
+use crate::config::AddPMode;
+use crate::delta::StateMachine;
+
+lazy_static! {
+    // E.g. "(1/2) Stage this hunk [y,n,q,a,d,e,?]? " or "Discard mode change from worktree [y,n]? "
+    static ref ADD_P_PROMPT_REGEX: Regex = Regex::new(
+        r"^(?:\(\d+/\d+\) )?(?:Stage|Unstage|Stash|Apply|Discard) (?:this hunk|mode change|deletion|addition)(?: (?:to|from) [a-z ]+)? \[[^\]]+\]\? ?$"
+    )
+    .unwrap();
+}
+
+impl StateMachine<'_> {
+    fn test_add_p_prompt_line(&self) -> bool {
+        match self.config.add_p_mode {
+            AddPMode::Never => false,
+            AddPMode::Auto => self.test_hunk_line() && ADD_P_PROMPT_REGEX.is_match(&self.line),
+            AddPMode::Always => ADD_P_PROMPT_REGEX.is_match(&self.line),
+        }
+    }
+
+    /// Write a prompt of an interactive command such as `git add -p` uncolored, without a trailing
+    /// newline, and immediately, so that the user can answer it.
+    pub fn handle_add_p_prompt_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_add_p_prompt_line() {
+            return Ok(false);
+        }
+        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.emit()?;
+        write!(
+            self.painter.writer,
+            "{}",
+            ansi::strip_ansi_codes(&self.raw_line)
+        )?;
+        self.painter.writer.flush()?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ADD_P_PROMPT_REGEX;
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    const DIFF_WITH_PROMPT: &str = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ a
+-b
++c
+\x1b[1;34m(1/1) Stage this hunk [y,n,q,a,d,e,?]? \x1b[m
+";
+
+    #[test]
+    fn test_add_p_prompt_regex() {
+        for prompt in [
+            "(1/2) Stage this hunk [y,n,q,a,d,j,J,g,/,e,?]? ",
+            "(2/2) Stage mode change [y,n,q,a,d,k,K,g,/,?]? ",
+            "(1/1) Unstage deletion [y,n,q,a,d,?]? ",
+            "(1/1) Discard this hunk from index and worktree [y,n,q,a,d,e,?]? ",
+            "Apply addition to worktree [y,n]? ",
+        ] {
+            assert!(ADD_P_PROMPT_REGEX.is_match(prompt), "{}", prompt);
+        }
+        for line in [" Stage this hunk [y,n]? ", "Stage this hunk"] {
+            assert!(!ADD_P_PROMPT_REGEX.is_match(line), "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_add_p_prompt() {
+        let output = DeltaTest::with_args(&[]).with_input(DIFF_WITH_PROMPT);
+        // The prompt follows the hunk, uncolored and without a newline.
+        assert!(output
+            .raw_output
+            .ends_with("\n(1/1) Stage this hunk [y,n,q,a,d,e,?]? "));
+        let lines: Vec<&str> = output.output.lines().collect();
+        assert_eq!(
+            lines[lines.len() - 3..],
+            ["b", "c", "(1/1) Stage this hunk [y,n,q,a,d,e,?]? "]
+        );
+    }
+
+    #[test]
+    fn test_add_p_prompt_outside_hunk() {
+        let input = "\x1b[1;34m(1/1) Stage this hunk [y,n,q,a,d,e,?]? \x1b[m\n";
+        let output = DeltaTest::with_args(&[]).with_input(input).raw_output;
+        assert!(output.contains('\x1b'));
+        assert!(output.ends_with('\n'));
+        let output = DeltaTest::with_args(&["--add-p-mode=always"])
+            .with_input(input)
+            .raw_output;
+        assert_eq!(output, "(1/1) Stage this hunk [y,n,q,a,d,e,?]? ");
+    }
+
+    #[test]
+    fn test_add_p_mode_never() {
+        let output = DeltaTest::with_args(&["--add-p-mode=never"])
+            .with_input(DIFF_WITH_PROMPT)
+            .raw_output;
+        assert!(output.ends_with("\x1b[m\n"));
+        assert!(strip_ansi_codes(&output).ends_with("\n(1/1) Stage this hunk [y,n,q,a,d,e,?]? \n"));
+    }
+}
@@ -0,0 +1,152 @@
+use crate::delta::{State, StateMachine};
+
+const BINARY_PATCH_LINE: &str = "GIT binary patch";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryPatchKind {
+    Literal,
+    Delta,
+}
+
+impl StateMachine<'_> {
+    #[inline]
+    fn test_binary_patch_line(&self) -> bool {
+        matches!(self.state, State::DiffHeader(_))
+            && (self.line == BINARY_PATCH_LINE || self.binary_patch_sections.is_some())
+            && self.should_handle()
+            && !self.config.color_only
+    }
+
+    /// Collapse the base85-encoded data of a `GIT binary patch` section, as emitted by
+    /// `git diff --binary`, to a one-line summary.
+    pub fn handle_binary_patch_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_binary_patch_line() {
+            return Ok(false);
+        }
+        if self.line == BINARY_PATCH_LINE {
+            self.mark_diff_files_as_binary();
+            self.handle_pending_line_with_diff_name()?;
+            self.binary_patch_sections = Some(0);
+            return Ok(true);
+        }
+        if let Some((kind, size)) = parse_binary_patch_sub_header(&self.line) {
+            // The first section transforms the old file into the new one; describe only that one,
+            // not the reverse section which follows it.
+            if self.binary_patch_sections == Some(0) {
+                self.painter.emit()?;
+                writeln!(
+                    self.painter.writer,
+                    "{}",
+                    ansi_term::Style::new()
+                        .dimmed()
+                        .paint(format_binary_patch_summary(kind, size))
+                )?;
+            }
+            self.binary_patch_sections = self.binary_patch_sections.map(|n| n + 1);
+        }
+        // The remaining lines are base85-encoded data, or blank lines ending a section.
+        Ok(true)
+    }
+}
+
+/// Parse a `literal <size>` or `delta <size>` line starting a section of a binary patch.
+pub fn parse_binary_patch_sub_header(line: &str) -> Option<(BinaryPatchKind, usize)> {
+    let (kind, size) = line.split_once(' ')?;
+    let kind = match kind {
+        "literal" => BinaryPatchKind::Literal,
+        "delta" => BinaryPatchKind::Delta,
+        _ => return None,
+    };
+    Some((kind, size.parse().ok()?))
+}
+
+fn format_binary_patch_summary(kind: BinaryPatchKind, size: usize) -> String {
+    let unit = if size == 1 { "byte" } else { "bytes" };
+    match kind {
+        BinaryPatchKind::Literal => format!("Binary patch ({size} {unit})"),
+        BinaryPatchKind::Delta => format!("Binary patch (delta, {size} {unit})"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+    use insta::assert_snapshot;
+
+    #[test]
+    fn test_parse_binary_patch_sub_header() {
+        assert_eq!(
+            parse_binary_patch_sub_header("literal 10"),
+            Some((BinaryPatchKind::Literal, 10))
+        );
+        assert_eq!(
+            parse_binary_patch_sub_header("delta 123"),
+            Some((BinaryPatchKind::Delta, 123))
+        );
+        assert_eq!(parse_binary_patch_sub_header("literal"), None);
+        assert_eq!(parse_binary_patch_sub_header("literal x"), None);
+        assert_eq!(
+            parse_binary_patch_sub_header("RcmZQzWJ*j*PDxE;000V*0$Bh6"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_binary_patch_is_summarized() {
+        let result = DeltaTest::with_args(&[]).with_input(BINARY_PATCH_DIFF);
+        assert_snapshot!(result.output, @r"
+        img.bin (binary file)
+        ───────────────────────────────────────────
+        Binary patch (10 bytes)
+
+        added: new.bin (binary file)
+        ───────────────────────────────────────────
+        Binary patch (delta, 8 bytes)
+
+        added: TXT
+        ───────────────────────────────────────────
+
+        ───┐
+        1: │
+        ───┘
+        plain text
+        ");
+    }
+
+    #[test]
+    fn test_binary_patch_is_kept_with_color_only() {
+        let result = DeltaTest::with_args(&["--color-only"]).with_input(BINARY_PATCH_DIFF);
+        assert!(result.output.contains("RcmZQzWJ*j*PDxE;000V*0$Bh6"));
+        assert!(!result.output.contains("Binary patch ("));
+    }
+
+    const BINARY_PATCH_DIFF: &str = "\
+diff --git a/img.bin b/img.bin
+index 5e07d261855586626d75321e8ab899341f972802..802622874a6c0923576c14d817d0bc5db0a98992 100644
+GIT binary patch
+literal 10
+RcmZQzWJ*j*PDxE;000V*0$Bh6
+
+literal 6
+NcmZQzWJ*j*1^@zG0V)6h
+
+diff --git a/new.bin b/new.bin
+new file mode 100644
+index 0000000000000000000000000000000000000000..e16438d31fa709966c53280d1fdd33e5fc883475
+GIT binary patch
+delta 8
+Pcmc~xEoVr}%t-|R4HW|G
+
+delta 0
+HcmV?d00001
+
+diff --git a/TXT b/TXT
+new file mode 100644
+index 0000000..323fae0
+--- /dev/null
++++ b/TXT
+@@ -0,0 +1 @@
++plain text
+";
+}
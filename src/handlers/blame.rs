@@ -55,8 +55,12 @@ impl StateMachine<'_> {
                     formatted_blame_metadata =
                         " ".repeat(measure_text_width(&formatted_blame_metadata))
                 };
+                let domain = match self.config.blame_color_by {
+                    config::BlameColorBy::Domain => email_domain(blame.author),
+                    config::BlameColorBy::Commit => None,
+                };
                 let metadata_style =
-                    self.blame_metadata_style(&key, previous_key.as_deref(), is_repeat);
+                    self.blame_metadata_style(&key, previous_key.as_deref(), is_repeat, domain);
                 let code_style = self.config.blame_code_style.unwrap_or(metadata_style);
                 let separator_style = self.config.blame_separator_style.unwrap_or(code_style);
 
@@ -105,6 +109,7 @@ impl StateMachine<'_> {
         key: &str,
         previous_key: Option<&str>,
         is_repeat: bool,
+        domain: Option<&str>,
     ) -> Style {
         let mut style = match paint::parse_style_sections(&self.raw_line, self.config).first() {
             Some((style, _)) if style != &Style::default() => {
@@ -114,7 +119,10 @@ impl StateMachine<'_> {
             }
             _ => {
                 // Compute the color ourselves.
-                let color = self.get_color(key, previous_key, is_repeat);
+                let color = match domain {
+                    Some(domain) => get_domain_color(domain, &self.config.blame_palette),
+                    None => self.get_color(key, previous_key, is_repeat),
+                };
                 // TODO: This will often be pointlessly updating a key with the
                 // value it already has. It might be nicer to do this (and
                 // compute the style) in get_color(), but as things stand the
@@ -187,6 +195,24 @@ impl StateMachine<'_> {
     }
 }
 
+/// Return the domain of the email address in the author field of a blame line, as shown by
+/// `git blame -e`, e.g. "example.com" for "<dan@example.com>".
+fn email_domain(author: &str) -> Option<&str> {
+    let email = author.strip_prefix('<')?.strip_suffix('>')?;
+    let (_, domain) = email.rsplit_once('@')?;
+    Some(domain).filter(|domain| !domain.is_empty())
+}
+
+/// Choose a color of the palette for the domain. This depends only on the domain (ignoring case)
+/// and the palette, so that a domain has the same color in every run.
+fn get_domain_color(domain: &str, palette: &[String]) -> String {
+    let hash = domain.bytes().fold(0_u32, |hash, byte| {
+        hash.wrapping_mul(31)
+            .wrapping_add(byte.to_ascii_lowercase() as u32)
+    });
+    palette[hash as usize % palette.len()].clone()
+}
+
 #[derive(Debug)]
 pub struct BlameLine<'a> {
     pub commit: &'a str,
@@ -538,6 +564,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_email_domain() {
+        assert_eq!(email_domain("<dan@example.com>"), Some("example.com"));
+        assert_eq!(email_domain("<a@b@example.com>"), Some("example.com"));
+        assert_eq!(email_domain("Dan Davison"), None);
+        assert_eq!(email_domain("<dan>"), None);
+        assert_eq!(email_domain("<dan@>"), None);
+    }
+
+    #[test]
+    fn test_color_by_domain() {
+        let mut writer = Cursor::new(vec![0; 512]);
+        let config = integration_test_utils::make_config_from_args(&[
+            "--blame-format",
+            "{author} {commit}",
+            "--blame-palette",
+            "1 2 3 4 5 6 7",
+            "--blame-color-by=domain",
+        ]);
+        let mut machine = StateMachine::new(&mut writer, &config);
+        let mut colors = Vec::new();
+        for line in [
+            "aaaaaaa (<dan@example.com>   2021-08-22 18:20:19 -0700 1) A",
+            "bbbbbbb (<ann@example.org>   2020-07-18 15:34:43 -0400 2) B",
+            "ccccccc (<bob@Example.com>   2020-07-18 15:34:43 -0400 3) C",
+            "ddddddd (<eve@example.org>   2020-07-18 15:34:43 -0400 4) D",
+            "eeeeeee (<dan@example.com>   2021-08-22 18:20:19 -0700 5) E",
+        ] {
+            machine.line = line.into();
+            machine.handle_blame_line().unwrap();
+            let State::Blame(key) = &machine.state else {
+                panic!("not a blame line: {}", line);
+            };
+            colors.push(machine.blame_key_colors[key].clone());
+        }
+        // Lines of the same domain share a color, even when they are consecutive.
+        assert_eq!(colors[0], colors[2]);
+        assert_eq!(colors[0], colors[4]);
+        assert_eq!(colors[1], colors[3]);
+        assert_ne!(colors[0], colors[1]);
+        assert_eq!(
+            colors[0],
+            get_domain_color("example.com", &config.blame_palette)
+        );
+        assert_eq!(
+            colors[1],
+            get_domain_color("example.org", &config.blame_palette)
+        );
+    }
+
+    #[test]
+    fn test_color_by_domain_without_email() {
+        let mut writer = Cursor::new(vec![0; 512]);
+        let config = integration_test_utils::make_config_from_args(&[
+            "--blame-format",
+            "{author} {commit}",
+            "--blame-palette",
+            "1 2",
+            "--blame-color-by=domain",
+        ]);
+        let mut machine = StateMachine::new(&mut writer, &config);
+        // Without an email address, lines are colored by commit.
+        for line in [
+            "aaaaaaa (Dan Davison  2021-08-22 18:20:19 -0700 1) A",
+            "bbbbbbb (Dan Davison  2020-07-18 15:34:43 -0400 2) B",
+        ] {
+            machine.line = line.into();
+            machine.handle_blame_line().unwrap();
+        }
+        assert_eq!(
+            hashmap_items(&machine.blame_key_colors),
+            &[
+                ("Dan Davison     aaaaaaa        ", "1"),
+                ("Dan Davison     bbbbbbb        ", "2")
+            ]
+        );
+    }
+
     fn hashmap_items(hashmap: &HashMap<String, String>) -> Vec<(&str, &str)> {
         hashmap
             .iter()
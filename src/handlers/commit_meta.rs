@@ -1,8 +1,20 @@
 use std::borrow::Cow;
+use std::collections::VecDeque;
+
+use itertools::Itertools;
+use lazy_static::lazy_static;
+use regex::Regex;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 use super::draw;
-use crate::delta::{State, StateMachine};
+use crate::ansi;
+use crate::config::{CommitMetaOverflow, CommitSubjectTruncation, Config};
+use crate::delta::{format_raw_line, State, StateMachine};
 use crate::features;
+use crate::handlers::blame::parse_git_blame_line;
+use crate::handlers::toc::get_changed_files;
+use crate::style::Style;
 
 impl StateMachine<'_> {
     #[inline]
@@ -18,7 +30,19 @@ impl StateMachine<'_> {
         self.painter.paint_buffered_minus_and_plus_lines();
         self.handle_pending_line_with_diff_name()?;
         self.state = State::CommitMeta;
-        if self.should_handle() {
+        self.pending_commit_files = self.commit_files.pop_front();
+        self.shown_file_category = None;
+        if let Some(blamer) = self.removed_line_blamer.as_mut() {
+            if let Some(commit) = self
+                .line
+                .strip_prefix("commit ")
+                .and_then(|rest| rest.split_whitespace().next())
+                .filter(|commit| commit.chars().all(|c| c.is_ascii_hexdigit()))
+            {
+                blamer.start_commit(commit);
+            }
+        }
+        if self.should_handle() || !ref_styles_are_raw(self.config) {
             self.painter.emit()?;
             self._handle_commit_meta_header_line()?;
             handled_line = true
@@ -26,12 +50,226 @@ impl StateMachine<'_> {
         Ok(handled_line)
     }
 
+    #[inline]
+    fn test_commit_notes_line(&mut self) -> bool {
+        if self.state != State::CommitMeta {
+            self.in_commit_notes = false;
+        } else if NOTES_HEADER_REGEX.is_match(&self.line) {
+            self.in_commit_notes = true;
+        } else if !self.line.starts_with("    ") {
+            self.in_commit_notes = false;
+        }
+        self.in_commit_notes
+    }
+
+    /// Handle a line of the notes which `git log --notes` appends to a commit message: a header
+    /// line such as "Notes:" or "Notes (review):", or an indented line of a note.
+    pub fn handle_commit_notes_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_commit_notes_line() {
+            return Ok(false);
+        }
+        let style = self.config.notes_style;
+        if style.is_omitted {
+            return Ok(true);
+        }
+        self.painter.emit()?;
+        if style.is_raw {
+            writeln!(self.painter.writer, "{}", self.raw_line)?;
+        } else {
+            writeln!(self.painter.writer, "{}", style.paint(&self.line))?;
+        }
+        Ok(true)
+    }
+
+    #[inline]
+    fn test_commit_merge_line(&self) -> bool {
+        self.state == State::CommitMeta
+            && MERGE_LINE_REGEX.is_match(&self.line)
+            && (!self.config.commit_merge_style.is_raw || self.config.hyperlinks)
+    }
+
+    /// Handle the "Merge:" line of a merge commit, which lists the hashes of its parents, by
+    /// styling it and, with --hyperlinks, linking each parent hash.
+    pub fn handle_commit_merge_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_commit_merge_line() {
+            return Ok(false);
+        }
+        let style = self.config.commit_merge_style;
+        if style.is_omitted {
+            return Ok(true);
+        }
+        self.painter.emit()?;
+        let line = if style.is_raw {
+            &self.raw_line
+        } else {
+            &self.line
+        };
+        let line = if self.config.hyperlinks {
+            features::hyperlinks::format_commit_line_with_osc8_commit_hyperlink(line, self.config)
+        } else {
+            Cow::from(line)
+        };
+        if style.is_raw {
+            writeln!(self.painter.writer, "{line}")?;
+        } else {
+            writeln!(self.painter.writer, "{}", style.paint(line))?;
+        }
+        Ok(true)
+    }
+
+    #[inline]
+    fn test_commit_meta_field_line(&self) -> bool {
+        self.state == State::CommitMeta
+            && self.config.commit_meta_width > 0
+            && COMMIT_META_FIELD_REGEX.is_match(&self.line)
+            && ansi::measure_text_width(&self.line) > self.config.commit_meta_width
+    }
+
+    /// Handle a field of the commit metadata, such as the "Author:" line, which is wider than
+    /// --commit-meta-width, by truncating or wrapping it.
+    pub fn handle_commit_meta_field_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_commit_meta_field_line() {
+            return Ok(false);
+        }
+        self.painter.emit()?;
+        let width = self.config.commit_meta_width;
+        match self.config.commit_meta_overflow {
+            CommitMetaOverflow::Truncate => {
+                let line = ansi::truncate_str(&self.raw_line, width, "…");
+                writeln!(
+                    self.painter.writer,
+                    "{}",
+                    format_raw_line(&line, self.config)
+                )?;
+            }
+            CommitMetaOverflow::Wrap => {
+                // Continuation lines are indented to the start of the value of the field.
+                let indent = COMMIT_META_FIELD_REGEX
+                    .find(&self.line)
+                    .map_or(0, |m| m.end().min(width / 2));
+                for (i, line) in wrap_text(&self.line[indent..], width, indent)
+                    .into_iter()
+                    .enumerate()
+                {
+                    let prefix = if i == 0 { &self.line[..indent] } else { "" };
+                    writeln!(
+                        self.painter.writer,
+                        "{:indent$}{}",
+                        prefix,
+                        format_raw_line(&line, self.config)
+                    )?;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    #[inline]
+    fn test_commit_message_line(&self) -> bool {
+        self.state == State::CommitMeta
+            && self.line.starts_with("    ")
+            && if self.raw_line.contains('\x1b') {
+                !self.config.preserve_commit_ansi
+            } else {
+                self.links_issues()
+            }
+    }
+
+    // Whether the current line contains references to issues to be linked (used by
+    // --issue-link-format).
+    fn links_issues(&self) -> bool {
+        self.config.hyperlinks
+            && self
+                .config
+                .issue_links
+                .iter()
+                .any(|(regex, _)| regex.is_match(&self.line))
+    }
+
+    /// Handle a line of a commit message which contains ANSI escape sequences, by writing it
+    /// without them (unless --preserve-commit-ansi is given), or references to issues, by writing
+    /// them as hyperlinks.
+    pub fn handle_commit_message_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_commit_message_line() {
+            return Ok(false);
+        }
+        self.painter.emit()?;
+        let line = format_raw_line(&self.line, self.config);
+        let line = if self.links_issues() {
+            features::hyperlinks::format_line_with_osc8_issue_hyperlinks(&line, self.config)
+                .into_owned()
+        } else {
+            line.into_owned()
+        };
+        writeln!(self.painter.writer, "{line}")?;
+        Ok(true)
+    }
+
+    #[inline]
+    fn test_oneline_commit_line(&self) -> bool {
+        self.config.commit_subject_truncation != CommitSubjectTruncation::None
+            && ONELINE_COMMIT_REGEX.is_match(&self.line)
+            && ansi::measure_text_width(&self.line)
+                > self
+                    .config
+                    .decorations_width
+                    .or_available(self.config.available_terminal_width)
+            && parse_git_blame_line(&self.line, &self.config.blame_timestamp_format).is_none()
+    }
+
+    /// Handle a line of `git log --oneline` output which is wider than the terminal by truncating
+    /// its subject, keeping the commit hash intact (used by --commit-subject-truncation).
+    pub fn handle_oneline_commit_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_oneline_commit_line() {
+            return Ok(false);
+        }
+        self.painter.paint_buffered_minus_and_plus_lines();
+        self.handle_pending_line_with_diff_name()?;
+        self.state = State::CommitMeta;
+        self.painter.emit()?;
+        let width = self
+            .config
+            .decorations_width
+            .or_available(self.config.available_terminal_width);
+        let line = match self.config.commit_subject_truncation {
+            CommitSubjectTruncation::Middle => {
+                // The commit hash and the space following it are kept.
+                let keep = self.line.find(' ').map_or(0, |i| i + 1);
+                truncate_middle(&self.raw_line, width, keep)
+            }
+            _ => ansi::truncate_str(&self.raw_line, width, "…").to_string(),
+        };
+        writeln!(self.painter.writer, "{line}")?;
+        Ok(true)
+    }
+
+    /// Write the list of the files changed by the current commit, if it is waiting to be written
+    /// between the commit message and the diff (used by --log-show-files).
+    pub fn emit_pending_commit_files(&mut self) -> std::io::Result<()> {
+        let files = match self.pending_commit_files.take() {
+            Some(files) if !files.is_empty() && !self.config.color_only => files,
+            _ => return Ok(()),
+        };
+        for file in &files {
+            writeln!(
+                self.painter.writer,
+                "    {}",
+                self.config.file_style.paint(file)
+            )?;
+        }
+        writeln!(self.painter.writer)?;
+        Ok(())
+    }
+
     fn _handle_commit_meta_header_line(&mut self) -> std::io::Result<()> {
         if self.config.commit_style.is_omitted {
             return Ok(());
         }
         let (mut draw_fn, pad, decoration_ansi_term_style) =
-            draw::get_draw_function(self.config.commit_style.decoration_style);
+            draw::get_draw_function(self.config.commit_style.decoration_style, self.config);
+        let ref_styled_line =
+            format_commit_line_with_ref_styles(&self.line, &self.raw_line, self.config);
+        let raw_line = ref_styled_line.as_ref().unwrap_or(&self.raw_line);
         let (formatted_line, formatted_raw_line) = if self.config.hyperlinks {
             (
                 features::hyperlinks::format_commit_line_with_osc8_commit_hyperlink(
@@ -39,12 +277,21 @@ impl StateMachine<'_> {
                     self.config,
                 ),
                 features::hyperlinks::format_commit_line_with_osc8_commit_hyperlink(
-                    &self.raw_line,
+                    raw_line,
                     self.config,
                 ),
             )
         } else {
-            (Cow::from(&self.line), Cow::from(&self.raw_line))
+            (Cow::from(&self.line), Cow::from(raw_line))
+        };
+        // The line styled by ref is written as it is, within the decoration of the commit style.
+        let commit_style = if ref_styled_line.is_some() {
+            Style {
+                is_raw: true,
+                ..self.config.commit_style
+            }
+        } else {
+            self.config.commit_style
         };
 
         draw_fn(
@@ -53,9 +300,601 @@ impl StateMachine<'_> {
             &format!("{}{}", formatted_raw_line, if pad { " " } else { "" }),
             "",
             &self.config.decorations_width,
-            self.config.commit_style,
+            commit_style,
             decoration_ansi_term_style,
         )?;
         Ok(())
     }
 }
+
+/// Return the paths of the files changed by each commit in `input`, in the order the commits
+/// appear in, as listed by --log-show-files. Lines preceding the first commit are ignored.
+pub fn get_commit_files(input: &[u8], config: &Config) -> VecDeque<Vec<String>> {
+    let mut commit_files = VecDeque::new();
+    let mut commit_start = None;
+    let mut offset = 0;
+    for line in input.split_inclusive(|&byte| byte == b'\n') {
+        let text = ansi::strip_ansi_codes(&String::from_utf8_lossy(line));
+        if config.commit_regex.is_match(text.trim_end_matches('\n')) {
+            if let Some(start) = commit_start {
+                commit_files.push_back(get_changed_files(&input[start..offset]));
+            }
+            commit_start = Some(offset);
+        }
+        offset += line.len();
+    }
+    if let Some(start) = commit_start {
+        commit_files.push_back(get_changed_files(&input[start..]));
+    }
+    commit_files
+}
+
+/// Return the commit hash line with each ref of its decoration, e.g. "(HEAD -> main, tag: v1.0,
+/// origin/main)", painted in the style of its kind, and the rest of it in the commit style. Text
+/// whose style is raw keeps the colors of `raw_line`. Return None if there is no decoration, or
+/// if all the styles are raw.
+fn format_commit_line_with_ref_styles(
+    line: &str,
+    raw_line: &str,
+    config: &Config,
+) -> Option<String> {
+    if config.commit_style.is_raw && ref_styles_are_raw(config) {
+        return None;
+    }
+    let decoration = COMMIT_DECORATION_REGEX.captures(line)?.get(1)?;
+    let mut sections = vec![(config.commit_style, 0..decoration.start())];
+    let mut start = decoration.start();
+    for (i, ref_name) in decoration.as_str().split(", ").enumerate() {
+        if i > 0 {
+            sections.push((config.commit_style, start..start + 2));
+            start += 2;
+        }
+        // E.g. "HEAD -> main": HEAD points at the branch which follows it.
+        let ref_name = match ref_name.strip_prefix("HEAD -> ") {
+            Some(branch) => {
+                sections.push((config.commit_ref_head_style, start..start + "HEAD".len()));
+                sections.push((
+                    config.commit_style,
+                    start + "HEAD".len()..start + "HEAD -> ".len(),
+                ));
+                start += "HEAD -> ".len();
+                branch
+            }
+            None => ref_name,
+        };
+        let style = match get_ref_kind(ref_name, config) {
+            RefKind::Head => config.commit_ref_head_style,
+            RefKind::Branch => config.commit_ref_branch_style,
+            RefKind::Remote => config.commit_ref_remote_style,
+            RefKind::Tag => config.commit_ref_tag_style,
+            RefKind::Other => config.commit_style,
+        };
+        sections.push((style, start..start + ref_name.len()));
+        start += ref_name.len();
+    }
+    sections.push((config.commit_style, start..line.len()));
+
+    // The colors of the raw line, by byte of the line.
+    let raw_styles: Vec<ansi_term::Style> = ansi::parse_style_sections(raw_line)
+        .into_iter()
+        .flat_map(|(style, text)| vec![style; text.len()])
+        .collect();
+    let raw_style = |i: usize| raw_styles.get(i).copied().unwrap_or_default();
+    let mut result = String::new();
+    for (style, range) in sections.into_iter().coalesce(|(a, a_range), (b, b_range)| {
+        if a == b {
+            Ok((a, a_range.start..b_range.end))
+        } else {
+            Err(((a, a_range), (b, b_range)))
+        }
+    }) {
+        if !style.is_raw {
+            result.push_str(&style.paint(&line[range]).to_string());
+            continue;
+        }
+        let mut i = range.start;
+        while i < range.end {
+            let end = (i..range.end)
+                .find(|&j| raw_style(j) != raw_style(i))
+                .unwrap_or(range.end);
+            result.push_str(&raw_style(i).paint(&line[i..end]).to_string());
+            i = end;
+        }
+    }
+    Some(result)
+}
+
+fn ref_styles_are_raw(config: &Config) -> bool {
+    [
+        config.commit_ref_branch_style,
+        config.commit_ref_head_style,
+        config.commit_ref_remote_style,
+        config.commit_ref_tag_style,
+    ]
+    .iter()
+    .all(|style| style.is_raw)
+}
+
+enum RefKind {
+    Head,
+    Branch,
+    Remote,
+    Tag,
+    Other,
+}
+
+fn get_ref_kind(ref_name: &str, config: &Config) -> RefKind {
+    let is_remote = |name: &str| {
+        config.git_config().is_some_and(|git_config| {
+            git_config
+                .get::<String>(&format!("remote.{name}.url"))
+                .is_some()
+        })
+    };
+    match ref_name {
+        "HEAD" => RefKind::Head,
+        name if name.starts_with("tag: ") || name.starts_with("refs/tags/") => RefKind::Tag,
+        name if name.starts_with("refs/remotes/") => RefKind::Remote,
+        name if name.starts_with("refs/heads/") => RefKind::Branch,
+        name if name.starts_with("refs/") || name == "grafted" || name == "replaced" => {
+            RefKind::Other
+        }
+        name => match name.split_once('/') {
+            Some((remote, _)) if is_remote(remote) => RefKind::Remote,
+            _ => RefKind::Branch,
+        },
+    }
+}
+
+/// Shorten `line` to `width` by replacing the middle of the text following its first `keep`
+/// columns with '…'. The colors of the line are kept.
+fn truncate_middle(line: &str, width: usize, keep: usize) -> String {
+    let text = ansi::strip_ansi_codes(line);
+    if ansi::measure_text_width(&text) <= width {
+        return line.to_string();
+    }
+    let available = width.saturating_sub(keep + 1);
+    let head = ansi::truncate_str_short(line, keep + available - available / 2);
+    let mut tail_width = 0;
+    let tail_start = text
+        .grapheme_indices(true)
+        .rev()
+        .take_while(|(_, g)| {
+            tail_width += g.width();
+            tail_width <= available / 2
+        })
+        .last()
+        .map_or(text.len(), |(i, _)| i);
+    format!("{head}…{}", ansi::ansi_preserving_slice(line, tail_start))
+}
+
+/// Split `text` into lines which fit into `width` after an indent of `indent`, breaking them
+/// after a space where possible.
+fn wrap_text(text: &str, width: usize, indent: usize) -> Vec<String> {
+    let available = width.saturating_sub(indent).max(1);
+    let mut lines = Vec::new();
+    let mut rest = text;
+    while ansi::measure_text_width(rest) > available {
+        let line = ansi::truncate_str_short(rest, available);
+        let end = match line.rfind(' ') {
+            Some(i) if i > 0 => i + 1,
+            _ => line
+                .len()
+                .max(rest.chars().next().map_or(1, char::len_utf8)),
+        };
+        lines.push(rest[..end].trim_end().to_string());
+        rest = &rest[end..];
+    }
+    lines.push(rest.to_string());
+    lines
+}
+
+lazy_static! {
+    // The commit hash line of `git log --decorate` output, with the list of refs in parentheses.
+    static ref COMMIT_DECORATION_REGEX: Regex =
+        Regex::new(r"^commit [0-9a-f]{7,64} \((.+?)\)").unwrap();
+    // The line of a merge commit listing its parents, of which there are more than two in an
+    // octopus merge.
+    static ref MERGE_LINE_REGEX: Regex = Regex::new(r"^Merge:(?: [0-9a-f]{7,64}){2,}$").unwrap();
+    static ref NOTES_HEADER_REGEX: Regex = Regex::new(r"^Notes(?: \(.+\))?:$").unwrap();
+    // A commit line of `git log --oneline` output: the abbreviated hash and the subject.
+    static ref ONELINE_COMMIT_REGEX: Regex = Regex::new(r"^[0-9a-f]{7,40} ").unwrap();
+    // The fields of `git log` commit metadata, including those of --format=fuller.
+    static ref COMMIT_META_FIELD_REGEX: Regex =
+        Regex::new(r"^(?:Author|AuthorDate|Commit|CommitDate|Date|Merge): +").unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::remove_file;
+
+    use crate::ansi::explain_ansi;
+    use crate::tests::integration_test_utils::{self, DeltaTest};
+
+    const GIT_LOG_WITH_NOTES: &str = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Thu May 14 11:13:17 2020 -0400
+
+    Fix the thing
+    Notes: this is not a note
+
+Notes:
+    Reviewed by Ann
+
+Notes (review):
+    Looks good
+    to me
+
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++b
+";
+
+    #[test]
+    fn test_commit_notes() {
+        DeltaTest::with_args(&["--notes-style=bold yellow", "--commit-style=raw"])
+            .explain_ansi()
+            .with_input(GIT_LOG_WITH_NOTES)
+            .expect_contains(
+                "
+    Fix the thing
+    Notes: this is not a note
+
+(bold yellow)Notes:(normal)
+(bold yellow)    Reviewed by Ann(normal)
+
+(bold yellow)Notes (review):(normal)
+(bold yellow)    Looks good(normal)
+(bold yellow)    to me(normal)
+",
+            );
+    }
+
+    #[test]
+    fn test_commit_notes_omitted() {
+        let result = DeltaTest::with_args(&["--notes-style=omit"]).with_input(GIT_LOG_WITH_NOTES);
+        assert!(!result.output.contains("Reviewed by Ann"));
+        assert!(!result.output.contains("Notes (review):"));
+        assert!(result.output.contains("Notes: this is not a note"));
+    }
+
+    const GIT_SHOW_MERGE: &str = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Merge: 1d2f3a4 5b6c7d8
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Thu May 14 11:13:17 2020 -0400
+
+    Merge branch 'feature'
+";
+
+    #[test]
+    fn test_commit_merge_line() {
+        DeltaTest::with_args(&["--commit-merge-style=bold yellow", "--commit-style=raw"])
+            .explain_ansi()
+            .with_input(GIT_SHOW_MERGE)
+            .expect_contains("\n(bold yellow)Merge: 1d2f3a4 5b6c7d8(normal)\nAuthor:");
+
+        let input = GIT_SHOW_MERGE.replace("5b6c7d8", "5b6c7d8 9e0fa1b");
+        DeltaTest::with_args(&["--commit-merge-style=bold yellow", "--commit-style=raw"])
+            .explain_ansi()
+            .with_input(&input)
+            .expect_contains("\n(bold yellow)Merge: 1d2f3a4 5b6c7d8 9e0fa1b(normal)\nAuthor:");
+
+        let result = DeltaTest::with_args(&["--commit-merge-style=omit"]).with_input(&input);
+        assert!(!result.output.contains("Merge:"));
+        assert!(result.output.contains("Merge branch 'feature'"));
+
+        let result = DeltaTest::with_args(&[]).with_input(GIT_SHOW_MERGE);
+        assert!(result.raw_output.contains("\nMerge: 1d2f3a4 5b6c7d8\n"));
+    }
+
+    #[test]
+    fn test_commit_merge_line_hyperlinks() {
+        let link = |commit: &str| format!("\x1b]8;;HERE:{commit}\x1b\\{commit}\x1b]8;;\x1b\\");
+        for parents in [
+            vec!["1d2f3a4", "5b6c7d8"],
+            vec!["1d2f3a4", "5b6c7d8", "9e0fa1b"],
+        ] {
+            let input = GIT_SHOW_MERGE.replace("1d2f3a4 5b6c7d8", &parents.join(" "));
+            let result = DeltaTest::with_args(&[
+                "--hyperlinks",
+                "--hyperlinks-commit-link-format=HERE:{commit}",
+            ])
+            .with_input(&input);
+            let expected = format!(
+                "\nMerge: {}\n",
+                parents
+                    .iter()
+                    .map(|commit| link(commit))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            );
+            assert!(result.raw_output.contains(&expected));
+        }
+    }
+
+    const GIT_LOG_WITH_ANSI_IN_MESSAGE: &str = "\
+\x1b[33mcommit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e\x1b[m
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Thu May 14 11:13:17 2020 -0400
+
+    Fix the \x1b[1;31mthing\x1b[0m
+    \x1b[32mwhich was broken
+
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++b
+";
+
+    #[test]
+    fn test_commit_message_ansi_is_stripped() {
+        let output = DeltaTest::with_args(&[]).with_input(GIT_LOG_WITH_ANSI_IN_MESSAGE);
+        let lines: Vec<&str> = output.raw_output.lines().collect();
+        // The colors of the commit header line are kept.
+        assert_eq!(
+            lines[0],
+            "\x1b[33mcommit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e\x1b[m"
+        );
+        assert_eq!(lines[4], "    Fix the thing");
+        assert_eq!(lines[5], "    which was broken");
+    }
+
+    #[test]
+    fn test_preserve_commit_ansi() {
+        let output = DeltaTest::with_args(&["--preserve-commit-ansi"])
+            .with_input(GIT_LOG_WITH_ANSI_IN_MESSAGE);
+        let lines: Vec<&str> = output.raw_output.lines().collect();
+        assert_eq!(lines[4], "    Fix the \x1b[1;31mthing\x1b[0m");
+        assert_eq!(lines[5], "    \x1b[32mwhich was broken");
+    }
+
+    const GIT_LOG_WITH_LONG_AUTHOR: &str = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Bartholomew Montgomery-Fitzgerald III <bartholomew.montgomery-fitzgerald@example.com>
+Date:   Thu May 14 11:13:17 2020 -0400
+
+    Fix the thing
+";
+
+    #[test]
+    fn test_commit_meta_width_truncate() {
+        DeltaTest::with_args(&["--commit-meta-width=40"])
+            .with_input(GIT_LOG_WITH_LONG_AUTHOR)
+            .expect_after_skip(
+                1,
+                "
+#indent_mark
+Author: Bartholomew Montgomery-Fitzgera…
+Date:   Thu May 14 11:13:17 2020 -0400
+
+    Fix the thing",
+            );
+    }
+
+    #[test]
+    fn test_commit_meta_width_wrap() {
+        DeltaTest::with_args(&["--commit-meta-width=40", "--commit-meta-overflow=wrap"])
+            .with_input(GIT_LOG_WITH_LONG_AUTHOR)
+            .expect_after_skip(
+                1,
+                "
+#indent_mark
+Author: Bartholomew
+        Montgomery-Fitzgerald III
+        <bartholomew.montgomery-fitzgera
+        ld@example.com>
+Date:   Thu May 14 11:13:17 2020 -0400
+
+    Fix the thing",
+            );
+    }
+
+    #[test]
+    fn test_commit_meta_width_default_is_unlimited() {
+        let result = DeltaTest::with_args(&[]).with_input(GIT_LOG_WITH_LONG_AUTHOR);
+        assert!(result
+            .output
+            .contains("<bartholomew.montgomery-fitzgerald@example.com>"));
+    }
+
+    const ONELINE_LOG: &str = "\
+94907c0 Fix the thing which was broken by the previous change to the parser
+8d2a5c1 Short subject
+";
+
+    #[test]
+    fn test_commit_subject_truncation_right() {
+        DeltaTest::with_args(&["--commit-subject-truncation=right", "--width=40"])
+            .with_input(ONELINE_LOG)
+            .expect_after_skip(
+                0,
+                "
+94907c0 Fix the thing which was broken …
+8d2a5c1 Short subject",
+            );
+    }
+
+    #[test]
+    fn test_commit_subject_truncation_middle() {
+        DeltaTest::with_args(&["--commit-subject-truncation=middle", "--width=40"])
+            .with_input(ONELINE_LOG)
+            .expect_after_skip(
+                0,
+                "
+94907c0 Fix the thing wh…e to the parser
+8d2a5c1 Short subject",
+            );
+    }
+
+    #[test]
+    fn test_commit_subject_truncation_middle_keeps_colors() {
+        let result = DeltaTest::with_args(&["--commit-subject-truncation=middle", "--width=40"])
+            .with_input(
+                "\x1b[33m94907c0\x1b[m Fix the thing which was broken by the previous change to the \x1b[1mparser\x1b[m\n",
+            );
+        assert_eq!(
+            result.output.trim_end(),
+            "94907c0 Fix the thing wh…e to the parser"
+        );
+        assert_eq!(
+            result.raw_output.trim_end(),
+            "\x1b[33m94907c0\x1b[m Fix the thing wh\x1b[1m\x1b[m…\x1b[33m\x1b[me to the \x1b[1mparser\x1b[m"
+        );
+    }
+
+    #[test]
+    fn test_commit_subject_truncation_none() {
+        let result = DeltaTest::with_args(&["--width=40"]).with_input(ONELINE_LOG);
+        assert!(result.output.contains("previous change to the parser"));
+    }
+
+    const GIT_LOG_WITH_DECORATION: &str = "\
+\x1b[33mcommit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e (\x1b[1;36mHEAD -> \x1b[1;32mmain\x1b[33m, \x1b[1;33mtag: v1.0\x1b[33m, \x1b[1;31morigin/main\x1b[33m, \x1b[1;32mfeature/x\x1b[33m)\x1b[m
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Thu May 14 11:13:17 2020 -0400
+
+    Fix the thing
+";
+
+    #[test]
+    fn test_commit_ref_styles() {
+        let git_config = b"[remote \"origin\"]\n    url = https://github.com/dandavison/delta\n";
+        let config = integration_test_utils::make_config_from_args_and_git_config(
+            &[
+                "--commit-style=yellow",
+                "--commit-ref-head-style=bold cyan",
+                "--commit-ref-branch-style=green",
+                "--commit-ref-remote-style=red",
+                "--commit-ref-tag-style=bold yellow",
+            ],
+            Some(git_config),
+            Some("delta__test_commit_ref_styles.gitconfig"),
+        );
+        let output = integration_test_utils::run_delta(GIT_LOG_WITH_DECORATION, &config);
+        assert_eq!(
+            explain_ansi(output.lines().next().unwrap(), false),
+            "(yellow)commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e ((bold cyan)HEAD(yellow) -> (green)main(yellow), (bold yellow)tag: v1.0(yellow), (red)origin/main(yellow), (green)feature/x(yellow))"
+        );
+        remove_file("delta__test_commit_ref_styles.gitconfig").unwrap();
+    }
+
+    #[test]
+    fn test_commit_ref_styles_keep_raw_colors() {
+        // Only the tags are restyled: the rest of the line keeps the colors given by git.
+        DeltaTest::with_args(&["--commit-ref-tag-style=reverse"])
+            .explain_ansi()
+            .with_input(GIT_LOG_WITH_DECORATION)
+            .expect_contains(
+                "(yellow)commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e ((bold cyan)HEAD -> (bold green)main(yellow), (reverse normal)tag: v1.0(yellow), (bold red)origin/main(yellow), (bold green)feature/x(yellow))(normal)",
+            );
+    }
+
+    #[test]
+    fn test_commit_ref_styles_default_to_commit_style() {
+        let output = DeltaTest::with_args(&[]).with_input(GIT_LOG_WITH_DECORATION);
+        assert!(output.raw_output.starts_with(
+            "\x1b[33mcommit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e (\x1b[1;36mHEAD -> "
+        ));
+        DeltaTest::with_args(&["--commit-style=blue"])
+            .explain_ansi()
+            .with_input(GIT_LOG_WITH_DECORATION)
+            .expect_contains(
+                "(blue)commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e (HEAD -> main, tag: v1.0, origin/main, feature/x)(normal)",
+            );
+    }
+
+    const GIT_LOG_OF_TWO_COMMITS: &str = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Thu May 14 11:13:17 2020 -0400
+
+    Change two files
+
+diff --git a/src/a.rs b/src/a.rs
+index 223ca50..367a6f6 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1 +1 @@
+-a
++b
+diff --git a/b.txt b/b.txt
+deleted file mode 100644
+index 223ca50..0000000
+--- a/b.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-b
+
+commit 5bd1c5a8e48f3c2d7aa4f9b1c2cb5e3df0c5a1e2
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Wed May 13 11:13:17 2020 -0400
+
+    Add c
+
+diff --git a/c.txt b/c.txt
+new file mode 100644
+index 0000000..367a6f6
+--- /dev/null
++++ b/c.txt
+@@ -0,0 +1 @@
++c
+";
+
+    #[test]
+    fn test_log_show_files() {
+        DeltaTest::with_args(&["--log-show-files", "--file-style=blue"])
+            .explain_ansi()
+            .with_input(GIT_LOG_OF_TWO_COMMITS)
+            .expect_contains(
+                "
+    Change two files
+
+    (blue)src/a.rs(normal)
+    (blue)b.txt(normal)
+
+",
+            )
+            .expect_contains(
+                "
+    Add c
+
+    (blue)c.txt(normal)
+
+",
+            );
+
+        let output = DeltaTest::with_args(&["--log-show-files"])
+            .with_input(GIT_LOG_OF_TWO_COMMITS)
+            .output;
+        assert!(output.contains("Change two files\n\n    src/a.rs\n    b.txt\n\n"));
+        // The files are not listed by default.
+        let output = DeltaTest::with_args(&[])
+            .with_input(GIT_LOG_OF_TWO_COMMITS)
+            .output;
+        assert!(!output.contains("    src/a.rs"));
+        assert!(!output.contains("    c.txt"));
+    }
+
+    #[test]
+    fn test_log_show_files_reads_one_commit_at_a_time() {
+        // The limit is exceeded by all of the input, but not by either commit.
+        let second_commit = GIT_LOG_OF_TWO_COMMITS.rfind("commit ").unwrap();
+        let limit = second_commit.max(GIT_LOG_OF_TWO_COMMITS.len() - second_commit) + 1;
+        assert!(limit < GIT_LOG_OF_TWO_COMMITS.len());
+        let output =
+            DeltaTest::with_args(&["--log-show-files", &format!("--buffer-limit-bytes={limit}")])
+                .with_input(GIT_LOG_OF_TWO_COMMITS)
+                .output;
+        assert!(output.contains("Change two files\n\n    src/a.rs\n    b.txt\n\n"));
+        assert!(output.contains("Add c\n\n    c.txt\n\n"));
+    }
+}
@@ -4,9 +4,11 @@ use std::path::Path;
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::draw;
+use crate::ansi;
 use crate::config::Config;
 use crate::delta::{DiffType, Source, State, StateMachine};
 use crate::paint::Painter;
+use crate::style::Style;
 use crate::{features, utils};
 
 // https://git-scm.com/docs/git-config#Documentation/git-config.txt-diffmnemonicPrefix
@@ -34,6 +36,9 @@ impl StateMachine<'_> {
             }
         } else if let Some(line_suf) = self.line.strip_prefix("new mode ") {
             self.state = State::DiffHeader(DiffType::Unified);
+            if self.config.show_index && !self.mode_info.is_empty() {
+                self.mode_change = Some((self.mode_info.clone(), line_suf.to_string()));
+            }
             if self.should_handle() && !self.config.color_only && !self.mode_info.is_empty() {
                 self.mode_info = match (self.mode_info.as_str(), line_suf) {
                     // 100755 for executable and 100644 for non-executable are the only file modes Git records.
@@ -126,6 +131,10 @@ impl StateMachine<'_> {
         utils::path::relativize_path_maybe(&mut path_or_mode, self.config);
         self.plus_file = path_or_mode;
         self.plus_file_event = file_event;
+        if self.config.reverse {
+            std::mem::swap(&mut self.minus_file, &mut self.plus_file);
+            std::mem::swap(&mut self.minus_file_event, &mut self.plus_file_event);
+        }
         self.painter
             .set_syntax(get_filename_from_diff_header_line_file_path(
                 &self.plus_file,
@@ -135,6 +144,11 @@ impl StateMachine<'_> {
         self.painter.paint_buffered_minus_and_plus_lines();
         if self.should_write_generic_diff_header_header_line()? {
             handled_line = true;
+        } else if self.should_compact_rename() {
+            self.pending_compact_renames
+                .push((self.minus_file.clone(), self.plus_file.clone()));
+            self.handled_diff_header_header_line_file_pair
+                .clone_from(&self.current_file_pair);
         } else if self.should_handle()
             && self.handled_diff_header_header_line_file_pair != self.current_file_pair
         {
@@ -146,6 +160,100 @@ impl StateMachine<'_> {
         Ok(handled_line)
     }
 
+    /// Return whether the line is a header line reporting a change which has no added or removed
+    /// lines: a rename, a copy, a change of mode, the creation or deletion of a file, or a change
+    /// of a binary file, including the lines of its binary patch.
+    pub fn test_diff_header_change_line(&self) -> bool {
+        const CHANGE_PREFIXES: [&str; 9] = [
+            "rename from ",
+            "rename to ",
+            "copy from ",
+            "copy to ",
+            "old mode ",
+            "new mode ",
+            "new file mode ",
+            "deleted file mode ",
+            "GIT binary patch",
+        ];
+        let is_change_header_line = matches!(self.state, State::DiffHeader(_))
+            && (self.binary_patch_sections.is_some()
+                || CHANGE_PREFIXES
+                    .iter()
+                    .any(|prefix| self.line.starts_with(prefix)));
+        // "Binary files ... differ" may also appear outside a header, in the output of `diff -r`.
+        let is_binary_files_line =
+            self.line.starts_with("Binary files ") && self.line.ends_with(" differ");
+        is_change_header_line || is_binary_files_line
+    }
+
+    /// Record that the input contains changes if the line is a header line reporting a change
+    /// which has no added or removed lines (used by --exit-code). The line is left to the other
+    /// handlers.
+    pub fn handle_diff_header_change_line(&mut self) -> std::io::Result<bool> {
+        if self.test_diff_header_change_line() {
+            self.changes_found = true;
+        }
+        Ok(false)
+    }
+
+    /// Check for the "similarity index" line and record whether the file is a pure rename.
+    pub fn handle_diff_header_similarity_line(&mut self) -> std::io::Result<bool> {
+        if matches!(self.state, State::DiffHeader(_)) {
+            if let Some(similarity) = self.line.strip_prefix("similarity index ") {
+                self.pure_rename = similarity == "100%";
+            }
+        }
+        // The line itself is skipped or emitted as any other diff header line.
+        Ok(false)
+    }
+
+    /// Is the "rename to" line being handled the end of the header of a pure rename which should
+    /// be listed in the compact renames table?
+    fn should_compact_rename(&self) -> bool {
+        self.config.compact_renames
+            && self.pure_rename
+            && self.line.starts_with("rename to ")
+            && self.mode_info.is_empty()
+            && self.should_handle()
+    }
+
+    /// Can the line be part of a run of pure renames, i.e. must the pending compact renames
+    /// table not be written yet?
+    pub fn test_compact_renames_continue(&self) -> bool {
+        self.line.starts_with("diff --git ")
+            || matches!(self.state, State::DiffHeader(_))
+                && (self.line.starts_with("similarity index ")
+                    || self.line.starts_with("rename from ")
+                    || self.line.starts_with("rename to "))
+    }
+
+    /// Write the pending pure renames as a table of `old ⟶ new` rows, with the old paths aligned.
+    pub fn emit_compact_renames(&mut self) -> std::io::Result<()> {
+        let renames = std::mem::take(&mut self.pending_compact_renames);
+        if renames.is_empty() || self.config.file_style.is_omitted {
+            return Ok(());
+        }
+        self.painter.emit()?;
+        let width = renames
+            .iter()
+            .map(|(minus_file, _)| ansi::measure_text_width(minus_file))
+            .max()
+            .unwrap_or(0);
+        writeln!(self.painter.writer)?;
+        for (minus_file, plus_file) in renames {
+            let padding = " ".repeat(width - ansi::measure_text_width(&minus_file));
+            writeln!(
+                self.painter.writer,
+                "{}",
+                self.config.file_style.paint(format!(
+                    "{}{} {} {}",
+                    minus_file, padding, self.config.right_arrow, plus_file
+                ))
+            )?;
+        }
+        Ok(())
+    }
+
     #[inline]
     fn test_diff_header_file_operation_line(&self) -> bool {
         (matches!(self.state, State::DiffHeader(_)) || self.source == Source::DiffUnified)
@@ -199,14 +307,21 @@ impl StateMachine<'_> {
             &self.plus_file_event,
             self.config,
         );
+        // Copies are distinguished from renames, and other file changes, by their style.
+        let style = match (&self.minus_file_event, &self.plus_file_event) {
+            (FileEvent::Copy, FileEvent::Copy) if !comparing => self.config.file_copied_style,
+            _ => self.config.file_style,
+        };
         // FIXME: no support for 'raw'
-        write_generic_diff_header_header_line(
+        write_diff_header_header_line_with_style(
             &line,
             &line,
             &mut self.painter,
             &mut self.mode_info,
+            style,
             self.config,
-        )
+        )?;
+        self.emit_pending_index_line()
     }
 
     #[inline]
@@ -227,17 +342,26 @@ impl StateMachine<'_> {
                     "".to_string()
                 }
             };
-            let format_file = |file| match (
-                self.config.hyperlinks,
-                utils::path::absolute_path(file, self.config),
-            ) {
-                (true, Some(absolute_path)) => features::hyperlinks::format_osc8_file_hyperlink(
-                    absolute_path,
-                    None,
-                    file,
-                    self.config,
-                ),
-                _ => Cow::from(file),
+            let format_file = |file| {
+                let formatted_file = if self.config.path_compress_dirs {
+                    utils::path::compress_dirs(file)
+                } else {
+                    Cow::from(file)
+                };
+                match (
+                    self.config.hyperlinks,
+                    utils::path::absolute_path(file, self.config),
+                ) {
+                    (true, Some(absolute_path)) => {
+                        features::hyperlinks::format_osc8_file_hyperlink(
+                            absolute_path,
+                            None,
+                            &formatted_file,
+                            self.config,
+                        )
+                    }
+                    _ => formatted_file,
+                }
             };
             let label = format_label(&self.config.file_modified_label);
             let name = get_repeated_file_path_from_diff_line(&self.diff_line).unwrap_or_default();
@@ -248,7 +372,8 @@ impl StateMachine<'_> {
                 &mut self.painter,
                 &mut self.mode_info,
                 self.config,
-            )
+            )?;
+            self.emit_pending_index_line()
         } else if !self.config.color_only
             && self.should_handle()
             && self.handled_diff_header_header_line_file_pair != self.current_file_pair
@@ -270,15 +395,34 @@ pub fn write_generic_diff_header_header_line(
     painter: &mut Painter,
     mode_info: &mut String,
     config: &Config,
+) -> std::io::Result<()> {
+    write_diff_header_header_line_with_style(
+        line,
+        raw_line,
+        painter,
+        mode_info,
+        config.file_style,
+        config,
+    )
+}
+
+/// Write `line` with DiffHeader styling, using `style` in place of file-style.
+fn write_diff_header_header_line_with_style(
+    line: &str,
+    raw_line: &str,
+    painter: &mut Painter,
+    mode_info: &mut String,
+    style: Style,
+    config: &Config,
 ) -> std::io::Result<()> {
     // If file_style is "omit", we'll skip the process and print nothing.
     // However in the case of color_only mode,
     // we won't skip because we can't change raw_line structure.
-    if config.file_style.is_omitted && !config.color_only {
+    if style.is_omitted && !config.color_only {
         return Ok(());
     }
     let (mut draw_fn, pad, decoration_ansi_term_style) =
-        draw::get_draw_function(config.file_style.decoration_style);
+        draw::get_draw_function(style.decoration_style, config);
     if !config.color_only {
         // Maintain 1-1 correspondence between input and output lines.
         writeln!(painter.writer)?;
@@ -289,7 +433,7 @@ pub fn write_generic_diff_header_header_line(
         &format!("{}{}", raw_line, if pad { " " } else { "" }),
         mode_info,
         &config.decorations_width,
-        config.file_style,
+        style,
         decoration_ansi_term_style,
     )?;
     if !mode_info.is_empty() {
@@ -319,7 +463,7 @@ fn get_filename_from_diff_header_line_file_path(path: &str) -> Option<&str> {
     })
 }
 
-fn parse_diff_header_line(line: &str, git_diff_name: bool) -> (String, FileEvent) {
+pub fn parse_diff_header_line(line: &str, git_diff_name: bool) -> (String, FileEvent) {
     match line {
         line if line.starts_with("--- ") || line.starts_with("+++ ") => {
             let offset = 4;
@@ -327,16 +471,28 @@ fn parse_diff_header_line(line: &str, git_diff_name: bool) -> (String, FileEvent
             (file, FileEvent::Change)
         }
         line if line.starts_with("rename from ") => {
-            (line[12..].to_string(), FileEvent::Rename) // "rename from ".len()
+            (
+                remove_trailing_slash(&line[12..]).to_string(),
+                FileEvent::Rename,
+            ) // "rename from ".len()
         }
         line if line.starts_with("rename to ") => {
-            (line[10..].to_string(), FileEvent::Rename) // "rename to ".len()
+            (
+                remove_trailing_slash(&line[10..]).to_string(),
+                FileEvent::Rename,
+            ) // "rename to ".len()
         }
         line if line.starts_with("copy from ") => {
-            (line[10..].to_string(), FileEvent::Copy) // "copy from ".len()
+            (
+                remove_trailing_slash(&line[10..]).to_string(),
+                FileEvent::Copy,
+            ) // "copy from ".len()
         }
         line if line.starts_with("copy to ") => {
-            (line[8..].to_string(), FileEvent::Copy) // "copy to ".len()
+            (
+                remove_trailing_slash(&line[8..]).to_string(),
+                FileEvent::Copy,
+            ) // "copy to ".len()
         }
         line if line.starts_with("new file mode ") => {
             (line[14..].to_string(), FileEvent::Added) // "new file mode ".len()
@@ -374,6 +530,14 @@ fn remove_surrounding_quotes(path: &str) -> &str {
     }
 }
 
+// Submodule paths are sometimes given with a trailing slash.
+fn remove_trailing_slash(path: &str) -> &str {
+    match path.strip_suffix('/') {
+        Some(path) if !path.is_empty() => path,
+        _ => path,
+    }
+}
+
 fn _parse_file_path(path: &str, git_diff_name: bool) -> String {
     // When git config 'core.quotepath = true' (the default), and `path` contains
     // non-ASCII characters, a backslash, or a quote; then it is quoted, so remove
@@ -386,12 +550,12 @@ fn _parse_file_path(path: &str, git_diff_name: bool) -> String {
     // index·d00491f..0cfbf08·100644␊
     // ---·a/a·b├──┤␊
     // +++·b/c·d├──┤␊
-    match path.strip_suffix('\t').unwrap_or(path) {
+    remove_trailing_slash(match path.strip_suffix('\t').unwrap_or(path) {
         "/dev/null" => "/dev/null",
         path if git_diff_name && DIFF_PREFIXES.iter().any(|s| path.starts_with(s)) => &path[2..],
         path if git_diff_name => path,
         path => path.split('\t').next().unwrap_or(""),
-    }
+    })
     .to_string()
 }
 
@@ -411,13 +575,28 @@ pub fn get_file_change_description_from_file_paths(
         }
     };
     if comparing {
-        format!(
-            "{}{} {} {}",
-            format_label(&config.file_modified_label),
-            minus_file,
-            config.right_arrow,
-            plus_file
-        )
+        let format_file = |file| {
+            if config.path_compress_dirs {
+                utils::path::compress_dirs(file)
+            } else {
+                Cow::from(file)
+            }
+        };
+        if config.collapse_identical_paths && minus_file == plus_file {
+            format!(
+                "{}{}",
+                format_label(&config.file_modified_label),
+                format_file(minus_file)
+            )
+        } else {
+            format!(
+                "{}{} {} {}",
+                format_label(&config.file_modified_label),
+                format_file(minus_file),
+                config.right_arrow,
+                format_file(plus_file)
+            )
+        }
     } else {
         let format_file = |file| {
             let formatted_file = if let Some(regex_replacement) = &config.file_regex_replacement {
@@ -425,6 +604,11 @@ pub fn get_file_change_description_from_file_paths(
             } else {
                 Cow::from(file)
             };
+            let formatted_file = if config.path_compress_dirs {
+                Cow::from(utils::path::compress_dirs(&formatted_file).into_owned())
+            } else {
+                formatted_file
+            };
             match (config.hyperlinks, utils::path::absolute_path(file, config)) {
                 (true, Some(absolute_path)) => features::hyperlinks::format_osc8_file_hyperlink(
                     absolute_path,
@@ -436,11 +620,15 @@ pub fn get_file_change_description_from_file_paths(
             }
         };
         match (minus_file, plus_file, minus_file_event, plus_file_event) {
-            (minus_file, plus_file, _, _) if minus_file == plus_file => format!(
-                "{}{}",
-                format_label(&config.file_modified_label),
-                format_file(minus_file)
-            ),
+            (minus_file, plus_file, _, _)
+                if config.collapse_identical_paths && minus_file == plus_file =>
+            {
+                format!(
+                    "{}{}",
+                    format_label(&config.file_modified_label),
+                    format_file(minus_file)
+                )
+            }
             (minus_file, "/dev/null", _, _) => format!(
                 "{}{}",
                 format_label(&config.file_removed_label),
@@ -605,6 +793,84 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_path_compress_dirs() {
+        let describe = |args: &[&str]| {
+            get_file_change_description_from_file_paths(
+                "src/handlers/foo/bar.rs",
+                "src/handlers/foo/bar.rs",
+                false,
+                &FileEvent::Change,
+                &FileEvent::Change,
+                &make_config_from_args(args),
+            )
+        };
+        assert_eq!(describe(&["--path-compress-dirs"]), "s/h/f/bar.rs");
+        // The target of the hyperlink is the full path.
+        let hyperlinked = describe(&[
+            "--path-compress-dirs",
+            "--hyperlinks",
+            "--hyperlinks-file-link-format={path}",
+        ]);
+        let target = utils::path::fake_delta_cwd_for_tests().join("src/handlers/foo/bar.rs");
+        assert_eq!(
+            hyperlinked,
+            format!(
+                "\x1b]8;;{}\x1b\\s/h/f/bar.rs\x1b]8;;\x1b\\",
+                target.display()
+            )
+        );
+        assert_eq!(describe(&[]), "src/handlers/foo/bar.rs");
+    }
+
+    #[test]
+    fn test_collapse_identical_paths() {
+        let describe = |minus_file, plus_file, comparing, file_event, args: &[&str]| {
+            get_file_change_description_from_file_paths(
+                minus_file,
+                plus_file,
+                comparing,
+                &file_event,
+                &file_event,
+                &make_config_from_args(&[&["--right-arrow=->"], args].concat()),
+            )
+        };
+        for comparing in [false, true] {
+            assert_eq!(
+                describe("a.txt", "a.txt", comparing, FileEvent::Change, &[]),
+                "a.txt"
+            );
+            assert_eq!(
+                describe(
+                    "a.txt",
+                    "a.txt",
+                    comparing,
+                    FileEvent::Change,
+                    &["--collapse-identical-paths=false"]
+                ),
+                "a.txt -> a.txt"
+            );
+        }
+        // A renamed file always has both paths.
+        for args in [&[][..], &["--collapse-identical-paths=false"]] {
+            assert_eq!(
+                describe("a.txt", "b.txt", false, FileEvent::Rename, args),
+                "renamed: a.txt -> b.txt"
+            );
+        }
+        // An added file only has a new path.
+        assert_eq!(
+            describe(
+                "/dev/null",
+                "a.txt",
+                false,
+                FileEvent::Change,
+                &["--collapse-identical-paths=false"]
+            ),
+            "added: a.txt"
+        );
+    }
+
     #[test]
     fn test_parse_diff_header_line() {
         assert_eq!(
@@ -615,6 +881,14 @@ mod tests {
             parse_diff_header_line("+++ src/delta.rs", false),
             ("src/delta.rs".to_string(), FileEvent::Change)
         );
+        assert_eq!(
+            parse_diff_header_line("+++ b/sub/", true),
+            ("sub".to_string(), FileEvent::Change)
+        );
+        assert_eq!(
+            parse_diff_header_line("rename to sub/", true),
+            ("sub".to_string(), FileEvent::Rename)
+        );
     }
 
     #[test]
@@ -644,6 +918,10 @@ mod tests {
             get_repeated_file_path_from_diff_line(r#"diff --git "a/quoted" "b/quoted""#),
             Some("quoted".to_string())
         );
+        assert_eq!(
+            get_repeated_file_path_from_diff_line("diff --git a/sub/ b/sub/"),
+            Some("sub".to_string())
+        );
     }
 
     #[test]
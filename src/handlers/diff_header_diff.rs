@@ -14,6 +14,9 @@ impl StateMachine<'_> {
         }
         self.painter.paint_buffered_minus_and_plus_lines();
         self.painter.emit()?;
+        self.emit_transcoding_notice()?;
+        self.file_encoding = None;
+        self.emit_pending_commit_files()?;
         self.state =
             if self.line.starts_with("diff --cc ") || self.line.starts_with("diff --combined ") {
                 // We will determine the number of parents when we see the hunk header.
@@ -25,7 +28,13 @@ impl StateMachine<'_> {
                 State::DiffHeader(DiffType::Unified)
             };
         self.handle_pending_line_with_diff_name()?;
+        self.emit_file_category_header()?;
         self.handled_diff_header_header_line_file_pair = None;
+        self.pure_rename = false;
+        self.binary_patch_sections = None;
+        self.blob_ids = None;
+        self.mode_change = None;
+        self.pending_index_line = None;
         self.diff_line.clone_from(&self.line);
 
         // Pre-fill header fields from the diff line. For added, removed or renamed files
@@ -45,4 +54,252 @@ impl StateMachine<'_> {
         }
         Ok(true)
     }
+
+    /// Skip every line of the files whose paths match --ignore-files, from their `diff` line up to
+    /// the next file or commit.
+    pub fn handle_ignored_file_line(&mut self) -> std::io::Result<bool> {
+        if self.config.ignore_files.is_empty() {
+            return Ok(false);
+        }
+        let ignore_files = &self.config.ignore_files;
+        self.handle_skipped_file_line(
+            |machine| &mut machine.ignoring_file,
+            |paths| paths.iter().any(|path| ignore_files.is_match(path)),
+        )
+    }
+
+    /// Reduce each file other than --focus-file to a line with its path, skipping every line of it
+    /// from its `diff` line up to the next file or commit.
+    pub fn handle_unfocused_file_line(&mut self) -> std::io::Result<bool> {
+        let Some(focus_file) = self.config.focus_file.as_deref() else {
+            return Ok(false);
+        };
+        let collapsing_file = self.handle_skipped_file_line(
+            |machine| &mut machine.collapsing_file,
+            |paths| !paths.is_empty() && !paths.iter().any(|path| path == focus_file),
+        )?;
+        if collapsing_file && self.test_diff_header_diff_line() {
+            let path = match &get_file_paths_from_diff_line(&self.line)[..] {
+                [old, new] => format!("{old} {} {new}", self.config.right_arrow),
+                paths => paths.concat(),
+            };
+            writeln!(
+                self.painter.writer,
+                "{} {}",
+                self.config.file_style.paint(&path),
+                ansi_term::Style::new().dimmed().paint("(collapsed)")
+            )?;
+        }
+        Ok(collapsing_file)
+    }
+
+    // Skip every line of the files for which `skip` holds, given the paths of their `diff` line,
+    // from that line up to the next file or commit. `skipping_file` is the field recording whether
+    // the current file is skipped.
+    fn handle_skipped_file_line(
+        &mut self,
+        skipping_file: fn(&mut Self) -> &mut bool,
+        skip: impl Fn(&[String]) -> bool,
+    ) -> std::io::Result<bool> {
+        if self.test_diff_header_diff_line() {
+            let was_skipping_file = *skipping_file(self);
+            let skip_file = skip(&get_file_paths_from_diff_line(&self.line));
+            *skipping_file(self) = skip_file;
+            if skip_file && !was_skipping_file {
+                // Finish the preceding file, which is displayed.
+                self.painter.paint_buffered_minus_and_plus_lines();
+                self.painter.emit()?;
+                self.emit_transcoding_notice()?;
+                self.handle_pending_line_with_diff_name()?;
+            }
+        } else if self.config.commit_regex.is_match(&self.line) {
+            *skipping_file(self) = false;
+        }
+        Ok(*skipping_file(self))
+    }
+
+    #[inline]
+    fn test_max_files_exceeded(&self) -> bool {
+        self.config.max_files > 0 && self.n_files > self.config.max_files
+    }
+
+    /// Skip every line after the first --max-files files, counting the files which are skipped.
+    pub fn handle_max_files_line(&mut self) -> std::io::Result<bool> {
+        if self.config.max_files == 0 {
+            return Ok(false);
+        }
+        if self.test_diff_header_diff_line() {
+            self.n_files += 1;
+            if self.n_files == self.config.max_files + 1 {
+                // Finish the last file which is displayed.
+                self.painter.paint_buffered_minus_and_plus_lines();
+                self.painter.emit()?;
+                self.emit_transcoding_notice()?;
+                self.handle_pending_line_with_diff_name()?;
+            }
+        }
+        Ok(self.test_max_files_exceeded())
+    }
+
+    /// Write the number of files skipped due to --max-files, if any.
+    pub fn emit_max_files_summary(&mut self) -> std::io::Result<()> {
+        if !self.test_max_files_exceeded() {
+            return Ok(());
+        }
+        let n_skipped = self.n_files - self.config.max_files;
+        writeln!(
+            self.painter.writer,
+            "… and {n_skipped} more file{}",
+            if n_skipped == 1 { "" } else { "s" }
+        )
+    }
+}
+
+// Return the paths of the file of a `diff` line: one path if it is unchanged, and the old and
+// the new path of a renamed file.
+fn get_file_paths_from_diff_line(line: &str) -> Vec<String> {
+    if let Some(path) = get_repeated_file_path_from_diff_line(line) {
+        return vec![path];
+    }
+    line.strip_prefix("diff --git a/")
+        .and_then(|paths| paths.split_once(" b/"))
+        .map(|(old, new)| vec![old.to_string(), new.to_string()])
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_file_paths_from_diff_line;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    const DIFF_OF_THREE_FILES: &str = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++aa
+diff --git a/b.txt b/b.txt
+index 223ca50..367a6f6 100644
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-b
++bb
+diff --git a/c.txt b/c.txt
+index 223ca50..367a6f6 100644
+--- a/c.txt
++++ b/c.txt
+@@ -1 +1 @@
+-c
++cc
+";
+
+    #[test]
+    fn test_max_files() {
+        let result = DeltaTest::with_args(&["--max-files=1"]).with_input(DIFF_OF_THREE_FILES);
+        assert!(result.output.contains("a.txt"));
+        assert!(result.output.contains("aa"));
+        assert!(!result.output.contains("b.txt"));
+        assert!(!result.output.contains("c.txt"));
+        assert!(result.output.ends_with("\n… and 2 more files\n"));
+
+        let result = DeltaTest::with_args(&["--max-files=2"]).with_input(DIFF_OF_THREE_FILES);
+        assert!(result.output.contains("bb"));
+        assert!(!result.output.contains("c.txt"));
+        assert!(result.output.ends_with("\n… and 1 more file\n"));
+    }
+
+    #[test]
+    fn test_max_files_not_exceeded() {
+        for args in [&["--max-files=3"], &["--max-files=0"]] {
+            let result = DeltaTest::with_args(args).with_input(DIFF_OF_THREE_FILES);
+            assert!(result.output.contains("cc"));
+            assert!(!result.output.contains("more file"));
+        }
+    }
+
+    #[test]
+    fn test_get_file_paths_from_diff_line() {
+        assert_eq!(
+            get_file_paths_from_diff_line("diff --git a/src/a.rs b/src/a.rs"),
+            ["src/a.rs"]
+        );
+        assert_eq!(
+            get_file_paths_from_diff_line("diff --git a/a.rs b/src/b.rs"),
+            ["a.rs", "src/b.rs"]
+        );
+        assert!(get_file_paths_from_diff_line("diff -u a.rs b.rs").is_empty());
+    }
+
+    #[test]
+    fn test_ignore_files() {
+        let result = DeltaTest::with_args(&["--ignore-files=b.*"]).with_input(DIFF_OF_THREE_FILES);
+        assert!(result.output.contains("aa"));
+        assert!(!result.output.contains("b.txt"));
+        assert!(!result.output.contains("bb"));
+        assert!(result.output.contains("c.txt"));
+        assert!(result.output.contains("cc"));
+
+        let result = DeltaTest::with_args(&["--ignore-files=a.txt", "--ignore-files=*c*"])
+            .with_input(DIFF_OF_THREE_FILES);
+        assert!(!result.output.contains("a.txt"));
+        assert!(result.output.contains("bb"));
+        assert!(!result.output.contains("c.txt"));
+        assert!(!result.output.contains("cc"));
+    }
+
+    // The last file of DIFF_OF_THREE_FILES in a first commit, then all of them in a second one.
+    fn two_commits_of_files() -> String {
+        format!(
+            "commit 1111111111111111111111111111111111111111\nAuthor: A <a@example.com>\n\n    one\n\n{}\
+             commit 2222222222222222222222222222222222222222\nAuthor: A <a@example.com>\n\n    two\n\n{}",
+            &DIFF_OF_THREE_FILES[DIFF_OF_THREE_FILES.find("diff --git a/c.txt").unwrap()..],
+            DIFF_OF_THREE_FILES
+        )
+    }
+
+    #[test]
+    fn test_ignore_files_of_each_commit() {
+        let result =
+            DeltaTest::with_args(&["--ignore-files=c.txt"]).with_input(&two_commits_of_files());
+        // The commit following an omitted file is displayed.
+        assert!(result.output.contains("2222222"));
+        assert!(result.output.contains("two"));
+        assert!(result.output.contains("bb"));
+        assert!(!result.output.contains("cc"));
+    }
+
+    #[test]
+    fn test_focus_file() {
+        let result = DeltaTest::with_args(&["--focus-file=b.txt"]).with_input(DIFF_OF_THREE_FILES);
+        let output = &result.output;
+        assert!(output.contains("a.txt (collapsed)"));
+        assert!(!output.contains("aa"));
+        assert!(!output.contains("b.txt (collapsed)"));
+        assert!(output.contains("bb"));
+        assert!(output.contains("c.txt (collapsed)"));
+        assert!(!output.contains("cc"));
+        assert!(output.find("a.txt") < output.find("bb"));
+        assert!(output.find("bb") < output.find("c.txt"));
+
+        // A file which is not in the diff leaves every file collapsed.
+        let result =
+            DeltaTest::with_args(&["--focus-file=./d.txt"]).with_input(DIFF_OF_THREE_FILES);
+        assert_eq!(result.output.matches("(collapsed)").count(), 3);
+        assert!(!result.output.contains("@@") && !result.output.contains("bb"));
+    }
+
+    #[test]
+    fn test_focus_file_of_each_commit() {
+        let result =
+            DeltaTest::with_args(&["--focus-file=a.txt"]).with_input(&two_commits_of_files());
+        // The commit following a collapsed file is displayed.
+        assert!(result.output.contains("2222222"));
+        assert!(result.output.contains("two"));
+        assert!(result.output.contains("aa"));
+        assert_eq!(result.output.matches("c.txt (collapsed)").count(), 2);
+        assert!(!result.output.contains("bb") && !result.output.contains("cc"));
+    }
 }
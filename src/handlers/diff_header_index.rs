@@ -0,0 +1,153 @@
+use crate::delta::{State, StateMachine};
+
+impl StateMachine<'_> {
+    #[inline]
+    fn test_diff_header_index_line(&self) -> bool {
+        self.config.show_index
+            && !self.config.color_only
+            && matches!(self.state, State::DiffHeader(_))
+            && self.line.starts_with("index ")
+    }
+
+    /// Paint the "index" line of the current file, to be written below its file header (used by
+    /// --show-index).
+    pub fn handle_diff_header_index_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_diff_header_index_line() {
+            return Ok(false);
+        }
+        let mode_change = self.mode_change.take();
+        let Some((blob_ids, modes)) = parse_index_line(&self.line) else {
+            return Ok(false);
+        };
+        let config = self.config;
+        let modes = modes
+            .map(|(old_mode, new_mode)| (old_mode.to_string(), new_mode.to_string()))
+            .or(mode_change);
+        let line = match modes {
+            Some((old_mode, new_mode)) if old_mode != new_mode => format!(
+                "{}{}",
+                config.index_style.paint(format!("index {blob_ids} ")),
+                config
+                    .index_mode_change_style
+                    .paint(format!("{old_mode} {} {new_mode}", config.right_arrow))
+            ),
+            Some((mode, _)) => config
+                .index_style
+                .paint(format!("index {blob_ids} {mode}"))
+                .to_string(),
+            None => config
+                .index_style
+                .paint(format!("index {blob_ids}"))
+                .to_string(),
+        };
+        self.pending_index_line = Some(line);
+        Ok(true)
+    }
+
+    /// Record the blob ids of the current file from its "index" line (used by --force-text and
+    /// --compute-function-context). The line is left to the other handlers.
+    pub fn handle_diff_header_blob_ids_line(&mut self) -> std::io::Result<bool> {
+        if matches!(self.state, State::DiffHeader(_)) {
+            if let Some((blob_ids, _)) = parse_index_line(&self.line) {
+                self.blob_ids = blob_ids
+                    .split_once("..")
+                    .map(|(minus_id, plus_id)| (minus_id.to_string(), plus_id.to_string()));
+            }
+        }
+        Ok(false)
+    }
+
+    /// Write the "index" line of the current file, if it is waiting to be written below the file
+    /// header which has just been written.
+    pub fn emit_pending_index_line(&mut self) -> std::io::Result<()> {
+        if let Some(line) = self.pending_index_line.take() {
+            writeln!(self.painter.writer, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse an "index" line, e.g. "index 223ca50..367a6f6 100644", into its blob ids and its old and
+/// new file modes. The mode is omitted by git when it changed, in which case "old mode" and "new
+/// mode" lines precede the "index" line; a changed mode may also be given as "100644..100755".
+pub fn parse_index_line(line: &str) -> Option<(&str, Option<(&str, &str)>)> {
+    let mut fields = line.strip_prefix("index ")?.split_whitespace();
+    let blob_ids = fields.next()?;
+    let modes = match fields.next() {
+        Some(modes) => Some(modes.split_once("..").unwrap_or((modes, modes))),
+        None => None,
+    };
+    Some((blob_ids, modes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_parse_index_line() {
+        assert_eq!(
+            parse_index_line("index 223ca50..367a6f6 100644"),
+            Some(("223ca50..367a6f6", Some(("100644", "100644"))))
+        );
+        assert_eq!(
+            parse_index_line("index 223ca50..367a6f6 100644..100755"),
+            Some(("223ca50..367a6f6", Some(("100644", "100755"))))
+        );
+        assert_eq!(
+            parse_index_line("index 223ca50..367a6f6"),
+            Some(("223ca50..367a6f6", None))
+        );
+        assert_eq!(parse_index_line("index"), None);
+    }
+
+    #[test]
+    fn test_show_index() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-a
++b
+diff --git a/b.sh b/b.sh
+old mode 100644
+new mode 100755
+index 223ca50..367a6f6
+--- a/b.sh
++++ b/b.sh
+@@ -1 +1 @@
+-a
++b
+";
+        let args = [
+            "--show-index",
+            "--file-style=normal",
+            "--file-decoration-style=none",
+            "--index-style=blue",
+            "--index-mode-change-style=bold yellow",
+        ];
+        DeltaTest::with_args(&args)
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                0,
+                "#partial
+(normal)
+a.txt
+(blue)index 223ca50..367a6f6 100644(normal)
+",
+            )
+            .expect_contains(
+                "
+b.sh (mode +x)
+(blue)index 223ca50..367a6f6 (bold yellow)100644 ⟶   100755(normal)
+",
+            );
+
+        let output = DeltaTest::with_args(&args[1..]).with_input(input).output;
+        assert!(!output.contains("index "));
+    }
+}
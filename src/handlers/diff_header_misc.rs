@@ -29,14 +29,7 @@ impl StateMachine<'_> {
                 return Ok(true);
             }
 
-            if self.minus_file != "/dev/null" {
-                relativize_path_maybe(&mut self.minus_file, self.config);
-                self.minus_file.push_str(" (binary file)");
-            }
-            if self.plus_file != "/dev/null" {
-                relativize_path_maybe(&mut self.plus_file, self.config);
-                self.plus_file.push_str(" (binary file)");
-            }
+            self.mark_diff_files_as_binary();
             return Ok(true);
         }
 
@@ -45,4 +38,64 @@ impl StateMachine<'_> {
             _ => State::DiffHeader(DiffType::Unified),
         })
     }
+
+    #[inline]
+    fn test_force_text_line(&self) -> bool {
+        self.text_diff_source.is_some()
+            && self.state == State::DiffHeader(DiffType::Unified)
+            && self.test_diff_is_binary()
+    }
+
+    /// Show the "Binary files ... differ" line of a file which git considers binary as the hunks
+    /// of its text diff instead, if that can be computed (used by --force-text), from the blob ids
+    /// recorded from the "index" line preceding it.
+    pub fn handle_force_text_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_force_text_line() {
+            return Ok(false);
+        }
+        let Some(hunk_lines) = self.get_text_diff_hunk_lines() else {
+            return Ok(false);
+        };
+        let header_path = |prefix: &str, file: &str| match file {
+            "/dev/null" => file.to_string(),
+            file => format!("{prefix}{file}"),
+        };
+        let header_lines = vec![
+            format!("--- {}", header_path("a/", &self.minus_file)),
+            format!("+++ {}", header_path("b/", &self.plus_file)),
+        ];
+        let (line, raw_line) = (self.line.clone(), self.raw_line.clone());
+        for text_diff_line in header_lines.into_iter().chain(hunk_lines) {
+            self.line.clone_from(&text_diff_line);
+            self.raw_line = text_diff_line;
+            self.handle_line()?;
+        }
+        (self.line, self.raw_line) = (line, raw_line);
+        Ok(true)
+    }
+
+    fn get_text_diff_hunk_lines(&mut self) -> Option<Vec<String>> {
+        let (minus_id, plus_id) = self.blob_ids.as_ref()?;
+        let text_diff = self
+            .text_diff_source
+            .as_mut()?
+            .text_diff(minus_id, plus_id)?;
+        let hunk_lines: Vec<String> = text_diff
+            .lines()
+            .skip_while(|line| !line.starts_with("@@"))
+            .map(str::to_string)
+            .collect();
+        (!hunk_lines.is_empty()).then_some(hunk_lines)
+    }
+
+    pub fn mark_diff_files_as_binary(&mut self) {
+        if self.minus_file != "/dev/null" {
+            relativize_path_maybe(&mut self.minus_file, self.config);
+            self.minus_file.push_str(" (binary file)");
+        }
+        if self.plus_file != "/dev/null" {
+            relativize_path_maybe(&mut self.plus_file, self.config);
+            self.plus_file.push_str(" (binary file)");
+        }
+    }
 }
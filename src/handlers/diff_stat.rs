@@ -1,6 +1,7 @@
 use lazy_static::lazy_static;
 use regex::Regex;
 use std::borrow::Cow;
+use unicode_width::UnicodeWidthStr;
 
 use crate::config::Config;
 use crate::delta::{State, StateMachine};
@@ -18,6 +19,16 @@ impl StateMachine<'_> {
         if !self.test_diff_stat_line() {
             return Ok(false);
         }
+        if self.config.stat_group_by_dir && !self.diff_stat_buffer_limit.is_exceeded() {
+            if let Some(entry) = DiffStatEntry::parse(&self.line, &self.raw_line) {
+                if !self.diff_stat_buffer_limit.add(self.line.len()) {
+                    self.pending_diff_stat.push(entry);
+                    return Ok(true);
+                }
+                // The lines collected so far are grouped, and this and later ones are not.
+                self.emit_grouped_diff_stat()?;
+            }
+        }
         let mut handled_line = false;
         if self.config.relative_paths {
             if let Some(cwd) = self.config.cwd_relative_to_repo_root.as_deref() {
@@ -32,6 +43,47 @@ impl StateMachine<'_> {
         }
         Ok(handled_line)
     }
+
+    pub fn test_diff_stat_continue(&self) -> bool {
+        self.test_diff_stat_line() && DIFF_STAT_ENTRY_REGEX.is_match(&self.line)
+    }
+
+    /// Write the pending diff stat lines grouped under their top-level directories, with the
+    /// total number of changed lines of each directory (used by --stat-group-by-dir).
+    pub fn emit_grouped_diff_stat(&mut self) -> std::io::Result<()> {
+        let entries = std::mem::take(&mut self.pending_diff_stat);
+        self.diff_stat_buffer_limit.reset();
+        if entries.is_empty() {
+            return Ok(());
+        }
+        self.painter.emit()?;
+        for line in format_grouped_diff_stat(&entries) {
+            writeln!(self.painter.writer, "{line}")?;
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn test_name_status_line(&self) -> bool {
+        (self.state == State::CommitMeta || self.state == State::Unknown)
+            && !self.config.status_map.is_empty()
+    }
+
+    /// Replace the status letter of a line of `git diff --name-status` or `git diff --raw` output
+    /// by its label from --status-map.
+    pub fn handle_name_status_line(&mut self) -> std::io::Result<bool> {
+        if !self.test_name_status_line() {
+            return Ok(false);
+        }
+        match map_status_in_name_status_line(&self.line, self.config) {
+            Some(replacement_line) => {
+                self.painter.emit()?;
+                writeln!(self.painter.writer, "{replacement_line}")?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
 }
 
 // A regex to capture the path, and the content from the pipe onwards, in lines
@@ -74,9 +126,143 @@ pub fn relativize_path_in_diff_stat_line(
     Some(format!(" {formatted_path}{padding}{suffix}"))
 }
 
+// A regex to capture the path and the stat in lines like these, which are indented by exactly one
+// space (unlike the lines of a commit message):
+// " src/delta.rs  | 14 ++++++++++----"
+// " image.png     | Bin 0 -> 1234 bytes"
+lazy_static! {
+    static ref DIFF_STAT_ENTRY_REGEX: Regex =
+        Regex::new(r"^ ([^ |][^|]*?) +\| +([0-9]+(?: .*)?|Bin .*)$").unwrap();
+}
+
+/// A line of a diff stat: the path of a file and its number of changed lines, if it is not a
+/// binary file. The graph of added and removed lines is kept with its colors from the raw line.
+pub struct DiffStatEntry {
+    path: String,
+    n_changes: Option<usize>,
+    graph: String,
+}
+
+impl DiffStatEntry {
+    fn parse(line: &str, raw_line: &str) -> Option<Self> {
+        let caps = DIFF_STAT_ENTRY_REGEX.captures(line)?;
+        let stat = caps.get(2).unwrap().as_str();
+        let raw_stat = raw_line
+            .split_once('|')
+            .map_or(stat, |(_, raw_stat)| raw_stat.trim_start());
+        let n_changes = stat.split(' ').next()?.parse().ok();
+        let graph = match n_changes {
+            Some(_) => raw_stat
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .to_string(),
+            None => format!(" {raw_stat}"),
+        };
+        Some(Self {
+            path: caps.get(1).unwrap().as_str().to_string(),
+            n_changes,
+            graph,
+        })
+    }
+}
+
+// A top-level directory, or None for a file at the top level, and the names of its entries.
+type DiffStatGroup<'a> = (Option<&'a str>, Vec<(&'a str, &'a DiffStatEntry)>);
+
+/// Group diff stat entries by their top-level directory, in order of first appearance. Files at
+/// the top level of the repository are not grouped.
+fn format_grouped_diff_stat(entries: &[DiffStatEntry]) -> Vec<String> {
+    let mut groups: Vec<DiffStatGroup> = Vec::new();
+    for entry in entries {
+        match entry.path.split_once('/') {
+            Some((dir, name)) => match groups.iter_mut().find(|(d, _)| *d == Some(dir)) {
+                Some((_, group)) => group.push((name, entry)),
+                None => groups.push((Some(dir), vec![(name, entry)])),
+            },
+            None => groups.push((None, vec![(&entry.path, entry)])),
+        }
+    }
+    let subtotal = |group: &[(&str, &DiffStatEntry)]| -> usize {
+        group.iter().filter_map(|(_, entry)| entry.n_changes).sum()
+    };
+
+    // The names are indented by 1, or by 3 within a directory.
+    let name_width = groups
+        .iter()
+        .flat_map(|(dir, group)| match dir {
+            Some(dir) => std::iter::once(dir.width() + 2)
+                .chain(group.iter().map(|(name, _)| name.width() + 3))
+                .collect::<Vec<_>>(),
+            None => vec![group[0].0.width() + 1],
+        })
+        .max()
+        .unwrap_or(0);
+    let count_width = groups
+        .iter()
+        .flat_map(|(dir, group)| {
+            let subtotal = dir.map(|_| subtotal(group));
+            group
+                .iter()
+                .filter_map(|(_, entry)| entry.n_changes)
+                .chain(subtotal)
+                .collect::<Vec<_>>()
+        })
+        .map(|n| n.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    let format_line = |name: String, n_changes: Option<usize>, graph: &str| {
+        let padding = " ".repeat(name_width.saturating_sub(name.width()));
+        match n_changes {
+            Some(n) => format!("{name}{padding} | {n:>count_width$}{graph}"),
+            None => format!("{name}{padding} |{graph}"),
+        }
+    };
+    let mut lines = Vec::new();
+    for (dir, group) in &groups {
+        if let Some(dir) = dir {
+            lines.push(format_line(format!(" {dir}/"), Some(subtotal(group)), ""));
+        }
+        let indent = if dir.is_some() { "   " } else { " " };
+        for (name, entry) in group {
+            lines.push(format_line(
+                format!("{indent}{name}"),
+                entry.n_changes,
+                &entry.graph,
+            ));
+        }
+    }
+    lines
+}
+
+// A regex to capture the status letter and score, and the parts of the line before and after them,
+// in lines like these:
+// "M\tsrc/delta.rs"
+// "R100\tsrc/old.rs\tsrc/new.rs"
+// ":100644 100644 bcd1234 0123456 M\tsrc/delta.rs"
+lazy_static! {
+    static ref NAME_STATUS_LINE_REGEX: Regex = Regex::new(
+        r"(?s)^(:[0-7]{6} [0-7]{6} [0-9a-f]+(?:\.\.\.)? [0-9a-f]+(?:\.\.\.)? )?([ACDMRTUX])([0-9]*)(\t.+)$"
+    )
+    .unwrap();
+}
+
+pub fn map_status_in_name_status_line(line: &str, config: &Config) -> Option<String> {
+    let caps = NAME_STATUS_LINE_REGEX.captures(line)?;
+    let status = caps.get(2).unwrap().as_str().chars().next()?;
+    let (label, style) = config.status_map.get(&status)?;
+    let prefix = caps.get(1).map_or("", |m| m.as_str());
+    let score = match caps.get(3).unwrap().as_str().parse::<usize>() {
+        Ok(score) => format!(" ({score}%)"),
+        Err(_) => String::new(),
+    };
+    let paths = caps.get(4).unwrap().as_str();
+    Some(format!("{prefix}{}{score}{paths}", style.paint(label)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
 
     #[test]
     fn test_diff_stat_line_regex_1() {
@@ -111,4 +297,109 @@ mod tests {
             )
         }
     }
+
+    #[test]
+    fn test_status_map() {
+        let input = "\
+M\tsrc/delta.rs
+A\tsrc/new.rs
+R097\tsrc/old.rs\tsrc/renamed.rs
+T\tsrc/link.rs
+";
+        DeltaTest::with_args(&["--status-map=M=modified:yellow, A=added:bold green, R=renamed"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                0,
+                "
+(yellow)modified(normal)\tsrc/delta.rs
+(bold green)added(normal)\tsrc/new.rs
+renamed (97%)\tsrc/old.rs\tsrc/renamed.rs
+T\tsrc/link.rs
+",
+            );
+    }
+
+    #[test]
+    fn test_status_map_raw_output() {
+        let input = "\
+:100644 100644 bcd1234 0123456 M\tsrc/delta.rs
+:000000 100644 0000000 abcdef0 A\tsrc/new.rs
+:100644 000000 abcdef0 0000000 D\tsrc/gone.rs
+";
+        DeltaTest::with_args(&["--status-map=M=modified:yellow,D=deleted:red"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                0,
+                "
+(normal):100644 100644 bcd1234 0123456 (yellow)modified(normal)\tsrc/delta.rs
+:000000 100644 0000000 abcdef0 A\tsrc/new.rs
+:100644 000000 abcdef0 0000000 (red)deleted(normal)\tsrc/gone.rs
+",
+            );
+    }
+
+    #[test]
+    fn test_stat_group_by_dir() {
+        let input = " README.md         |  1 +
+ src/delta.rs      | 14 ++++++++++----
+ src/handlers/a.rs |  2 ++
+ tests/x.png       | Bin 0 -> 1234 bytes
+ Makefile          |  3 ---
+ 4 files changed, 13 insertions(+), 7 deletions(-)
+";
+        DeltaTest::with_args(&["--stat-group-by-dir"])
+            .with_input(input)
+            .expect_after_skip(
+                0,
+                "
+#indent_mark
+ README.md       |  1 +
+ src/            | 16
+   delta.rs      | 14 ++++++++++----
+   handlers/a.rs |  2 ++
+ tests/          |  0
+   x.png         | Bin 0 -> 1234 bytes
+ Makefile        |  3 ---
+ 4 files changed, 13 insertions(+), 7 deletions(-)
+",
+            );
+    }
+
+    #[test]
+    fn test_stat_group_by_dir_past_buffer_limit() {
+        let input = " src/a.rs | 1 +
+ src/b.rs | 2 ++
+ src/c.rs | 3 +++
+ 3 files changed, 6 insertions(+)
+";
+        // The lines past the limit are written as they are.
+        DeltaTest::with_args(&["--stat-group-by-dir", "--buffer-limit-bytes=40"])
+            .with_input(input)
+            .expect_after_skip(
+                0,
+                "
+#indent_mark
+ src/   | 3
+   a.rs | 1 +
+   b.rs | 2 ++
+ src/c.rs | 3 +++
+ 3 files changed, 6 insertions(+)
+",
+            );
+    }
+
+    #[test]
+    fn test_stat_group_by_dir_keeps_colors_of_graph() {
+        let input =
+            " src/a.rs | 3 \x1b[32m++\x1b[m\x1b[31m-\x1b[m\n src/b.rs | 1 \x1b[32m+\x1b[m\n";
+        let output = DeltaTest::with_args(&["--stat-group-by-dir", "--raw"])
+            .with_input(input)
+            .raw_output;
+        assert_eq!(
+            output,
+            " src/   | 4\n   a.rs | 3 \x1b[32m++\x1b[m\x1b[31m-\x1b[m\n   b.rs | 1 \x1b[32m+\x1b[m\n"
+        );
+    }
 }
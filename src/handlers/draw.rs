@@ -3,15 +3,31 @@ use std::io::Write;
 
 use crate::ansi;
 use crate::cli::Width;
+use crate::config::Config;
 use crate::style::{DecorationStyle, Style};
+use crate::utils::tabs;
 
 fn paint_text(text_style: Style, text: &str, addendum: &str) -> String {
+    text_style.paint(with_addendum(text, addendum)).to_string()
+}
+
+// The text followed by the addendum in parentheses, e.g. "a.sh (mode +x)", keeping the padding
+// of the text at the end.
+fn with_addendum(text: &str, addendum: &str) -> String {
     if addendum.is_empty() {
-        text_style.paint(text).to_string()
+        return text.to_string();
+    }
+    let unpadded_text = text.trim_end_matches(' ');
+    let padding = &text[unpadded_text.len()..];
+    format!("{unpadded_text} ({addendum}){padding}")
+}
+
+// The width of the text as it is written: raw text is written without the addendum.
+fn text_width(text_style: Style, text: &str, addendum: &str) -> usize {
+    if text_style.is_raw {
+        ansi::measure_text_width(text)
     } else {
-        text_style
-            .paint(text.to_string() + " (" + addendum + ")")
-            .to_string()
+        ansi::measure_text_width(&with_addendum(text, addendum))
     }
 }
 
@@ -25,8 +41,45 @@ pub type DrawFunction = dyn FnMut(
     ansi_term::Style,
 ) -> std::io::Result<()>;
 
+/// Return the function drawing `decoration_style` around a header, whether the text should be
+/// padded by a space, and the style of the decoration. Tabs in the text are expanded before it is
+/// measured and written, so that the decoration is aligned with it.
 pub fn get_draw_function(
     decoration_style: DecorationStyle,
+    config: &Config,
+) -> (Box<DrawFunction>, bool, ansi_term::Style) {
+    let (mut draw_fn, pad, decoration_ansi_term_style) = _get_draw_function(decoration_style);
+    if config.color_only {
+        return (draw_fn, pad, decoration_ansi_term_style);
+    }
+    let tab_cfg = config.tab_cfg.for_decorations();
+    let draw_fn_expanding_tabs =
+        move |writer: &mut dyn Write,
+              text: &str,
+              raw_text: &str,
+              addendum: &str,
+              line_width: &Width,
+              text_style: Style,
+              decoration_style: ansi_term::Style| {
+            draw_fn(
+                writer,
+                &tabs::expand(text, &tab_cfg),
+                &tabs::expand(raw_text, &tab_cfg),
+                addendum,
+                line_width,
+                text_style,
+                decoration_style,
+            )
+        };
+    (
+        Box::new(draw_fn_expanding_tabs),
+        pad,
+        decoration_ansi_term_style,
+    )
+}
+
+fn _get_draw_function(
+    decoration_style: DecorationStyle,
 ) -> (Box<DrawFunction>, bool, ansi_term::Style) {
     match decoration_style {
         DecorationStyle::Box(style) => (Box::new(write_boxed), true, style),
@@ -85,7 +138,7 @@ pub fn write_boxed(
     } else {
         box_drawing::light::UP_LEFT
     };
-    let box_width = ansi::measure_text_width(text);
+    let box_width = text_width(text_style, text, addendum);
     write_boxed_partial(
         writer,
         text,
@@ -110,7 +163,7 @@ fn write_boxed_with_underline(
     text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
-    let box_width = ansi::measure_text_width(text);
+    let box_width = text_width(text_style, text, addendum);
     write_boxed_with_horizontal_whisker(
         writer,
         text,
@@ -218,7 +271,7 @@ fn _write_under_or_over_lined(
     text_style: Style,
     decoration_style: ansi_term::Style,
 ) -> std::io::Result<()> {
-    let text_width = ansi::measure_text_width(text);
+    let text_width = text_width(text_style, text, addendum);
     let line_width = match *line_width {
         Width::Fixed(n) => max(n, text_width),
         Width::Variable => text_width,
@@ -0,0 +1,286 @@
+use crate::ansi;
+use crate::delta::{DiffType, State, StateMachine};
+use crate::handlers::hunk_header::parse_hunk_header;
+
+/// The change of the line endings of a file whose hunks change nothing else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EolChange {
+    LfToCrlf,
+    CrlfToLf,
+    // The input does not show the carriage returns, as when it is not colored.
+    Unknown,
+}
+
+impl EolChange {
+    fn notice(self) -> &'static str {
+        match self {
+            EolChange::LfToCrlf => "(line endings changed: LF → CRLF)",
+            EolChange::CrlfToLf => "(line endings changed: CRLF → LF)",
+            EolChange::Unknown => "(line endings changed)",
+        }
+    }
+}
+
+// The changed lines of the hunks of a file seen so far.
+#[derive(Default)]
+struct FileChanges {
+    // The index of the first hunk of the file.
+    first_hunk: usize,
+    n_crlf_to_lf: usize,
+    n_lf_to_crlf: usize,
+    n_unknown: usize,
+    // Whether a hunk changes more than line endings.
+    other_change: bool,
+}
+
+impl FileChanges {
+    fn new(first_hunk: usize) -> Self {
+        Self {
+            first_hunk,
+            ..Self::default()
+        }
+    }
+
+    // Add a run of consecutive removed and added lines, without their markers.
+    fn add_run(&mut self, minus_lines: &[&str], plus_lines: &[&str]) {
+        if minus_lines.len() != plus_lines.len() {
+            self.other_change = true;
+            return;
+        }
+        for (minus, plus) in minus_lines.iter().zip(plus_lines) {
+            if minus.trim_end_matches('\r') != plus.trim_end_matches('\r') {
+                self.other_change = true;
+            }
+            match (minus.ends_with('\r'), plus.ends_with('\r')) {
+                (true, false) => self.n_crlf_to_lf += 1,
+                (false, true) => self.n_lf_to_crlf += 1,
+                _ => self.n_unknown += 1,
+            }
+        }
+    }
+
+    fn eol_change(&self) -> Option<EolChange> {
+        let n_lines = self.n_crlf_to_lf + self.n_lf_to_crlf + self.n_unknown;
+        if self.other_change || n_lines == 0 {
+            None
+        } else if self.n_crlf_to_lf == n_lines {
+            Some(EolChange::CrlfToLf)
+        } else if self.n_lf_to_crlf == n_lines {
+            Some(EolChange::LfToCrlf)
+        } else {
+            Some(EolChange::Unknown)
+        }
+    }
+}
+
+/// Return, for each hunk of the input in order, the change of the line endings of its file if
+/// the hunks of the file change nothing else (used by --collapse-eol-only).
+pub fn get_hunk_eol_changes(input: &[u8]) -> Vec<Option<EolChange>> {
+    let input = String::from_utf8_lossy(input);
+    let lines: Vec<String> = input.lines().map(ansi::strip_ansi_codes).collect();
+    let mut eol_changes = Vec::new();
+    let mut file = FileChanges::default();
+    let mut i = 0;
+    while i < lines.len() {
+        let line = &lines[i];
+        i += 1;
+        if line.starts_with("diff ")
+            || line.starts_with("--- ") && lines.get(i).is_some_and(|next| next.starts_with("+++ "))
+        {
+            set_eol_changes(&mut eol_changes, &file);
+            file = FileChanges::new(eol_changes.len());
+        } else if line.starts_with('\\') {
+            // "\ No newline at end of file"
+            file.other_change = true;
+        } else if line.starts_with("@@") {
+            let Some(hunk_header) = parse_hunk_header(line) else {
+                continue;
+            };
+            eol_changes.push(None);
+            let &[(_, mut n_minus), (_, mut n_plus)] =
+                hunk_header.line_numbers_and_hunk_lengths.as_slice()
+            else {
+                // A hunk of a combined diff.
+                file.other_change = true;
+                continue;
+            };
+            let (mut minus_lines, mut plus_lines) = (Vec::new(), Vec::new());
+            while n_minus > 0 || n_plus > 0 {
+                let Some(line) = lines.get(i) else {
+                    break;
+                };
+                match line.chars().next() {
+                    Some('-') if n_minus > 0 => {
+                        minus_lines.push(&line[1..]);
+                        n_minus -= 1;
+                    }
+                    Some('+') if n_plus > 0 => {
+                        plus_lines.push(&line[1..]);
+                        n_plus -= 1;
+                    }
+                    Some(' ') | None => {
+                        file.add_run(&minus_lines, &plus_lines);
+                        minus_lines.clear();
+                        plus_lines.clear();
+                        n_minus = n_minus.saturating_sub(1);
+                        n_plus = n_plus.saturating_sub(1);
+                    }
+                    _ => break,
+                }
+                i += 1;
+            }
+            file.add_run(&minus_lines, &plus_lines);
+        }
+    }
+    set_eol_changes(&mut eol_changes, &file);
+    eol_changes
+}
+
+fn set_eol_changes(eol_changes: &mut [Option<EolChange>], file: &FileChanges) {
+    let eol_change = file.eol_change();
+    for hunk_eol_change in &mut eol_changes[file.first_hunk..] {
+        *hunk_eol_change = eol_change;
+    }
+}
+
+impl StateMachine<'_> {
+    /// Skip the hunks of a file which only change line endings, writing a notice in place of the
+    /// first of them (used by --collapse-eol-only).
+    pub fn handle_eol_only_hunk_line(&mut self) -> std::io::Result<bool> {
+        if let Some((n_minus, n_plus)) = self.eol_only_hunk_remaining.as_mut() {
+            match self.line.chars().next() {
+                Some('-') if *n_minus > 0 => *n_minus -= 1,
+                Some('+') if *n_plus > 0 => *n_plus -= 1,
+                Some(' ') | None if *n_minus > 0 || *n_plus > 0 => {
+                    *n_minus = n_minus.saturating_sub(1);
+                    *n_plus = n_plus.saturating_sub(1);
+                }
+                _ => self.eol_only_hunk_remaining = None,
+            }
+            if self.eol_only_hunk_remaining.is_some() {
+                return Ok(true);
+            }
+        }
+        if !self.line.starts_with("@@") {
+            return Ok(false);
+        }
+        let Some(eol_change) = self
+            .get_current_hunk_value(&self.hunk_eol_changes)
+            .flatten()
+        else {
+            return Ok(false);
+        };
+        let Some(hunk_header) = parse_hunk_header(&self.line) else {
+            return Ok(false);
+        };
+        let &[(_, n_minus), (_, n_plus)] = hunk_header.line_numbers_and_hunk_lengths.as_slice()
+        else {
+            return Ok(false);
+        };
+        if matches!(self.state, State::DiffHeader(_)) {
+            self.painter.paint_buffered_minus_and_plus_lines();
+            self.painter.emit()?;
+            writeln!(
+                self.painter.writer,
+                "{}",
+                ansi_term::Style::new().dimmed().paint(eol_change.notice())
+            )?;
+        }
+        self.state = State::HunkZero(DiffType::Unified, None);
+        self.eol_only_hunk_remaining = Some((n_minus, n_plus));
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    const EOL_ONLY_DIFF: &str = "\
+diff --git a/a.txt b/a.txt
+index 1111111..2222222 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+-one
+-two
++one
++two
+ three
+@@ -10 +10 @@
+-ten
++ten
+diff --git a/b.txt b/b.txt
+index 3333333..4444444 100644
+--- a/b.txt
++++ b/b.txt
+@@ -1,2 +1,2 @@
+-one
++one
+-two
++zwei
+";
+
+    #[test]
+    fn test_get_hunk_eol_changes() {
+        assert_eq!(
+            get_hunk_eol_changes(EOL_ONLY_DIFF.as_bytes()),
+            [Some(EolChange::Unknown), Some(EolChange::Unknown), None]
+        );
+        // The carriage returns are kept by the input when they are followed by escape sequences.
+        let input = "\
+--- a.txt
++++ a.txt
+@@ -1,2 +1,2 @@
+-one
+-two
++one\r\x1b[m
++two\r\x1b[m
+";
+        assert_eq!(
+            get_hunk_eol_changes(input.as_bytes()),
+            [Some(EolChange::LfToCrlf)]
+        );
+        let input = input
+            .replace("-one", "-one\r\x1b[m")
+            .replace("+one\r", "+one");
+        assert_eq!(
+            get_hunk_eol_changes(input.as_bytes()),
+            [Some(EolChange::Unknown)]
+        );
+        let input = "--- a.txt\n+++ a.txt\n@@ -1 +1 @@\n-one\n+one\n\\ No newline at end of file\n";
+        assert_eq!(get_hunk_eol_changes(input.as_bytes()), [None]);
+    }
+
+    #[test]
+    fn test_collapse_eol_only() {
+        let result = DeltaTest::with_args(&["--collapse-eol-only"]).with_input(EOL_ONLY_DIFF);
+        let output = &result.output;
+        assert_eq!(output.matches("(line endings changed)").count(), 1);
+        assert!(output.contains("a.txt"));
+        assert!(!output.contains("three"));
+        assert!(!output.contains("ten"));
+        // The other file changes more than line endings.
+        assert!(output.contains("zwei"));
+        assert!(output.find("(line endings changed)") < output.find("b.txt"));
+
+        let result = DeltaTest::with_args(&[]).with_input(EOL_ONLY_DIFF);
+        assert!(!result.output.contains("line endings changed"));
+        assert!(result.output.contains("three"));
+    }
+
+    #[test]
+    fn test_collapse_eol_only_gives_direction() {
+        let input = EOL_ONLY_DIFF.replace("+one\n+two\n", "+one\r\x1b[m\n+two\r\x1b[m\n");
+        let input = input.replace("+ten\n", "+ten\r\x1b[m\n");
+        let result = DeltaTest::with_args(&["--collapse-eol-only"]).with_input(&input);
+        assert_eq!(
+            result
+                .output
+                .matches("(line endings changed: LF → CRLF)")
+                .count(),
+            1
+        );
+    }
+}
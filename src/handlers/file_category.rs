@@ -0,0 +1,242 @@
+use std::collections::VecDeque;
+
+use crate::ansi;
+use crate::delta::StateMachine;
+
+/// The kind of change made to a file, by which files are grouped with --group-by-category. The
+/// categories are listed in the order in which they are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum FileCategory {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+}
+
+impl FileCategory {
+    fn header(self) -> &'static str {
+        match self {
+            FileCategory::Added => "Added files",
+            FileCategory::Deleted => "Deleted files",
+            FileCategory::Modified => "Modified files",
+            FileCategory::Renamed => "Renamed files",
+        }
+    }
+}
+
+// Return the category implied by a line of the header of a file, if any. A copied file is an
+// added file.
+fn get_header_line_category(line: &str) -> Option<FileCategory> {
+    if line.starts_with("new file mode ") || line.starts_with("copy to ") || line == "--- /dev/null"
+    {
+        Some(FileCategory::Added)
+    } else if line.starts_with("deleted file mode ") || line == "+++ /dev/null" {
+        Some(FileCategory::Deleted)
+    } else if line.starts_with("rename to ") {
+        Some(FileCategory::Renamed)
+    } else {
+        None
+    }
+}
+
+// Return the category of a file from the lines of its header following its `diff` line.
+fn get_header_category(lines: &[String]) -> FileCategory {
+    lines
+        .iter()
+        .take_while(|line| !line.starts_with("@@") && !line.starts_with("diff "))
+        .find_map(|line| get_header_line_category(line))
+        .unwrap_or(FileCategory::Modified)
+}
+
+/// Return the category of a file, starting with its `diff` line.
+pub fn get_file_category(file: &[u8]) -> FileCategory {
+    let lines: Vec<String> = String::from_utf8_lossy(file)
+        .lines()
+        .skip(1)
+        .map(ansi::strip_ansi_codes)
+        .collect();
+    get_header_category(&lines)
+}
+
+/// Return the category of each file of the input which starts with a `diff` line, in the order
+/// the files appear in (used by --group-by-category).
+pub fn get_file_categories(input: &[u8]) -> VecDeque<FileCategory> {
+    let lines: Vec<String> = String::from_utf8_lossy(input)
+        .lines()
+        .map(ansi::strip_ansi_codes)
+        .collect();
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(_, line)| line.starts_with("diff "))
+        .map(|(i, _)| get_header_category(&lines[i + 1..]))
+        .collect()
+}
+
+impl StateMachine<'_> {
+    /// Take the category of the file starting at a `diff` line, whether or not the file is shown.
+    /// The line is left to the other handlers.
+    pub fn handle_file_category_line(&mut self) -> std::io::Result<bool> {
+        if self.config.group_by_category && self.line.starts_with("diff ") {
+            self.file_category = self.file_categories.pop_front();
+        }
+        Ok(false)
+    }
+
+    /// Write the header of the category of the current file if it is the first file of its
+    /// category shown since the start of the input or of the current commit.
+    pub fn emit_file_category_header(&mut self) -> std::io::Result<()> {
+        if self.file_category.is_none() || self.file_category == self.shown_file_category {
+            return Ok(());
+        }
+        if self.shown_file_category.is_some() {
+            // Separate the section from the last file of the preceding one.
+            writeln!(self.painter.writer)?;
+        }
+        self.shown_file_category = self.file_category;
+        if let Some(category) = self.file_category {
+            writeln!(
+                self.painter.writer,
+                "{}",
+                self.config.file_category_style.paint(category.header())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    const DIFF: &str = "\
+diff --git a/modified.txt b/modified.txt
+index 1111111..2222222 100644
+--- a/modified.txt
++++ b/modified.txt
+@@ -1 +1 @@
+-one
++two
+diff --git a/old.txt b/renamed.txt
+similarity index 90%
+rename from old.txt
+rename to renamed.txt
+index 3333333..4444444 100644
+--- a/old.txt
++++ b/renamed.txt
+@@ -1 +1 @@
+-three
++four
+diff --git a/deleted.txt b/deleted.txt
+deleted file mode 100644
+index 5555555..0000000
+--- a/deleted.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-five
+diff --git a/added.txt b/added.txt
+new file mode 100644
+index 0000000..6666666
+--- /dev/null
++++ b/added.txt
+@@ -0,0 +1 @@
++six
+diff --git a/mode.sh b/mode.sh
+old mode 100644
+new mode 100755
+";
+
+    #[test]
+    fn test_get_file_categories() {
+        assert_eq!(
+            get_file_categories(DIFF.as_bytes()),
+            [
+                FileCategory::Modified,
+                FileCategory::Renamed,
+                FileCategory::Deleted,
+                FileCategory::Added,
+                FileCategory::Modified,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_by_category() {
+        let result = DeltaTest::with_args(&["--group-by-category"]).with_input(DIFF);
+        let output = &result.output;
+        let position = |text: &str| output.find(text).unwrap();
+        let headers = [
+            position("Added files"),
+            position("Deleted files"),
+            position("Modified files"),
+            position("Renamed files"),
+        ];
+        assert!(headers.is_sorted());
+        assert!(headers[0] < position("added.txt") && position("added.txt") < headers[1]);
+        assert!(headers[1] < position("deleted.txt") && position("deleted.txt") < headers[2]);
+        assert!(headers[2] < position("modified.txt") && position("modified.txt") < headers[3]);
+        assert!(headers[2] < position("mode.sh") && position("mode.sh") < headers[3]);
+        assert!(headers[3] < position("renamed.txt"));
+        assert_eq!(output.matches("Modified files").count(), 1);
+        assert!(result.raw_output.contains(
+            &ansi_term::Style::new()
+                .bold()
+                .paint("Added files")
+                .to_string()
+        ));
+
+        let result = DeltaTest::with_args(&[]).with_input(DIFF);
+        assert!(!result.output.contains("Modified files"));
+        assert!(result.output.find("modified.txt") < result.output.find("added.txt"));
+    }
+
+    #[test]
+    fn test_group_by_category_style() {
+        let result = DeltaTest::with_args(&["--group-by-category", "--file-category-style=red ul"])
+            .with_input(DIFF);
+        let style = ansi_term::Colour::Red.underline();
+        assert!(result
+            .raw_output
+            .contains(&style.paint("Added files").to_string()));
+
+        let result = DeltaTest::with_args(&["--group-by-category"])
+            .set_config(|config| config.no_color = true)
+            .with_input(DIFF);
+        assert!(result.raw_output.starts_with("Added files\n"));
+    }
+
+    #[test]
+    fn test_group_by_category_of_each_commit() {
+        let commit = |hash: char, message: &str| {
+            format!(
+                "commit {}\nAuthor: A <a@example.com>\n\n    {message}\n\n",
+                hash.to_string().repeat(40)
+            )
+        };
+        let input = [
+            &commit('1', "first commit"),
+            DIFF,
+            &commit('2', "second commit"),
+            &DIFF[..DIFF.find("diff --git a/old.txt").unwrap()],
+        ]
+        .concat();
+        let result = DeltaTest::with_args(&["--group-by-category"]).with_input(&input);
+        let output = &result.output;
+        assert_eq!(output.matches("Added files").count(), 1);
+        assert_eq!(output.matches("Modified files").count(), 2);
+        let second_commit = output.find("second commit").unwrap();
+        assert!(output[second_commit..].contains("Modified files"));
+    }
+
+    #[test]
+    fn test_group_by_category_with_ignored_files() {
+        let result = DeltaTest::with_args(&["--group-by-category", "--ignore-files=modified.txt"])
+            .with_input(DIFF);
+        let output = &result.output;
+        // The header of a category is written before its first file which is shown.
+        assert_eq!(output.matches("Modified files").count(), 1);
+        assert!(output.find("Modified files") < output.find("mode.sh"));
+        assert!(!output.contains("modified.txt"));
+    }
+}
@@ -6,7 +6,7 @@ use serde::Deserialize;
 
 use crate::ansi;
 use crate::config::{
-    delta_unreachable, GrepType, HunkHeaderIncludeCodeFragment, HunkHeaderIncludeFilePath,
+    delta_unreachable, Config, GrepType, HunkHeaderIncludeCodeFragment, HunkHeaderIncludeFilePath,
     HunkHeaderIncludeLineNumber,
 };
 use crate::delta::{State, StateMachine};
@@ -79,7 +79,69 @@ impl LineType {
     }
 }
 
+/// The lines of grep output for one file, held back until the number of matches in the file is
+/// known (used with --grep-show-counts).
+pub struct PendingGrepFile {
+    path: String,
+    // The state before the first line of the file.
+    state: State,
+    // The line and raw line of each line of grep output.
+    lines: Vec<(String, String)>,
+    n_matches: usize,
+}
+
+fn format_match_count(n_matches: usize) -> String {
+    if n_matches == 1 {
+        "1 match".to_string()
+    } else {
+        format!("{n_matches} matches")
+    }
+}
+
 impl StateMachine<'_> {
+    /// Is the current line a line of grep output for the file whose lines are held back?
+    pub fn test_pending_grep_file_continue(&self) -> bool {
+        let Some(pending) = &self.pending_grep_file else {
+            return false;
+        };
+        let raw_line = replace_separator_tabs(&self.raw_line, self.config);
+        let line = replace_separator_tabs(&self.line, self.config);
+        parse_raw_grep_line(&raw_line)
+            .or_else(|| parse_grep_line(&line))
+            .is_some_and(|grep_line| grep_line.path == pending.path)
+    }
+
+    /// Emit the held back lines of grep output, now that the number of matches is known.
+    pub fn emit_pending_grep_file(&mut self) -> std::io::Result<()> {
+        self.grep_buffer_limit.reset();
+        let Some(pending) = self.pending_grep_file.take() else {
+            return Ok(());
+        };
+        let n_matches = pending.n_matches;
+        self.write_pending_grep_file(pending, Some(n_matches))
+    }
+
+    // Write the held back lines of grep output, with the number of matches in their file if known.
+    fn write_pending_grep_file(
+        &mut self,
+        pending: PendingGrepFile,
+        n_matches: Option<usize>,
+    ) -> std::io::Result<()> {
+        let line = std::mem::take(&mut self.line);
+        let raw_line = std::mem::take(&mut self.raw_line);
+        self.state = pending.state;
+        self.grep_match_count = n_matches;
+        for (line, raw_line) in pending.lines {
+            self.line = line;
+            self.raw_line = raw_line;
+            self.handle_grep_line()?;
+        }
+        self.grep_match_count = None;
+        self.line = line;
+        self.raw_line = raw_line;
+        Ok(())
+    }
+
     // If this is a line of grep output then render it accordingly.
     pub fn handle_grep_line(&mut self) -> std::io::Result<bool> {
         self.painter.emit()?;
@@ -96,12 +158,12 @@ impl StateMachine<'_> {
         }
 
         // Try parse_raw_grep_line on raw_line, and fall back to parse_grep_line
-        let raw_line = self.raw_line.clone(); // TODO: avoid clone
+        let raw_line = replace_separator_tabs(&self.raw_line, self.config).into_owned(); // TODO: avoid clone
         let line;
         let grep_line = if let Some(grep_line) = parse_raw_grep_line(&raw_line) {
             grep_line
         } else {
-            line = self.line.clone(); // TODO: avoid clone
+            line = replace_separator_tabs(&self.line, self.config).into_owned(); // TODO: avoid clone
             if let Some(grep_line) = parse_grep_line(&line) {
                 grep_line
             } else {
@@ -112,6 +174,50 @@ impl StateMachine<'_> {
         if matches!(grep_line.line_type, LineType::Ignore) {
             return Ok(true);
         }
+        if self.config.grep_show_counts
+            && self.grep_match_count.is_none()
+            && !self.grep_buffer_limit.is_exceeded()
+        {
+            let state = &self.state;
+            let pending = self
+                .pending_grep_file
+                .get_or_insert_with(|| PendingGrepFile {
+                    path: grep_line.path.to_string(),
+                    state: state.clone(),
+                    lines: Vec::new(),
+                    n_matches: 0,
+                });
+            pending
+                .lines
+                .push((self.line.clone(), self.raw_line.clone()));
+            if grep_line.line_type == LineType::Match {
+                pending.n_matches += 1;
+            }
+            if self
+                .grep_buffer_limit
+                .add(self.line.len() + self.raw_line.len())
+            {
+                // The rest is written as it is read, so the number of matches is not known.
+                let pending = self.pending_grep_file.take().unwrap();
+                self.write_pending_grep_file(pending, None)?;
+                return Ok(true);
+            }
+            self.state = State::Grep(
+                self.config
+                    .grep_output_type
+                    .clone()
+                    .unwrap_or(grep_line.grep_type),
+                grep_line.line_type,
+                grep_line.path.to_string(),
+                grep_line.line_number,
+            );
+            return Ok(true);
+        }
+        if self.config.grep_separator_tabs > 0 {
+            // The lines are written without the tabs read as field separators.
+            self.line = replace_separator_tabs(&self.line, self.config).into_owned();
+            self.raw_line = raw_line.clone();
+        }
         let first_path = previous_path.is_none();
         let new_path = first_path || previous_path.as_deref() != Some(&grep_line.path);
         let line_number_jump = previous_line < &grep_line.line_number.as_ref().map(|n| n - 1);
@@ -140,6 +246,23 @@ impl StateMachine<'_> {
                 self.emit_ripgrep_format_grep_line(grep_line, new_path, first_path, new_section)
             }
             State::Grep(GrepType::Classic, _, _, _) => {
+                if let (true, Some(n_matches)) = (new_path, self.grep_match_count) {
+                    writeln!(
+                        self.painter.writer,
+                        "{} {}",
+                        paint::paint_file_path_with_line_number(
+                            None,
+                            &grep_line.path,
+                            false,
+                            ":",
+                            true,
+                            Some(self.config.grep_file_style),
+                            None,
+                            self.config
+                        ),
+                        format_match_count(n_matches)
+                    )?;
+                }
                 self.emit_classic_format_grep_line(grep_line)
             }
             _ => delta_unreachable("Impossible state while handling grep line."),
@@ -161,10 +284,13 @@ impl StateMachine<'_> {
             if !first_path {
                 writeln!(self.painter.writer)?;
             }
+            let match_count = self.grep_match_count.map(format_match_count);
             handlers::hunk_header::write_line_of_code_with_optional_path_and_line_number(
-                "",
+                match_count.as_deref().unwrap_or(""),
                 &[(0, 0)],
-                None,
+                match_count
+                    .as_ref()
+                    .map(|_| StyleSectionSpecifier::Style(self.config.grep_line_number_style)),
                 &mut self.painter,
                 &self.line,
                 &grep_line.path,
@@ -174,8 +300,9 @@ impl StateMachine<'_> {
                 &HunkHeaderIncludeFilePath::Yes,
                 &HunkHeaderIncludeLineNumber::No,
                 &HunkHeaderIncludeHunkLabel::Yes,
+                None,
                 &HunkHeaderIncludeCodeFragment::Yes,
-                "",
+                if match_count.is_some() { ": " } else { "" },
                 self.config,
             )?
         }
@@ -199,6 +326,13 @@ impl StateMachine<'_> {
                     self.config.grep_match_line_style,
                 )
             }
+            (_, None) if !self.config.grep_strip_input_ansi && self.raw_line.contains('\x1b') => {
+                self.raw_line = tabs::expand(&self.raw_line, &self.config.tab_cfg);
+                get_input_style_sections(&self.raw_line, &grep_line.path, grep_line.line_number)
+                    .unwrap_or(StyleSectionSpecifier::Style(
+                        self.config.grep_context_line_style,
+                    ))
+            }
             (LineType::Match, None) => {
                 // HACK: We need tabs expanded, and we need
                 // the &str passed to
@@ -237,6 +371,7 @@ impl StateMachine<'_> {
                 &HunkHeaderIncludeLineNumber::No
             },
             &HunkHeaderIncludeHunkLabel::No,
+            None,
             &HunkHeaderIncludeCodeFragment::Yes,
             grep_line.line_type.file_path_separator(),
             self.config,
@@ -263,6 +398,7 @@ impl StateMachine<'_> {
                     &self.config.hunk_header_style_include_file_path,
                     &self.config.hunk_header_style_include_line_number,
                     &HunkHeaderIncludeHunkLabel::Yes,
+                    None,
                     &HunkHeaderIncludeCodeFragment::Yes,
                     grep_line.line_type.file_path_separator(),
                     self.config,
@@ -340,6 +476,13 @@ impl StateMachine<'_> {
                     self.config.grep_match_line_style,
                 )
             }
+            (_, None) if !self.config.grep_strip_input_ansi && self.raw_line.contains('\x1b') => {
+                self.raw_line = tabs::expand(&self.raw_line, &self.config.tab_cfg);
+                get_input_style_sections(&self.raw_line, &grep_line.path, grep_line.line_number)
+                    .unwrap_or(StyleSectionSpecifier::Style(
+                        self.config.grep_context_line_style,
+                    ))
+            }
             (LineType::Match, None) => {
                 // HACK: We need tabs expanded, and we need
                 // the &str passed to
@@ -401,13 +544,7 @@ fn get_code_style_sections<'b>(
     path: &str,
     line_number: Option<usize>,
 ) -> Option<StyleSectionSpecifier<'b>> {
-    if let Some(prefix_end) = ansi::ansi_preserving_index(
-        raw_line,
-        match line_number {
-            Some(n) => format!("{path}:{n}:").len() - 1,
-            None => path.len(),
-        },
-    ) {
+    if let Some(prefix_end) = get_prefix_end(raw_line, path, line_number) {
         let match_style_sections = ansi::parse_style_sections(&raw_line[(prefix_end + 1)..])
             .iter()
             .map(|(ansi_term_style, s)| {
@@ -426,6 +563,84 @@ fn get_code_style_sections<'b>(
     }
 }
 
+// Return style sections giving the line contents the colors they were received with.
+fn get_input_style_sections<'b>(
+    raw_line: &'b str,
+    path: &str,
+    line_number: Option<usize>,
+) -> Option<StyleSectionSpecifier<'b>> {
+    let prefix_end = get_prefix_end(raw_line, path, line_number)?;
+    Some(StyleSectionSpecifier::StyleSections(
+        ansi::parse_style_sections(&raw_line[(prefix_end + 1)..])
+            .iter()
+            .map(|(ansi_term_style, s)| {
+                (
+                    Style {
+                        ansi_term_style: *ansi_term_style,
+                        ..Style::new()
+                    },
+                    *s,
+                )
+            })
+            .collect(),
+    ))
+}
+
+// Return the index in the raw line of the separator preceding the line contents.
+fn get_prefix_end(raw_line: &str, path: &str, line_number: Option<usize>) -> Option<usize> {
+    ansi::ansi_preserving_index(
+        raw_line,
+        match line_number {
+            Some(n) => format!("{path}:{n}:").len() - 1,
+            None => path.len(),
+        },
+    )
+}
+
+/// Return the line with its first tabs read as field separators, as given by
+/// --grep-separator-tabs. A tab following a separator symbol (as written by `grep -T`) is removed,
+/// along with the spaces padding the line number before it; any other tab is replaced by ":". The
+/// last of these tabs is followed by --grep-separator-tab-gap. ANSI escape sequences are kept.
+fn replace_separator_tabs<'a>(line: &'a str, config: &Config) -> Cow<'a, str> {
+    if config.grep_separator_tabs == 0 || !line.contains('\t') {
+        return Cow::Borrowed(line);
+    }
+    let is_separator = |c: char| matches!(c, ':' | '-' | '=');
+    let text = ansi::strip_ansi_codes(line);
+    let tabs: Vec<usize> = text
+        .match_indices('\t')
+        .map(|(i, _)| i)
+        .take(config.grep_separator_tabs)
+        .collect();
+    // The index in the text of each character to replace, and its replacement.
+    let mut edits = Vec::new();
+    for (k, &i) in tabs.iter().enumerate() {
+        let follows_separator = text[..i].ends_with(is_separator);
+        let mut replacement = if follows_separator { "" } else { ":" }.to_string();
+        if k == tabs.len() - 1 {
+            replacement.push_str(&config.grep_separator_tab_gap);
+        }
+        edits.push((i, replacement));
+        if follows_separator {
+            // Remove the spaces padding a line number, as in "file: 7:\t".
+            let before_number = text[..i - 1].trim_end_matches(|c: char| c.is_ascii_digit());
+            let before_spaces = before_number.trim_end_matches(' ');
+            if before_number.len() < i - 1 && before_spaces.ends_with(is_separator) {
+                edits
+                    .extend((before_spaces.len()..before_number.len()).map(|j| (j, String::new())));
+            }
+        }
+    }
+    edits.sort_by_key(|(i, _)| std::cmp::Reverse(*i));
+    let mut line = line.to_string();
+    for (i, replacement) in edits {
+        if let Some(j) = ansi::ansi_preserving_index(&line, i) {
+            line.replace_range(j..j + 1, &replacement);
+        }
+    }
+    Cow::Owned(line)
+}
+
 fn make_output_config() -> GrepOutputConfig {
     match &*process::calling_process() {
         process::CallingProcess::GitGrep(command_line)
@@ -732,8 +947,9 @@ pub fn _parse_grep_line<'b>(regex: &Regex, line: &'b str) -> Option<GrepLine<'b>
 #[cfg(test)]
 mod tests {
     use crate::handlers::grep::{
-        parse_grep_line, parse_raw_grep_line, GrepLine, GrepType, LineType,
+        parse_grep_line, parse_raw_grep_line, replace_separator_tabs, GrepLine, GrepType, LineType,
     };
+    use crate::tests::integration_test_utils::{self, DeltaTest};
     use crate::utils::process::tests::FakeParentArgs;
 
     #[test]
@@ -1291,4 +1507,163 @@ mod tests {
             ]))
         );
     }
+
+    #[test]
+    fn test_grep_show_counts() {
+        let input = "\
+src/a.rs:1:fn foo() {}
+src/a.rs-2-
+src/a.rs:3:fn bar() {}
+src/b.rs:7:let foo = 1;
+";
+        let output = DeltaTest::with_args(&["--grep-show-counts", "--grep-output-type=ripgrep"])
+            .with_calling_process("git grep -n -C1 foo")
+            .with_input(input)
+            .output;
+        assert_eq!(
+            output,
+            "src/a.rs: 2 matches \n1:fn foo() {}\n2- \n3:fn bar() {}\n\nsrc/b.rs: 1 match \n7:let foo = 1;\n"
+        );
+
+        let output = DeltaTest::with_args(&["--grep-show-counts", "--grep-output-type=classic"])
+            .with_calling_process("git grep -n -C1 foo")
+            .with_input(input)
+            .output;
+        assert_eq!(
+            output,
+            "\
+src/a.rs: 2 matches
+src/a.rs:1:  fn foo() {}
+src/a.rs:2:  \n\
+src/a.rs:3:  fn bar() {}
+src/b.rs: 1 match
+src/b.rs:7:  let foo = 1;
+"
+        );
+    }
+
+    #[test]
+    fn test_grep_show_counts_buffer_limit() {
+        let input = "\
+src/a.rs:1:fn foo() {}
+src/a.rs:3:fn bar() {}
+src/a.rs:5:fn baz() {}
+src/b.rs:7:let foo = 1;
+";
+        // The lines of src/a.rs exceed the limit, and are written without a count, as is the rest.
+        let output = DeltaTest::with_args(&[
+            "--grep-show-counts",
+            "--grep-output-type=classic",
+            "--buffer-limit-bytes=100",
+        ])
+        .with_calling_process("git grep -n foo")
+        .with_input(input)
+        .output;
+        assert_eq!(
+            output,
+            "\
+src/a.rs:1:  fn foo() {}
+src/a.rs:3:  fn bar() {}
+src/a.rs:5:  fn baz() {}
+src/b.rs:7:  let foo = 1;
+"
+        );
+    }
+
+    #[test]
+    fn test_grep_strip_input_ansi() {
+        let e = "\x1b";
+        let input = format!(
+            "{e}[0m{e}[35msrc/a.rs{e}[0m:{e}[0m{e}[32m1{e}[0m:fn {e}[0m{e}[1m{e}[31mfoo{e}[0m() {{}}
+{e}[0m{e}[35msrc/a.rs{e}[0m-{e}[0m{e}[32m2{e}[0m-let {e}[33mx{e}[0m = 1;
+"
+        );
+        for args in [&[][..], &["--grep-strip-input-ansi=true"]] {
+            let output = DeltaTest::with_args(args)
+                .with_calling_process("rg -n --color=always -C1 foo")
+                .explain_ansi()
+                .with_input(&input);
+            output.expect_after_skip(
+                0,
+                "
+                (purple)src/a.rs(normal):(green)1(normal):  (81)fn(231) (149)foo(231)() {}(normal)
+                (purple)src/a.rs(normal):(green)2(normal):  (81)let(231) x (203)=(231) (141)1(231);(normal)",
+            );
+        }
+        let output = DeltaTest::with_args(&["--grep-strip-input-ansi=false"])
+            .with_calling_process("rg -n --color=always -C1 foo")
+            .explain_ansi()
+            .with_input(&input);
+        output.expect_after_skip(
+            0,
+            "
+            (purple)src/a.rs(normal):(green)1(normal):  fn (red)foo(normal)() {}
+            (purple)src/a.rs(normal):(green)2(normal):  let (yellow)x(normal) = 1;",
+        );
+    }
+
+    #[test]
+    fn test_grep_strip_input_ansi_uncolored_input() {
+        let input = "src/a.rs:1:fn foo() {}\n";
+        let output = |args: &[&str]| {
+            DeltaTest::with_args(args)
+                .with_calling_process("git grep -n foo")
+                .with_input(input)
+                .raw_output
+        };
+        assert_eq!(output(&[]), output(&["--grep-strip-input-ansi=false"]));
+    }
+
+    #[test]
+    fn test_replace_separator_tabs() {
+        let replace = |args: &[&str], line: &str| {
+            let config = integration_test_utils::make_config_from_args(args);
+            replace_separator_tabs(line, &config).into_owned()
+        };
+        let args = &["--grep-separator-tabs=1"];
+        // grep -T
+        assert_eq!(replace(args, "a.rs: 7:\tlet x;"), "a.rs:7:let x;");
+        assert_eq!(replace(args, "a.rs- 8-\t\tlet y;"), "a.rs-8-\tlet y;");
+        assert_eq!(replace(args, "a.rs:\tlet x;"), "a.rs:let x;");
+        assert_eq!(replace(args, "a.rs:let x;"), "a.rs:let x;");
+        // rg --field-match-separator=$'\t'
+        let args = &["--grep-separator-tabs=2", "--grep-separator-tab-gap=  "];
+        assert_eq!(replace(args, "a.rs\t7\tlet\tx;"), "a.rs:7:  let\tx;");
+        assert_eq!(replace(&[], "a.rs\t7\tlet x;"), "a.rs\t7\tlet x;");
+        // The tabs of colored lines
+        let e = "\x1b";
+        assert_eq!(
+            replace(
+                &["--grep-separator-tabs=1"],
+                &format!("{e}[35ma.rs{e}[m{e}[36m:{e}[m{e}[32m 7{e}[m{e}[36m:{e}[m\tlet x;")
+            ),
+            format!("{e}[35ma.rs{e}[m{e}[36m:{e}[m{e}[32m7{e}[m{e}[36m:{e}[mlet x;")
+        );
+    }
+
+    #[test]
+    fn test_grep_separator_tabs() {
+        let input = "a.rs\t7\tlet x;\na.rs\t8\t\tlet y;\n";
+        let output = |args: &[&str]| {
+            DeltaTest::with_args(args)
+                .with_calling_process("rg -n --field-match-separator=\t let")
+                .with_input(input)
+                .output
+        };
+        assert_eq!(
+            output(&["--grep-separator-tabs=2", "--grep-output-type=ripgrep"]),
+            "a.rs \n7:let x;\n8:        let y;\n"
+        );
+        assert_eq!(
+            output(&[
+                "--grep-separator-tabs=2",
+                "--grep-separator-tab-gap=| ",
+                "--grep-output-type=classic"
+            ]),
+            "a.rs:7:  | let x;\na.rs:8:  |         let y;\n"
+        );
+        // Without the option, the lines are not recognized as grep output, and are written as they
+        // are.
+        assert_eq!(output(&["--grep-output-type=classic"]), input);
+    }
 }
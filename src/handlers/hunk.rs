@@ -2,13 +2,17 @@ use std::cmp::min;
 
 use lazy_static::lazy_static;
 
+use crate::ansi;
 use crate::cli;
-use crate::config::{delta_unreachable, Config};
+use crate::config::{delta_unreachable, Config, GroupChanges};
 use crate::delta::{DiffType, InMergeConflict, MergeParents, State, StateMachine};
-use crate::paint::{prepare, prepare_raw_line};
+use crate::features::side_by_side;
+use crate::minusplus::MinusPlusIndex::{Minus, Plus};
+use crate::paint::{format_line_tag, prepare, prepare_raw_line};
 use crate::style;
+use crate::utils::blame;
 use crate::utils::process::{self, CallingProcess};
-use crate::utils::tabs;
+use crate::utils::{tabs, whitespace};
 
 // HACK: WordDiff should probably be a distinct top-level line state
 pub fn is_word_diff() -> bool {
@@ -40,9 +44,28 @@ fn compute_is_word_diff() -> bool {
     }
 }
 
+/// The numbers of hunks, and of removed and added lines, of a file (used by --file-footer).
+pub struct FileFooter {
+    path: String,
+    pub n_hunks: usize,
+    pub n_minus: usize,
+    pub n_plus: usize,
+}
+
+impl FileFooter {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+            n_hunks: 0,
+            n_minus: 0,
+            n_plus: 0,
+        }
+    }
+}
+
 impl StateMachine<'_> {
     #[inline]
-    fn test_hunk_line(&self) -> bool {
+    pub fn test_hunk_line(&self) -> bool {
         matches!(
             self.state,
             State::HunkHeader(_, _, _, _)
@@ -80,7 +103,10 @@ impl StateMachine<'_> {
         }
         self.state = match new_line_state(&self.line, &self.raw_line, &self.state, self.config) {
             Some(HunkMinus(diff_type, raw_line)) => {
-                if let HunkPlus(_, _) = self.state {
+                let reversed = is_reversed(&diff_type, self.config);
+                if let (HunkPlus(_, _), false, GroupChanges::Interleaved) =
+                    (&self.state, reversed, self.config.group_changes)
+                {
                     // We have just entered a new subhunk; process the previous one
                     // and flush the line buffers.
                     self.painter.paint_buffered_minus_and_plus_lines();
@@ -88,15 +114,57 @@ impl StateMachine<'_> {
                 let n_parents = diff_type.n_parents();
                 let line = prepare(&self.line, n_parents, self.config);
                 let state = HunkMinus(diff_type, raw_line);
+                if self.painter.next_line_is_focus_line(&[Minus]) {
+                    self.painter.buffered_focus_line =
+                        Some((Minus, self.painter.minus_lines.len()));
+                }
+                if let Some(moved_blocks) = self.moved_blocks.as_mut() {
+                    let line_number = self.painter.hunk_line_numbers[Minus] - 1;
+                    moved_blocks.push(Minus, line_number, line.trim_end_matches('\n'));
+                }
                 self.painter.minus_lines.push((line, state.clone()));
-                self.minus_line_counter.count_line();
+                if let Some(file_footer) = self.file_footer.as_mut() {
+                    file_footer.n_minus += 1;
+                }
+                if !reversed {
+                    self.minus_line_counter.count_line();
+                }
+                self.changes_found = true;
                 state
             }
             Some(HunkPlus(diff_type, raw_line)) => {
+                let reversed = is_reversed(&diff_type, self.config);
+                if let (HunkMinus(_, _), true, GroupChanges::Interleaved) =
+                    (&self.state, reversed, self.config.group_changes)
+                {
+                    // In a reversed subhunk the added lines are the ones git emits last, so we
+                    // have just entered a new subhunk.
+                    self.painter.paint_buffered_minus_and_plus_lines();
+                }
                 let n_parents = diff_type.n_parents();
                 let line = prepare(&self.line, n_parents, self.config);
                 let state = HunkPlus(diff_type, raw_line);
+                if self.painter.next_line_is_focus_line(&[Plus]) {
+                    self.painter.buffered_focus_line = Some((Plus, self.painter.plus_lines.len()));
+                }
+                if let Some(moved_blocks) = self.moved_blocks.as_mut() {
+                    let line_number = self.painter.hunk_line_numbers[Plus] - 1;
+                    moved_blocks.push(Plus, line_number, line.trim_end_matches('\n'));
+                }
                 self.painter.plus_lines.push((line, state.clone()));
+                if let Some(file_footer) = self.file_footer.as_mut() {
+                    file_footer.n_plus += 1;
+                }
+                if self.config.check {
+                    self.push_whitespace_errors(n_parents);
+                }
+                if reversed {
+                    self.minus_line_counter.count_line();
+                }
+                self.changes_found = true;
+                if let Some(age_annotator) = self.age_annotator.as_mut() {
+                    age_annotator.skip_line();
+                }
                 state
             }
             Some(HunkZero(diff_type, raw_line)) => {
@@ -111,7 +179,30 @@ impl StateMachine<'_> {
                 };
                 let line = prepare(&self.line, n_parents, self.config);
                 let state = State::HunkZero(diff_type, raw_line);
+                let start = self.painter.output_buffer.len();
+                let is_focus_line = self.painter.next_line_is_focus_line(&[Minus, Plus]);
                 self.painter.paint_zero_line(&line, state.clone());
+                if is_focus_line {
+                    self.painter.paint_focus_line(start, 0);
+                }
+                if self.config.annotate_lines {
+                    let tag = format_line_tag(' ', self.painter.hunk_line_numbers[Plus] - 1);
+                    self.painter.insert_line_tags(start, [tag]);
+                }
+                if self.config.annotate_age {
+                    let age = self
+                        .age_annotator
+                        .as_mut()
+                        .and_then(|age_annotator| age_annotator.next_line_age());
+                    blame::insert_age_gutter(
+                        &mut self.painter.output_buffer,
+                        start,
+                        age.as_deref(),
+                    );
+                }
+                if self.config.blame_removed {
+                    blame::insert_author_gutter(&mut self.painter.output_buffer, start, &[]);
+                }
                 self.minus_line_counter.count_line();
                 state
             }
@@ -120,16 +211,144 @@ impl StateMachine<'_> {
                 // is not a hunk line, but the parser does not have a more accurate state corresponding
                 // to this.
                 self.painter.paint_buffered_minus_and_plus_lines();
-                self.painter
-                    .output_buffer
-                    .push_str(&tabs::expand(&self.raw_line, &self.config.tab_cfg));
-                self.painter.output_buffer.push('\n');
+                let line = tabs::expand(&self.raw_line, &self.config.tab_cfg);
+                if self.config.side_by_side && self.line.starts_with('\\') {
+                    // In side-by-side mode, place the marker under the side it refers to,
+                    // i.e. the side of the line preceding it.
+                    let panel_side = match self.state {
+                        HunkMinus(_, _) => Some(side_by_side::Left),
+                        HunkPlus(_, _) => Some(side_by_side::Right),
+                        _ => None,
+                    };
+                    side_by_side::paint_no_newline_marker_side_by_side(
+                        &line,
+                        panel_side,
+                        &mut self.painter.output_buffer,
+                        self.config,
+                    );
+                } else {
+                    self.painter.output_buffer.push_str(&line);
+                    self.painter.output_buffer.push('\n');
+                }
                 State::HunkZero(Unified, None)
             }
         };
         self.painter.emit()?;
         Ok(true)
     }
+
+    // Record the whitespace errors of the added line just buffered (used by --check).
+    fn push_whitespace_errors(&mut self, n_parents: usize) {
+        let unexpanded =
+            tabs::remove_prefix_and_expand(n_parents, &self.line, &tabs::TabCfg::new(0));
+        let errors = whitespace::whitespace_errors(&unexpanded, &self.config.tab_cfg);
+        self.painter.plus_lines_whitespace_errors.push(errors);
+    }
+
+    /// Can the line be part of the current hunk or start another hunk of the same file, i.e. must
+    /// the lines following the hunk in the file not be shown yet? (used by
+    /// --show-trailing-context and --show-moves-paired)
+    pub fn test_hunks_continue(&self) -> bool {
+        self.line.starts_with(['+', '-', ' ', '\\', '@']) || self.line.is_empty()
+    }
+
+    /// Show the lines following the last hunk of a file as unchanged lines, read from the
+    /// working tree version of the file.
+    pub fn emit_trailing_context(&mut self) -> std::io::Result<()> {
+        let (Some((file, line_number)), Some(trailing_context)) = (
+            self.pending_trailing_context.take(),
+            self.trailing_context.as_mut(),
+        ) else {
+            return Ok(());
+        };
+        let lines = trailing_context.lines_from(&file, line_number);
+        let (line, raw_line) = (self.line.clone(), self.raw_line.clone());
+        for context_line in lines {
+            self.line = format!(" {context_line}");
+            self.raw_line.clone_from(&self.line);
+            self.handle_hunk_line()?;
+        }
+        (self.line, self.raw_line) = (line, raw_line);
+        Ok(())
+    }
+
+    /// Write the summary line of the current file, following its hunks.
+    pub fn emit_file_footer(&mut self) -> std::io::Result<()> {
+        let Some(file_footer) = self.file_footer.take() else {
+            return Ok(());
+        };
+        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.emit()?;
+        let FileFooter {
+            path,
+            n_hunks,
+            n_minus,
+            n_plus,
+        } = file_footer;
+        let footer = format!(
+            "── {path}: +{n_plus} −{n_minus} across {n_hunks} hunk{} ──",
+            if n_hunks == 1 { "" } else { "s" }
+        );
+        writeln!(
+            self.painter.writer,
+            "{}",
+            self.config.file_style.paint(footer)
+        )
+    }
+
+    /// Show each block moved within the current file as a pair of panes: the removed block on the
+    /// left and the identical added block on the right.
+    pub fn emit_moved_blocks(&mut self) -> std::io::Result<()> {
+        let Some(moved_blocks) = self.moved_blocks.as_mut() else {
+            return Ok(());
+        };
+        let moves = moved_blocks.take_moves();
+        if moves.is_empty() {
+            return Ok(());
+        }
+        self.painter.paint_buffered_minus_and_plus_lines();
+        self.painter.emit()?;
+        let file = if self.plus_file == "/dev/null" {
+            &self.minus_file
+        } else {
+            &self.plus_file
+        };
+        for (removed, added) in moves {
+            let header = format!(
+                "moved, identical: {file} {}-{} {}{}-{}",
+                removed.start,
+                removed.end(),
+                self.config.right_arrow,
+                added.start,
+                added.end()
+            );
+            writeln!(
+                self.painter.writer,
+                "{}",
+                self.config.inline_hint_style.paint(header)
+            )?;
+            let number_width = added.end().max(removed.end()).to_string().len();
+            let config = self.config;
+            let pane = |side: side_by_side::PanelSide, line_number: usize, line: &str| {
+                let width = config.side_by_side_data[side].width;
+                let text = format!("{line_number:>number_width$} │ {line}");
+                let text = ansi::truncate_str(&text, width, &config.truncation_symbol);
+                let padding = width.saturating_sub(ansi::measure_text_width(&text));
+                format!("{text}{}", " ".repeat(padding))
+            };
+            for (i, line) in removed.lines.iter().enumerate() {
+                let left = pane(side_by_side::Left, removed.start + i, line);
+                let right = pane(side_by_side::Right, added.start + i, line);
+                writeln!(
+                    self.painter.writer,
+                    "{}{}",
+                    self.config.minus_style.paint(left),
+                    self.config.plus_style.paint(right.trim_end()),
+                )?;
+            }
+        }
+        Ok(())
+    }
 }
 
 // Return Some(prepared_raw_line) if delta should emit this line raw.
@@ -151,6 +370,11 @@ fn maybe_raw_line(
     }
 }
 
+// Whether --reverse applies to lines of the given diff type. Combined diffs are not reversed.
+fn is_reversed(diff_type: &DiffType, config: &Config) -> bool {
+    config.reverse && *diff_type == DiffType::Unified
+}
+
 // Return the new state corresponding to `new_line`, given the previous state. A return value of
 // None means that `new_line` is not recognized as a hunk line.
 fn new_line_state(
@@ -222,12 +446,28 @@ fn new_line_state(
         _ => delta_unreachable(""),
     };
 
+    // With --reverse, a '-' line is shown as an added line and a '+' line as a removed line, but
+    // the colors of the raw line are still those git uses for the original side.
+    let reversed = is_reversed(&diff_type, config);
+    let prefix_char = match prefix_char {
+        Some('-') if reversed => Some('+'),
+        Some('+') if reversed => Some('-'),
+        prefix_char => prefix_char,
+    };
+    let git_minus_styles = [*style::GIT_DEFAULT_MINUS_STYLE, config.git_minus_style];
+    let git_plus_styles = [*style::GIT_DEFAULT_PLUS_STYLE, config.git_plus_style];
+    let (git_minus_styles, git_plus_styles) = if reversed {
+        (git_plus_styles, git_minus_styles)
+    } else {
+        (git_minus_styles, git_plus_styles)
+    };
+
     let maybe_minus_raw_line = || {
         maybe_raw_line(
             new_raw_line,
             config.minus_style.is_raw,
             diff_type.n_parents(),
-            &[*style::GIT_DEFAULT_MINUS_STYLE, config.git_minus_style],
+            &git_minus_styles,
             config,
         )
     };
@@ -245,7 +485,7 @@ fn new_line_state(
             new_raw_line,
             config.plus_style.is_raw,
             diff_type.n_parents(),
-            &[*style::GIT_DEFAULT_PLUS_STYLE, config.git_plus_style],
+            &git_plus_styles,
             config,
         )
     };
@@ -419,4 +659,278 @@ Date:   Sat Dec 11 17:08:56 2021 -0500
     [31m[-aaa-][m[32m{+bbb+}[m
 "#;
     }
+
+    #[test]
+    fn test_reverse() {
+        let input = "\
+diff --git a/a.txt b/b.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/b.txt
+@@ -1,3 +1,4 @@
+ a
+-b
++c
++d
+ e
+";
+        DeltaTest::with_args(&[
+            "--reverse",
+            "--line-numbers",
+            "--minus-style=red",
+            "--plus-style=green",
+            "--line-numbers-minus-style=red",
+            "--line-numbers-plus-style=green",
+            "--line-numbers-zero-style=normal",
+            "--line-numbers-left-style=normal",
+            "--line-numbers-right-style=normal",
+        ])
+        .explain_ansi()
+        .with_input(input)
+        .expect_contains("b.txt ⟶   a.txt")
+        .expect_after_skip(
+            7,
+            "
+#indent_mark
+  1 ⋮  1 │(231)a(normal)
+(red)  2 (normal)⋮(green)    (normal)│(red)c(normal)
+(red)  3 (normal)⋮(green)    (normal)│(red)d(normal)
+(red)    (normal)⋮(green)  2 (normal)│(green)b(normal)
+  4 ⋮  3 │(231)e(normal)
+",
+        );
+    }
+
+    #[test]
+    fn test_group_changes() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,4 +1,4 @@
+-b
++c
+-d
++e
+ f
+";
+        for (group_changes, expected) in [
+            (
+                "interleaved",
+                "
+#indent_mark
+(red)  1 (normal)⋮(green)    (normal)│(red)b(normal)
+(red)    (normal)⋮(green)  1 (normal)│(green)c(normal)
+(red)  2 (normal)⋮(green)    (normal)│(red)d(normal)
+(red)    (normal)⋮(green)  2 (normal)│(green)e(normal)
+  3 ⋮  3 │(231)f(normal)
+",
+            ),
+            (
+                "grouped",
+                "
+#indent_mark
+(red)  1 (normal)⋮(green)    (normal)│(red)b(normal)
+(red)  2 (normal)⋮(green)    (normal)│(red)d(normal)
+(red)    (normal)⋮(green)  1 (normal)│(green)c(normal)
+(red)    (normal)⋮(green)  2 (normal)│(green)e(normal)
+  3 ⋮  3 │(231)f(normal)
+",
+            ),
+        ] {
+            DeltaTest::with_args(&[
+                "--line-numbers",
+                &format!("--group-changes={group_changes}"),
+                "--minus-style=red",
+                "--plus-style=green",
+                "--line-numbers-minus-style=red",
+                "--line-numbers-plus-style=green",
+                "--line-numbers-zero-style=normal",
+                "--line-numbers-left-style=normal",
+                "--line-numbers-right-style=normal",
+            ])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(7, expected);
+        }
+    }
+
+    #[test]
+    fn test_focus_line() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,4 @@
+ a
+-b
++c
++d
+ e
+";
+        for (focus_line, expected) in [
+            (
+                "new:3",
+                "
+#indent_mark
+  1 ⋮  1 │(231)a(normal)
+(red)  2 (normal)⋮(green)    (normal)│(red)b(normal)
+(red)    (normal)⋮(green)  2 (normal)│(green)c(normal)
+(bold normal)    ⋮  3 │d(normal)
+  3 ⋮  4 │(231)e(normal)
+",
+            ),
+            (
+                "old:3",
+                "
+#indent_mark
+  1 ⋮  1 │(231)a(normal)
+(red)  2 (normal)⋮(green)    (normal)│(red)b(normal)
+(red)    (normal)⋮(green)  2 (normal)│(green)c(normal)
+(red)    (normal)⋮(green)  3 (normal)│(green)d(normal)
+(bold normal)  3 ⋮  4 │e(normal)
+",
+            ),
+        ] {
+            DeltaTest::with_args(&[
+                "--line-numbers",
+                &format!("--focus-line={focus_line}"),
+                "--focus-line-style=bold",
+                "--minus-style=red",
+                "--plus-style=green",
+                "--line-numbers-minus-style=red",
+                "--line-numbers-plus-style=green",
+                "--line-numbers-zero-style=normal",
+                "--line-numbers-left-style=normal",
+                "--line-numbers-right-style=normal",
+            ])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(7, expected);
+        }
+    }
+
+    #[test]
+    fn test_annotate_lines() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -40,4 +40,5 @@
+ a
+-b
++c
++d
+ e
+-f
+ g
+";
+        DeltaTest::with_args(&["--annotate-lines"])
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+[ ][L40] a
+[-][L41] b
+[+][L41] c
+[+][L42] d
+[ ][L43] e
+[-][L43] f
+[ ][L44] g
+",
+            );
+    }
+
+    #[test]
+    fn test_annotate_and_focus_lines_with_collapsed_blank_additions() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,6 @@
+ a
+-b
++
++
++
++x
+ c
+";
+        DeltaTest::with_args(&["--collapse-blank-additions", "--annotate-lines"])
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+[ ][L1] a
+[-][L2] b
+[+][L2] + 3 blank lines
+[+][L5] x
+[ ][L6] c
+",
+            );
+        DeltaTest::with_args(&[
+            "--collapse-blank-additions",
+            "--focus-line=new:5",
+            "--focus-line-style=bold",
+            "--minus-style=red",
+            "--plus-style=green",
+        ])
+        .explain_ansi()
+        .with_input(input)
+        .expect_after_skip(
+            7,
+            "
+(231)a(normal)
+(red)b(normal)
+(blue)+ 3 blank lines(normal)
+(bold normal)x(normal)
+(231)c(normal)
+",
+        );
+    }
+
+    #[test]
+    fn test_file_footer() {
+        let input = "\
+diff --git a/src/a.rs b/src/a.rs
+index 223ca50..367a6f6 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,3 +1,4 @@
+ a
+-b
++c
++d
+ e
+@@ -10,2 +11,1 @@
+-f
+ g
+diff --git a/b.txt b/b.txt
+deleted file mode 100644
+index 223ca50..0000000
+--- a/b.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-x
+";
+        let result = DeltaTest::with_args(&["--file-footer"]).with_input(input);
+        let lines: Vec<&str> = result.output.lines().collect();
+        // Each footer follows the last line of the hunks of its file.
+        let footer = lines
+            .iter()
+            .position(|line| *line == "── src/a.rs: +2 −2 across 2 hunks ──")
+            .unwrap();
+        assert_eq!(lines[footer - 1], "g");
+        assert!(lines[footer + 1..]
+            .iter()
+            .any(|line| line.contains("b.txt")));
+        assert_eq!(lines.last(), Some(&"── b.txt: +0 −1 across 1 hunk ──"));
+
+        let result = DeltaTest::with_args(&[]).with_input(input);
+        assert!(!result.output.contains("across"));
+    }
 }
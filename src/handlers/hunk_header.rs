@@ -18,23 +18,30 @@
 // src/hunk_header.rs:119: fn write_to_output_buffer( │
 // ───────────────────────────────────────────────────┘
 // ```
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::fmt::Write as FmtWrite;
 
 use super::draw;
+use crate::ansi;
 use crate::config::{
-    Config, HunkHeaderIncludeCodeFragment, HunkHeaderIncludeFilePath, HunkHeaderIncludeLineNumber,
+    Config, HunkHeaderEmptyRange, HunkHeaderIncludeCodeFragment, HunkHeaderIncludeFilePath,
+    HunkHeaderIncludeLineNumber,
 };
 use crate::delta::{self, DiffType, InMergeConflict, MergeParents, State, StateMachine};
+use crate::features;
+use crate::handlers::hunk::FileFooter;
+use crate::minusplus::MinusPlus;
 use crate::paint::{self, BgShouldFill, Painter, StyleSectionSpecifier};
 use crate::style::{DecorationStyle, Style};
+use crate::utils;
 use lazy_static::lazy_static;
 use regex::Regex;
 
 #[derive(Clone, Default, Debug, PartialEq, Eq)]
 pub struct ParsedHunkHeader {
     code_fragment: String,
-    line_numbers_and_hunk_lengths: Vec<(usize, usize)>,
+    pub line_numbers_and_hunk_lengths: Vec<(usize, usize)>,
 }
 
 pub enum HunkHeaderIncludeHunkLabel {
@@ -107,6 +114,21 @@ impl StateMachine<'_> {
         !matches!(self.state, State::MergeConflict(_, _))
     }
 
+    /// Count the hunk headers of the input, including those of files which are not shown, as the
+    /// functions reading the input ahead for each hunk do. The line is left to the other handlers.
+    pub fn handle_hunk_count_line(&mut self) -> std::io::Result<bool> {
+        if self.line.starts_with("@@") && parse_hunk_header(&self.line).is_some() {
+            self.n_hunks += 1;
+        }
+        Ok(false)
+    }
+
+    /// Return the value read ahead for the current hunk, from values given for each hunk of the
+    /// input in order.
+    pub fn get_current_hunk_value<T: Copy>(&self, values: &[T]) -> Option<T> {
+        values.get(self.n_hunks.checked_sub(1)?).copied()
+    }
+
     pub fn handle_hunk_header_line(&mut self) -> std::io::Result<bool> {
         use DiffType::*;
         use State::*;
@@ -135,7 +157,21 @@ impl StateMachine<'_> {
                     self.minus_line_counter = AmbiguousDiffMinusCounter::count_from(minus_lines);
                 }
             }
+            let mut parsed_hunk_header = parsed_hunk_header;
+            if self.config.reverse && diff_type == Unified {
+                parsed_hunk_header.line_numbers_and_hunk_lengths.reverse();
+            }
 
+            if self.config.hunk_spacing > 0
+                && matches!(self.state, HunkMinus(..) | HunkZero(..) | HunkPlus(..))
+            {
+                // Separate the hunk from the preceding hunk of the file.
+                self.painter.paint_buffered_minus_and_plus_lines();
+                self.painter.emit()?;
+                for _ in 0..self.config.hunk_spacing {
+                    writeln!(self.painter.writer)?;
+                }
+            }
             self.state = HunkHeader(
                 diff_type,
                 parsed_hunk_header,
@@ -147,6 +183,21 @@ impl StateMachine<'_> {
         Ok(handled_line)
     }
 
+    /// Set the syntax highlighters for the current hunk, unless the hunks of its file are larger
+    /// than --syntax-highlight-max-file-bytes.
+    fn set_hunk_highlighter(&mut self) {
+        self.painter.set_highlighter();
+        let max_file_bytes = self.config.syntax_highlight_max_file_bytes;
+        if max_file_bytes > 0
+            && self
+                .get_current_hunk_value(&self.hunk_file_sizes)
+                .is_some_and(|file_size| file_size > max_file_bytes)
+        {
+            self.painter.highlighter = None;
+            self.painter.plus_highlighter = None;
+        }
+    }
+
     /// Emit the hunk header, with any requested decoration.
     pub fn emit_hunk_header_line(
         &mut self,
@@ -155,7 +206,15 @@ impl StateMachine<'_> {
         raw_line: &str,
     ) -> std::io::Result<bool> {
         self.painter.paint_buffered_minus_and_plus_lines();
-        self.painter.set_highlighter();
+        // The highlighters of the last hunk, which those of this hunk may continue from (used by
+        // --stateful-highlighting).
+        let last_highlighters = self.highlighting_context.is_some().then(|| {
+            (
+                self.painter.highlighter.take(),
+                self.painter.plus_highlighter.take(),
+            )
+        });
+        self.set_hunk_highlighter();
         self.painter.emit()?;
 
         let ParsedHunkHeader {
@@ -164,15 +223,104 @@ impl StateMachine<'_> {
         } = parsed_hunk_header;
 
         if self.config.line_numbers {
+            let min_width = self
+                .get_current_hunk_value(&self.hunk_line_number_widths)
+                .map_or(self.config.line_numbers_min_width, |width| {
+                    width.max(self.config.line_numbers_min_width)
+                });
             self.painter
                 .line_numbers_data
                 .as_mut()
                 .unwrap()
-                .initialize_hunk(line_numbers_and_hunk_lengths, self.plus_file.to_string());
+                .initialize_hunk(
+                    line_numbers_and_hunk_lengths,
+                    self.plus_file.to_string(),
+                    min_width,
+                );
+        }
+        if let (Some((minus_line_number, _)), Some((plus_line_number, _))) = (
+            line_numbers_and_hunk_lengths.first(),
+            line_numbers_and_hunk_lengths.last(),
+        ) {
+            self.painter.hunk_line_numbers = MinusPlus::new(*minus_line_number, *plus_line_number);
+        }
+        if let Some(age_annotator) = self.age_annotator.as_mut() {
+            let plus_line_number = line_numbers_and_hunk_lengths
+                .last()
+                .map_or(0, |(line_number, _)| *line_number);
+            age_annotator.start_hunk(
+                utils::path::absolute_path(&self.plus_file, self.config),
+                plus_line_number,
+            );
+        }
+
+        if let Some(blamer) = self.removed_line_blamer.as_mut() {
+            self.painter.removed_line_authors = match self.minus_file.as_str() {
+                "/dev/null" => None,
+                minus_file => blamer.authors(minus_file),
+            };
         }
 
+        if self.config.file_footer {
+            let path = if self.plus_file == "/dev/null" {
+                &self.minus_file
+            } else {
+                &self.plus_file
+            };
+            self.file_footer
+                .get_or_insert_with(|| FileFooter::new(path))
+                .n_hunks += 1;
+        }
+        if self.trailing_context.is_some() {
+            // In a unified diff, the hunk is followed by the line after its last plus line.
+            self.pending_trailing_context = match line_numbers_and_hunk_lengths[..] {
+                [_, (plus_line_number, plus_length)] if self.plus_file != "/dev/null" => {
+                    utils::path::absolute_path(&self.plus_file, self.config)
+                        .map(|path| (path, plus_line_number + plus_length.max(1)))
+                }
+                _ => None,
+            };
+        }
+
+        // Git omits the function context if it does not know the diff driver of the file.
+        let found_code_fragment = match &self.blob_ids {
+            Some((_, plus_blob_id))
+                if self.config.compute_function_context
+                    && code_fragment.trim().is_empty()
+                    && !self.config.color_only =>
+            {
+                let plus_line_number = line_numbers_and_hunk_lengths
+                    .last()
+                    .map_or(0, |(line_number, _)| *line_number);
+                self.function_context_finder
+                    .find(
+                        &self.plus_file,
+                        plus_blob_id,
+                        &self.painter.syntax.name,
+                        plus_line_number,
+                        self.config,
+                    )
+                    .map(|function_context| format!(" {function_context}"))
+            }
+            _ => None,
+        };
+        let code_fragment = found_code_fragment.as_ref().unwrap_or(code_fragment);
+
         if self.config.hunk_header_style.is_raw {
-            write_hunk_header_raw(&mut self.painter, line, raw_line, self.config)?;
+            let raw_line = match line_numbers_and_hunk_lengths.last() {
+                Some((plus_line_number, _))
+                    if self.config.hyperlinks && self.config.hyperlinks_hunk_header_range =>
+                {
+                    format_plus_range_hyperlink(
+                        raw_line,
+                        &self.plus_file,
+                        *plus_line_number,
+                        self.config,
+                    )
+                }
+                _ => Cow::from(raw_line),
+            };
+            write_hunk_header_raw(&mut self.painter, line, &raw_line, self.config)?;
         } else if self.config.hunk_header_style.is_omitted {
             writeln!(self.painter.writer)?;
         } else {
@@ -182,6 +330,7 @@ impl StateMachine<'_> {
                 writeln!(self.painter.writer)?;
             }
 
+            let hunk_position = self.get_current_hunk_value(&self.hunk_positions);
             write_line_of_code_with_optional_path_and_line_number(
                 code_fragment,
                 line_numbers_and_hunk_lengths,
@@ -197,20 +346,58 @@ impl StateMachine<'_> {
                 &self.config.hunk_header_file_style,
                 &self.config.hunk_header_line_number_style,
                 &self.config.hunk_header_style_include_file_path,
-                &self.config.hunk_header_style_include_line_number,
+                if self.config.hunk_header_empty_range == HunkHeaderEmptyRange::Hide
+                    && matches!(line_numbers_and_hunk_lengths.last(), Some((_, 0)))
+                {
+                    &HunkHeaderIncludeLineNumber::No
+                } else {
+                    &self.config.hunk_header_style_include_line_number
+                },
                 &HunkHeaderIncludeHunkLabel::Yes,
+                hunk_position,
                 &self.config.hunk_header_style_include_code_fragment,
                 ":",
                 self.config,
             )?;
         };
-        self.painter.set_highlighter();
+        self.set_hunk_highlighter();
+        if let Some(context) = self.highlighting_context.as_mut() {
+            // Both sides are given the lines preceding the hunk in the new file.
+            let (plus_line_number, plus_length) = line_numbers_and_hunk_lengths
+                .last()
+                .copied()
+                .unwrap_or_default();
+            let file = match self.plus_file.as_str() {
+                "/dev/null" => None,
+                plus_file => utils::path::absolute_path(plus_file, self.config),
+            };
+            let (continues, lines) =
+                context.lines_before_hunk(file.as_deref(), plus_line_number, plus_length);
+            if let (true, Some((Some(highlighter), Some(plus_highlighter))), Some(_)) =
+                (continues, last_highlighters, &self.painter.highlighter)
+            {
+                self.painter.highlighter = Some(highlighter);
+                self.painter.plus_highlighter = Some(plus_highlighter);
+            }
+            self.painter.prime_highlighters(lines);
+        }
         Ok(true)
     }
 }
 
 lazy_static! {
-    static ref HUNK_HEADER_REGEX: Regex = Regex::new(r"@+ ([^@]+)@+(.*\s?)").unwrap();
+    // The file coordinates are matched exactly, so that any text following them, whether a code
+    // fragment or something added by another tool, is taken as the code fragment, even if it
+    // contains '@', '-' or '+' characters.
+    static ref HUNK_HEADER_REGEX: Regex = Regex::new(
+        r"^@{2,} +([-+]\d+(?:,\d+)?(?: +[-+]\d+(?:,\d+)?)*) *@{2,}(.*\s?)"
+    )
+    .unwrap();
+}
+
+lazy_static! {
+    // The first range of the new file in a hunk header, which precedes the code fragment.
+    static ref HUNK_HEADER_PLUS_RANGE_REGEX: Regex = Regex::new(r"\+\d+(?:,\d+)?").unwrap();
 }
 
 // Parse unified diff hunk header format. See
@@ -232,7 +419,7 @@ lazy_static! {
 /// Given input like
 /// "@@ -74,15 +74,14 @@ pub fn delta("
 /// Return " pub fn delta(" and a vector of (line_number, hunk_length) tuples.
-fn parse_hunk_header(line: &str) -> Option<ParsedHunkHeader> {
+pub fn parse_hunk_header(line: &str) -> Option<ParsedHunkHeader> {
     if let Some(caps) = HUNK_HEADER_REGEX.captures(line) {
         let file_coordinates = &caps[1];
         let line_numbers_and_hunk_lengths: Vec<(usize, usize)> = HUNK_HEADER_FILE_COORDINATE_REGEX
@@ -263,6 +450,95 @@ fn parse_hunk_header(line: &str) -> Option<ParsedHunkHeader> {
     }
 }
 
+/// Return the position of each hunk of the input among the hunks of its file, and the number of
+/// hunks of the file, in the order of the hunks (used by --hunk-header-show-position).
+pub fn get_hunk_positions(input: &[u8]) -> Vec<(usize, usize)> {
+    let input = String::from_utf8_lossy(input);
+    let lines: Vec<String> = input.lines().map(ansi::strip_ansi_codes).collect();
+    let mut positions: Vec<(usize, usize)> = Vec::new();
+    // The index of the first hunk of the current file.
+    let mut file_start = 0;
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("diff ")
+            || line.starts_with("--- ")
+                && lines
+                    .get(i + 1)
+                    .is_some_and(|next| next.starts_with("+++ "))
+        {
+            file_start = positions.len();
+        } else if line.starts_with("@@") && parse_hunk_header(line).is_some() {
+            positions.push((positions.len() - file_start + 1, 0));
+            let n_hunks = positions.len() - file_start;
+            for position in &mut positions[file_start..] {
+                position.1 = n_hunks;
+            }
+        }
+    }
+    positions
+}
+
+/// Return the number of bytes of the hunks of the file of each hunk of the input, in the order of
+/// the hunks (used by --syntax-highlight-max-file-bytes).
+pub fn get_hunk_file_sizes(input: &[u8]) -> Vec<usize> {
+    let input = String::from_utf8_lossy(input);
+    let lines: Vec<String> = input.lines().map(ansi::strip_ansi_codes).collect();
+    let mut sizes: Vec<usize> = Vec::new();
+    // The index of the first hunk of the current file.
+    let mut file_start = 0;
+    let mut in_hunk = false;
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("diff ")
+            || line.starts_with("--- ")
+                && lines
+                    .get(i + 1)
+                    .is_some_and(|next| next.starts_with("+++ "))
+        {
+            file_start = sizes.len();
+            in_hunk = false;
+        } else if line.starts_with("@@") && parse_hunk_header(line).is_some() {
+            sizes.push(sizes.get(file_start).copied().unwrap_or(0));
+            in_hunk = true;
+        } else if in_hunk && line.starts_with([' ', '-', '+', '\\']) {
+            let file_size = sizes[file_start] + line.len() + 1;
+            sizes[file_start..].fill(file_size);
+        } else {
+            in_hunk = false;
+        }
+    }
+    sizes
+}
+
+/// Make the range of the new file in `raw_line`, e.g. `+12,8`, a hyperlink to line
+/// `plus_line_number` of `plus_file` (used with --hyperlinks-hunk-header-range).
+fn format_plus_range_hyperlink<'a>(
+    raw_line: &'a str,
+    plus_file: &str,
+    plus_line_number: usize,
+    config: &Config,
+) -> Cow<'a, str> {
+    let absolute_path = match plus_file {
+        "/dev/null" => None,
+        plus_file => utils::path::absolute_path(plus_file, config),
+    };
+    match (absolute_path, HUNK_HEADER_PLUS_RANGE_REGEX.find(raw_line)) {
+        (Some(absolute_path), Some(range)) => {
+            let hyperlink = features::hyperlinks::format_osc8_file_hyperlink(
+                absolute_path,
+                Some(plus_line_number),
+                range.as_str(),
+                config,
+            );
+            Cow::from(format!(
+                "{}{}{}",
+                &raw_line[..range.start()],
+                hyperlink,
+                &raw_line[range.end()..]
+            ))
+        }
+        _ => Cow::from(raw_line),
+    }
+}
+
 fn write_hunk_header_raw(
     painter: &mut Painter,
     line: &str,
@@ -270,7 +546,7 @@ fn write_hunk_header_raw(
     config: &Config,
 ) -> std::io::Result<()> {
     let (mut draw_fn, pad, decoration_ansi_term_style) =
-        draw::get_draw_function(config.hunk_header_style.decoration_style);
+        draw::get_draw_function(config.hunk_header_style.decoration_style, config);
     if config.hunk_header_style.decoration_style != DecorationStyle::NoDecoration {
         writeln!(painter.writer)?;
     }
@@ -300,11 +576,13 @@ pub fn write_line_of_code_with_optional_path_and_line_number(
     include_file_path: &HunkHeaderIncludeFilePath,
     include_line_number: &HunkHeaderIncludeLineNumber,
     include_hunk_label: &HunkHeaderIncludeHunkLabel,
+    hunk_position: Option<(usize, usize)>,
     include_code_fragment: &HunkHeaderIncludeCodeFragment,
     file_path_separator: &str,
     config: &Config,
 ) -> std::io::Result<()> {
-    let (mut draw_fn, _, decoration_ansi_term_style) = draw::get_draw_function(decoration_style);
+    let (mut draw_fn, _, decoration_ansi_term_style) =
+        draw::get_draw_function(decoration_style, config);
     let line = if config.color_only {
         line.to_string()
     } else if matches!(include_code_fragment, HunkHeaderIncludeCodeFragment::Yes)
@@ -334,6 +612,7 @@ pub fn write_line_of_code_with_optional_path_and_line_number(
             line,
             style_sections,
             include_hunk_label,
+            hunk_position,
             painter,
             config,
         );
@@ -389,12 +668,14 @@ fn paint_file_path_with_line_number(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
 fn write_to_output_buffer(
     file_with_line_number: &str,
     file_path_separator: &str,
     line: String,
     style_sections: Option<StyleSectionSpecifier>,
     include_hunk_label: &HunkHeaderIncludeHunkLabel,
+    hunk_position: Option<(usize, usize)>,
     painter: &mut Painter,
     config: &Config,
 ) {
@@ -407,6 +688,15 @@ fn write_to_output_buffer(
             config.hunk_header_file_style.paint(&config.hunk_label)
         );
     }
+    if let Some((i, n_hunks)) = hunk_position {
+        let _ = write!(
+            &mut painter.output_buffer,
+            "{} ",
+            config
+                .hunk_header_file_style
+                .paint(format!("[hunk {i}/{n_hunks}]"))
+        );
+    }
     if !file_with_line_number.is_empty() {
         // The code fragment in "line" adds whitespace, but if only a line number is printed
         // then the trailing space must be added.
@@ -435,8 +725,10 @@ fn write_to_output_buffer(
 #[cfg(test)]
 pub mod tests {
     use super::*;
+    use crate::ansi;
     use crate::ansi::strip_ansi_codes;
-    use crate::tests::integration_test_utils;
+    use crate::tests::integration_test_utils::{self, StubFiles};
+    use crate::utils::highlighting_context::HighlightingContext;
 
     #[test]
     fn test_parse_hunk_header() {
@@ -513,6 +805,51 @@ pub mod tests {
         assert_eq!(line_numbers_and_hunk_lengths[65], (446, 6),);
     }
 
+    #[test]
+    fn test_parse_hunk_header_with_unusual_trailing_text() {
+        for (line, expected_code_fragment) in [
+            (
+                "@@ -1,2 +1,3 @@ user@example.com @@ x\n",
+                " user@example.com @@ x\n",
+            ),
+            ("@@ -1,2 +1,3 @@ [tool -5 +6]", " [tool -5 +6]"),
+            ("@@ -1,2 +1,3 @@@@ extra", " extra"),
+            ("@@ -1,2 +1,3 @@\tfn f()", "\tfn f()"),
+            ("@@ -1,2 +1,3 @@fn f()", "fn f()"),
+            ("@@ -1,2 +1,3@@ fn f()", " fn f()"),
+        ] {
+            let ParsedHunkHeader {
+                code_fragment,
+                line_numbers_and_hunk_lengths,
+            } = parse_hunk_header(line).unwrap();
+            assert_eq!(code_fragment, expected_code_fragment, "{}", line);
+            assert_eq!(line_numbers_and_hunk_lengths, [(1, 2), (1, 3)], "{}", line);
+        }
+    }
+
+    #[test]
+    fn test_parse_hunk_header_with_invalid_coordinates() {
+        assert_eq!(parse_hunk_header("@@ -1,2 +1,3 extra @@ fn f()"), None);
+        assert_eq!(parse_hunk_header("@@ @@ -1,2 +1,3 @@"), None);
+        assert_eq!(parse_hunk_header("@ -1,2 +1,3 @"), None);
+    }
+
+    #[test]
+    fn test_hunk_header_with_unusual_trailing_text() {
+        integration_test_utils::DeltaTest::with_args(&["--hunk-header-style=bold"])
+            .explain_ansi()
+            .with_input(
+                "\
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@ [reviewed by a@b.c -1 +2]
+-a
++b
+",
+            )
+            .expect_contains("\n(bold normal) [reviewed by a@b.c -1 +2] (blue)│(normal)\n");
+    }
+
     #[test]
     fn test_paint_file_path_with_line_number_default() {
         // hunk-header-style (by default) includes 'line-number' but not 'file'.
@@ -662,6 +999,103 @@ pub mod tests {
         assert!(output.contains("@@@2021-12-05"));
     }
 
+    #[test]
+    fn test_hunk_header_empty_range() {
+        let args = [
+            "--hunk-header-style",
+            "file line-number",
+            "--hunk-header-decoration-style=none",
+        ];
+        let hunk_header = |input: &str, extra_args: &[&str]| {
+            let config =
+                integration_test_utils::make_config_from_args(&[&args[..], extra_args].concat());
+            let output = strip_ansi_codes(&integration_test_utils::run_delta(input, &config));
+            output
+                .lines()
+                .find(|line| line.starts_with("a.txt:"))
+                .unwrap()
+                .trim_end()
+                .to_string()
+        };
+        // The new file range of a pure addition is not empty.
+        assert_eq!(hunk_header(PURE_ADD_HUNK, &[]), "a.txt:1:");
+        assert_eq!(
+            hunk_header(PURE_ADD_HUNK, &["--hunk-header-empty-range=hide"]),
+            "a.txt:1:"
+        );
+        assert_eq!(hunk_header(PURE_DELETE_HUNK, &[]), "a.txt:0:");
+        assert_eq!(
+            hunk_header(PURE_DELETE_HUNK, &["--hunk-header-empty-range=show"]),
+            "a.txt:0:"
+        );
+        assert_eq!(
+            hunk_header(PURE_DELETE_HUNK, &["--hunk-header-empty-range=hide"]),
+            "a.txt:"
+        );
+    }
+
+    #[test]
+    fn test_hyperlinks_hunk_header_range() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -10,2 +12,3 @@ fn f(x: i32) -> i32 { x +1 }
+ a
++b
+ c
+";
+        let hunk_header = |extra_args: &[&str]| {
+            let args = [
+                &[
+                    "--hyperlinks",
+                    "--hyperlinks-file-link-format={path}:{line}",
+                    "--hunk-header-style=raw",
+                    "--hunk-header-decoration-style=none",
+                ][..],
+                extra_args,
+            ]
+            .concat();
+            let config = integration_test_utils::make_config_from_args(&args);
+            integration_test_utils::run_delta(input, &config)
+                .lines()
+                .find(|line| line.starts_with("@@"))
+                .unwrap()
+                .to_string()
+        };
+        let path = utils::path::fake_delta_cwd_for_tests().join("a.txt");
+        assert_eq!(
+            hunk_header(&["--hyperlinks-hunk-header-range"]),
+            format!(
+                "@@ -10,2 \x1b]8;;{}:12\x1b\\+12,3\x1b]8;;\x1b\\ @@ fn f(x: i32) -> i32 {{ x +1 }}",
+                path.display()
+            )
+        );
+        assert_eq!(
+            hunk_header(&[]),
+            "@@ -10,2 +12,3 @@ fn f(x: i32) -> i32 { x +1 }"
+        );
+    }
+
+    const PURE_ADD_HUNK: &str = "\
+diff --git a/a.txt b/a.txt
+index e69de29..7898192 100644
+--- a/a.txt
++++ b/a.txt
+@@ -0,0 +1 @@
++a
+";
+
+    const PURE_DELETE_HUNK: &str = "\
+diff --git a/a.txt b/a.txt
+index 7898192..e69de29 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +0,0 @@
+-a
+";
+
     const GIT_LOG_OUTPUT_WITH_NOT_A_HUNK_HEADER: &str = "\
 @@@2021-12-05
 
@@ -674,4 +1108,225 @@ src/handlers/submodule.rs      |   4 ++--
 src/paint.rs                   |   2 +-
 7 files changed, 90 insertions(+), 54 deletions(-)
 ";
+
+    fn run_stateful(args: &[&str], input: &str) -> Vec<String> {
+        let config = integration_test_utils::make_config_from_args(args);
+        let output = integration_test_utils::run_delta_with_machine(input, &config, |machine| {
+            if config.stateful_highlighting {
+                machine.highlighting_context =
+                    Some(HighlightingContext::new(Box::new(StubFiles {
+                        path: "a.py",
+                        lines: &["x = \"\"\"", "one", "two", "\"\"\""],
+                    })));
+            }
+        });
+        ansi::explain_ansi(&output, false)
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_stateful_highlighting_primes_highlighters() {
+        let input = "\
+diff --git a/a.py b/a.py
+index 1111111..2222222 100644
+--- a/a.py
++++ b/a.py
+@@ -2,2 +2,2 @@
+ one
+-two
++three
+";
+        // The hunk starts inside a string, which is only known from the lines preceding it.
+        let lines = run_stateful(&[], input);
+        assert!(lines.contains(&"(231)one(normal)".to_string()));
+        assert!(lines.contains(&"(231 22)three(normal)".to_string()));
+        let lines = run_stateful(&["--stateful-highlighting"], input);
+        assert!(lines.contains(&"(186)one(normal)".to_string()));
+        assert!(lines.contains(&"(186 22)three(normal)".to_string()));
+    }
+
+    #[test]
+    fn test_stateful_highlighting_continues_across_hunks() {
+        let input = "\
+diff --git a/a.py b/a.py
+index 1111111..2222222 100644
+--- a/a.py
++++ b/a.py
+@@ -2,1 +2,1 @@
+-1
++one
+@@ -3,1 +3,1 @@
+-2
++two
+";
+        // The highlighters of the second hunk carry on from the first, still inside the string.
+        let lines = run_stateful(&["--stateful-highlighting"], input);
+        assert!(lines.contains(&"(186 22)one(normal)".to_string()));
+        assert!(lines.contains(&"(186 22)two(normal)".to_string()));
+    }
+
+    #[test]
+    fn test_stateful_highlighting_separates_minus_and_plus() {
+        let input = "\
+diff --git a/b.py b/b.py
+index 1111111..2222222 100644
+--- a/b.py
++++ b/b.py
+@@ -1,2 +1,2 @@
+-x = \"\"\"
++x = 1
+ y
+";
+        // The string opened by the removed line does not continue into the added line.
+        let lines = run_stateful(&["--minus-style=syntax 52"], input);
+        assert!(lines.contains(&"(186 22)x = (normal 28)1(normal)".to_string()));
+        assert!(lines.contains(&"(186)y(normal)".to_string()));
+        let lines = run_stateful(
+            &["--minus-style=syntax 52", "--stateful-highlighting"],
+            input,
+        );
+        assert!(lines.contains(&"(231 22)x (203)=(231) (141 28)1(normal)".to_string()));
+        assert!(lines.contains(&"(231)y(normal)".to_string()));
+    }
+
+    #[test]
+    fn test_hunk_header_with_tab_in_code_fragment() {
+        let input = "\
+diff --git a/a.rs b/a.rs
+index 223ca50..367a6f6 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@ fn\tfoo() {
+ a
+-b
++c
+";
+        for (args, expected) in [
+            (&["--tabs=4"][..], "1: fn    foo() { │"),
+            (&["--tabs=0"], "1: fn foo() { │"),
+            (
+                &["--tabs=4", "--hunk-header-style=raw"],
+                "@@ -1,2 +1,2 @@ fn    foo() { │",
+            ),
+        ] {
+            let output = integration_test_utils::DeltaTest::with_args(
+                &[
+                    &["--hunk-header-decoration-style=box", "--file-style=omit"],
+                    args,
+                ]
+                .concat(),
+            )
+            .with_input(input)
+            .output;
+            let lines: Vec<&str> = output.lines().skip(1).take(3).collect();
+            // The box is as wide as the text it surrounds.
+            assert_eq!(lines[1], expected);
+            assert_eq!(
+                lines[0].chars().count(),
+                lines[1].chars().count(),
+                "{output}"
+            );
+            assert_eq!(lines[2].chars().count(), lines[1].chars().count());
+        }
+    }
+
+    const TWO_FILES_DIFF: &str = "\
+diff --git a/a.rs b/a.rs
+index 1111111..2222222 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1 +1 @@
+-a
++A
+@@ -10 +10 @@ fn b() {
+-b
++B
+@@ -20 +20 @@ fn c() {
+-c
++C
+diff --git a/d.rs b/d.rs
+index 3333333..4444444 100644
+--- a/d.rs
++++ b/d.rs
+@@ -1 +1 @@
+-d
++D
+";
+
+    #[test]
+    fn test_get_hunk_positions() {
+        assert_eq!(
+            get_hunk_positions(TWO_FILES_DIFF.as_bytes()),
+            [(1, 3), (2, 3), (3, 3), (1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_get_hunk_file_sizes() {
+        // The hunks of a.rs have 3 lines of 3 bytes each, with their newlines.
+        assert_eq!(
+            get_hunk_file_sizes(TWO_FILES_DIFF.as_bytes()),
+            [18, 18, 18, 6]
+        );
+        let input = "--- a.rs\n+++ a.rs\n@@ -1 +1 @@\n-a\n+bb\n\\ No newline at end of file\n";
+        assert_eq!(get_hunk_file_sizes(input.as_bytes()), [35]);
+    }
+
+    #[test]
+    fn test_hunk_header_show_position() {
+        let output = integration_test_utils::DeltaTest::with_args(&[
+            "--hunk-header-show-position",
+            "--hunk-header-decoration-style=none",
+        ])
+        .with_input(TWO_FILES_DIFF)
+        .output;
+        let hunk_headers: Vec<&str> = output
+            .lines()
+            .filter(|line| line.starts_with("[hunk"))
+            .collect();
+        assert_eq!(
+            hunk_headers,
+            [
+                "[hunk 1/3] 1: ",
+                "[hunk 2/3] 10: fn b() { ",
+                "[hunk 3/3] 20: fn c() { ",
+                "[hunk 1/1] 1: "
+            ]
+        );
+
+        let output = integration_test_utils::DeltaTest::with_args(&[])
+            .with_input(TWO_FILES_DIFF)
+            .output;
+        assert!(!output.contains("[hunk"));
+    }
+
+    #[test]
+    fn test_hunk_spacing() {
+        let args = ["--hunk-header-decoration-style=none", "--line-numbers"];
+        let output = integration_test_utils::DeltaTest::with_args(&args)
+            .with_input(TWO_FILES_DIFF)
+            .output;
+        let output_with_spacing = integration_test_utils::DeltaTest::with_args(
+            &[&args[..], &["--hunk-spacing=2"]].concat(),
+        )
+        .with_input(TWO_FILES_DIFF)
+        .output;
+        // Two blank lines are written before the second and third hunks of a.rs only; the line
+        // numbers are unchanged.
+        let mut expected: Vec<&str> = Vec::new();
+        for line in output.lines() {
+            if line.starts_with("10: ") || line.starts_with("20: ") {
+                let blank_line = expected.pop().unwrap();
+                expected.extend(["", "", blank_line]);
+            }
+            expected.push(line);
+        }
+        assert_eq!(output_with_spacing.lines().collect::<Vec<_>>(), expected);
+        assert_eq!(
+            output_with_spacing.lines().count(),
+            output.lines().count() + 4
+        );
+    }
 }
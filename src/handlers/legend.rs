@@ -0,0 +1,91 @@
+use crate::delta::StateMachine;
+use crate::style::Style;
+
+// The character painted in the color of each kind of line.
+const SWATCH: &str = "■";
+
+impl StateMachine<'_> {
+    /// Write a line showing the colors of added, removed and unchanged lines before the first line
+    /// of output, if it has not been written yet (used by --show-legend).
+    pub fn emit_pending_legend(&mut self) -> std::io::Result<()> {
+        if !self.pending_legend {
+            return Ok(());
+        }
+        self.pending_legend = false;
+        let legend = [
+            (self.config.plus_style, "added"),
+            (self.config.minus_style, "removed"),
+            (self.config.zero_style, "context"),
+        ]
+        .iter()
+        .map(|(style, label)| format!("{} {label}", swatch_style(*style).paint(SWATCH)))
+        .collect::<Vec<_>>()
+        .join("  ");
+        writeln!(self.painter.writer, "{legend}")?;
+        writeln!(self.painter.writer)?;
+        Ok(())
+    }
+}
+
+// The swatch of a style is painted in its background color, or in its foreground color if it
+// has no background color.
+fn swatch_style(style: Style) -> Style {
+    Style::from_colors(
+        style
+            .get_background_color()
+            .or(style.ansi_term_style.foreground),
+        None,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    const DIFF: &str = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ a
+-b
++c
+";
+
+    #[test]
+    fn test_show_legend() {
+        DeltaTest::with_args(&[
+            "--show-legend",
+            "--plus-style=bold green",
+            "--minus-style=normal 52",
+            "--zero-style=blue",
+        ])
+        .explain_ansi()
+        .with_input(DIFF)
+        .expect_after_skip(
+            0,
+            "#partial
+(green)■(normal) added  (52)■(normal) removed  (blue)■(normal) context
+",
+        );
+    }
+
+    #[test]
+    fn test_show_legend_is_written_once_before_output() {
+        let output = DeltaTest::with_args(&["--show-legend", "--toc"])
+            .with_input(DIFF)
+            .output;
+        assert!(output.starts_with("■ added  ■ removed  ■ context\n\n1. a.txt\n"));
+        assert_eq!(output.matches('■').count(), 3);
+
+        let output = DeltaTest::with_args(&["--show-legend"])
+            .with_input("")
+            .output;
+        assert!(!output.contains('■'));
+        let output = DeltaTest::with_args(&["--show-legend", "--color-only"])
+            .with_input(DIFF)
+            .output;
+        assert!(!output.contains('■'));
+    }
+}
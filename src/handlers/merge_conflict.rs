@@ -4,7 +4,6 @@ use itertools::Itertools;
 use unicode_segmentation::UnicodeSegmentation;
 
 use super::draw;
-use crate::cli;
 use crate::config::{self, delta_unreachable};
 use crate::delta::{DiffType, InMergeConflict, MergeParents, State, StateMachine};
 use crate::minusplus::MinusPlus;
@@ -153,6 +152,10 @@ impl StateMachine<'_> {
                 self.config,
             )?;
             self.painter.emit()?;
+            let options = paint::BlockPaintOptions {
+                reflow_prose: self.painter.reflows_prose(),
+                ..Default::default()
+            };
             paint::paint_minus_and_plus_lines(
                 MinusPlus::new(
                     &self.painter.merge_conflict_lines[Ancestral],
@@ -160,6 +163,8 @@ impl StateMachine<'_> {
                 ),
                 &mut self.painter.line_numbers_data,
                 &mut self.painter.highlighter,
+                None,
+                options,
                 &mut self.painter.output_buffer,
                 self.config,
             );
@@ -184,7 +189,7 @@ fn write_diff_header(
     config: &config::Config,
 ) -> std::io::Result<()> {
     let (mut draw_fn, pad, decoration_ansi_term_style) =
-        draw::get_draw_function(style.decoration_style);
+        draw::get_draw_function(style.decoration_style, config);
     let derived_commit_name = &painter.merge_conflict_commit_names[derived_commit_type];
     let text = if let Some(_ancestral_commit) = &painter.merge_conflict_commit_names[Ancestral] {
         format!(
@@ -213,10 +218,9 @@ fn write_merge_conflict_bar(
     painter: &mut paint::Painter,
     config: &config::Config,
 ) -> std::io::Result<()> {
-    let width = match config.decorations_width {
-        cli::Width::Fixed(width) => width,
-        cli::Width::Variable => config.available_terminal_width,
-    };
+    let width = config
+        .decorations_width
+        .or_available(config.available_terminal_width);
     writeln!(
         painter.writer,
         "{}",
@@ -1,19 +1,26 @@
 /// This module contains functions handling input lines encountered during the
 /// main `StateMachine::consume()` loop.
+pub mod add_p;
+pub mod binary_patch;
 pub mod blame;
 pub mod commit_meta;
 pub mod diff_header;
 pub mod diff_header_diff;
+pub mod diff_header_index;
 pub mod diff_header_misc;
 pub mod diff_stat;
 pub mod draw;
+pub mod eol_change;
+pub mod file_category;
 pub mod git_show_file;
 pub mod grep;
 pub mod hunk;
 pub mod hunk_header;
+pub mod legend;
 pub mod merge_conflict;
 mod ripgrep_json;
 pub mod submodule;
+pub mod toc;
 
 use crate::delta::{State, StateMachine};
 
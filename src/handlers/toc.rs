@@ -0,0 +1,166 @@
+use std::borrow::Cow;
+
+use crate::ansi;
+use crate::delta::StateMachine;
+use crate::handlers::diff_header::{
+    get_repeated_file_path_from_diff_line, parse_diff_header_line, FileEvent,
+};
+use crate::{features, utils};
+
+/// Return the paths of the changed files of a diff, in the order they appear in. The path of a
+/// file is its new path, or its old path if it was removed.
+pub fn get_changed_files(input: &[u8]) -> Vec<String> {
+    let input = String::from_utf8_lossy(input);
+    let lines: Vec<String> = input.lines().map(ansi::strip_ansi_codes).collect();
+    let git_diff_name = lines.iter().any(|line| line.starts_with("diff --git "));
+    let mut files: Vec<String> = Vec::new();
+    // Whether the last file was started by a `diff` line, and not yet named by its header.
+    let mut in_diff_header = false;
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with("diff ") {
+            files.push(get_repeated_file_path_from_diff_line(line).unwrap_or_default());
+            in_diff_header = true;
+        } else if line.starts_with("--- ")
+            && lines
+                .get(i + 1)
+                .is_some_and(|next| next.starts_with("+++ "))
+        {
+            let (minus_file, _) = parse_diff_header_line(line, git_diff_name);
+            let (plus_file, _) = parse_diff_header_line(&lines[i + 1], git_diff_name);
+            let file = if plus_file == "/dev/null" {
+                minus_file
+            } else {
+                plus_file
+            };
+            match files.last_mut() {
+                Some(last) if in_diff_header => *last = file,
+                _ => files.push(file),
+            }
+            in_diff_header = false;
+        } else if in_diff_header && line.starts_with("rename to ") {
+            if let (file, FileEvent::Rename) = parse_diff_header_line(line, git_diff_name) {
+                if let Some(last) = files.last_mut() {
+                    *last = file;
+                }
+            }
+        } else if line.starts_with("@@") {
+            in_diff_header = false;
+        }
+    }
+    files.retain(|file| !file.is_empty());
+    files
+}
+
+impl StateMachine<'_> {
+    /// Write a numbered list of the changed files in the input, each an OSC 8 hyperlink to the
+    /// file.
+    pub fn emit_toc(&mut self, input: &[u8]) -> std::io::Result<()> {
+        let files = get_changed_files(input);
+        if files.is_empty() {
+            return Ok(());
+        }
+        let width = files.len().to_string().len();
+        for (i, file) in files.iter().enumerate() {
+            let text = self.config.file_style.paint(file).to_string();
+            let link = match utils::path::absolute_path(file, self.config) {
+                Some(absolute_path) => features::hyperlinks::format_osc8_file_hyperlink(
+                    absolute_path,
+                    None,
+                    &text,
+                    self.config,
+                ),
+                None => Cow::from(text.as_str()),
+            };
+            writeln!(self.painter.writer, "{:>width$}. {}", i + 1, link)?;
+        }
+        writeln!(self.painter.writer)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_changed_files;
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    const DIFF: &str = "\
+diff --git a/src/a.rs b/src/a.rs
+index 223ca50..367a6f6 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1 +1 @@
+-a
++b
+diff --git a/b.txt b/b.txt
+deleted file mode 100644
+index 223ca50..0000000
+--- a/b.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-b
+diff --git a/old name.txt b/new name.txt
+similarity index 100%
+rename from old name.txt
+rename to new name.txt
+";
+
+    #[test]
+    fn test_get_changed_files() {
+        assert_eq!(
+            get_changed_files(DIFF.as_bytes()),
+            vec!["src/a.rs", "b.txt", "new name.txt"]
+        );
+        let input = "\
+--- a.txt	2024-01-01 00:00:00
++++ b.txt	2024-01-01 00:00:00
+@@ -1 +1 @@
+-a
++b
+";
+        assert_eq!(get_changed_files(input.as_bytes()), vec!["b.txt"]);
+    }
+
+    #[test]
+    fn test_toc() {
+        let output = DeltaTest::with_args(&["--toc", "--file-style=raw"])
+            .with_input(DIFF)
+            .raw_output;
+        let lines: Vec<&str> = output.lines().collect();
+        for (line, (n, file)) in
+            lines
+                .iter()
+                .zip([(1, "src/a.rs"), (2, "b.txt"), (3, "new name.txt")])
+        {
+            assert!(line.starts_with(&format!("{n}. \x1b]8;;file://")));
+            assert!(line.ends_with(&format!("/{file}\x1b\\{file}\x1b]8;;\x1b\\")));
+        }
+        assert_eq!(lines[3], "");
+        // The diff follows the list.
+        let output = strip_ansi_codes(&output);
+        assert!(output.contains("\nsrc/a.rs\n"));
+        assert_eq!(output.matches("new name.txt").count(), 2);
+    }
+
+    #[test]
+    fn test_toc_past_buffer_limit() {
+        // The diff is written without the list of files once the limit is exceeded.
+        let output = DeltaTest::with_args(&["--toc", "--buffer-limit-bytes=100"])
+            .with_input(DIFF)
+            .raw_output;
+        assert_eq!(
+            output,
+            DeltaTest::with_args(&[]).with_input(DIFF).raw_output
+        );
+        let output = DeltaTest::with_args(&["--toc", "--buffer-limit-bytes=1000"])
+            .with_input(DIFF)
+            .raw_output;
+        assert!(output.starts_with("1. \x1b]8;;"));
+    }
+
+    #[test]
+    fn test_toc_is_not_written_by_default() {
+        let output = DeltaTest::with_args(&[]).with_input(DIFF).raw_output;
+        assert!(!output.contains("\x1b]8;;"));
+    }
+}
@@ -31,7 +31,7 @@ use std::process::{self, Command, Stdio};
 use bytelines::ByteLinesReader;
 
 use crate::cli::Call;
-use crate::config::delta_unreachable;
+use crate::config::{delta_unreachable, TwoFileBackend};
 use crate::delta::delta;
 use crate::subcommands::{SubCmdKind, SubCommand};
 use crate::utils::bat::assets::list_languages;
@@ -123,6 +123,14 @@ pub fn run_app(
         Some(subcommands::show_colors::show_colors())
     } else if opt.parse_ansi {
         Some(subcommands::parse_ansi::parse_ansi())
+    } else if let Some(style) = &opt.parse_style {
+        Some(subcommands::parse_style::parse_style(
+            style,
+            opt.computed.true_color,
+            opt.git_config(),
+        ))
+    } else if opt.strip_ansi {
+        Some(subcommands::strip_ansi::strip_ansi())
     } else {
         None
     };
@@ -137,6 +145,7 @@ pub fn run_app(
     };
 
     let _show_config = opt.show_config;
+    let _html_stylesheet = opt.html_stylesheet;
     let config = config::Config::from(opt);
 
     if _show_config {
@@ -144,6 +153,16 @@ pub fn run_app(
         let mut stdout = stdout.lock();
         subcommands::show_config::show_config(&config, &mut stdout)?;
         return Ok(0);
+    } else if _html_stylesheet {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+        subcommands::html_stylesheet::html_stylesheet(&config, &mut stdout)?;
+        return Ok(0);
+    }
+
+    if config.guard_nested && matches!(call, Call::Delta(_)) && utils::process::is_nested_delta() {
+        io::copy(&mut io::stdin().lock(), &mut io::stdout().lock())?;
+        return Ok(0);
     }
 
     // The following block structure is because of `writer` and related lifetimes:
@@ -161,7 +180,17 @@ pub fn run_app(
         output_type.handle().unwrap()
     };
 
+    // The diff computed within delta of the two files given by --two-file-backend=internal, or
+    // of the files formatted by --format-command, which git cannot diff.
+    let mut internal_diff = None;
     let subcmd = match call {
+        Call::DeltaDiff(_, minus, plus)
+            if config.two_file_backend == TwoFileBackend::Internal
+                || !config.format_commands.is_empty() =>
+        {
+            internal_diff = Some(subcommands::diff::diff_files(&minus, &plus, &config));
+            SubCommand::none()
+        }
         Call::DeltaDiff(_, minus, plus) => {
             match subcommands::diff::build_diff_cmd(&minus, &plus, &config) {
                 Err(code) => return Ok(code),
@@ -175,8 +204,25 @@ pub fn run_app(
 
     if subcmd.is_none() {
         // Default delta run: read input from stdin, write to stdout or pager (pager started already^).
+        // With --old-fd and --new-fd, or two files and --two-file-backend=internal, the input is
+        // the diff of the files instead.
 
-        if io::stdin().is_terminal() {
+        let diff = match config.diff_fds {
+            Some((old_fd, new_fd)) => Some(subcommands::diff::diff_fds(old_fd, new_fd, &config)),
+            None => internal_diff,
+        };
+        // Like `git diff --no-index`, two files which differ give an exit code of 1.
+        let is_two_file_diff = diff.is_some() && config.diff_fds.is_none();
+        let res = if let Some(diff) = diff {
+            let diff = match diff {
+                Ok(diff) => diff,
+                Err(err) => {
+                    eprintln!("Failed to read the files to diff: {err}");
+                    return Ok(config.error_exit_code);
+                }
+            };
+            delta(diff.as_bytes().byte_lines(), &mut writer, &config)
+        } else if io::stdin().is_terminal() {
             eprintln!(
                 "\
                     The main way to use delta is to configure it as the pager for git: \
@@ -184,21 +230,21 @@ pub fn run_app(
                     You can also use delta to diff two files: `delta file_A file_B`."
             );
             return Ok(config.error_exit_code);
-        }
-
-        let res = delta(io::stdin().lock().byte_lines(), &mut writer, &config);
+        } else {
+            delta(io::stdin().lock().byte_lines(), &mut writer, &config)
+        };
 
-        if let Err(error) = res {
-            match error.kind() {
-                ErrorKind::BrokenPipe => return Ok(0),
+        match res {
+            Ok(changes_found) if is_two_file_diff => Ok(i32::from(changes_found)),
+            Ok(changes_found) => Ok(config.success_exit_code(changes_found)),
+            Err(error) => match error.kind() {
+                ErrorKind::BrokenPipe => Ok(0),
                 _ => {
                     eprintln!("{error}");
-                    return Ok(config.error_exit_code);
+                    Ok(config.error_exit_code)
                 }
-            }
+            },
         }
-
-        Ok(0)
     } else {
         // First start a subcommand, and pipe input from it to delta(). Also handle
         // subcommand exit code and stderr (maybe truncate it, e.g. for git and diff logic).
@@ -234,13 +280,16 @@ pub fn run_app(
 
         let res = delta(cmd_stdout_buf.byte_lines(), &mut writer, &config);
 
-        if let Err(error) = res {
-            let _ = cmd.wait(); // for clippy::zombie_processes
-            match error.kind() {
-                ErrorKind::BrokenPipe => return Ok(0),
-                _ => {
-                    eprintln!("{error}");
-                    return Ok(config.error_exit_code);
+        let changes_found = match res {
+            Ok(changes_found) => changes_found,
+            Err(error) => {
+                let _ = cmd.wait(); // for clippy::zombie_processes
+                match error.kind() {
+                    ErrorKind::BrokenPipe => return Ok(0),
+                    _ => {
+                        eprintln!("{error}");
+                        return Ok(config.error_exit_code);
+                    }
                 }
             }
         };
@@ -298,7 +347,12 @@ pub fn run_app(
             );
         }
 
-        Ok(subcmd_status)
+        // A non-zero exit status of the subcommand is passed through unaltered.
+        if subcmd_status == 0 {
+            Ok(config.success_exit_code(changes_found))
+        } else {
+            Ok(subcmd_status)
+        }
     }
 
     // `output_type` drop impl runs here
@@ -125,15 +125,19 @@ pub trait GetOptionValue {
 
 impl GetOptionValue for Option<String> {}
 impl GetOptionValue for String {}
+impl GetOptionValue for Vec<String> {}
 impl GetOptionValue for bool {}
 impl GetOptionValue for f64 {}
+impl GetOptionValue for Option<f64> {}
 impl GetOptionValue for usize {}
+impl GetOptionValue for Option<usize> {}
+impl GetOptionValue for cli::SideBySide {}
 
 #[cfg(test)]
 pub mod tests {
     use std::fs::remove_file;
 
-    use crate::cli::Opt;
+    use crate::cli::{Opt, SideBySide};
     use crate::env::DeltaEnv;
     use crate::options::get::get_themes;
     use crate::tests::integration_test_utils;
@@ -225,8 +229,8 @@ pub mod tests {
             git_config_contents,
             git_config_path,
             "'delta.side-by-side=false'".into(),
-            &|opt: Opt| assert!(opt.side_by_side),
-            &|opt: Opt| assert!(!opt.side_by_side),
+            &|opt: Opt| assert_eq!(opt.side_by_side, SideBySide::True),
+            &|opt: Opt| assert_eq!(opt.side_by_side, SideBySide::False),
         );
     }
 
@@ -276,7 +280,7 @@ pub mod tests {
             Some(git_config_path),
         );
         assert_eq!(opt.features.unwrap(), "feature-from-gitconfig");
-        assert!(!opt.side_by_side);
+        assert_eq!(opt.side_by_side, SideBySide::False);
 
         let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
             DeltaEnv {
@@ -289,7 +293,7 @@ pub mod tests {
         );
         // `line-numbers` is a builtin feature induced by side-by-side
         assert_eq!(opt.features.unwrap(), "line-numbers side-by-side");
-        assert!(opt.side_by_side);
+        assert_eq!(opt.side_by_side, SideBySide::True);
 
         let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
             DeltaEnv {
@@ -304,7 +308,7 @@ pub mod tests {
             opt.features.unwrap(),
             "feature-from-gitconfig line-numbers side-by-side"
         );
-        assert!(opt.side_by_side);
+        assert_eq!(opt.side_by_side, SideBySide::True);
 
         remove_file(git_config_path).unwrap();
     }
@@ -1,12 +1,16 @@
+use crate::cli::SideBySide;
 use crate::config::delta_unreachable;
 
 /// A value associated with a Delta command-line option name.
 pub enum OptionValue {
     Boolean(bool),
     Float(f64),
+    OptionFloat(Option<f64>),
     OptionString(Option<String>),
     String(String),
+    StringList(Vec<String>),
     Int(usize),
+    OptionInt(Option<usize>),
 }
 
 /// An OptionValue, tagged according to its provenance/semantics.
@@ -45,6 +49,21 @@ impl From<OptionValue> for f64 {
     }
 }
 
+impl From<Option<f64>> for OptionValue {
+    fn from(value: Option<f64>) -> Self {
+        OptionValue::OptionFloat(value)
+    }
+}
+
+impl From<OptionValue> for Option<f64> {
+    fn from(value: OptionValue) -> Self {
+        match value {
+            OptionValue::OptionFloat(value) => value,
+            _ => delta_unreachable("Error converting OptionValue to Option<f64>."),
+        }
+    }
+}
+
 impl From<Option<String>> for OptionValue {
     fn from(value: Option<String>) -> Self {
         OptionValue::OptionString(value)
@@ -81,6 +100,21 @@ impl From<OptionValue> for String {
     }
 }
 
+impl From<Vec<String>> for OptionValue {
+    fn from(value: Vec<String>) -> Self {
+        OptionValue::StringList(value)
+    }
+}
+
+impl From<OptionValue> for Vec<String> {
+    fn from(value: OptionValue) -> Self {
+        match value {
+            OptionValue::StringList(value) => value,
+            _ => delta_unreachable("Error converting OptionValue to Vec<String>."),
+        }
+    }
+}
+
 impl From<usize> for OptionValue {
     fn from(value: usize) -> Self {
         OptionValue::Int(value)
@@ -95,3 +129,39 @@ impl From<OptionValue> for usize {
         }
     }
 }
+
+impl From<Option<usize>> for OptionValue {
+    fn from(value: Option<usize>) -> Self {
+        OptionValue::OptionInt(value)
+    }
+}
+
+impl From<OptionValue> for Option<usize> {
+    fn from(value: OptionValue) -> Self {
+        match value {
+            OptionValue::OptionInt(value) => value,
+            _ => delta_unreachable("Error converting OptionValue to Option<usize>."),
+        }
+    }
+}
+
+impl From<SideBySide> for OptionValue {
+    fn from(value: SideBySide) -> Self {
+        match value {
+            SideBySide::True => OptionValue::Boolean(true),
+            SideBySide::False => OptionValue::Boolean(false),
+            SideBySide::Auto => OptionValue::String("auto".to_string()),
+        }
+    }
+}
+
+impl From<OptionValue> for SideBySide {
+    fn from(value: OptionValue) -> Self {
+        match value {
+            OptionValue::Boolean(true) => SideBySide::True,
+            OptionValue::Boolean(false) => SideBySide::False,
+            OptionValue::String(value) if value == "auto" => SideBySide::Auto,
+            _ => delta_unreachable("Error converting OptionValue to SideBySide."),
+        }
+    }
+}
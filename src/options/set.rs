@@ -6,13 +6,17 @@ use std::str::FromStr;
 use bat::assets::HighlightingAssets;
 use console::Term;
 
-use crate::cli;
+use crate::cli::{self, SideBySide};
 use crate::config;
 use crate::env::DeltaEnv;
 use crate::errors::*;
 use crate::fatal;
 use crate::features;
+use crate::features::line_numbers::LineNumbersData;
+use crate::features::side_by_side::ansifill::UseFullPanelWidth;
+use crate::features::side_by_side::{Left, Right};
 use crate::git_config::GitConfig;
+use crate::minusplus::MinusPlus;
 use crate::options::option_value::{OptionValue, ProvenancedOptionValue};
 use crate::options::theme;
 use crate::utils::bat::output::PagingMode;
@@ -45,6 +49,8 @@ macro_rules! set_options {
                 "diff-so-fancy", // Does not exist as a flag on config
                 "detect-dark-light", // Does not exist as a flag on config
                 "features",  // Processed differently
+                "new-fd", // Only given on the command line
+                "old-fd", // Only given on the command line
                 // Set prior to the rest
                 "no-gitconfig",
                 "dark",
@@ -77,7 +83,8 @@ pub fn set_options(
             git_config.enabled = false;
         }
     }
-    opt.navigate = opt.navigate || opt.env.navigate.is_some();
+    // DELTA_NAVIGATE can enable navigate, but does not override --navigate.
+    opt.navigate = opt.navigate || opt.env.navigate.unwrap_or(false);
     if opt.syntax_theme.is_none() {
         opt.syntax_theme.clone_from(&opt.env.bat_theme);
     }
@@ -127,24 +134,58 @@ pub fn set_options(
 
     set_options!(
         [
+            add_p_mode,
+            align_edits,
+            annotate_age,
+            annotate_lines,
+            assume_encoding,
             blame_code_style,
+            blame_color_by,
             blame_format,
             blame_separator_format,
             blame_palette,
+            blame_removed,
             blame_separator_style,
             blame_timestamp_format,
             blame_timestamp_output_format,
+            buffer_limit_bytes,
+            change_heat,
+            change_symbol_style,
+            change_symbols,
+            check,
+            collapse_blank_additions,
+            collapse_eol_only,
+            collapse_identical_paths,
+            color_dither,
             color_only,
             config,
             commit_decoration_style,
+            commit_merge_style,
+            commit_meta_overflow,
+            commit_meta_width,
+            commit_ref_branch_style,
+            commit_ref_head_style,
+            commit_ref_remote_style,
+            commit_ref_tag_style,
             commit_regex,
             commit_style,
+            commit_subject_truncation,
+            compact_renames,
+            compute_function_context,
+            context,
+            debug_grid,
             default_language,
             diff_args,
             diff_stat_align_width,
+            digest,
+            emph_extend,
+            exit_code,
             file_added_label,
+            file_category_style,
             file_copied_label,
+            file_copied_style,
             file_decoration_style,
+            file_footer,
             file_modified_label,
             file_removed_label,
             file_renamed_label,
@@ -152,6 +193,13 @@ pub fn set_options(
             right_arrow,
             hunk_label,
             file_style,
+            final_newline,
+            final_reset,
+            focus_file,
+            focus_line,
+            focus_line_style,
+            force_text,
+            format_command,
             grep_context_line_style,
             grep_file_style,
             grep_header_decoration_style,
@@ -161,18 +209,41 @@ pub fn set_options(
             grep_match_line_style,
             grep_match_word_style,
             grep_separator_symbol,
+            grep_separator_tab_gap,
+            grep_separator_tabs,
+            grep_show_counts,
+            grep_strip_input_ansi,
+            group_by_category,
+            group_changes,
+            guard_nested,
+            high_contrast,
+            high_contrast_ratio,
             hunk_header_decoration_style,
+            hunk_header_empty_range,
             hunk_header_file_style,
             hunk_header_line_number_style,
+            hunk_header_show_position,
             hunk_header_style,
+            hunk_spacing,
             hyperlinks,
             hyperlinks_commit_link_format,
             hyperlinks_file_link_format,
+            hyperlinks_hunk_header_range,
+            ignore_files,
+            indent,
+            index_mode_change_style,
+            index_style,
+            inline_highlight_budget,
             inline_hint_style,
+            inline_only,
+            inline_single_word,
+            insertion_point_style,
             inspect_raw_lines,
+            issue_link_format,
             keep_plus_minus_markers,
             line_buffer_size,
             map_styles,
+            max_files,
             max_line_distance,
             max_line_length,
             max_syntax_length,
@@ -184,44 +255,86 @@ pub fn set_options(
             merge_conflict_ours_diff_header_style,
             merge_conflict_theirs_diff_header_decoration_style,
             merge_conflict_theirs_diff_header_style,
+            min_line_similarity,
+            minimap,
             minus_style,
             minus_emph_style,
             minus_empty_line_marker_style,
             minus_non_emph_style,
             minus_non_emph_style,
             navigate,
+            navigate_prompt_format,
             navigate_regex,
+            notes_style,
+            null_input,
             line_fill_method,
             line_numbers,
             line_numbers_left_format,
             line_numbers_left_style,
+            line_numbers_min_width,
             line_numbers_minus_style,
             line_numbers_plus_style,
             line_numbers_right_format,
             line_numbers_right_style,
+            line_numbers_zero_pad,
             line_numbers_zero_style,
+            line_prefixes,
+            literal_content_escapes,
+            log_show_files,
+            output,
             pager,
+            pager_quit_if_one_screen,
+            pager_without_color,
             paging_mode,
+            pairing_heuristic,
             parse_ansi,
+            parse_style,
+            path_compress_dirs,
             // Hack: plus-style must come before plus-*emph-style because the latter default
             // dynamically to the value of the former.
             plus_style,
             plus_emph_style,
             plus_empty_line_marker_style,
             plus_non_emph_style,
+            preserve_commit_ansi,
+            query_terminal_background,
             raw,
+            reflow_prose,
             relative_paths,
+            reverse,
+            semantic_highlight,
             show_colors,
+            show_index,
+            show_insertion_point,
+            show_legend,
+            show_moves_paired,
+            show_trailing_context,
             show_themes,
             side_by_side,
+            side_by_side_gap,
+            side_by_side_gap_style,
+            side_by_side_min_width,
+            side_by_side_shared_gutter,
+            sort_files,
+            stat_group_by_dir,
+            stateful_highlighting,
+            status_map,
+            strip_ansi,
+            syntax_highlight_context,
+            syntax_highlight_max_file_bytes,
             wrap_max_lines,
             wrap_right_prefix_symbol,
             wrap_right_percent,
             wrap_right_symbol,
             wrap_left_symbol,
+            wrap_symbol_minus_style,
+            wrap_symbol_plus_style,
             tab_width,
+            toc,
             tokenization_regex,
             true_color,
+            two_file_backend,
+            validate_line_numbers,
             whitespace_error_style,
             width,
             zero_style
@@ -236,6 +349,15 @@ pub fn set_options(
 
     // Setting ComputedValues
     set_widths_and_isatty(opt);
+    if opt.side_by_side == SideBySide::Auto {
+        opt.side_by_side = if output_width(opt) < opt.side_by_side_min_width {
+            SideBySide::False
+        } else {
+            SideBySide::True
+        };
+    }
+    set_width_of_change_symbols(opt);
+    set_line_numbers_and_decorations_for_narrow_output(opt);
     set_true_color(opt);
     theme::set__color_mode__syntax_theme__syntax_set(opt, assets);
     opt.computed.inspect_raw_lines =
@@ -246,7 +368,7 @@ pub fn set_options(
     // **-decoration-style cannot be used there (does not emit lines in 1-1 correspondence with raw git output).
     // See #274.
     if opt.color_only {
-        opt.side_by_side = false;
+        opt.side_by_side = SideBySide::False;
         opt.file_decoration_style = "none".to_string();
         opt.commit_decoration_style = "none".to_string();
         opt.hunk_header_decoration_style = "none".to_string();
@@ -385,7 +507,7 @@ fn gather_features(
     if opt.diff_so_fancy {
         gather_builtin_features_recursively("diff-so-fancy", &mut features, builtin_features, opt);
     }
-    if opt.hyperlinks {
+    if opt.hyperlinks != "false" {
         gather_builtin_features_recursively("hyperlinks", &mut features, builtin_features, opt);
     }
     if opt.line_numbers {
@@ -394,7 +516,7 @@ fn gather_features(
     if opt.navigate {
         gather_builtin_features_recursively("navigate", &mut features, builtin_features, opt);
     }
-    if opt.side_by_side {
+    if opt.side_by_side != SideBySide::False {
         gather_builtin_features_recursively("side-by-side", &mut features, builtin_features, opt);
     }
 
@@ -602,6 +724,21 @@ fn parse_width_specifier(width_arg: &str, terminal_width: usize) -> Result<usize
     Ok(width)
 }
 
+// The change symbols are written in front of the lines of a unified diff, leaving less width for
+// the diff itself. Whether the diff is side-by-side is only known once --side-by-side=auto has been
+// resolved against the full width.
+fn set_width_of_change_symbols(opt: &mut cli::Opt) {
+    if !opt.change_symbols || opt.side_by_side == SideBySide::True {
+        return;
+    }
+    let width = crate::paint::CHANGE_SYMBOL_WIDTH;
+    if let cli::Width::Fixed(decorations_width) = opt.computed.decorations_width {
+        opt.computed.decorations_width = cli::Width::Fixed(decorations_width.saturating_sub(width));
+    }
+    opt.computed.available_terminal_width =
+        opt.computed.available_terminal_width.saturating_sub(width);
+}
+
 fn set_widths_and_isatty(opt: &mut cli::Opt) {
     let term_stdout = Term::stdout();
     opt.computed.stdout_is_term = term_stdout.is_term();
@@ -610,6 +747,16 @@ fn set_widths_and_isatty(opt: &mut cli::Opt) {
     // as an argument, also see #41, #10, #115 and #727.
     opt.computed.available_terminal_width =
         crate::utils::workarounds::windows_msys2_width_fix(term_stdout.size(), &term_stdout);
+    opt.computed.available_terminal_height = {
+        #[cfg(test)]
+        {
+            tests::TERMINAL_HEIGHT_IN_TESTS
+        }
+        #[cfg(not(test))]
+        {
+            term_stdout.size().0 as usize
+        }
+    };
 
     let (decorations_width, background_color_extends_to_terminal_width) = match opt.width.as_deref()
     {
@@ -634,11 +781,87 @@ fn set_widths_and_isatty(opt: &mut cli::Opt) {
             }
         }
     };
-    opt.computed.decorations_width = decorations_width;
+    // The indentation and the age and author gutters are written in front of lines, and the
+    // minimap after them, leaving less width for the diff itself.
+    let gutter_width = opt.indent
+        + if opt.annotate_age {
+            crate::utils::blame::AGE_GUTTER_WIDTH
+        } else {
+            0
+        }
+        + if opt.blame_removed {
+            crate::utils::blame::AUTHOR_GUTTER_WIDTH
+        } else {
+            0
+        }
+        + if opt.minimap {
+            crate::utils::minimap::MINIMAP_WIDTH
+        } else {
+            0
+        };
+    opt.computed.decorations_width = match decorations_width {
+        cli::Width::Fixed(width) => cli::Width::Fixed(width.saturating_sub(gutter_width)),
+        cli::Width::Variable => cli::Width::Variable,
+    };
+    opt.computed.available_terminal_width = opt
+        .computed
+        .available_terminal_width
+        .saturating_sub(gutter_width);
     opt.computed.background_color_extends_to_terminal_width =
         background_color_extends_to_terminal_width;
 }
 
+// The width available to the diff, as used to lay out side-by-side panels.
+fn output_width(opt: &cli::Opt) -> usize {
+    opt.computed
+        .decorations_width
+        .or_available(opt.computed.available_terminal_width)
+}
+
+/// On an output too narrow to leave any width for the content of lines, drop the line numbers,
+/// and then, if that is not enough, the decorations, rather than writing lines without content.
+fn set_line_numbers_and_decorations_for_narrow_output(opt: &mut cli::Opt) {
+    if opt.line_numbers && content_width(opt, true) == 0 {
+        opt.line_numbers = false;
+    }
+    if content_width(opt, false) == 0 {
+        opt.file_decoration_style = "none".to_string();
+        opt.commit_decoration_style = "none".to_string();
+        opt.hunk_header_decoration_style = "none".to_string();
+    }
+}
+
+// The width left for the content of a line, in the narrowest side-by-side panel if side-by-side
+// layout is enabled.
+fn content_width(opt: &cli::Opt, line_numbers: bool) -> usize {
+    let format = match (
+        line_numbers,
+        opt.side_by_side == SideBySide::True && opt.side_by_side_shared_gutter,
+    ) {
+        (false, _) => MinusPlus::default(),
+        (true, true) => MinusPlus::new(String::new(), opt.line_numbers_right_format.clone()),
+        (true, false) => MinusPlus::new(
+            opt.line_numbers_left_format.clone(),
+            opt.line_numbers_right_format.clone(),
+        ),
+    };
+    let mut line_numbers_data =
+        LineNumbersData::from_format_strings(&format, UseFullPanelWidth(false));
+    line_numbers_data.hunk_max_line_number_width = opt.line_numbers_min_width;
+    let line_numbers_width = line_numbers_data.formatted_width();
+    let markers_width = opt.keep_plus_minus_markers as usize;
+    if opt.side_by_side == SideBySide::True {
+        let panel_width = output_width(opt).saturating_sub(opt.side_by_side_gap) / 2;
+        panel_width
+            .saturating_sub(line_numbers_width[Left].max(line_numbers_width[Right]))
+            .saturating_sub(markers_width)
+    } else {
+        output_width(opt)
+            .saturating_sub(line_numbers_width[Left] + line_numbers_width[Right])
+            .saturating_sub(markers_width)
+    }
+}
+
 fn set_true_color(opt: &mut cli::Opt) {
     if opt.true_color == "auto" {
         // It's equal to its default, so the user might be using the deprecated
@@ -651,7 +874,8 @@ fn set_true_color(opt: &mut cli::Opt) {
     opt.computed.true_color = match opt.true_color.as_ref() {
         "always" => true,
         "never" => false,
-        "auto" => is_truecolor_terminal(&opt.env),
+        // An SVG image is not limited by the colors of a terminal.
+        "auto" => opt.output == "svg" || is_truecolor_terminal(&opt.env),
         _ => {
             fatal(format!(
                 "Invalid value for --true-color option: {} (valid values are \"always\", \"never\", and \"auto\")",
@@ -659,24 +883,36 @@ fn set_true_color(opt: &mut cli::Opt) {
             ));
         }
     };
+    opt.computed.color_dither = opt.color_dither && !opt.computed.true_color;
 }
 
+// Values of TERM of terminals which do not render 24-bit color, even when COLORTERM claims that
+// they do, e.g. because it was inherited from the terminal application they are running in.
+const NON_TRUECOLOR_TERMS: &[&str] = &["dumb", "eterm", "eterm-color", "linux"];
+
 fn is_truecolor_terminal(env: &DeltaEnv) -> bool {
     env.colorterm
         .as_ref()
         .map(|colorterm| colorterm == "truecolor" || colorterm == "24bit")
         .unwrap_or(false)
+        && !env
+            .term
+            .as_deref()
+            .map(|term| NON_TRUECOLOR_TERMS.contains(&term))
+            .unwrap_or(false)
 }
 
 #[cfg(test)]
 pub mod tests {
     use std::fs::remove_file;
 
-    use crate::cli;
+    use crate::cli::{self, SideBySide};
+    use crate::env::DeltaEnv;
     use crate::tests::integration_test_utils;
     use crate::utils::bat::output::PagingMode;
 
     pub const TERMINAL_WIDTH_IN_TESTS: usize = 43;
+    pub const TERMINAL_HEIGHT_IN_TESTS: usize = 24;
 
     #[test]
     fn test_options_can_be_set_in_git_config() {
@@ -792,7 +1028,7 @@ pub mod tests {
         assert_eq!(opt.plus_non_emph_style, "black black");
         assert_eq!(opt.plus_style, "black black");
         assert!(opt.raw);
-        assert!(opt.side_by_side);
+        assert_eq!(opt.side_by_side, SideBySide::True);
         assert_eq!(opt.syntax_theme, Some("xxxyyyzzz".to_string()));
         assert_eq!(opt.tab_width, 77);
         assert_eq!(opt.true_color, "never");
@@ -806,6 +1042,159 @@ pub mod tests {
         remove_file(git_config_path).unwrap();
     }
 
+    #[test]
+    fn test_side_by_side_min_width() {
+        let side_by_side = |args: &[&str]| {
+            integration_test_utils::make_options_from_args_and_git_config(args, None, None)
+                .side_by_side
+        };
+        assert_eq!(side_by_side(&["--width=100"]), SideBySide::False);
+        assert_eq!(
+            side_by_side(&["--side-by-side", "--width=100"]),
+            SideBySide::True
+        );
+        assert_eq!(
+            side_by_side(&["--side-by-side=false", "--width=100"]),
+            SideBySide::False
+        );
+        assert_eq!(
+            side_by_side(&["--side-by-side=auto", "--width=100"]),
+            SideBySide::True
+        );
+        assert_eq!(
+            side_by_side(&[
+                "--side-by-side=auto",
+                "--side-by-side-min-width=80",
+                "--width=100"
+            ]),
+            SideBySide::True
+        );
+        assert_eq!(
+            side_by_side(&[
+                "--side-by-side=auto",
+                "--side-by-side-min-width=80",
+                "--width=60"
+            ]),
+            SideBySide::False
+        );
+        // Only "auto" falls back to the unified layout.
+        assert_eq!(
+            side_by_side(&["-s", "--side-by-side-min-width=80", "--width=60"]),
+            SideBySide::True
+        );
+    }
+
+    #[test]
+    fn test_side_by_side_auto_with_change_symbols() {
+        let make_options = |args: &[&str]| {
+            integration_test_utils::make_options_from_args_and_git_config(args, None, None)
+        };
+        // The change symbols do not narrow the width compared with --side-by-side-min-width, nor
+        // a side-by-side diff.
+        let opt = make_options(&[
+            "--side-by-side=auto",
+            "--side-by-side-min-width=100",
+            "--change-symbols",
+            "--width=100",
+        ]);
+        assert_eq!(opt.side_by_side, SideBySide::True);
+        assert_eq!(opt.computed.decorations_width, cli::Width::Fixed(100));
+        // They do narrow a unified diff.
+        let opt = make_options(&[
+            "--side-by-side=auto",
+            "--side-by-side-min-width=120",
+            "--change-symbols",
+            "--width=100",
+        ]);
+        assert_eq!(opt.side_by_side, SideBySide::False);
+        assert_eq!(opt.computed.decorations_width, cli::Width::Fixed(98));
+    }
+
+    #[test]
+    fn test_side_by_side_auto_in_git_config() {
+        let git_config_contents = b"
+[delta]
+    side-by-side = auto
+    side-by-side-min-width = 80
+";
+        let git_config_path = "delta__test_side_by_side_auto_in_git_config.gitconfig";
+        let side_by_side = |args: &[&str]| {
+            integration_test_utils::make_options_from_args_and_git_config(
+                args,
+                Some(git_config_contents),
+                Some(git_config_path),
+            )
+            .side_by_side
+        };
+        assert_eq!(side_by_side(&["--width=100"]), SideBySide::True);
+        assert_eq!(side_by_side(&["--width=60"]), SideBySide::False);
+        remove_file(git_config_path).unwrap();
+    }
+
+    #[test]
+    fn test_narrow_output_drops_line_numbers_then_decorations() {
+        let opt = |args: &[&str]| {
+            integration_test_utils::make_options_from_args_and_git_config(args, None, None)
+        };
+        let opt_ = opt(&["--line-numbers", "--width=11"]);
+        assert!(opt_.line_numbers);
+        assert_eq!(opt_.file_decoration_style, "blue ul");
+
+        // The line numbers, "  1 ⋮    │", would fill the width.
+        let opt_ = opt(&["--line-numbers", "--width=10"]);
+        assert!(!opt_.line_numbers);
+        assert_eq!(opt_.file_decoration_style, "blue ul");
+
+        // The line numbers of a panel, "│  1 │", would fill its width.
+        let opt_ = opt(&["--side-by-side", "--width=14"]);
+        assert!(opt_.line_numbers);
+        let opt_ = opt(&["--side-by-side", "--width=13"]);
+        assert!(!opt_.line_numbers);
+        assert_eq!(opt_.hunk_header_decoration_style, "blue box");
+
+        // Not even one column is left for the content of the panels.
+        let opt_ = opt(&["--side-by-side", "--width=1"]);
+        assert!(!opt_.line_numbers);
+        assert_eq!(opt_.file_decoration_style, "none");
+        assert_eq!(opt_.commit_decoration_style, "none");
+        assert_eq!(opt_.hunk_header_decoration_style, "none");
+    }
+
+    #[test]
+    fn test_narrow_output_does_not_panic() {
+        let input = "\
+commit 94907c0f136f46dc46ffae2dc92dca9af7eb7c2e
+Author: Dan Davison <dandavison7@gmail.com>
+
+    Fix the thing
+
+diff --git a/src/a.rs b/src/a.rs
+index 1111111..2222222 100644
+--- a/src/a.rs
++++ b/src/a.rs
+@@ -1,3 +1,3 @@ fn main() {
+-    let x = 1;
++    let x = 2;
+     ctx\tline
+";
+        for width in 0..=20 {
+            for args in [
+                &["--line-numbers"][..],
+                &["--side-by-side"],
+                &["--side-by-side", "--keep-plus-minus-markers"],
+                &["--side-by-side", "--wrap-max-lines=100"],
+            ] {
+                let width = format!("--width={width}");
+                let output = integration_test_utils::DeltaTest::with_args(
+                    &[args, &[width.as_str()]].concat(),
+                )
+                .with_input(input)
+                .output;
+                assert!(output.contains("src/a.rs"));
+            }
+        }
+    }
+
     #[test]
     fn test_width_in_git_config_is_honored() {
         let git_config_contents = b"
@@ -828,6 +1217,63 @@ pub mod tests {
         remove_file(git_config_path).unwrap();
     }
 
+    #[test]
+    fn test_navigate_env_var() {
+        for (env_navigate, args, expected) in [
+            (None, &[][..], false),
+            (Some(true), &[][..], true),
+            (Some(false), &[][..], false),
+            (None, &["--navigate"][..], true),
+            (Some(false), &["--navigate"][..], true),
+        ] {
+            let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
+                DeltaEnv {
+                    navigate: env_navigate,
+                    ..DeltaEnv::default()
+                },
+                args,
+                None,
+                None,
+            );
+            assert_eq!(opt.navigate, expected, "{:?} {:?}", env_navigate, args);
+        }
+    }
+
+    #[test]
+    fn test_true_color_env_vars() {
+        for (colorterm, term, args, expected) in [
+            (None, None, &[][..], false),
+            (Some("truecolor"), None, &[][..], true),
+            (Some("24bit"), Some("xterm-256color"), &[][..], true),
+            (Some("truecolor"), Some("linux"), &[][..], false),
+            // TERM=screen is also set by tmux, which renders 24-bit color.
+            (Some("truecolor"), Some("screen"), &[][..], true),
+            (
+                Some("truecolor"),
+                Some("linux"),
+                &["--true-color=always"][..],
+                true,
+            ),
+            (Some("truecolor"), None, &["--true-color=never"][..], false),
+        ] {
+            let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
+                DeltaEnv {
+                    colorterm: colorterm.map(str::to_string),
+                    term: term.map(str::to_string),
+                    ..DeltaEnv::default()
+                },
+                args,
+                None,
+                None,
+            );
+            assert_eq!(
+                opt.computed.true_color, expected,
+                "{:?} {:?} {:?}",
+                colorterm, term, args
+            );
+        }
+    }
+
     #[test]
     fn test_parse_width_specifier() {
         use super::parse_width_specifier;
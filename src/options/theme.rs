@@ -14,11 +14,14 @@
 //! In the absence of other factors, the default assumes a dark terminal background.
 
 use std::io::{stdout, IsTerminal};
+#[cfg(not(test))]
+use std::time::Duration;
 
 use bat;
 use bat::assets::HighlightingAssets;
+use terminal_colorsaurus::Color;
 #[cfg(not(test))]
-use terminal_colorsaurus::{color_scheme, QueryOptions};
+use terminal_colorsaurus::{background_color, color_scheme, QueryOptions};
 
 use crate::cli::{self, DetectDarkLight};
 use crate::color::{ColorMode, ColorMode::*};
@@ -62,8 +65,12 @@ const LIGHT_SYNTAX_THEMES: [&str; 7] = [
 const DEFAULT_LIGHT_SYNTAX_THEME: &str = "GitHub";
 const DEFAULT_DARK_SYNTAX_THEME: &str = "Monokai Extended";
 
+/// The syntax theme names "none" (case-insensitive) and "" are sentinels meaning that syntax
+/// highlighting is disabled. This is the only place where that decision is made: downstream code
+/// sees `syntax_theme == None` and never constructs a syntect highlighter.
 fn is_no_syntax_highlighting_syntax_theme_name(theme_name: &str) -> bool {
-    theme_name.to_lowercase() == "none"
+    let theme_name = theme_name.trim();
+    theme_name.is_empty() || theme_name.eq_ignore_ascii_case("none")
 }
 
 /// Return a (theme_name, color_mode) tuple.
@@ -86,7 +93,13 @@ fn get_color_mode(opt: &cli::Opt) -> Option<ColorMode> {
     } else if opt.dark {
         Some(Dark)
     } else if should_detect_color_mode(opt) {
-        detect_color_mode()
+        if opt.query_terminal_background {
+            query_background_color()
+                .map(|color| color_mode_from_background_color(&color))
+                .or_else(|| color_mode_from_colorfgbg(opt.env.colorfgbg.as_deref()?))
+        } else {
+            detect_color_mode()
+        }
     } else {
         None
     }
@@ -122,10 +135,42 @@ fn detect_color_mode() -> Option<ColorMode> {
     None
 }
 
+/// Query the background color of the terminal (used by --query-terminal-background).
+#[cfg(not(test))]
+fn query_background_color() -> Option<Color> {
+    let mut options = QueryOptions::default();
+    options.timeout = Duration::from_millis(100);
+    background_color(options).ok()
+}
+
+#[cfg(test)]
+fn query_background_color() -> Option<Color> {
+    None
+}
+
+fn color_mode_from_background_color(color: &Color) -> ColorMode {
+    if color.perceived_lightness() <= 50 {
+        Dark
+    } else {
+        Light
+    }
+}
+
+/// Return the color mode given by the COLORFGBG environment variable, whose value is of the
+/// form "FG;BG" or "FG;DEFAULT;BG", where BG is the number of a color of the 16-color palette.
+fn color_mode_from_colorfgbg(colorfgbg: &str) -> Option<ColorMode> {
+    match colorfgbg.rsplit(';').next()?.trim().parse::<u8>().ok()? {
+        0..=6 | 8 => Some(Dark),
+        7 | 9..=15 => Some(Light),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::color;
+    use crate::env::DeltaEnv;
     use crate::tests::integration_test_utils;
 
     // TODO: Test influence of BAT_THEME env var. E.g. see utils::process::tests::FakeParentArgs.
@@ -149,6 +194,8 @@ mod tests {
             (Some("none"), None, "none", Dark),
             (Some("none"), Some(Dark), "none", Dark),
             (Some("None"), Some(Light), "none", Light),
+            (Some(""), None, "none", Dark),
+            (Some(""), Some(Light), "none", Light),
         ] {
             let mut args = vec![];
             if let Some(syntax_theme) = syntax_theme {
@@ -207,4 +254,71 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_color_mode_from_background_color() {
+        for (rgb, expected_mode) in [
+            ((0, 0, 0), Dark),
+            ((0x2828, 0x2a2a, 0x3636), Dark),
+            ((0xfdfd, 0xf6f6, 0xe3e3), Light),
+            ((u16::MAX, u16::MAX, u16::MAX), Light),
+        ] {
+            let (r, g, b) = rgb;
+            let color = Color { r, g, b };
+            assert_eq!(
+                color_mode_from_background_color(&color),
+                expected_mode,
+                "{:?}",
+                color
+            );
+        }
+    }
+
+    #[test]
+    fn test_color_mode_from_colorfgbg() {
+        for (colorfgbg, expected_mode) in [
+            ("15;0", Some(Dark)),
+            ("7;8", Some(Dark)),
+            ("0;15", Some(Light)),
+            ("0;default;7", Some(Light)),
+            ("0;16", None),
+            ("default", None),
+            ("", None),
+        ] {
+            assert_eq!(
+                color_mode_from_colorfgbg(colorfgbg),
+                expected_mode,
+                "{:?}",
+                colorfgbg
+            );
+        }
+    }
+
+    #[test]
+    fn test_query_terminal_background_falls_back_to_colorfgbg() {
+        // The terminal is never queried in tests, as if it did not answer.
+        let env = DeltaEnv {
+            colorfgbg: Some("0;15".to_string()),
+            ..DeltaEnv::default()
+        };
+        for (args, expected_mode) in [
+            (&["--detect-dark-light=always"][..], Dark),
+            (
+                &["--detect-dark-light=always", "--query-terminal-background"],
+                Light,
+            ),
+            (
+                &["--detect-dark-light=never", "--query-terminal-background"],
+                Dark,
+            ),
+        ] {
+            let opt = integration_test_utils::make_options_from_args_and_git_config_with_custom_env(
+                env.clone(),
+                args,
+                None,
+                None,
+            );
+            assert_eq!(opt.computed.color_mode, expected_mode, "{:?}", args);
+        }
+    }
 }
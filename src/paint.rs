@@ -1,6 +1,8 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io::Write;
+use std::ops::Range;
+use std::rc::Rc;
 
 use ansi_term::ANSIString;
 use itertools::Itertools;
@@ -8,7 +10,7 @@ use syntect::easy::HighlightLines;
 use syntect::highlighting::Style as SyntectStyle;
 use syntect::parsing::{SyntaxReference, SyntaxSet};
 
-use crate::config::{self, delta_unreachable, Config};
+use crate::config::{self, delta_unreachable, Config, EmphExtend};
 use crate::delta::{DiffType, InMergeConflict, MergeParents, State};
 use crate::features::hyperlinks;
 use crate::features::line_numbers::{self, LineNumbersData};
@@ -18,6 +20,7 @@ use crate::handlers::merge_conflict;
 use crate::minusplus::*;
 use crate::paint::superimpose_style_sections::superimpose_style_sections;
 use crate::style::Style;
+use crate::utils::blame;
 use crate::{ansi, style};
 use crate::{edits, utils, utils::tabs};
 
@@ -26,9 +29,14 @@ pub type LineSections<'a, S> = Vec<(S, &'a str)>;
 pub struct Painter<'p> {
     pub minus_lines: Vec<(String, State)>,
     pub plus_lines: Vec<(String, State)>,
+    // With --check, the byte ranges of the whitespace errors of each of the plus lines.
+    pub plus_lines_whitespace_errors: Vec<Vec<Range<usize>>>,
     pub writer: &'p mut dyn Write,
     pub syntax: &'p SyntaxReference,
     pub highlighter: Option<HighlightLines<'p>>,
+    // With --stateful-highlighting, the highlighter of the new version of the file: `highlighter`
+    // is then only fed the old version.
+    pub plus_highlighter: Option<HighlightLines<'p>>,
     pub config: &'p config::Config,
     pub output_buffer: String,
     // If config.line_numbers is true, then the following is always Some().
@@ -37,6 +45,13 @@ pub struct Painter<'p> {
     pub line_numbers_data: Option<line_numbers::LineNumbersData<'p>>,
     pub merge_conflict_lines: merge_conflict::MergeConflictLines,
     pub merge_conflict_commit_names: merge_conflict::MergeConflictCommitNames,
+    // The line numbers of the next minus and plus lines of the current hunk, and the position in
+    // the line buffers of the line given by --focus-line, if it has been buffered.
+    pub hunk_line_numbers: MinusPlus<usize>,
+    pub buffered_focus_line: Option<(MinusPlusIndex, usize)>,
+    // The author of each line of the old version of the file of the current hunk (used by
+    // --blame-removed).
+    pub removed_line_authors: Option<Rc<Vec<String>>>,
 }
 
 // How the background of a line is filled up to the end
@@ -91,14 +106,19 @@ impl<'p> Painter<'p> {
         Self {
             minus_lines: Vec::new(),
             plus_lines: Vec::new(),
+            plus_lines_whitespace_errors: Vec::new(),
             output_buffer: String::new(),
             syntax: default_syntax,
             highlighter: None,
+            plus_highlighter: None,
             writer,
             config,
             line_numbers_data,
             merge_conflict_lines: merge_conflict::MergeConflictLines::new(),
             merge_conflict_commit_names: merge_conflict::MergeConflictCommitNames::new(),
+            hunk_line_numbers: MinusPlus::default(),
+            buffered_focus_line: None,
+            removed_line_authors: None,
         }
     }
 
@@ -148,32 +168,176 @@ impl<'p> Painter<'p> {
         }
     }
 
+    /// Whether the paragraphs of the current file are compared rather than its lines (used by
+    /// --reflow-prose).
+    pub fn reflows_prose(&self) -> bool {
+        self.config.reflow_prose && PROSE_SYNTAXES.contains(&self.syntax.name.as_str())
+    }
+
     pub fn set_highlighter(&mut self) {
         if let Some(ref syntax_theme) = self.config.syntax_theme {
-            self.highlighter = Some(HighlightLines::new(self.syntax, syntax_theme))
+            self.highlighter = Some(HighlightLines::new(self.syntax, syntax_theme));
+            if self.config.stateful_highlighting {
+                self.plus_highlighter = Some(HighlightLines::new(self.syntax, syntax_theme));
+            }
         };
     }
 
+    /// Feed the lines of the file preceding a hunk to the highlighters, so that the hunk is
+    /// highlighted in the syntactic context it occurs in (used by --stateful-highlighting).
+    pub fn prime_highlighters(&mut self, lines: &[String]) {
+        for highlighter in self
+            .highlighter
+            .iter_mut()
+            .chain(self.plus_highlighter.iter_mut())
+        {
+            for line in lines {
+                let _ = highlighter.highlight_line(&format!("{line}\n"), &self.config.syntax_set);
+            }
+        }
+    }
+
     pub fn paint_buffered_minus_and_plus_lines(&mut self) {
         if self.minus_lines.is_empty() && self.plus_lines.is_empty() {
             return;
         }
+        let start = self.output_buffer.len();
+        let options = BlockPaintOptions {
+            plus_whitespace_errors: &self.plus_lines_whitespace_errors,
+            reflow_prose: self.reflows_prose(),
+        };
         paint_minus_and_plus_lines(
             MinusPlus::new(&self.minus_lines, &self.plus_lines),
             &mut self.line_numbers_data,
             &mut self.highlighter,
+            self.plus_highlighter.as_mut(),
+            options,
             &mut self.output_buffer,
             self.config,
         );
+        // In unified mode the minus lines are written before the plus lines, one per line but for
+        // collapsed runs of blank added lines.
+        let plus_rows = get_plus_line_rows(&self.plus_lines, self.config);
+        if let Some((buffer, index)) = self.buffered_focus_line.take() {
+            let row = match buffer {
+                Minus => index,
+                Plus => self.minus_lines.len() + plus_rows[index],
+            };
+            self.paint_focus_line(start, row);
+        }
+        if self.config.annotate_lines {
+            // A collapsed run of blank added lines is tagged with its first line.
+            let (minus_end, plus_end) =
+                (self.hunk_line_numbers[Minus], self.hunk_line_numbers[Plus]);
+            let tags: Vec<String> = (minus_end.saturating_sub(self.minus_lines.len())..minus_end)
+                .map(|n| format_line_tag('-', n))
+                .chain(
+                    (plus_end.saturating_sub(self.plus_lines.len())..plus_end)
+                        .zip_eq(&plus_rows)
+                        .dedup_by(|(_, row), (_, next_row)| row == next_row)
+                        .map(|(n, _)| format_line_tag('+', n)),
+                )
+                .collect();
+            self.insert_line_tags(start, tags);
+        }
+        if self.config.annotate_age {
+            blame::insert_age_gutter(&mut self.output_buffer, start, None);
+        }
+        if self.config.blame_removed {
+            // In unified mode the minus lines are written before the plus lines, one per line.
+            let minus_end = self.hunk_line_numbers[Minus];
+            let removed_line_authors = self.removed_line_authors.clone();
+            let authors: Vec<Option<&str>> = (minus_end.saturating_sub(self.minus_lines.len())
+                ..minus_end)
+                .map(|n| {
+                    let authors = removed_line_authors.as_ref()?;
+                    authors.get(n.checked_sub(1)?).map(String::as_str)
+                })
+                .collect();
+            blame::insert_author_gutter(&mut self.output_buffer, start, &authors);
+        }
         self.minus_lines.clear();
         self.plus_lines.clear();
+        self.plus_lines_whitespace_errors.clear();
+    }
+
+    /// Advance the line numbers of the current hunk past a line shown on the given sides, and
+    /// return whether it is the line given by --focus-line.
+    pub fn next_line_is_focus_line(&mut self, sides: &[MinusPlusIndex]) -> bool {
+        let mut is_focus_line = false;
+        for &side in sides {
+            is_focus_line |= self.config.focus_line == Some((side, self.hunk_line_numbers[side]));
+            self.hunk_line_numbers[side] += 1;
+        }
+        is_focus_line
+    }
+
+    /// Repaint the `row`-th line written to the output buffer after `start` in the focus style,
+    /// replacing the styles of both its line number gutter and its content.
+    pub fn paint_focus_line(&mut self, start: usize, row: usize) {
+        if self.config.side_by_side {
+            return;
+        }
+        let painted = self.output_buffer.split_off(start);
+        for (i, line) in painted.split_inclusive('\n').enumerate() {
+            if i != row {
+                self.output_buffer.push_str(line);
+                continue;
+            }
+            let (line, newline) = match line.strip_suffix('\n') {
+                Some(line) => (line, "\n"),
+                None => (line, ""),
+            };
+            let style = self.config.focus_line_style;
+            let mut line = style.paint(ansi::strip_ansi_codes(line)).to_string();
+            if self.config.background_color_extends_to_terminal_width
+                && style.get_background_color().is_some()
+            {
+                Painter::right_fill_background_color(&mut line, style);
+            }
+            self.output_buffer.push_str(&line);
+            self.output_buffer.push_str(newline);
+        }
+    }
+
+    /// Prefix the lines written to the output buffer after `start` with the given tags (used by
+    /// --annotate-lines). Lines beyond the last tag are not changed.
+    pub fn insert_line_tags<I>(&mut self, start: usize, tags: I)
+    where
+        I: IntoIterator<Item = String>,
+    {
+        if self.config.side_by_side {
+            return;
+        }
+        let painted = self.output_buffer.split_off(start);
+        let mut tags = tags.into_iter();
+        for line in painted.split_inclusive('\n') {
+            if let Some(tag) = tags.next() {
+                self.output_buffer.push_str(&tag);
+            }
+            self.output_buffer.push_str(line);
+        }
     }
 
     pub fn paint_zero_line(&mut self, line: &str, state: State) {
         let lines = &[(line.to_string(), state.clone())];
-        let syntax_style_sections =
-            get_syntax_style_sections_for_lines(lines, self.highlighter.as_mut(), self.config);
-        let mut diff_style_sections = vec![vec![(self.config.zero_style, lines[0].0.as_str())]]; // TODO: compute style from state
+        // With --stateful-highlighting, an unchanged line is shown as highlighted in the new
+        // version of the file, and continues the old version.
+        let syntax_style_sections = match self.plus_highlighter.as_mut() {
+            Some(plus_highlighter) => {
+                get_syntax_style_sections_for_lines(lines, self.highlighter.as_mut(), self.config);
+                get_syntax_style_sections_for_lines(lines, Some(plus_highlighter), self.config)
+            }
+            None => {
+                get_syntax_style_sections_for_lines(lines, self.highlighter.as_mut(), self.config)
+            }
+        };
+        let mut zero_style = self.config.zero_style;
+        if !self.config.syntax_highlight_context {
+            // The line has still been highlighted, to keep the highlighter's state.
+            zero_style.is_syntax_highlighted = false;
+        }
+        let mut diff_style_sections = vec![vec![(zero_style, lines[0].0.as_str())]]; // TODO: compute style from state
         Painter::update_diff_style_sections(
             lines,
             &mut diff_style_sections,
@@ -230,12 +394,34 @@ impl<'p> Painter<'p> {
         // 2. We must ensure that we fill rightwards with the appropriate
         //    non-emph background color. In that case we don't use the last
         //    style of the line, because this might be emph.
-        for ((((_, state), syntax_sections), diff_sections), &line_has_homolog) in lines
+        // The number of lines still to be skipped of a run of blank added lines which has been
+        // collapsed into a single line (used with --collapse-blank-additions).
+        let mut n_collapsed = 0;
+        for (i, ((((_, state), syntax_sections), diff_sections), &line_has_homolog)) in lines
             .iter()
             .zip_eq(syntax_style_sections)
             .zip_eq(diff_style_sections)
             .zip_eq(lines_have_homolog)
+            .enumerate()
         {
+            if n_collapsed > 0 {
+                n_collapsed -= 1;
+                continue;
+            }
+            if config.collapse_blank_additions {
+                let n_blank = blank_additions_run_length(&lines[i..]);
+                if n_blank > 1 {
+                    output_buffer.push_str(&Painter::paint_collapsed_blank_additions(
+                        n_blank,
+                        state,
+                        line_numbers_data,
+                        config,
+                    ));
+                    output_buffer.push('\n');
+                    n_collapsed = n_blank - 1;
+                    continue;
+                }
+            }
             let (mut line, line_is_empty) = Painter::paint_line(
                 syntax_sections,
                 diff_sections,
@@ -279,6 +465,28 @@ impl<'p> Painter<'p> {
         }
     }
 
+    /// Paint a run of `n_blank` blank added lines as a single line, consisting of the line numbers
+    /// of the first of them and a marker. The line numbers are advanced past all of them.
+    fn paint_collapsed_blank_additions(
+        n_blank: usize,
+        state: &State,
+        line_numbers_data: &mut Option<&mut line_numbers::LineNumbersData>,
+        config: &config::Config,
+    ) -> String {
+        let (mut line, _) =
+            Painter::paint_line(&[], &[], state, line_numbers_data, None, None, config);
+        if let Some(line_numbers_data) = line_numbers_data {
+            line_numbers_data.line_number[Plus] += n_blank - 1;
+        }
+        line.push_str(
+            &config
+                .inline_hint_style
+                .paint(format!("+ {n_blank} blank lines"))
+                .to_string(),
+        );
+        line
+    }
+
     /// Write painted line to the output buffer, with syntax-highlighting and `style` superimposed.
     // Note that, if passing `style_sections` as
     // `StyleSectionSpecifier::StyleSections`, then tabs must already have been
@@ -322,7 +530,9 @@ impl<'p> Painter<'p> {
         let fill_style = match state {
             State::HunkMinus(_, None) | State::HunkMinusWrapped => {
                 if let Some(true) = line_has_homolog {
-                    config.minus_non_emph_style
+                    change_heat_fill_style(diff_sections, config)
+                        .or_else(|| extended_emph_fill_style(diff_sections, config))
+                        .unwrap_or(config.minus_non_emph_style)
                 } else {
                     config.minus_style
                 }
@@ -330,7 +540,9 @@ impl<'p> Painter<'p> {
             State::HunkZero(_, None) | State::HunkZeroWrapped => config.zero_style,
             State::HunkPlus(_, None) | State::HunkPlusWrapped => {
                 if let Some(true) = line_has_homolog {
-                    config.plus_non_emph_style
+                    change_heat_fill_style(diff_sections, config)
+                        .or_else(|| extended_emph_fill_style(diff_sections, config))
+                        .unwrap_or(config.plus_non_emph_style)
                 } else {
                     config.plus_style
                 }
@@ -409,6 +621,18 @@ impl<'p> Painter<'p> {
     ) -> (String, bool) {
         let mut ansi_strings = Vec::new();
 
+        if config.change_symbols && side_by_side_panel.is_none() {
+            let symbol = match state {
+                State::HunkMinus(_, _) => Some("- "),
+                State::HunkPlus(_, _) => Some("+ "),
+                State::HunkZero(_, _) => Some("  "),
+                _ => None,
+            };
+            if let Some(symbol) = symbol {
+                ansi_strings.push(config.change_symbol_style.paint(symbol));
+            }
+        }
+
         let output_line_numbers = line_numbers_data.is_some();
         if output_line_numbers {
             // Unified diff lines are printed in one go, but side-by-side lines
@@ -521,6 +745,7 @@ impl<'p> Painter<'p> {
     ///    are going to be preserved in the output, then replace delta's
     ///    computed diff styles with these styles from the raw line. (This is
     ///    how support for git's --color-moved is implemented.)
+    /// 4. Tokens matched by the rules of --semantic-highlight are painted in their styles.
     fn update_diff_style_sections<'a>(
         lines: &'a [(String, State)],
         diff_style_sections: &mut Vec<LineSections<'a, Style>>,
@@ -529,7 +754,7 @@ impl<'p> Painter<'p> {
         lines_have_homolog: &[bool],
         config: &config::Config,
     ) {
-        for (((_, state), style_sections), line_has_homolog) in lines
+        for (((line, state), style_sections), line_has_homolog) in lines
             .iter()
             .zip_eq(diff_style_sections)
             .zip_eq(lines_have_homolog)
@@ -574,10 +799,42 @@ impl<'p> Painter<'p> {
                     }
                 }
             }
+            for (regex, token_style) in config
+                .semantic_highlight
+                .iter()
+                .map(|(_, regex, style)| (regex, *style))
+            {
+                let ranges: Vec<_> = regex.find_iter(line).map(|m| m.range()).collect();
+                style_ranges(style_sections, &ranges, |style| {
+                    overlay_token_style(style, token_style)
+                });
+            }
         }
     }
 }
 
+// Paint a token matched by a --semantic-highlight rule in `token_style` on top of `style`.
+fn overlay_token_style(style: Style, token_style: Style) -> Style {
+    let (base, token) = (style.ansi_term_style, token_style.ansi_term_style);
+    Style {
+        ansi_term_style: ansi_term::Style {
+            foreground: token.foreground.or(base.foreground),
+            background: token.background.or(base.background),
+            is_bold: base.is_bold || token.is_bold,
+            is_dimmed: base.is_dimmed || token.is_dimmed,
+            is_italic: base.is_italic || token.is_italic,
+            is_underline: base.is_underline || token.is_underline,
+            is_blink: base.is_blink || token.is_blink,
+            is_reverse: base.is_reverse || token.is_reverse,
+            is_hidden: base.is_hidden || token.is_hidden,
+            is_strikethrough: base.is_strikethrough || token.is_strikethrough,
+        },
+        // A foreground color of the token replaces that of syntax highlighting.
+        is_syntax_highlighted: style.is_syntax_highlighted && token.foreground.is_none(),
+        ..style
+    }
+}
+
 /// Remove initial -/+ character, expand tabs as spaces, and terminate with newline.
 // Terminating with newline character is necessary for many of the sublime syntax definitions to
 // highlight correctly.
@@ -603,18 +860,82 @@ pub fn prepare_raw_line(raw_line: &str, prefix_length: usize, config: &config::C
     ansi::ansi_preserving_slice(&line, prefix_length)
 }
 
-pub fn paint_minus_and_plus_lines(
+/// How a block of removed and added lines is painted, besides what the config determines.
+#[derive(Default)]
+pub struct BlockPaintOptions<'b> {
+    /// The byte ranges of each added line which are painted as whitespace errors.
+    pub plus_whitespace_errors: &'b [Vec<Range<usize>>],
+    /// Whether the edits are inferred between paragraphs rather than lines (used by
+    /// --reflow-prose).
+    pub reflow_prose: bool,
+}
+
+/// Paint the removed and added lines of a hunk. The added lines are highlighted by
+/// `plus_highlighter` if given, otherwise both by `highlighter`.
+pub fn paint_minus_and_plus_lines<'a>(
     lines: MinusPlus<&Vec<(String, State)>>,
     line_numbers_data: &mut Option<LineNumbersData>,
-    highlighter: &mut Option<HighlightLines>,
+    highlighter: &mut Option<HighlightLines<'a>>,
+    plus_highlighter: Option<&mut HighlightLines<'a>>,
+    options: BlockPaintOptions,
     output_buffer: &mut String,
     config: &config::Config,
 ) {
-    let syntax_style_sections = MinusPlus::new(
+    if (config.inline_only || config.inline_single_word)
+        && !config.side_by_side
+        && lines[Minus]
+            .iter()
+            .chain(lines[Plus].iter())
+            .all(|(_, state)| {
+                matches!(
+                    state,
+                    State::HunkMinus(DiffType::Unified, None)
+                        | State::HunkPlus(DiffType::Unified, None)
+                )
+            })
+    {
+        let edits = infer_edits(&lines, config);
+        let (minus_edits, plus_edits, line_alignment) = &edits;
+        // With --inline-single-word alone, only blocks in which each pair of homologous lines
+        // differs in a single word are shown inline.
+        if config.inline_only
+            || line_alignment
+                .iter()
+                .any(|alignment| matches!(alignment, (Some(_), Some(_))))
+                && line_alignment.iter().all(|alignment| match alignment {
+                    (Some(minus_index), Some(plus_index)) => {
+                        single_word_change(&minus_edits[*minus_index], &plus_edits[*plus_index])
+                            .is_some()
+                    }
+                    _ => true,
+                })
+        {
+            paint_minus_and_plus_lines_inline(
+                lines,
+                edits,
+                line_numbers_data,
+                highlighter,
+                output_buffer,
+                config,
+            );
+            return;
+        }
+    }
+    let mut syntax_style_sections = MinusPlus::new(
         get_syntax_style_sections_for_lines(lines[Minus], highlighter.as_mut(), config),
-        get_syntax_style_sections_for_lines(lines[Plus], highlighter.as_mut(), config),
+        get_syntax_style_sections_for_lines(
+            lines[Plus],
+            plus_highlighter.or(highlighter.as_mut()),
+            config,
+        ),
     );
-    let (mut diff_style_sections, line_alignment) = get_diff_style_sections(&lines, config);
+    let (mut diff_style_sections, line_alignment) =
+        get_diff_style_sections(&lines, options.reflow_prose, config);
+    let insertion_points = if config.show_insertion_point {
+        get_insertion_points(&lines, &diff_style_sections, &line_alignment)
+    } else {
+        Vec::new()
+    };
     let lines_have_homolog = edits::make_lines_have_homolog(&line_alignment);
     Painter::update_diff_style_sections(
         lines[Minus],
@@ -640,6 +961,64 @@ pub fn paint_minus_and_plus_lines(
         &lines_have_homolog[Plus],
         config,
     );
+    if config.change_heat {
+        tint_by_change_heat(&mut diff_style_sections, &line_alignment, config);
+    }
+    if config.inline_highlight_budget > 0 {
+        for (side, non_emph_style, emph_style, style) in [
+            (
+                Minus,
+                config.minus_non_emph_style,
+                config.minus_emph_style,
+                config.minus_style,
+            ),
+            (
+                Plus,
+                config.plus_non_emph_style,
+                config.plus_emph_style,
+                config.plus_style,
+            ),
+        ] {
+            let plain_style = if non_emph_style != emph_style {
+                non_emph_style
+            } else {
+                style
+            };
+            for sections in diff_style_sections[side].iter_mut() {
+                limit_emph_to_budget(sections, config.inline_highlight_budget, plain_style);
+            }
+        }
+    }
+    if config.emph_extend == EmphExtend::Line {
+        for side in [Minus, Plus] {
+            for sections in diff_style_sections[side].iter_mut() {
+                extend_emph_to_end_of_line(sections);
+            }
+        }
+    }
+    if config.show_insertion_point {
+        insert_insertion_point_markers(
+            &mut syntax_style_sections[Minus],
+            &mut diff_style_sections[Minus],
+            &insertion_points,
+            config,
+        );
+    }
+    for (sections, ranges) in diff_style_sections[Plus]
+        .iter_mut()
+        .zip(options.plus_whitespace_errors)
+    {
+        style_ranges(sections, ranges, |_| config.whitespace_error_style);
+    }
+    if config.align_edits && !config.side_by_side {
+        for (side, style) in [(Minus, config.minus_style), (Plus, config.plus_style)] {
+            align_edits(
+                &mut syntax_style_sections[side],
+                &mut diff_style_sections[side],
+                style,
+            );
+        }
+    }
     if config.side_by_side {
         side_by_side::paint_minus_and_plus_lines_side_by_side(
             lines,
@@ -682,6 +1061,288 @@ pub fn paint_minus_and_plus_lines(
     }
 }
 
+const INSERTION_POINT_SYMBOL: &str = "‸";
+
+/// The separator of the removed and added words of a line shown by --inline-single-word.
+const SINGLE_WORD_CHANGE_ARROW: &str = "→";
+
+/// The number of columns taken by --change-symbols: a symbol and a space.
+pub const CHANGE_SYMBOL_WIDTH: usize = 2;
+
+/// The greatest number of columns by which --align-edits moves the edits of a line.
+const ALIGN_EDITS_MAX_PADDING: usize = 8;
+const ALIGN_EDITS_PADDING: &str = "        ";
+
+/// The languages of the files whose paragraphs are compared by --reflow-prose.
+const PROSE_SYNTAXES: [&str; 6] = [
+    "AsciiDoc (Asciidoctor)",
+    "Markdown",
+    "orgmode",
+    "Plain Text",
+    "reStructuredText",
+    "Textile",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Edit {
+    Unchanged,
+    Deleted,
+    Inserted,
+}
+
+/// Infer the edit operations between the minus and plus lines, without styling them.
+#[allow(clippy::type_complexity)]
+fn infer_edits<'a>(
+    lines: &MinusPlus<&'a Vec<(String, State)>>,
+    config: &config::Config,
+) -> (
+    Vec<Vec<(Edit, &'a str)>>,
+    Vec<Vec<(Edit, &'a str)>>,
+    Vec<(Option<usize>, Option<usize>)>,
+) {
+    edits::infer_edits(
+        lines[Minus].iter().map(|(line, _)| line.as_str()).collect(),
+        lines[Plus].iter().map(|(line, _)| line.as_str()).collect(),
+        vec![Edit::Unchanged; lines[Minus].len()],
+        Edit::Deleted,
+        vec![Edit::Unchanged; lines[Plus].len()],
+        Edit::Inserted,
+        &config.tokenization_regex,
+        config.max_line_distance,
+        config.max_line_distance_for_naively_paired_lines,
+        config.pairing_heuristic,
+    )
+}
+
+/// Return the byte offsets of the points of each minus line at which text was inserted without
+/// any text being removed there (used with --show-insertion-point), given the diff sections of the
+/// lines, in which the removed and added text is emph.
+fn get_insertion_points(
+    lines: &MinusPlus<&Vec<(String, State)>>,
+    diff_style_sections: &MinusPlus<Vec<LineSections<Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+) -> Vec<Vec<usize>> {
+    let mut insertion_points = vec![Vec::new(); lines[Minus].len()];
+    for &(minus_index, plus_index) in line_alignment {
+        let (Some(minus_index), Some(plus_index)) = (minus_index, plus_index) else {
+            continue;
+        };
+        // Raw lines are painted with the styles of their raw input.
+        if !matches!(lines[Minus][minus_index].1, State::HunkMinus(_, None)) {
+            continue;
+        }
+        let points = &mut insertion_points[minus_index];
+        let mut offset = 0;
+        let mut deleted = false;
+        let mut plus_sections = diff_style_sections[Plus][plus_index].iter().peekable();
+        for &(style, section) in &diff_style_sections[Minus][minus_index] {
+            if style.is_emph {
+                deleted = true;
+                offset += section.len();
+                continue;
+            }
+            let mut inserted = false;
+            while plus_sections.next_if(|(style, _)| style.is_emph).is_some() {
+                inserted = true;
+            }
+            if inserted && !deleted {
+                points.push(offset);
+            }
+            deleted = false;
+            offset += section.len();
+            // Skip the plus line's copy of the unchanged section.
+            let mut n_bytes = section.len();
+            while n_bytes > 0 {
+                match plus_sections.next() {
+                    Some((_, plus_section)) => n_bytes = n_bytes.saturating_sub(plus_section.len()),
+                    None => break,
+                }
+            }
+        }
+        if plus_sections.any(|(style, _)| style.is_emph) && !deleted {
+            points.push(offset);
+        }
+        let content_length = lines[Minus][minus_index].0.trim_end_matches('\n').len();
+        for point in points.iter_mut() {
+            *point = (*point).min(content_length);
+        }
+    }
+    insertion_points
+}
+
+/// Insert a marker in insertion-point-style at each of the insertion points of the minus lines.
+fn insert_insertion_point_markers(
+    syntax_sections: &mut [LineSections<SyntectStyle>],
+    diff_sections: &mut [LineSections<Style>],
+    insertion_points: &[Vec<usize>],
+    config: &config::Config,
+) {
+    let marker_length = INSERTION_POINT_SYMBOL.len();
+    for ((syntax_sections, diff_sections), points) in syntax_sections
+        .iter_mut()
+        .zip(diff_sections.iter_mut())
+        .zip(insertion_points)
+    {
+        // Insert the markers from the end of the line, so that the points before them stay put.
+        for &point in points.iter().rev() {
+            insert_section(syntax_sections, point, INSERTION_POINT_SYMBOL, None);
+            insert_section(diff_sections, point, INSERTION_POINT_SYMBOL, None);
+        }
+        let marker_ranges: Vec<Range<usize>> = points
+            .iter()
+            .enumerate()
+            .map(|(i, point)| {
+                let offset = point + i * marker_length;
+                offset..offset + marker_length
+            })
+            .collect();
+        style_ranges(diff_sections, &marker_ranges, |_| {
+            config.insertion_point_style
+        });
+    }
+}
+
+/// If the only edit between a pair of homologous lines is the replacement of a single word by
+/// another, return the text preceding the word, the removed and added words, and the text
+/// following them.
+fn single_word_change<'a>(
+    minus_sections: &[(Edit, &'a str)],
+    plus_sections: &[(Edit, &'a str)],
+) -> Option<(String, &'a str, &'a str, String)> {
+    // The single edited section of a line, which is a word, and the text around it.
+    fn split_at_word<'a>(sections: &[(Edit, &'a str)]) -> Option<(String, &'a str, String)> {
+        let mut edited = sections
+            .iter()
+            .enumerate()
+            .filter(|(_, (edit, _))| *edit != Edit::Unchanged);
+        let (i, &(_, word)) = edited.next()?;
+        let is_word = !word.is_empty() && word.chars().all(|c| c.is_alphanumeric() || c == '_');
+        if edited.next().is_some() || !is_word {
+            return None;
+        }
+        let text = |sections: &[(Edit, &str)]| sections.iter().map(|(_, s)| *s).collect();
+        Some((text(&sections[..i]), word, text(&sections[i + 1..])))
+    }
+    let (prefix, old_word, suffix) = split_at_word(minus_sections)?;
+    let (plus_prefix, new_word, plus_suffix) = split_at_word(plus_sections)?;
+    (prefix == plus_prefix && suffix == plus_suffix).then_some((prefix, old_word, new_word, suffix))
+}
+
+/// Paint each pair of homologous minus and plus lines as a single line, in which the deleted
+/// sections are struck through and the inserted sections are emphasized (used with
+/// --inline-only). A pair differing in a single word is shown as the line with the word replaced
+/// by `old→new` instead, with --inline-single-word. Lines without a homolog are painted as usual.
+#[allow(clippy::type_complexity)]
+fn paint_minus_and_plus_lines_inline(
+    lines: MinusPlus<&Vec<(String, State)>>,
+    (minus_edits, plus_edits, line_alignment): (
+        Vec<Vec<(Edit, &str)>>,
+        Vec<Vec<(Edit, &str)>>,
+        Vec<(Option<usize>, Option<usize>)>,
+    ),
+    line_numbers_data: &mut Option<LineNumbersData>,
+    highlighter: &mut Option<HighlightLines>,
+    output_buffer: &mut String,
+    config: &config::Config,
+) {
+    let mut deleted_style = config.minus_emph_style;
+    deleted_style.ansi_term_style.is_strikethrough = true;
+
+    for (minus_index, plus_index) in line_alignment {
+        let (line, state, sections, empty_line_style) = match (minus_index, plus_index) {
+            (Some(minus_index), Some(plus_index)) => {
+                let mut line = String::new();
+                let mut sections = Vec::new();
+                let mut push_section = |style: Style, section: &str| {
+                    sections.push((style, line.len()..line.len() + section.len()));
+                    line.push_str(section);
+                };
+                let single_word_change = config
+                    .inline_single_word
+                    .then(|| single_word_change(&minus_edits[minus_index], &plus_edits[plus_index]))
+                    .flatten();
+                if let Some((prefix, old_word, new_word, suffix)) = single_word_change {
+                    push_section(config.zero_style, &prefix);
+                    push_section(config.minus_emph_style, old_word);
+                    push_section(config.inline_hint_style, SINGLE_WORD_CHANGE_ARROW);
+                    push_section(config.plus_emph_style, new_word);
+                    push_section(config.zero_style, &suffix);
+                    let state = State::HunkZero(DiffType::Unified, None);
+                    (line, state, sections, None)
+                } else {
+                    let mut plus_sections = plus_edits[plus_index].iter().peekable();
+                    for &(edit, section) in &minus_edits[minus_index] {
+                        if edit == Edit::Deleted {
+                            push_section(deleted_style, section);
+                            continue;
+                        }
+                        while let Some((_, inserted)) =
+                            plus_sections.next_if(|(edit, _)| *edit == Edit::Inserted)
+                        {
+                            push_section(config.plus_emph_style, inserted);
+                        }
+                        push_section(config.zero_style, section);
+                        // Skip the plus line's copy of the unchanged section.
+                        let mut n_bytes = section.len();
+                        while n_bytes > 0 {
+                            match plus_sections.next() {
+                                Some((_, plus_section)) => {
+                                    n_bytes = n_bytes.saturating_sub(plus_section.len())
+                                }
+                                None => break,
+                            }
+                        }
+                    }
+                    for (_, inserted) in plus_sections {
+                        push_section(config.plus_emph_style, inserted);
+                    }
+                    let state = State::HunkZero(DiffType::Unified, None);
+                    (line, state, sections, None)
+                }
+            }
+            (Some(minus_index), None) => {
+                let (line, state) = lines[Minus][minus_index].clone();
+                let sections = vec![(config.minus_style, 0..line.len())];
+                (
+                    line,
+                    state,
+                    sections,
+                    Some(config.minus_empty_line_marker_style),
+                )
+            }
+            (None, Some(plus_index)) => {
+                let (line, state) = lines[Plus][plus_index].clone();
+                let sections = vec![(config.plus_style, 0..line.len())];
+                (
+                    line,
+                    state,
+                    sections,
+                    Some(config.plus_empty_line_marker_style),
+                )
+            }
+            (None, None) => continue,
+        };
+        let lines = [(line, state)];
+        let syntax_style_sections =
+            get_syntax_style_sections_for_lines(&lines, highlighter.as_mut(), config);
+        let diff_style_sections = [sections
+            .into_iter()
+            .map(|(style, range)| (style, &lines[0].0[range]))
+            .collect::<Vec<_>>()];
+        Painter::paint_lines(
+            &lines,
+            &syntax_style_sections,
+            &diff_style_sections,
+            &[false],
+            output_buffer,
+            config,
+            &mut line_numbers_data.as_mut(),
+            empty_line_style,
+            BgShouldFill::default(),
+        );
+    }
+}
+
 pub fn get_syntax_style_sections_for_lines<'a>(
     lines: &'a [(String, State)],
     highlighter: Option<&mut HighlightLines>,
@@ -745,6 +1406,7 @@ pub fn get_syntax_style_sections_for_lines<'a>(
 #[allow(clippy::type_complexity)]
 fn get_diff_style_sections<'a>(
     lines: &MinusPlus<&'a Vec<(String, State)>>,
+    reflow_prose: bool,
     config: &config::Config,
 ) -> (
     MinusPlus<Vec<LineSections<'a, Style>>>,
@@ -758,8 +1420,13 @@ fn get_diff_style_sections<'a>(
         .iter()
         .map(|(s, state)| (s.as_str(), *config.get_style(state)))
         .unzip();
+    let infer_edits = if reflow_prose {
+        edits::infer_edits_of_paragraphs
+    } else {
+        edits::infer_edits
+    };
     let (minus_line_diff_style_sections, plus_line_diff_style_sections, line_alignment) =
-        edits::infer_edits(
+        infer_edits(
             minus_lines,
             plus_lines,
             minus_styles,
@@ -769,6 +1436,7 @@ fn get_diff_style_sections<'a>(
             &config.tokenization_regex,
             config.max_line_distance,
             config.max_line_distance_for_naively_paired_lines,
+            config.pairing_heuristic,
         );
     let diff_sections = MinusPlus::new(
         minus_line_diff_style_sections,
@@ -777,6 +1445,292 @@ fn get_diff_style_sections<'a>(
     (diff_sections, line_alignment)
 }
 
+/// If the first edit of each line of a block starts at nearly the same column, pad the lines so
+/// that they start at the same column, the greatest of them (used with --align-edits). The padding
+/// is given the style of the text preceding it, or `style` at the start of a line.
+fn align_edits<'a>(
+    syntax_sections: &mut [LineSections<'a, SyntectStyle>],
+    diff_sections: &mut [LineSections<'a, Style>],
+    style: Style,
+) {
+    // The byte offset and column of the first edit of each line.
+    let edits: Vec<Option<(usize, usize)>> = diff_sections
+        .iter()
+        .map(|sections| {
+            let i = sections.iter().position(|(style, _)| style.is_emph)?;
+            let preceding = &sections[..i];
+            Some((
+                preceding.iter().map(|(_, s)| s.len()).sum(),
+                preceding
+                    .iter()
+                    .map(|(_, s)| ansi::measure_text_width(s))
+                    .sum(),
+            ))
+        })
+        .collect();
+    let columns = || edits.iter().flatten().map(|(_, column)| *column);
+    let (Some(min_column), Some(max_column)) = (columns().min(), columns().max()) else {
+        return;
+    };
+    if columns().count() < 2 || max_column - min_column > ALIGN_EDITS_MAX_PADDING {
+        return;
+    }
+    for ((syntax_sections, diff_sections), edit) in syntax_sections
+        .iter_mut()
+        .zip(diff_sections.iter_mut())
+        .zip(edits)
+    {
+        let Some((offset, column)) = edit.filter(|(_, column)| *column < max_column) else {
+            continue;
+        };
+        let padding = &ALIGN_EDITS_PADDING[..max_column - column];
+        insert_section(syntax_sections, offset, padding, None);
+        insert_section(diff_sections, offset, padding, Some(style));
+    }
+}
+
+// Insert a section of `text` at byte `offset` of the line, splitting the section there if need
+// be. It is given the style of the preceding section, or `style` (if given) at the start of the
+// line, or that of the following section.
+fn insert_section<'a, S: Copy>(
+    sections: &mut LineSections<'a, S>,
+    offset: usize,
+    text: &'a str,
+    style: Option<S>,
+) {
+    let mut start = 0;
+    for i in 0..sections.len() {
+        let (section_style, section_text) = sections[i];
+        let end = start + section_text.len();
+        if offset == start {
+            let style = match i {
+                0 => style.unwrap_or(section_style),
+                _ => sections[i - 1].0,
+            };
+            sections.insert(i, (style, text));
+            return;
+        } else if offset < end {
+            let (head, tail) = section_text.split_at(offset - start);
+            sections.splice(
+                i..=i,
+                [
+                    (section_style, head),
+                    (section_style, text),
+                    (section_style, tail),
+                ],
+            );
+            return;
+        }
+        start = end;
+    }
+    if let Some(&(last_style, _)) = sections.last() {
+        sections.push((last_style, text));
+    }
+}
+
+// Replace the style of the byte `ranges` of the line, which are in order and do not overlap, by
+// `restyle` of it, splitting sections at their ends if need be.
+fn style_ranges<'a>(
+    sections: &mut LineSections<'a, Style>,
+    ranges: &[Range<usize>],
+    restyle: impl Fn(Style) -> Style,
+) {
+    if ranges.is_empty() {
+        return;
+    }
+    let mut styled = Vec::with_capacity(sections.len() + 2 * ranges.len());
+    let mut start = 0;
+    for &(section_style, text) in sections.iter() {
+        let end = start + text.len();
+        let cuts = ranges
+            .iter()
+            .flat_map(|range| [range.start, range.end])
+            .filter(|&cut| start < cut && cut < end)
+            .chain([end]);
+        let mut offset = start;
+        for cut in cuts {
+            let in_range = ranges.iter().any(|range| range.contains(&offset));
+            styled.push((
+                if in_range {
+                    restyle(section_style)
+                } else {
+                    section_style
+                },
+                &text[offset - start..cut - start],
+            ));
+            offset = cut;
+        }
+        start = end;
+    }
+    *sections = styled;
+}
+
+/// Paint the emph sections of a line beyond its first `budget` emph characters like its unchanged
+/// sections, or in `plain_style` if it has none (used with --inline-highlight-budget).
+fn limit_emph_to_budget(sections: &mut LineSections<Style>, budget: usize, plain_style: Style) {
+    let plain_style = sections
+        .iter()
+        .find(|(style, _)| !style.is_emph)
+        .map_or(plain_style, |(style, _)| *style);
+    let mut ranges = Vec::new();
+    let mut n_chars = 0;
+    let mut offset = 0;
+    for (style, text) in sections.iter() {
+        if style.is_emph {
+            if let Some((i, _)) = text.char_indices().nth(budget.saturating_sub(n_chars)) {
+                ranges.push(offset + i..offset + text.len());
+            }
+            n_chars += text.chars().count();
+        }
+        offset += text.len();
+    }
+    style_ranges(sections, &ranges, |_| plain_style);
+}
+
+/// Paint the sections of a line following its first emph section in the style of that section
+/// (used with --emph-extend=line).
+fn extend_emph_to_end_of_line(sections: &mut LineSections<Style>) {
+    let Some(i) = sections.iter().position(|(style, _)| style.is_emph) else {
+        return;
+    };
+    let emph_style = sections[i].0;
+    for (style, _) in sections[i + 1..].iter_mut() {
+        *style = emph_style;
+    }
+}
+
+/// Paint the unchanged sections of each pair of homologous lines with a background between the
+/// base color and the background of the emph style, according to the fraction of the pair which
+/// changed (used with --change-heat).
+fn tint_by_change_heat(
+    diff_sections: &mut MinusPlus<Vec<LineSections<Style>>>,
+    line_alignment: &[(Option<usize>, Option<usize>)],
+    config: &config::Config,
+) {
+    let n_bytes = |sections: &LineSections<Style>, emph: bool| -> usize {
+        sections
+            .iter()
+            .filter(|(style, _)| style.is_emph == emph)
+            .map(|(_, s)| s.len())
+            .sum()
+    };
+    for (minus_index, plus_index) in line_alignment {
+        let (Some(minus_index), Some(plus_index)) = (*minus_index, *plus_index) else {
+            continue;
+        };
+        let pair = [
+            &diff_sections[Minus][minus_index],
+            &diff_sections[Plus][plus_index],
+        ];
+        let changed: usize = pair.iter().map(|sections| n_bytes(sections, true)).sum();
+        let unchanged: usize = pair.iter().map(|sections| n_bytes(sections, false)).sum();
+        if changed + unchanged == 0 {
+            continue;
+        }
+        let heat = changed as f64 / (changed + unchanged) as f64;
+        for (side, index, emph_style) in [
+            (Minus, minus_index, config.minus_emph_style),
+            (Plus, plus_index, config.plus_emph_style),
+        ] {
+            let Some(background) = tint(emph_style.ansi_term_style.background, heat, config) else {
+                continue;
+            };
+            for (style, _) in diff_sections[side][index].iter_mut() {
+                if !style.is_emph && style.ansi_term_style.background.is_some() {
+                    style.ansi_term_style.background = Some(background);
+                }
+            }
+        }
+    }
+}
+
+// With --change-heat, the background of a line with a homolog extends in the tint of its unchanged
+// sections.
+fn change_heat_fill_style(
+    diff_sections: &[(Style, &str)],
+    config: &config::Config,
+) -> Option<Style> {
+    if !config.change_heat {
+        return None;
+    }
+    diff_sections
+        .iter()
+        .rev()
+        .map(|(style, _)| *style)
+        .find(|style| !style.is_emph && style.ansi_term_style.background.is_some())
+}
+
+// With --emph-extend=line, the background of a line with a change extends in the emph style.
+fn extended_emph_fill_style(
+    diff_sections: &[(Style, &str)],
+    config: &config::Config,
+) -> Option<Style> {
+    if config.emph_extend != EmphExtend::Line {
+        return None;
+    }
+    diff_sections
+        .iter()
+        .rev()
+        .find(|(_, s)| s != &"\n")
+        .map(|(style, _)| *style)
+        .filter(|style| style.is_emph)
+}
+
+// The color at `heat` (between 0 and 1) of the way from the base color to `color`.
+fn tint(
+    color: Option<ansi_term::Color>,
+    heat: f64,
+    config: &config::Config,
+) -> Option<ansi_term::Color> {
+    let (r, g, b) = match color? {
+        ansi_term::Color::RGB(r, g, b) => (r, g, b),
+        ansi_term::Color::Fixed(n) => ansi_colours::rgb_from_ansi256(n),
+        _ => return None,
+    };
+    let (base_r, base_g, base_b) = config.change_heat_base_color;
+    let mix = |base: u8, c: u8| (base as f64 + (c as f64 - base as f64) * heat).round() as u8;
+    let rgb = (mix(base_r, r), mix(base_g, g), mix(base_b, b));
+    if config.true_color || config.color_dither {
+        Some(ansi_term::Color::RGB(rgb.0, rgb.1, rgb.2))
+    } else {
+        Some(ansi_term::Color::Fixed(ansi_colours::ansi256_from_rgb(rgb)))
+    }
+}
+
+/// Return the number of consecutive blank added lines at the start of `lines`.
+fn blank_additions_run_length(lines: &[(String, State)]) -> usize {
+    lines
+        .iter()
+        .take_while(|(line, state)| {
+            matches!(state, State::HunkPlus(_, None)) && line.trim_end_matches('\n').is_empty()
+        })
+        .count()
+}
+
+/// Return the output row of each added line, counting from the first one: a run of blank added
+/// lines collapsed by --collapse-blank-additions is written on a single row.
+fn get_plus_line_rows(plus_lines: &[(String, State)], config: &Config) -> Vec<usize> {
+    let mut rows = Vec::with_capacity(plus_lines.len());
+    let mut row = 0;
+    let mut i = 0;
+    while i < plus_lines.len() {
+        let n_lines = if config.collapse_blank_additions {
+            blank_additions_run_length(&plus_lines[i..]).max(1)
+        } else {
+            1
+        };
+        rows.extend(std::iter::repeat_n(row, n_lines));
+        row += 1;
+        i += n_lines;
+    }
+    rows
+}
+
+/// Format the --annotate-lines tag of a line of the given kind ('-', '+' or ' ').
+pub fn format_line_tag(kind: char, line_number: usize) -> String {
+    format!("[{kind}][L{line_number}] ")
+}
+
 fn painted_prefix(state: State, config: &config::Config) -> Option<ANSIString> {
     use DiffType::*;
     use State::*;
@@ -795,7 +1749,19 @@ fn painted_prefix(state: State, config: &config::Config) -> Option<ANSIString> {
         (HunkPlus(Combined(MergeParents::Prefix(prefix), InMergeConflict::No), _), _) => {
             Some(config.plus_style.paint(prefix))
         }
-        // But otherwise we honor keep_plus_minus_markers
+        // But otherwise we honor line_prefixes, and then keep_plus_minus_markers
+        (HunkMinus(_, _), _) if config.line_prefixes.is_some() => config
+            .line_prefixes
+            .as_ref()
+            .map(|(_, minus, _)| config.minus_style.paint(minus.clone())),
+        (HunkZero(_, _), _) if config.line_prefixes.is_some() => config
+            .line_prefixes
+            .as_ref()
+            .map(|(_, _, zero)| config.zero_style.paint(zero.clone())),
+        (HunkPlus(_, _), _) if config.line_prefixes.is_some() => config
+            .line_prefixes
+            .as_ref()
+            .map(|(plus, _, _)| config.plus_style.paint(plus.clone())),
         (HunkMinus(_, _), true) => Some(config.minus_style.paint("-".to_string())),
         (HunkZero(_, _), true) => Some(config.zero_style.paint(" ".to_string())),
         (HunkPlus(_, _), true) => Some(config.plus_style.paint("+".to_string())),
@@ -1,5 +1,7 @@
 use std::collections::{HashMap, HashSet};
 
+use regex::Regex;
+
 use crate::cli;
 use crate::color;
 use crate::fatal;
@@ -60,6 +62,76 @@ pub fn parse_styles_map(opt: &cli::Opt) -> Option<HashMap<style::AnsiTermStyleEq
     }
 }
 
+/// Parse --status-map, e.g. "M=modified:yellow, A=added", into the label and style of each
+/// status letter.
+pub fn parse_status_map(opt: &cli::Opt) -> HashMap<char, (String, Style)> {
+    let mut status_map = HashMap::new();
+    for entry_str in opt.status_map.iter().flat_map(|s| s.split(',')) {
+        let entry_str = entry_str.trim();
+        if entry_str.is_empty() {
+            continue;
+        }
+        let mut letter_and_rest = entry_str.splitn(2, '=');
+        let (letter, rest) = match (letter_and_rest.next(), letter_and_rest.next()) {
+            (Some(letter), Some(rest)) if letter.trim().chars().count() == 1 => {
+                (letter.trim().chars().next().unwrap(), rest)
+            }
+            _ => fatal(format!(
+                "Invalid status-map entry: {entry_str}. \
+                 Expected LETTER=LABEL or LETTER=LABEL:STYLE."
+            )),
+        };
+        let (label, style) = match rest.split_once(':') {
+            Some((label, style_str)) => (
+                label,
+                parse_as_style_or_reference_to_git_config(style_str.trim(), opt),
+            ),
+            None => (rest, Style::new()),
+        };
+        status_map.insert(letter, (label.trim().to_string(), style));
+    }
+    status_map
+}
+
+/// Parse --semantic-highlight, a semicolon-separated list of rules of the form
+/// NAME:/REGEX/:STYLE, into the name, compiled regex and style of each rule.
+pub fn parse_semantic_highlight(opt: &cli::Opt) -> Vec<(String, Regex, Style)> {
+    let invalid_rule = |rule_str: &str| -> ! {
+        fatal(format!(
+            "Invalid semantic-highlight rule: {rule_str}. Expected NAME:/REGEX/:STYLE."
+        ))
+    };
+    let mut rules = Vec::new();
+    let mut rest = opt.semantic_highlight.as_deref().unwrap_or("");
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ';' || c.is_whitespace());
+        if rest.is_empty() {
+            break;
+        }
+        // The regex may contain ':' and ';', so the rule is split at ":/" and "/:" first.
+        let (name, regex_and_style) = rest.split_once(":/").unwrap_or_else(|| invalid_rule(rest));
+        let (regex_str, style_and_rest) = regex_and_style
+            .split_once("/:")
+            .unwrap_or_else(|| invalid_rule(rest));
+        let (style_str, next) = style_and_rest
+            .split_once(';')
+            .unwrap_or((style_and_rest, ""));
+        let name = name.trim();
+        if name.is_empty() || name.contains(';') || style_str.trim().is_empty() {
+            invalid_rule(rest);
+        }
+        let regex = Regex::new(regex_str).unwrap_or_else(|err| {
+            fatal(format!(
+                "Invalid regex in semantic-highlight rule {name}: {err}"
+            ))
+        });
+        let style = parse_as_style_or_reference_to_git_config(style_str.trim(), opt);
+        rules.push((name.to_string(), regex, style));
+        rest = next;
+    }
+    rules
+}
+
 fn resolve_style_references(
     edges: HashMap<&str, StyleReference>,
     opt: &cli::Opt,
@@ -123,7 +195,9 @@ fn parse_as_reference_to_git_config(style_string: &str, opt: &cli::Opt) -> Style
 
 fn make_hunk_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>) {
     let color_mode = opt.computed.color_mode;
-    let true_color = opt.computed.true_color;
+    // With --color-dither, keep 24-bit colors here. They are downsampled, with dithered
+    // backgrounds, when the output is written (see `ansi::dither`).
+    let true_color = opt.computed.true_color || opt.computed.color_dither;
     let minus_style = style_from_str(
         &opt.minus_style,
         Some(Style::from_colors(
@@ -300,6 +374,13 @@ fn make_line_number_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleRefer
     ])
 }
 
+fn style_from_str_or_commit_style(style: &Option<String>, opt: &cli::Opt) -> StyleReference {
+    match style {
+        Some(style) => style_from_str(style, None, None, opt.computed.true_color, opt.git_config()),
+        None => StyleReference::Reference("commit-style".to_owned()),
+    }
+}
+
 fn make_commit_file_hunk_header_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>) {
     let true_color = opt.computed.true_color;
     styles.extend([
@@ -313,6 +394,30 @@ fn make_commit_file_hunk_header_styles(opt: &cli::Opt, styles: &mut HashMap<&str
                 opt.git_config(),
             ),
         ),
+        (
+            "commit-merge-style",
+            style_from_str_or_commit_style(&opt.commit_merge_style, opt),
+        ),
+        (
+            "commit-ref-branch-style",
+            style_from_str_or_commit_style(&opt.commit_ref_branch_style, opt),
+        ),
+        (
+            "commit-ref-head-style",
+            style_from_str_or_commit_style(&opt.commit_ref_head_style, opt),
+        ),
+        (
+            "commit-ref-remote-style",
+            style_from_str_or_commit_style(&opt.commit_ref_remote_style, opt),
+        ),
+        (
+            "commit-ref-tag-style",
+            style_from_str_or_commit_style(&opt.commit_ref_tag_style, opt),
+        ),
+        (
+            "notes-style",
+            style_from_str(&opt.notes_style, None, None, true_color, opt.git_config()),
+        ),
         (
             "file-style",
             style_from_str_with_handling_of_special_decoration_attributes(
@@ -323,6 +428,20 @@ fn make_commit_file_hunk_header_styles(opt: &cli::Opt, styles: &mut HashMap<&str
                 opt.git_config(),
             ),
         ),
+        (
+            "file-copied-style",
+            if let Some(s) = &opt.file_copied_style {
+                style_from_str_with_handling_of_special_decoration_attributes(
+                    s,
+                    None,
+                    Some(&opt.file_decoration_style),
+                    true_color,
+                    opt.git_config(),
+                )
+            } else {
+                StyleReference::Reference("file-style".to_owned())
+            },
+        ),
         (
             "classic-grep-header-style",
             style_from_str_with_handling_of_special_decoration_attributes(
@@ -500,6 +619,56 @@ fn make_merge_conflict_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleRe
 }
 
 fn make_misc_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>) {
+    styles.insert(
+        "change-symbol-style",
+        style_from_str(
+            &opt.change_symbol_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "focus-line-style",
+        style_from_str(
+            &opt.focus_line_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "file-category-style",
+        style_from_str(
+            &opt.file_category_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "index-mode-change-style",
+        style_from_str(
+            &opt.index_mode_change_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "index-style",
+        style_from_str(
+            &opt.index_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
     styles.insert(
         "inline-hint-style",
         style_from_str(
@@ -510,6 +679,46 @@ fn make_misc_styles(opt: &cli::Opt, styles: &mut HashMap<&str, StyleReference>)
             opt.git_config(),
         ),
     );
+    styles.insert(
+        "insertion-point-style",
+        style_from_str(
+            &opt.insertion_point_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "side-by-side-gap-style",
+        style_from_str(
+            &opt.side_by_side_gap_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "wrap-symbol-minus-style",
+        style_from_str(
+            &opt.wrap_symbol_minus_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
+    styles.insert(
+        "wrap-symbol-plus-style",
+        style_from_str(
+            &opt.wrap_symbol_plus_style,
+            None,
+            None,
+            opt.computed.true_color,
+            opt.git_config(),
+        ),
+    );
     styles.insert(
         "git-minus-style",
         StyleReference::Style(
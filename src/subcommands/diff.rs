@@ -2,7 +2,9 @@ use std::path::Path;
 
 use crate::config::{self};
 
+use crate::utils::format_command;
 use crate::utils::git::retrieve_git_version;
+use crate::utils::line_diff;
 
 use crate::subcommands::{SubCmdKind, SubCommand};
 use std::ffi::OsString;
@@ -37,6 +39,8 @@ pub fn build_diff_cmd(
     let via_process_substitution =
         |f: &Path| f.starts_with("/proc/self/fd/") || f.starts_with("/dev/fd/");
 
+    let context_arg = config.context.map(|n| format!("-U{n}"));
+
     // https://stackoverflow.com/questions/22706714/why-does-git-diff-not-work-with-process-substitution
     // git <2.42 does not support process substitution
     let (differ, mut diff_cmd) = match retrieve_git_version() {
@@ -45,17 +49,16 @@ pub fn build_diff_cmd(
                 || !(via_process_substitution(minus_file)
                     || via_process_substitution(plus_file)) =>
         {
-            (
-                SubCmdKind::GitDiff,
-                vec!["git", "diff", "--no-index", "--color"],
-            )
+            let mut diff_cmd = vec!["git", "diff", "--no-index", "--color"];
+            diff_cmd.extend(context_arg.as_deref());
+            (SubCmdKind::GitDiff, diff_cmd)
         }
         _ => (
             SubCmdKind::Diff,
             if diff_args_set_unified_context(&diff_args) {
                 vec!["diff"]
             } else {
-                vec!["diff", "-U3"]
+                vec!["diff", context_arg.as_deref().unwrap_or("-U3")]
             },
         ),
     };
@@ -73,6 +76,39 @@ pub fn build_diff_cmd(
     Ok(SubCommand::new(differ, diff_cmd))
 }
 
+/// Read the old and new versions of a file from the file descriptors given by --old-fd and
+/// --new-fd, and return the unified diff of their lines.
+pub fn diff_fds(old_fd: u32, new_fd: u32, config: &config::Config) -> std::io::Result<String> {
+    diff_files(
+        Path::new(&format!("/dev/fd/{old_fd}")),
+        Path::new(&format!("/dev/fd/{new_fd}")),
+        config,
+    )
+}
+
+/// Return the unified diff of the lines of two files, computed without running a command (used
+/// by --old-fd and --two-file-backend=internal). The files are formatted first if --format-command
+/// gives a command for their language.
+pub fn diff_files(
+    minus_file: &Path,
+    plus_file: &Path,
+    config: &config::Config,
+) -> std::io::Result<String> {
+    let read_file = |path: &Path| {
+        std::fs::read(path).map(|contents| {
+            let contents = String::from_utf8_lossy(&contents).into_owned();
+            format_command::format_file(&config.format_commands, path, contents)
+        })
+    };
+    Ok(line_diff::unified_diff(
+        &read_file(minus_file)?,
+        &read_file(plus_file)?,
+        &minus_file.to_string_lossy(),
+        &plus_file.to_string_lossy(),
+        config.context.unwrap_or(3),
+    ))
+}
+
 /// Do the user-supplied `diff` args set the unified context?
 fn diff_args_set_unified_context<I, S>(args: I) -> bool
 where
@@ -100,7 +136,12 @@ mod main_tests {
     use std::ffi::OsString;
     use std::io::Cursor;
 
-    use super::diff_args_set_unified_context;
+    use std::path::Path;
+
+    use super::{build_diff_cmd, diff_args_set_unified_context, diff_fds};
+    use crate::ansi::strip_ansi_codes;
+    use crate::config::TwoFileBackend;
+    use crate::tests::integration_test_utils::make_config_from_args;
 
     use rstest::rstest;
 
@@ -120,6 +161,26 @@ mod main_tests {
         assert_eq!(diff_args_set_unified_context(diff_args), expected)
     }
 
+    fn diff_cmd_args(args: &[&str]) -> Vec<OsString> {
+        let config = make_config_from_args(&[args, &["/dev/null"]].concat());
+        build_diff_cmd(Path::new("/dev/null"), Path::new("/dev/null"), &config)
+            .unwrap()
+            .args
+    }
+
+    #[test]
+    fn test_diff_cmd_context() {
+        let args = diff_cmd_args(&["--context=7"]);
+        assert_eq!(args[..5], ["git", "diff", "--no-index", "--color", "-U7"]);
+        // A context set by --diff-args takes precedence.
+        let args = diff_cmd_args(&["--context=7", "--diff-args=-U2"]);
+        assert_eq!(args[4..6], ["-U7", "-U2"]);
+        let args = diff_cmd_args(&[]);
+        assert!(!args
+            .iter()
+            .any(|arg| arg.to_string_lossy().starts_with("-U")));
+    }
+
     enum ExpectDiff {
         Yes,
         No,
@@ -159,4 +220,120 @@ mod main_tests {
             }
         );
     }
+
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn test_two_file_backend() {
+        let dir = std::env::temp_dir().join("delta__test_two_file_backend");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (old_path, new_path) = (dir.join("old.txt"), dir.join("new.txt"));
+        std::fs::write(&old_path, "a\nb\nc\n").unwrap();
+        std::fs::write(&new_path, "a\nB\nc\n").unwrap();
+        let run = |args: &[&str]| {
+            let mut writer = Cursor::new(vec![]);
+            let mut runargs: Vec<OsString> = ["--no-gitconfig"]
+                .iter()
+                .chain(args)
+                .map(OsString::from)
+                .collect();
+            runargs.extend([old_path.clone().into(), new_path.clone().into()]);
+            let exit_code = crate::run_app(runargs, Some(&mut writer)).unwrap();
+            let output = strip_ansi_codes(std::str::from_utf8(writer.get_ref()).unwrap());
+            (exit_code, output)
+        };
+
+        let config = make_config_from_args(&[]);
+        assert_eq!(config.two_file_backend, TwoFileBackend::Git);
+
+        // git is run, and rejects the option which is passed to it.
+        let (exit_code, output) = run(&["--diff-args=--no-such-option"]);
+        assert_eq!(exit_code, 129);
+        assert!(output.is_empty());
+
+        // No command is run, so the option has no effect.
+        let (exit_code, output) = run(&[
+            "--two-file-backend=internal",
+            "--diff-args=--no-such-option",
+        ]);
+        assert_eq!(exit_code, 1);
+        assert!(output.contains(&format!(
+            "{} ⟶   {}",
+            old_path.display(),
+            new_path.display()
+        )));
+        assert!(output.contains("\nb\nB\n"));
+
+        let (exit_code, output) = run(&["--two-file-backend=git"]);
+        assert_eq!(exit_code, 1);
+        assert!(output.contains("\nb\nB\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_format_command() {
+        let dir = std::env::temp_dir().join("delta__test_format_command");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (old_path, new_path) = (dir.join("old.py"), dir.join("new.py"));
+        std::fs::write(&old_path, "a = 1\nb = 2\n").unwrap();
+        std::fs::write(&new_path, "a  =  1\nb = 3\n").unwrap();
+        let run = |args: &[&str]| {
+            let mut writer = Cursor::new(vec![]);
+            let mut runargs: Vec<OsString> = ["--no-gitconfig"]
+                .iter()
+                .chain(args)
+                .map(OsString::from)
+                .collect();
+            runargs.extend([old_path.clone().into(), new_path.clone().into()]);
+            crate::run_app(runargs, Some(&mut writer)).unwrap();
+            strip_ansi_codes(std::str::from_utf8(writer.get_ref()).unwrap())
+        };
+
+        let output = run(&[]);
+        assert!(output.contains("\na = 1\nb = 2\na  =  1\nb = 3\n"));
+        // The formatting noise is removed by the formatter before the files are diffed.
+        let output = run(&["--format-command=python=tr -s ' '"]);
+        assert!(output.contains("\na = 1\nb = 2\nb = 3\n"));
+        assert!(!output.contains("a  =  1"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_diff_fds() {
+        use std::os::unix::io::AsRawFd;
+
+        let dir = std::env::temp_dir().join("delta__test_diff_fds");
+        std::fs::create_dir_all(&dir).unwrap();
+        let (old_path, new_path) = (dir.join("old.txt"), dir.join("new.txt"));
+        std::fs::write(&old_path, "a\nb\nc\n").unwrap();
+        std::fs::write(&new_path, "a\nB\nc\n").unwrap();
+        let old = std::fs::File::open(&old_path).unwrap();
+        let new = std::fs::File::open(&new_path).unwrap();
+        let (old_fd, new_fd) = (old.as_raw_fd(), new.as_raw_fd());
+
+        let config = make_config_from_args(&[]);
+        assert_eq!(
+            diff_fds(old_fd as u32, new_fd as u32, &config).unwrap(),
+            format!(
+                "--- /dev/fd/{old_fd}\n+++ /dev/fd/{new_fd}\n@@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n"
+            )
+        );
+
+        let mut writer = Cursor::new(vec![]);
+        let exit_code = crate::run_app(
+            [
+                "--no-gitconfig",
+                "--exit-code",
+                &format!("--old-fd={old_fd}"),
+                &format!("--new-fd={new_fd}"),
+            ]
+            .iter()
+            .map(OsString::from)
+            .collect(),
+            Some(&mut writer),
+        );
+        assert_eq!(exit_code.unwrap(), 1);
+        let output = strip_ansi_codes(std::str::from_utf8(writer.get_ref()).unwrap());
+        assert!(output.contains(&format!("/dev/fd/{old_fd} ⟶   /dev/fd/{new_fd}")));
+        assert!(output.contains("\nb\nB\n"));
+    }
 }
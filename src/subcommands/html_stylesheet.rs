@@ -0,0 +1,32 @@
+use std::io::Write;
+
+use crate::ansi;
+use crate::config;
+
+pub fn html_stylesheet(config: &config::Config, writer: &mut dyn Write) -> std::io::Result<()> {
+    write!(writer, "{}", ansi::html::stylesheet(config))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tests::integration_test_utils;
+
+    use super::*;
+
+    #[test]
+    fn test_html_stylesheet() {
+        let config = integration_test_utils::make_config_from_args(&[
+            "--true-color=always",
+            "--minus-style=#ff0000 #3f0000",
+            "--plus-emph-style=bold #00ff00 #007f00",
+        ]);
+        let mut css = Vec::new();
+        html_stylesheet(&config, &mut css).unwrap();
+        let css = String::from_utf8(css).unwrap();
+        assert!(css.contains(".delta-fill { display: inline-block; width: 100%; }\n"));
+        assert!(css.contains(".delta-minus { color: #ff0000; background-color: #3f0000; }\n"));
+        assert!(css.contains(
+            ".delta-plus-emph { color: #00ff00; background-color: #007f00; font-weight: bold; }\n"
+        ));
+    }
+}
@@ -1,12 +1,15 @@
 // internal subcommands:
 pub mod generate_completion;
+pub mod html_stylesheet;
 pub mod list_syntax_themes;
 pub mod parse_ansi;
+pub mod parse_style;
 mod sample_diff;
 pub mod show_colors;
 pub mod show_config;
 pub mod show_syntax_themes;
 pub mod show_themes;
+pub mod strip_ansi;
 
 // start external processes, e.g. `git diff` or `rg`, output is read by delta
 pub mod diff;
@@ -0,0 +1,139 @@
+use std::io::{self, Write};
+
+use crate::color;
+use crate::git_config::GitConfig;
+use crate::style::Style;
+
+const SAMPLE_TEXT: &str = "The quick brown fox jumps over the lazy dog";
+
+#[cfg(not(tarpaulin_include))]
+pub fn parse_style(
+    style_string: &str,
+    true_color: bool,
+    git_config: Option<&GitConfig>,
+) -> std::io::Result<()> {
+    let style = Style::from_str(style_string, None, None, true_color, git_config);
+    write_parsed_style(&style, &mut io::stdout().lock())
+}
+
+/// Write the colors and attributes of a parsed style, and a sample of text painted in it (used
+/// by --parse-style).
+fn write_parsed_style(style: &Style, writer: &mut dyn Write) -> std::io::Result<()> {
+    let color_name = |color: Option<ansi_term::Color>| {
+        color.map_or("normal".to_string(), color::color_to_string)
+    };
+    let foreground = if style.is_syntax_highlighted {
+        "syntax".to_string()
+    } else {
+        color_name(style.ansi_term_style.foreground)
+    };
+    let attributes = get_attributes(style);
+    writeln!(writer, "style:      {style}")?;
+    writeln!(writer, "foreground: {foreground}")?;
+    writeln!(
+        writer,
+        "background: {}",
+        color_name(style.ansi_term_style.background)
+    )?;
+    writeln!(
+        writer,
+        "attributes: {}",
+        if attributes.is_empty() {
+            "none".to_string()
+        } else {
+            attributes.join(" ")
+        }
+    )?;
+    writeln!(
+        writer,
+        "sample:     {}",
+        style.ansi_term_style.paint(SAMPLE_TEXT)
+    )?;
+    Ok(())
+}
+
+// The names of the attributes of the style, as they are given in style strings.
+fn get_attributes(style: &Style) -> Vec<&'static str> {
+    let ansi_term_style = style.ansi_term_style;
+    [
+        (style.is_raw, "raw"),
+        (style.is_omitted, "omit"),
+        (ansi_term_style.is_blink, "blink"),
+        (ansi_term_style.is_bold, "bold"),
+        (ansi_term_style.is_dimmed, "dim"),
+        (ansi_term_style.is_hidden, "hidden"),
+        (ansi_term_style.is_italic, "italic"),
+        (ansi_term_style.is_reverse, "reverse"),
+        (ansi_term_style.is_strikethrough, "strike"),
+        (ansi_term_style.is_underline, "ul"),
+    ]
+    .iter()
+    .filter_map(|&(is_set, name)| is_set.then_some(name))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::strip_ansi_codes;
+
+    fn parse(style_string: &str) -> String {
+        let style = Style::from_str(style_string, None, None, true, None);
+        let mut output = Vec::new();
+        write_parsed_style(&style, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_parse_style() {
+        let output = parse("bold red ul");
+        assert_eq!(
+            strip_ansi_codes(&output),
+            "\
+style:      bold ul red
+foreground: red
+background: normal
+attributes: bold ul
+sample:     The quick brown fox jumps over the lazy dog
+"
+        );
+        assert!(output.contains("\x1b[1;4;31mThe quick brown fox"));
+
+        assert_eq!(
+            strip_ansi_codes(&parse("syntax \"#ff0000\" italic strike")),
+            "\
+style:      italic strike syntax \"#ff0000\"
+foreground: syntax
+background: \"#ff0000\"
+attributes: italic strike
+sample:     The quick brown fox jumps over the lazy dog
+"
+        );
+
+        let output = strip_ansi_codes(&parse(""));
+        assert!(output.contains("foreground: normal\n"));
+        assert!(output.contains("attributes: none\n"));
+
+        let output = strip_ansi_codes(&parse("raw"));
+        assert!(output.starts_with("style:      raw\n"));
+        assert!(output.contains("attributes: raw\n"));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid color or style attribute: bolder")]
+    fn test_parse_style_invalid_attribute() {
+        parse("bolder red");
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid style string: red blue green")]
+    fn test_parse_style_too_many_colors() {
+        parse("red blue green");
+    }
+
+    #[test]
+    #[should_panic(expected = "'syntax' as a background color")]
+    fn test_parse_style_syntax_background() {
+        parse("red syntax");
+    }
+}
@@ -0,0 +1,46 @@
+use std::io::{self, BufRead, Write};
+
+use crate::ansi;
+
+#[cfg(not(tarpaulin_include))]
+pub fn strip_ansi() -> std::io::Result<()> {
+    strip_ansi_from(&mut io::stdin().lock(), &mut io::stdout().lock())
+}
+
+/// Write the lines read from `reader` with all ANSI escape sequences (e.g. colors and
+/// hyperlinks) removed.
+fn strip_ansi_from(reader: &mut dyn BufRead, writer: &mut dyn Write) -> std::io::Result<()> {
+    let mut line = Vec::new();
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        writer.write_all(ansi::strip_ansi_codes(&String::from_utf8_lossy(&line)).as_bytes())?;
+        line.clear();
+    }
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strip(input: &str) -> String {
+        let mut output = Vec::new();
+        strip_ansi_from(&mut input.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_strip_ansi() {
+        assert_eq!(
+            strip("\x1b[1;31m- a\x1b[0m\n\x1b[32m+ b\x1b[K\x1b[0m\n  c\n"),
+            "- a\n+ b\n  c\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_ansi_removes_hyperlinks() {
+        assert_eq!(
+            strip("\x1b]8;;file:///a.txt\x1b\\a.txt\x1b]8;;\x1b\\:\x1b[34m1\x1b[0m"),
+            "a.txt:1"
+        );
+    }
+}
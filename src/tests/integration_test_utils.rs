@@ -12,11 +12,13 @@ use itertools::Itertools;
 use crate::ansi;
 use crate::cli;
 use crate::config;
-use crate::delta::delta;
+use crate::delta::{delta, StateMachine};
 use crate::env::DeltaEnv;
 use crate::git_config::GitConfig;
 use crate::tests::test_utils;
 use crate::utils::process::tests::FakeParentArgs;
+use crate::utils::text_diff::TextDiffSource;
+use crate::utils::trailing_context::FileSource;
 
 pub fn make_options_from_args_and_git_config(
     args: &[&str],
@@ -312,6 +314,47 @@ pub fn run_delta(input: &str, config: &config::Config) -> String {
     String::from_utf8(writer).unwrap()
 }
 
+/// Run delta on `input` as `run_delta` does, but with the state machine first prepared by
+/// `set_up`, e.g. to replace a source it reads from with a stub.
+pub fn run_delta_with_machine<F>(input: &str, config: &config::Config, set_up: F) -> String
+where
+    F: FnOnce(&mut StateMachine),
+{
+    let mut writer: Vec<u8> = Vec::new();
+    let mut machine = StateMachine::new(&mut writer, config);
+    set_up(&mut machine);
+    machine
+        .consume(ByteLines::new(BufReader::new(input.as_bytes())))
+        .unwrap();
+    drop(machine);
+    String::from_utf8(writer).unwrap()
+}
+
+/// A source of files which only has the file whose path ends with `path`, holding `lines`.
+pub struct StubFiles {
+    pub path: &'static str,
+    pub lines: &'static [&'static str],
+}
+
+impl FileSource for StubFiles {
+    fn read_lines(&mut self, path: &Path) -> Option<Vec<String>> {
+        path.ends_with(self.path)
+            .then(|| self.lines.iter().map(|line| line.to_string()).collect())
+    }
+}
+
+/// A source of text diffs which can only diff the blobs with ids `blob_ids`, giving `text_diff`.
+pub struct StubTextDiff {
+    pub blob_ids: (&'static str, &'static str),
+    pub text_diff: &'static str,
+}
+
+impl TextDiffSource for StubTextDiff {
+    fn text_diff(&mut self, minus_blob_id: &str, plus_blob_id: &str) -> Option<String> {
+        ((minus_blob_id, plus_blob_id) == self.blob_ids).then(|| self.text_diff.to_string())
+    }
+}
+
 pub mod tests {
     use super::*;
 
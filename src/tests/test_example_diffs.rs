@@ -46,6 +46,32 @@ mod tests {
             .expect_contains_once("\ncopied: first_file ⟶   copied_file\n");
     }
 
+    #[test]
+    fn test_copied_file_style() {
+        let args = &[
+            "--file-style=blue",
+            "--file-copied-style=bold green",
+            "--file-decoration-style=none",
+        ];
+        DeltaTest::with_args(args)
+            .explain_ansi()
+            .with_input(GIT_DIFF_WITH_COPIED_FILE)
+            .expect_contains_once("\n(bold green)copied: first_file ⟶   copied_file(normal)\n");
+        DeltaTest::with_args(args)
+            .explain_ansi()
+            .with_input(RENAMED_FILE_INPUT)
+            .expect_contains_once("\n(blue)renamed: a.py ⟶   b.py(normal)\n");
+    }
+
+    #[test]
+    fn test_copied_file_with_changes() {
+        DeltaTest::with_args(&["--file-copied-style=green", "--file-decoration-style=none"])
+            .explain_ansi()
+            .with_input(GIT_DIFF_WITH_COPIED_FILE_WITH_CHANGES)
+            .expect_contains_once("\n(green)copied: a.txt ⟶   b.txt(normal)\n")
+            .expect_contains("\n(normal 52)b(normal)\n(231 22)c(normal)");
+    }
+
     #[test]
     fn test_renamed_file_with_changes() {
         let t = DeltaTest::with_args(&[])
@@ -56,6 +82,45 @@ mod tests {
         println!("{}", t.output);
     }
 
+    #[test]
+    fn test_compact_renames() {
+        let config = integration_test_utils::make_config_from_args(&["--compact-renames"]);
+        let output = integration_test_utils::run_delta(RENAMES_ONLY_DIFF, &config);
+        let output = strip_ansi_codes(&output);
+        assert!(!output.contains("renamed:"));
+        assert!(output.contains(
+            "\n\
+src/a.py          ⟶   src/b.py
+README            ⟶   README.md
+docs/old/guide.md ⟶   docs/guide.md
+"
+        ));
+    }
+
+    #[test]
+    fn test_compact_renames_is_interrupted_by_other_files() {
+        let config = integration_test_utils::make_config_from_args(&["--compact-renames"]);
+        let output = integration_test_utils::run_delta(
+            &format!("{}{}", RENAMES_ONLY_DIFF, RENAMED_FILE_WITH_CHANGES_INPUT),
+            &config,
+        );
+        let output = strip_ansi_codes(&output);
+        let table_row = output.find("docs/old/guide.md ⟶   docs/guide.md").unwrap();
+        let renamed_with_changes = output
+            .find("renamed: Casks/font-dejavusansmono-nerd-font.rb")
+            .unwrap();
+        assert!(table_row < renamed_with_changes);
+        assert_eq!(output.matches("renamed:").count(), 1);
+    }
+
+    #[test]
+    fn test_compact_renames_disabled() {
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let output = integration_test_utils::run_delta(RENAMES_ONLY_DIFF, &config);
+        let output = strip_ansi_codes(&output);
+        assert_eq!(output.matches("renamed:").count(), 3);
+    }
+
     #[test]
     fn test_recognized_file_type() {
         // In addition to the background color, the code has language syntax highlighting.
@@ -96,6 +161,61 @@ mod tests {
         ansi_test_utils::assert_has_plus_color_only(&output, &config);
     }
 
+    #[test]
+    fn test_recognized_file_type_no_syntax_theme() {
+        // Syntax highlighting is disabled by both "none" and the empty string, but the diff
+        // background colors are retained.
+        for syntax_theme in ["none", "None", ""] {
+            let config = integration_test_utils::make_config_from_args(&[
+                "--syntax-theme",
+                syntax_theme,
+                "--width",
+                "variable",
+            ]);
+            assert!(config.syntax_theme.is_none());
+            let output = integration_test_utils::get_line_of_code_from_delta(
+                ADDED_FILE_INPUT,
+                14,
+                "class X:",
+                &config,
+            );
+            ansi_test_utils::assert_has_plus_color_only(&output, &config);
+        }
+    }
+
+    #[test]
+    fn test_no_syntax_theme_keeps_minus_and_plus_colors() {
+        let input = "\
+diff --git a/a.py b/a.py
+index 8d1c8b6..8b13789 100644
+--- a/a.py
++++ b/a.py
+@@ -1 +1 @@
+-class X:
++class Y:
+";
+        let config = integration_test_utils::make_config_from_args(&[
+            "--syntax-theme",
+            "none",
+            "--width",
+            "variable",
+        ]);
+        let output = integration_test_utils::run_delta(input, &config);
+        for expected in ["class X:", "class Y:"] {
+            let line = output
+                .lines()
+                .find(|line| strip_ansi_codes(line) == expected)
+                .unwrap();
+            for (style, s) in ansi::parse_style_sections(line) {
+                if !s.is_empty() {
+                    // The diff background color is present, but there is no syntax foreground.
+                    assert!(style.background.is_some(), "{:?} lacks background color", s);
+                    assert!(style.foreground.is_none(), "{:?} has foreground color", s);
+                }
+            }
+        }
+    }
+
     #[test]
     fn test_default_language_is_used_for_syntax_highlighting() {
         // Note: default-language will be used for files with no extension, but also
@@ -226,6 +346,112 @@ index 0123456..1234567 100644
         assert_eq!(output, NOT_A_DIFF_OUTPUT.to_owned() + "\n");
     }
 
+    #[test]
+    fn test_exit_code() {
+        use crate::delta::delta;
+        use bytelines::ByteLines;
+        use std::io::BufReader;
+
+        let run = |args: &[&str], input: &str| {
+            let config = integration_test_utils::make_config_from_args(args);
+            let mut writer = Vec::new();
+            let changes_found = delta(
+                ByteLines::new(BufReader::new(input.as_bytes())),
+                &mut writer,
+                &config,
+            )
+            .unwrap();
+            config.success_exit_code(changes_found)
+        };
+
+        // Without --exit-code, success is always reported as 0.
+        assert_eq!(run(&[], ""), 0);
+        assert_eq!(run(&[], ADDED_FILE_INPUT), 0);
+
+        assert_eq!(run(&["--exit-code"], ""), 0);
+        assert_eq!(run(&["--exit-code"], NOT_A_DIFF_OUTPUT), 0);
+        // Changes without added or removed lines are changes.
+        assert_eq!(run(&["--exit-code"], RENAMED_FILE_INPUT), 1);
+        assert_eq!(run(&["--exit-code"], BINARY_FILES_DIFFER), 1);
+        let mode_change = "diff --git a/a.sh b/a.sh\nold mode 100644\nnew mode 100755\n";
+        assert_eq!(run(&["--exit-code"], mode_change), 1);
+        let empty_file = "diff --git a/a b/a\nnew file mode 100644\nindex 0000000..e69de29\n";
+        assert_eq!(run(&["--exit-code"], empty_file), 1);
+        assert_eq!(run(&["--exit-code"], ADDED_FILE_INPUT), 1);
+        assert_eq!(run(&["--exit-code"], DIFF_UNIFIED_TWO_FILES), 1);
+    }
+
+    #[test]
+    fn test_latin_1_content_is_transcoded() {
+        use crate::delta::delta;
+        use bytelines::ByteLines;
+        use std::io::BufReader;
+
+        let input: &[u8] = b"\
+diff --git a/menu.txt b/menu.txt
+index 8b0d958..e69de29 100644
+--- a/menu.txt
++++ b/menu.txt
+@@ -1 +1 @@
+-caf\xe9
++cr\xe8me br\xfbl\xe9e
+";
+        let run = |args: &[&str]| {
+            let config = integration_test_utils::make_config_from_args(args);
+            let mut writer = Vec::new();
+            delta(ByteLines::new(BufReader::new(input)), &mut writer, &config).unwrap();
+            strip_ansi_codes(&String::from_utf8(writer).unwrap())
+        };
+
+        let output = run(&[]);
+        assert!(output.contains("\ncafé\n"));
+        assert!(output.contains("\ncrème brûlée\n"));
+        assert!(output.ends_with("\n(transcoded from Latin-1)\n"));
+
+        let output = run(&["--assume-encoding=utf-8"]);
+        assert!(output.contains("\ncaf\u{fffd}\n"));
+        assert!(!output.contains("transcoded"));
+    }
+
+    #[test]
+    fn test_utf_16_file_is_transcoded() {
+        use crate::delta::delta;
+        use bytelines::ByteLines;
+        use std::io::BufReader;
+
+        // The lines of content of the first file are UTF-16LE, and only the first starts with a
+        // byte order mark. The second line is valid UTF-8.
+        let input: &[u8] = b"\
+diff --git a/menu.txt b/menu.txt
+index 8b0d958..e69de29 100644
+--- a/menu.txt
++++ b/menu.txt
+@@ -1,2 +1,2 @@
+-\xff\xfec\x00a\x00f\x00\xe9\x00
+-\x00t\x00e\x00a\x00
++\xff\xfec\x00a\x00f\x00\xe9\x00
++\x00m\x00i\x00l\x00k\x00
+diff --git a/b.txt b/b.txt
+index 8b0d958..e69de29 100644
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-caf\xe9
++cr\xe8me
+";
+        let config = integration_test_utils::make_config_from_args(&[]);
+        let mut writer = Vec::new();
+        delta(ByteLines::new(BufReader::new(input)), &mut writer, &config).unwrap();
+        let output = strip_ansi_codes(&String::from_utf8(writer).unwrap());
+        assert!(output.contains("\ncafé\n"));
+        assert!(output.contains("\ntea\n"));
+        assert!(output.contains("\nmilk\n"));
+        assert!(output.contains("\n(transcoded from UTF-16LE)\n"));
+        // The second file is not UTF-16.
+        assert!(output.contains("\ncrème\n"));
+        assert!(output.ends_with("\n(transcoded from Latin-1)\n"));
+    }
+
     #[test]
     fn test_certain_bugs_are_not_present() {
         for input in [
@@ -281,6 +507,19 @@ index 0123456..1234567 100644
             );
     }
 
+    #[test]
+    fn test_submodule_diff_with_trailing_slash() {
+        DeltaTest::with_args(&["--width", "30"])
+            .with_input(SUBMODULE_WITH_TRAILING_SLASH)
+            .expect_after_skip(
+                1,
+                r#"
+            some_submodule
+            ──────────────────────────────
+            ca030fd1a022..803be42ca46a"#,
+            );
+    }
+
     #[test]
     fn test_submodule_diff_log() {
         // See etc/examples/662-submodules
@@ -2157,6 +2396,714 @@ src/align.rs:71: impl<'a> Alignment<'a> { │
         "###);
     }
 
+    #[test]
+    fn test_inline_only() {
+        let result = DeltaTest::with_args(&[
+            "--inline-only",
+            "--syntax-theme=none",
+            "--minus-style=red",
+            "--minus-emph-style=bold red",
+            "--plus-style=green",
+            "--plus-emph-style=bold green",
+        ])
+        .explain_ansi()
+        .with_input(GIT_DIFF_SINGLE_CHAR_CHANGE);
+
+        assert_snapshot!(result.output, @r###"
+        (normal)
+        (blue)a.txt(normal)
+        (blue)───────────────────────────────────────────(normal)
+
+        (blue)───(blue)┐(normal)
+        (blue)1(normal): (blue)│(normal)
+        (blue)───(blue)┘(normal)
+        x
+        let a = (bold strike red)1(bold green)2(normal);
+        (red)removed(normal)
+        y
+        "###);
+    }
+
+    #[test]
+    fn test_inline_single_word() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+ x
+-let value = compute(alpha);
++let value = compute(beta);
+ y
+@@ -10,1 +10,1 @@
+-one two
++one three four
+";
+        let args = [
+            "--syntax-theme=none",
+            "--minus-style=red",
+            "--minus-emph-style=bold red",
+            "--plus-style=green",
+            "--plus-emph-style=bold green",
+            "--inline-hint-style=blue",
+        ];
+        DeltaTest::with_args(&[&args[..], &["--inline-single-word"]].concat())
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+x
+let value = compute((bold red)alpha(blue)→(bold green)beta(normal));
+y
+
+(blue)────(blue)┐(normal)
+(blue)10(normal): (blue)│(normal)
+(blue)────(blue)┘(normal)
+(red)one two(normal)
+(green)one three four(normal)",
+            );
+        // Without the option, the single word change is shown as a pair of lines.
+        DeltaTest::with_args(&args)
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "#partial
+x
+(red)let value = compute((bold normal)alpha(red));(normal)
+(green)let value = compute((bold normal)beta(green));(normal)
+y",
+            );
+    }
+
+    #[test]
+    fn test_show_insertion_point() {
+        let result = DeltaTest::with_args(&[
+            "--show-insertion-point",
+            "--insertion-point-style=bold yellow",
+            "--syntax-theme=none",
+            "--minus-style=red",
+            "--minus-emph-style=bold red",
+            "--plus-style=green",
+            "--plus-emph-style=bold green",
+        ])
+        .explain_ansi()
+        .with_input(GIT_DIFF_PURE_INSERTION);
+
+        assert_snapshot!(result.output, @r###"
+        (normal)
+        (blue)a.txt(normal)
+        (blue)───────────────────────────────────────────(normal)
+
+        (blue)───(blue)┐(normal)
+        (blue)1(normal): (blue)│(normal)
+        (blue)───(blue)┘(normal)
+        (red)let a = foo(b(bold yellow)‸(red));(normal)
+        (red)let b = (bold normal)1(red);(normal)
+        (green)let a = foo(b(bold normal), c(green));(normal)
+        (green)let b = (bold normal)2(green);(normal)
+        "###);
+    }
+
+    #[test]
+    fn test_collapse_blank_additions() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,6 @@
+ a
++
++
++
++x
+ b
++
+";
+        DeltaTest::with_args(&["--collapse-blank-additions", "--inline-hint-style=blue"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(231)a(normal)
+(blue)+ 3 blank lines(normal)
+(231 22)x(normal)
+(231)b(normal)
+(normal)
+",
+            );
+        // The lines following the run keep their line numbers.
+        DeltaTest::with_args(&["--collapse-blank-additions", "--line-numbers"])
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+#indent_mark
+  1 ⋮  1 │a
+    ⋮  2 │+ 3 blank lines
+    ⋮  5 │x
+  2 ⋮  6 │b
+    ⋮  7 │
+",
+            );
+    }
+
+    #[test]
+    fn test_line_prefixes() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+ a
+-b
++c
+ d
+";
+        DeltaTest::with_args(&["--line-prefixes=> ,< ,= "])
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+= a
+< b
+> c
+= d",
+            );
+    }
+
+    #[test]
+    fn test_check() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -0,0 +1,4 @@
++a\t
++ \tb
++        c
++\td
+";
+        DeltaTest::with_args(&["--check", "--tabs=2"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(231 22)a(reverse purple)  (normal)
+(reverse purple)   (231 22)b(normal)
+(reverse purple)        (231 22)c(normal)
+(231 22)  d(normal)",
+            );
+    }
+
+    #[test]
+    fn test_min_line_similarity() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-let value = compute(alpha);
++let value = compute(beta);
+";
+        // The lines are similar enough to be paired, and their edits are highlighted.
+        DeltaTest::with_args(&["--min-line-similarity=0.3"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(normal 52)let value = compute((normal 124)alpha(normal 52));(normal)
+(231 22)let value = compute((normal 28)beta(normal 22));(normal)",
+            );
+        // They are not similar enough, and are shown as a whole removed and added line.
+        DeltaTest::with_args(&["--min-line-similarity=0.95"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(normal 52)let value = compute(alpha);(normal)
+(231 22)let value = compute(beta);(normal)",
+            );
+        let config = integration_test_utils::make_config_from_args(&["--min-line-similarity=0.25"]);
+        assert_eq!(config.max_line_distance, 0.75);
+        let config = integration_test_utils::make_config_from_args(&[
+            "--min-line-similarity=1",
+            "--max-line-distance=0.2",
+        ]);
+        assert_eq!(config.max_line_distance, 0.0);
+    }
+
+    #[test]
+    fn test_syntax_highlight_context() {
+        let input = "\
+diff --git a/a.rs b/a.rs
+index 223ca50..367a6f6 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+ fn f() {}
+-let x = 1;
++let y = 2;
+";
+        DeltaTest::with_args(&[])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(81)fn(231) (149)f(231)() {}(normal)
+(normal 52)let (normal 124)x(normal 52) = (normal 124)1(normal 52);(normal)
+(81 22)let(231) (normal 28)y(normal 22) (203)=(231) (141 28)2(231 22);(normal)",
+            );
+        // Context lines are plain, and removed and added lines are highlighted as before.
+        DeltaTest::with_args(&["--syntax-highlight-context=false"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+fn f() {}
+(normal 52)let (normal 124)x(normal 52) = (normal 124)1(normal 52);(normal)
+(81 22)let(231) (normal 28)y(normal 22) (203)=(231) (141 28)2(231 22);(normal)",
+            );
+    }
+
+    #[test]
+    fn test_syntax_highlight_max_file_bytes() {
+        let input = "\
+diff --git a/a.rs b/a.rs
+index 223ca50..367a6f6 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,2 +1,2 @@
+ fn f() {}
+-let x = 1;
++let y = 2;
+diff --git a/b.rs b/b.rs
+index 223ca50..367a6f6 100644
+--- a/b.rs
++++ b/b.rs
+@@ -1 +1 @@
+-fn g() {}
++fn h() {}
+";
+        // The hunk of a.rs has 35 bytes, and that of b.rs 20.
+        DeltaTest::with_args(&["--syntax-highlight-max-file-bytes=35"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_contains(
+                "
+(81)fn(231) (149)f(231)() {}(normal)
+(normal 52)let (normal 124)x(normal 52) = (normal 124)1(normal 52);(normal)
+(81 22)let(231) (normal 28)y(normal 22) (203)=(231) (141 28)2(231 22);(normal)
+",
+            );
+        // The lines of a.rs keep the colors of removed and added lines only, and b.rs is
+        // highlighted.
+        DeltaTest::with_args(&["--syntax-highlight-max-file-bytes=34"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_contains(
+                "
+fn f() {}
+(normal 52)let (normal 124)x(normal 52) = (normal 124)1(normal 52);(normal)
+(normal 22)let (normal 28)y(normal 22) = (normal 28)2(normal 22);(normal)
+",
+            )
+            .expect_contains("(81 22)fn(231) (149 28)h");
+    }
+
+    #[test]
+    fn test_semantic_highlight() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ keep 1 TODO
+-old 22 FIXME: x
++new 333 TODOS
+";
+        DeltaTest::with_args(&[
+            "--semantic-highlight=keyword:/\\b(TODO|FIXME)\\b/:red bold; number:/\\d+/:cyan",
+        ])
+        .explain_ansi()
+        .with_input(input)
+        .expect_after_skip(
+            7,
+            "
+(231)keep (cyan)1(231) (bold red)TODO(normal)
+(normal 52)old (cyan)22(normal 52) (bold red)FIXME(normal 52): x(normal)
+(231 22)new (cyan)333(231) TODOS(normal)",
+        );
+    }
+
+    #[test]
+    fn test_reflow_prose() {
+        let input = "\
+diff --git a/a.md b/a.md
+index 223ca50..367a6f6 100644
+--- a/a.md
++++ b/a.md
+@@ -1,3 +1,3 @@
+-The quick brown fox jumps over
+-the lazy dog and runs away into
+-the forest.
++The quick brown fox leaps
++over the lazy dog and runs away
++into the forest.
+";
+        DeltaTest::with_args(&["--reflow-prose"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(normal 52)The quick brown fox (normal 124)jumps(normal 52) over(normal)
+(normal 52)the lazy dog and runs away into(normal)
+(normal 52)the forest.(normal)
+(231 22)The quick brown fox (normal 28)leaps(normal)
+(231 22)over the lazy dog and runs away(normal)
+(231 22)into the forest.(normal)",
+            );
+
+        // The lines of other files are compared as usual.
+        let input = input.replace("a.md", "a.rs");
+        let output = DeltaTest::with_args(&["--reflow-prose"]).with_input(&input);
+        assert_eq!(
+            output.raw_output,
+            DeltaTest::with_args(&[]).with_input(&input).raw_output
+        );
+    }
+
+    #[test]
+    fn test_align_edits() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,4 +1,4 @@
+-let a = 1;
+-let bb = 2;
+-let ccc = 3;
++let a = 10;
++let bb = 20;
++let ccc = 30;
+ x
+-a long line
++a long one
+";
+        DeltaTest::with_args(&["--align-edits"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(normal 52)let a =   (normal 124)1(normal 52);(normal)
+(normal 52)let bb =  (normal 124)2(normal 52);(normal)
+(normal 52)let ccc = (normal 124)3(normal 52);(normal)
+(231 22)let a =   (normal 28)10(normal 22);(normal)
+(231 22)let bb =  (normal 28)20(normal 22);(normal)
+(231 22)let ccc = (normal 28)30(normal 22);(normal)
+(231)x(normal)
+(normal 52)a long (normal 124)line(normal)
+(231 22)a long (normal 28)one(normal)",
+            );
+        // Only the padding differs from the output without the option.
+        let words = |args: &[&str]| -> Vec<String> {
+            let output = DeltaTest::with_args(args).with_input(input).output;
+            output.split_whitespace().map(str::to_string).collect()
+        };
+        assert_eq!(words(&["--align-edits"]), words(&[]));
+    }
+
+    #[test]
+    fn test_align_edits_leaves_distant_edits() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+-a = 1;
+-a_very_long_variable_name = 2;
++a = 10;
++a_very_long_variable_name = 20;
+";
+        let aligned = DeltaTest::with_args(&["--align-edits"])
+            .with_input(input)
+            .raw_output;
+        assert_eq!(
+            aligned,
+            DeltaTest::with_args(&[]).with_input(input).raw_output
+        );
+    }
+
+    #[test]
+    fn test_change_symbols() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+ a
+-b
++c
+ d
+";
+        DeltaTest::with_args(&["--change-symbols", "--line-numbers"])
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+#indent_mark
+    1 ⋮  1 │a
+-   2 ⋮    │b
++     ⋮  2 │c
+    3 ⋮  3 │d
+",
+            );
+        DeltaTest::with_args(&["--change-symbols", "--change-symbol-style=bold"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(bold normal)  (231)a(normal)
+(bold normal)- (normal 52)b(normal)
+(bold normal)+ (231 22)c(normal)
+(bold normal)  (231)d(normal)
+",
+            );
+        // The symbols are not shown in the side-by-side layout.
+        DeltaTest::with_args(&["--change-symbols", "--side-by-side"])
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+│  1 │a               │  1 │a
+│  2 │b               │    │
+│    │                │  2 │c
+│  3 │d               │  3 │d
+",
+            );
+    }
+
+    #[test]
+    fn test_change_heat() {
+        let args = [
+            "--syntax-theme=none",
+            "--true-color=always",
+            "--minus-style=normal #400000",
+            "--minus-emph-style=normal #ff0000",
+            "--plus-style=normal #004000",
+            "--plus-emph-style=normal #00ff00",
+        ];
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,5 +1,5 @@
+-one two three four five six seven eight
++one two three four five six seven EIGHT
+ x
+-one two three four five six seven eight
++one two three four FIVE SIX SEVEN EIGHT
+";
+        // A small edit gets a subtle tint, a large edit a strong one.
+        DeltaTest::with_args(&[&args[..], &["--change-heat"]].concat())
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(normal \"#200000\")one two three four five six seven (normal \"#ff0000\")eight(normal)
+(normal \"#002000\")one two three four five six seven (normal \"#00ff00\")EIGHT(normal)
+x
+(normal \"#800000\")one two three four (normal \"#ff0000\")five six seven eight(normal)
+(normal \"#008000\")one two three four (normal \"#00ff00\")FIVE SIX SEVEN EIGHT(normal)
+",
+            );
+        // Without --change-heat, both edits are painted alike.
+        DeltaTest::with_args(&args)
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(normal \"#400000\")one two three four five six seven (normal \"#ff0000\")eight(normal)
+(normal \"#004000\")one two three four five six seven (normal \"#00ff00\")EIGHT(normal)
+x
+(normal \"#400000\")one two three four (normal \"#ff0000\")five six seven eight(normal)
+(normal \"#004000\")one two three four (normal \"#00ff00\")FIVE SIX SEVEN EIGHT(normal)
+",
+            );
+    }
+
+    #[test]
+    fn test_inline_highlight_budget() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+-one two three four five six seven eight
++one two three four FIVE SIX SEVEN EIGHT
+ x
+-a b
++a c
+";
+        // Only the first 8 changed characters of each line are highlighted.
+        DeltaTest::with_args(&["--inline-highlight-budget=8", "--syntax-theme=none"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(normal 52)one two three four (normal 124)five six(normal 52) seven eight(normal)
+(normal 22)one two three four (normal 28)FIVE SIX(normal 22) SEVEN EIGHT(normal)
+x
+(normal 52)a (normal 124)b(normal)
+(normal 22)a (normal 28)c(normal)
+",
+            );
+        // Without a budget, all changes are highlighted.
+        DeltaTest::with_args(&["--syntax-theme=none"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "#partial
+(normal 52)one two three four (normal 124)five six seven eight(normal)
+(normal 22)one two three four (normal 28)FIVE SIX SEVEN EIGHT(normal)
+",
+            );
+    }
+
+    #[test]
+    fn test_emph_extend() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+-one two three four five six seven eight
++ONE two three four five six seven eight
+ x
+-a b
++c
+";
+        DeltaTest::with_args(&["--emph-extend=span", "--syntax-theme=none"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "#partial
+(normal 124)one(normal 52) two three four five six seven eight(normal)
+(normal 28)ONE(normal 22) two three four five six seven eight(normal)
+",
+            );
+        // The rest of each changed line, and its background, are in the emph style; lines
+        // without a homolog are not affected.
+        let output = DeltaTest::with_args(&["--emph-extend=line", "--syntax-theme=none"])
+            .with_input(input)
+            .raw_output;
+        let fill = |background: u8| format!("\x1b[48;5;{background}m\x1b[0K");
+        assert!(output.contains(&fill(124)));
+        assert!(output.contains(&fill(28)));
+        DeltaTest::with_args(&["--emph-extend=line", "--syntax-theme=none"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                7,
+                "
+(normal 124)one two three four five six seven eight(normal)
+(normal 28)ONE two three four five six seven eight(normal)
+x
+(normal 52)a b(normal)
+(normal 22)c(normal)
+",
+            );
+    }
+
+    #[test]
+    fn test_literal_content_escapes() {
+        let input = "\
+commit 1111111111111111111111111111111111111111
+Author: A <a@example.com>
+
+    see \x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\
+
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-see docs
++see \x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x1b\\
+";
+        let result = DeltaTest::with_args(&["--literal-content-escapes"]).with_input(input);
+        assert_eq!(
+            result
+                .output
+                .matches("see ␛]8;;https://example.com␛\\docs␛]8;;␛\\")
+                .count(),
+            2
+        );
+        assert!(!result.raw_output.contains("\x1b]8;"));
+        // Lines written as they are pass the sequences through to the terminal, unless escaped.
+        let result = DeltaTest::with_args(&["--preserve-commit-ansi"]).with_input(input);
+        assert!(result.raw_output.contains("\x1b]8;;https://example.com"));
+        let result = DeltaTest::with_args(&["--preserve-commit-ansi", "--literal-content-escapes"])
+            .with_input(input);
+        assert!(!result.raw_output.contains("\x1b]8;"));
+        // By default, the sequences are removed from the lines of the diff.
+        let result = DeltaTest::with_args(&[]).with_input(input);
+        assert!(result.output.contains("see docs"));
+        assert!(!result.output.contains('␛'));
+    }
+
+    const GIT_DIFF_PURE_INSERTION: &str = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+-let a = foo(b);
+-let b = 1;
++let a = foo(b, c);
++let b = 2;
+";
+
+    const GIT_DIFF_SINGLE_CHAR_CHANGE: &str = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,4 +1,3 @@
+ x
+-let a = 1;
+-removed
++let a = 2;
+ y
+";
+
     const GIT_DIFF_OF_WIDTH_81: &str = r#"
 --- a.rs
 +++ b.rs
@@ -2421,6 +3368,21 @@ diff --git a/a.py b/b.py
 similarity index 100%
 rename from a.py
 rename to b.py
+";
+
+    const RENAMES_ONLY_DIFF: &str = "\
+diff --git a/src/a.py b/src/b.py
+similarity index 100%
+rename from src/a.py
+rename to src/b.py
+diff --git a/README b/README.md
+similarity index 100%
+rename from README
+rename to README.md
+diff --git a/docs/old/guide.md b/docs/guide.md
+similarity index 100%
+rename from docs/old/guide.md
+rename to docs/guide.md
 ";
 
     const RENAMED_FILE_WITH_CHANGES_INPUT: &str = "\
@@ -2525,6 +3487,16 @@ index ca030fd1a0..803be42ca4 160000
 @@ -1 +1 @@
 -Subproject commit ca030fd1a02225a6fc1a834c480276d9c97a8c6f
 +Subproject commit 803be42ca46af0fbc65b54a9abfb499389516939-dirty
+";
+
+    const SUBMODULE_WITH_TRAILING_SLASH: &str = "\
+diff --git a/some_submodule b/some_submodule/
+index ca030fd1a0..803be42ca4 160000
+--- a/some_submodule/
++++ b/some_submodule/
+@@ -1 +1 @@
+-Subproject commit ca030fd1a02225a6fc1a834c480276d9c97a8c6f
++Subproject commit 803be42ca46af0fbc65b54a9abfb499389516939
 ";
 
     // See etc/examples/662-submodules
@@ -2716,6 +3688,20 @@ diff --git a/first_file b/copied_file
 similarity index 100%
 copy from first_file
 copy to copied_file
+";
+
+    const GIT_DIFF_WITH_COPIED_FILE_WITH_CHANGES: &str = "\
+diff --git a/a.txt b/b.txt
+similarity index 90%
+copy from a.txt
+copy to b.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/b.txt
+@@ -1,2 +1,2 @@
+ a
+-b
++c
 ";
 
     // git --no-pager show -p --cc --format=  --numstat --stat
@@ -13,11 +13,29 @@ use crate::env::DeltaEnv;
 use crate::fatal;
 use crate::features::navigate;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct PagerCfg {
     pub navigate: bool,
     pub show_themes: bool,
     pub navigate_regex: Option<String>,
+    // The prompt of less, as given to its --prompt option (see --navigate-prompt-format).
+    pub navigate_prompt: Option<String>,
+    pub quit_if_one_screen: bool,
+    // Whether the output may contain ANSI escape sequences, which less is told to interpret.
+    pub raw_control_chars: bool,
+}
+
+impl Default for PagerCfg {
+    fn default() -> Self {
+        PagerCfg {
+            navigate: false,
+            show_themes: false,
+            navigate_regex: None,
+            navigate_prompt: None,
+            quit_if_one_screen: true,
+            raw_control_chars: true,
+        }
+    }
 }
 
 impl From<&config::Config> for PagerCfg {
@@ -26,6 +44,9 @@ impl From<&config::Config> for PagerCfg {
             navigate: cfg.navigate,
             show_themes: cfg.show_themes,
             navigate_regex: cfg.navigate_regex.clone(),
+            navigate_prompt: cfg.navigate_prompt.clone(),
+            quit_if_one_screen: cfg.pager_quit_if_one_screen,
+            raw_control_chars: !cfg.no_color,
         }
     }
 }
@@ -85,7 +106,9 @@ impl OutputType {
         use self::PagingMode::*;
         Ok(match mode {
             Always => OutputType::try_pager(env, false, pager, config)?,
-            QuitIfOneScreen => OutputType::try_pager(env, true, pager, config)?,
+            QuitIfOneScreen => {
+                OutputType::try_pager(env, config.quit_if_one_screen, pager, config)?
+            }
             Capture => OutputType::Capture,
             _ => OutputType::stdout(),
         })
@@ -181,7 +204,9 @@ fn _make_process_from_less_path(
     if let Ok(less_path) = grep_cli::resolve_binary(less_path) {
         let mut p = Command::new(less_path.clone());
         if args.is_empty() || replace_arguments_to_less {
-            p.args(vec!["--RAW-CONTROL-CHARS"]);
+            if config.raw_control_chars {
+                p.arg("--RAW-CONTROL-CHARS");
+            }
 
             // Passing '--no-init' fixes a bug with '--quit-if-one-screen' in older
             // versions of 'less'. Unfortunately, it also breaks mouse-wheel support.
@@ -230,6 +255,9 @@ fn _make_process_from_less_path(
                 }
             }
         }
+        if let Some(prompt) = &config.navigate_prompt {
+            p.arg(format!("--prompt={prompt}"));
+        }
         Some(p)
     } else {
         None
@@ -254,3 +282,94 @@ delta is not an appropriate value for $PAGER \
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn less_args(quit_if_one_screen: bool) -> Option<Vec<String>> {
+        less_args_with_pager_cfg(quit_if_one_screen, &PagerCfg::default())
+    }
+
+    fn less_args_with_pager_cfg(
+        quit_if_one_screen: bool,
+        pager_cfg: &PagerCfg,
+    ) -> Option<Vec<String>> {
+        let process = _make_process_from_less_path(
+            PathBuf::from("less"),
+            &[],
+            false,
+            quit_if_one_screen,
+            pager_cfg,
+        )?;
+        assert!(process.get_envs().all(|(name, _)| name != "LESS"));
+        Some(
+            process
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_less_quit_if_one_screen() {
+        // Skip if less is not installed.
+        let Some(args) = less_args(true) else {
+            return;
+        };
+        assert!(args.contains(&"--quit-if-one-screen".to_string()));
+        let args = less_args(false).unwrap();
+        assert!(!args.contains(&"--quit-if-one-screen".to_string()));
+        assert!(args.contains(&"--RAW-CONTROL-CHARS".to_string()));
+    }
+
+    #[test]
+    fn test_pager_quit_if_one_screen_option() {
+        let pager_cfg = |args: &[&str]| -> PagerCfg {
+            (&crate::tests::integration_test_utils::make_config_from_args(args)).into()
+        };
+        assert!(pager_cfg(&[]).quit_if_one_screen);
+        assert!(pager_cfg(&["--pager-quit-if-one-screen=true"]).quit_if_one_screen);
+        assert!(!pager_cfg(&["--pager-quit-if-one-screen=false"]).quit_if_one_screen);
+    }
+
+    #[test]
+    fn test_less_raw_control_chars_without_color() {
+        let pager_cfg = |no_color: bool| -> PagerCfg {
+            let mut config = crate::tests::integration_test_utils::make_config_from_args(&[]);
+            config.no_color = no_color;
+            (&config).into()
+        };
+        // Skip if less is not installed.
+        let Some(args) = less_args_with_pager_cfg(true, &pager_cfg(false)) else {
+            return;
+        };
+        assert!(args.contains(&"--RAW-CONTROL-CHARS".to_string()));
+        let args = less_args_with_pager_cfg(true, &pager_cfg(true)).unwrap();
+        assert!(!args.contains(&"--RAW-CONTROL-CHARS".to_string()));
+        assert!(args.contains(&"--quit-if-one-screen".to_string()));
+    }
+
+    #[test]
+    fn test_less_navigate_prompt() {
+        let pager_cfg = |args: &[&str]| -> PagerCfg {
+            (&crate::tests::integration_test_utils::make_config_from_args(args)).into()
+        };
+        let args = ["--navigate-prompt-format=file {current}/{total}: {percent}"];
+        assert_eq!(pager_cfg(&args).navigate_prompt, None);
+        let mut pager_cfg = pager_cfg(&[&args[..], &["--navigate"]].concat());
+        assert_eq!(
+            pager_cfg.navigate_prompt.as_deref(),
+            Some("file %i/%m\\: %pt\\%")
+        );
+        // The history file of --navigate is not needed to test the prompt.
+        pager_cfg.navigate = false;
+        // Skip if less is not installed.
+        let Some(args) = less_args_with_pager_cfg(true, &pager_cfg) else {
+            return;
+        };
+        assert!(args.contains(&"--prompt=file %i/%m\\: %pt\\%".to_string()));
+        let args = less_args_with_pager_cfg(true, &PagerCfg::default()).unwrap();
+        assert!(!args.iter().any(|arg| arg.starts_with("--prompt")));
+    }
+}
@@ -81,3 +81,23 @@ pub fn as_terminal_escaped(
     style.background = background_color.and_then(|c| to_ansi_color(c, true_color));
     style.paint(text).to_string()
 }
+
+// Threshold map for ordered dithering, see https://en.wikipedia.org/wiki/Ordered_dithering
+const BAYER_MATRIX_4X4: [[u8; 4]; 4] =
+    [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// Approximate distance between neighboring levels of the 6x6x6 color cube of the 256-color palette.
+const ANSI_256_COLOR_LEVEL_DISTANCE: f64 = 40.0;
+
+/// Return the 256-color palette entry for the RGB color of the terminal cell at `(column, row)`,
+/// using ordered dithering: the color is offset by a position-dependent threshold before choosing
+/// the nearest palette entry, so that neighboring cells approximate the color on average.
+pub fn ansi256_from_rgb_dithered(rgb: (u8, u8, u8), column: usize, row: usize) -> u8 {
+    let threshold = (BAYER_MATRIX_4X4[row % 4][column % 4] as f64 + 0.5) / 16.0 - 0.5;
+    let offset = |c: u8| {
+        (c as f64 + threshold * ANSI_256_COLOR_LEVEL_DISTANCE)
+            .round()
+            .clamp(0.0, 255.0) as u8
+    };
+    ansi_colours::ansi256_from_rgb((offset(rgb.0), offset(rgb.1), offset(rgb.2)))
+}
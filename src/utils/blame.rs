@@ -0,0 +1,514 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::rc::Rc;
+
+use crate::ansi;
+use crate::config::Config;
+use crate::utils::process::{self, CallingProcess};
+
+/// The width of the gutter in which --annotate-age shows the age of unchanged lines.
+pub const AGE_GUTTER_WIDTH: usize = 5;
+
+/// The width of the gutter in which --blame-removed shows the author of removed lines.
+pub const AUTHOR_GUTTER_WIDTH: usize = 12;
+
+/// A source of the times at which the lines of a file were last changed.
+pub trait BlameSource {
+    /// Return the author time (in seconds since the epoch) of each line of the file at `path`,
+    /// or None if it cannot be blamed.
+    fn author_times(&mut self, path: &Path) -> Option<Vec<i64>>;
+}
+
+/// Blame the working tree version of a file by running `git blame`.
+pub struct GitBlame;
+
+impl BlameSource for GitBlame {
+    fn author_times(&mut self, path: &Path) -> Option<Vec<i64>> {
+        let git_path = grep_cli::resolve_binary("git").ok()?;
+        let output = Command::new(git_path)
+            .arg("-C")
+            .arg(path.parent()?)
+            .args(["blame", "--line-porcelain", "--"])
+            .arg(path)
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(parse_line_porcelain_author_times(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+}
+
+/// A source of the authors of the lines of files at a revision.
+pub trait AuthorSource {
+    /// Return the author of each line of the file at `path`, relative to the root of the
+    /// repository, at `revision`, or None if it cannot be blamed.
+    fn authors(&mut self, revision: &str, path: &str) -> Option<Vec<String>>;
+}
+
+/// Blame the files of a repository at a revision by running `git blame`.
+pub struct GitBlameAtRevision {
+    repo_root: PathBuf,
+}
+
+impl AuthorSource for GitBlameAtRevision {
+    fn authors(&mut self, revision: &str, path: &str) -> Option<Vec<String>> {
+        let git_path = grep_cli::resolve_binary("git").ok()?;
+        let output = Command::new(git_path)
+            .arg("-C")
+            .arg(&self.repo_root)
+            .args(["blame", "--line-porcelain", revision, "--", path])
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(parse_line_porcelain_authors(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+}
+
+// In `git blame --line-porcelain` output every line of the file is preceded by all the fields of
+// its commit; the line itself is prefixed by a tab, so cannot be mistaken for a field.
+fn parse_line_porcelain_author_times(blame: &str) -> Vec<i64> {
+    blame
+        .lines()
+        .filter_map(|line| line.strip_prefix("author-time "))
+        .filter_map(|time| time.parse().ok())
+        .collect()
+}
+
+fn parse_line_porcelain_authors(blame: &str) -> Vec<String> {
+    blame
+        .lines()
+        .filter_map(|line| line.strip_prefix("author "))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Compute the ages of the unchanged lines of the hunks of a diff (used by --annotate-age).
+/// Files are blamed when their first unchanged line is reached, and the result is cached.
+pub struct AgeAnnotator {
+    source: Box<dyn BlameSource>,
+    cache: HashMap<PathBuf, Option<Vec<i64>>>,
+    now: i64,
+    file: Option<PathBuf>,
+    line_number: usize,
+}
+
+impl AgeAnnotator {
+    pub fn new(source: Box<dyn BlameSource>, now: i64) -> Self {
+        Self {
+            source,
+            cache: HashMap::new(),
+            now,
+            file: None,
+            line_number: 0,
+        }
+    }
+
+    /// Return an annotator using `git blame`, if requested and delta is running in a repository.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if !config.annotate_age {
+            return None;
+        }
+        git2::Repository::discover(config.cwd_of_delta_process.as_ref()?).ok()?;
+        Some(Self::new(
+            Box::new(GitBlame),
+            chrono::Utc::now().timestamp(),
+        ))
+    }
+
+    /// Start a hunk of `file` (in the new version), whose first line is `line_number`.
+    pub fn start_hunk(&mut self, file: Option<PathBuf>, line_number: usize) {
+        self.file = file;
+        self.line_number = line_number;
+    }
+
+    /// Move past an added line.
+    pub fn skip_line(&mut self) {
+        self.line_number += 1;
+    }
+
+    /// Move past an unchanged line, returning its age.
+    pub fn next_line_age(&mut self) -> Option<String> {
+        let line_number = self.line_number;
+        self.line_number += 1;
+        let file = self.file.as_ref()?;
+        let source = &mut self.source;
+        let times = self
+            .cache
+            .entry(file.clone())
+            .or_insert_with(|| source.author_times(file));
+        let time = times.as_ref()?.get(line_number.checked_sub(1)?)?;
+        Some(format_age(self.now - time))
+    }
+}
+
+/// Find the authors of the removed lines of the hunks of a diff (used by --blame-removed). The old
+/// version of a file is blamed when its first hunk is reached, and the result is cached.
+pub struct RemovedLineBlamer {
+    source: Box<dyn AuthorSource>,
+    cache: HashMap<(String, String), Option<Rc<Vec<String>>>>,
+    // The revision of the old versions of the files of the current diff.
+    revision: String,
+}
+
+impl RemovedLineBlamer {
+    pub fn new(source: Box<dyn AuthorSource>, revision: String) -> Self {
+        Self {
+            source,
+            cache: HashMap::new(),
+            revision,
+        }
+    }
+
+    /// Return a blamer using `git blame`, if requested and delta is running in a repository.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if !config.blame_removed {
+            return None;
+        }
+        let repo = git2::Repository::discover(config.cwd_of_delta_process.as_ref()?).ok()?;
+        let revision = match &*process::calling_process() {
+            CallingProcess::GitDiff(command_line) => get_old_revision(&repo, &command_line.args),
+            _ => None,
+        };
+        Some(Self::new(
+            Box::new(GitBlameAtRevision {
+                repo_root: repo.workdir()?.to_path_buf(),
+            }),
+            revision.unwrap_or_else(|| "HEAD".to_string()),
+        ))
+    }
+
+    /// Start the diff of a commit: the old versions of its files are those of its parent. The
+    /// diff of the input preceding any commit is taken to be a diff of the revision it was
+    /// created with.
+    pub fn start_commit(&mut self, commit: &str) {
+        self.revision = format!("{commit}^");
+    }
+
+    /// Return the author of each line of the old version of the file at `path`.
+    pub fn authors(&mut self, path: &str) -> Option<Rc<Vec<String>>> {
+        let source = &mut self.source;
+        self.cache
+            .entry((self.revision.clone(), path.to_string()))
+            .or_insert_with_key(|(revision, path)| source.authors(revision, path).map(Rc::new))
+            .clone()
+    }
+}
+
+// Return the revision of the old versions of the files compared by `git diff` called with
+// `args`: the first of them which is a revision, e.g. A in `git diff A B` or `git diff A..B`, or
+// the merge base of A and B in `git diff A...B`. If none is a revision, HEAD is compared.
+fn get_old_revision(repo: &git2::Repository, args: &[String]) -> Option<String> {
+    args.iter().find_map(|arg| {
+        let revspec = repo.revparse(arg).ok()?;
+        let from = match revspec.from() {
+            Some(from) => from.id(),
+            None => repo.head().ok()?.target()?,
+        };
+        if revspec.mode().contains(git2::RevparseMode::MERGE_BASE) {
+            let to = revspec.to()?.id();
+            Some(repo.merge_base(from, to).ok()?.to_string())
+        } else {
+            Some(from.to_string())
+        }
+    })
+}
+
+/// Format a duration in seconds compactly, e.g. "3d" or "11mo".
+pub fn format_age(seconds: i64) -> String {
+    const MINUTE: i64 = 60;
+    const HOUR: i64 = 60 * MINUTE;
+    const DAY: i64 = 24 * HOUR;
+    const WEEK: i64 = 7 * DAY;
+    const MONTH: i64 = 30 * DAY;
+    const YEAR: i64 = 365 * DAY;
+    match seconds.max(0) {
+        s if s < MINUTE => "now".to_string(),
+        s if s < HOUR => format!("{}m", s / MINUTE),
+        s if s < DAY => format!("{}h", s / HOUR),
+        s if s < WEEK => format!("{}d", s / DAY),
+        s if s < MONTH => format!("{}w", s / WEEK),
+        s if s < YEAR => format!("{}mo", s / MONTH),
+        s => format!("{}y", s / YEAR),
+    }
+}
+
+/// Prefix the lines painted to `output_buffer` from `start` onwards with the age gutter, showing
+/// `age` on the first one. Further lines, e.g. the continuations of a wrapped line, are padded.
+pub fn insert_age_gutter(output_buffer: &mut String, start: usize, age: Option<&str>) {
+    let painted = output_buffer.split_off(start);
+    let mut age = age;
+    for line in painted.split_inclusive('\n') {
+        match age.take() {
+            Some(age) => output_buffer.push_str(
+                &ansi_term::Style::new()
+                    .dimmed()
+                    .paint(format!("{age:>width$} ", width = AGE_GUTTER_WIDTH - 1))
+                    .to_string(),
+            ),
+            None => output_buffer.push_str(&" ".repeat(AGE_GUTTER_WIDTH)),
+        }
+        output_buffer.push_str(line);
+    }
+}
+
+/// Prefix the lines painted to `output_buffer` from `start` onwards with the author gutter,
+/// showing the given authors on the first lines. Further lines are padded.
+pub fn insert_author_gutter(output_buffer: &mut String, start: usize, authors: &[Option<&str>]) {
+    let painted = output_buffer.split_off(start);
+    let mut authors = authors.iter();
+    for line in painted.split_inclusive('\n') {
+        match authors.next() {
+            Some(Some(author)) => output_buffer.push_str(
+                &ansi_term::Style::new()
+                    .dimmed()
+                    .paint(format!(
+                        "{:<width$} ",
+                        ansi::truncate_str(author, AUTHOR_GUTTER_WIDTH - 1, "…"),
+                        width = AUTHOR_GUTTER_WIDTH - 1
+                    ))
+                    .to_string(),
+            ),
+            _ => output_buffer.push_str(&" ".repeat(AUTHOR_GUTTER_WIDTH)),
+        }
+        output_buffer.push_str(line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::{make_config_from_args, run_delta_with_machine};
+
+    const NOW: i64 = 1_700_000_000;
+    const DAY: i64 = 24 * 60 * 60;
+
+    struct StubBlame {
+        calls: std::rc::Rc<std::cell::Cell<usize>>,
+    }
+
+    impl BlameSource for StubBlame {
+        fn author_times(&mut self, path: &Path) -> Option<Vec<i64>> {
+            self.calls.set(self.calls.get() + 1);
+            if path.ends_with("a.txt") {
+                Some(vec![
+                    NOW - 400 * DAY,
+                    NOW - 3 * DAY,
+                    NOW - 3 * DAY,
+                    NOW - 10,
+                ])
+            } else {
+                None
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_age() {
+        assert_eq!(format_age(-5), "now");
+        assert_eq!(format_age(59), "now");
+        assert_eq!(format_age(90 * 60), "1h");
+        assert_eq!(format_age(3 * DAY), "3d");
+        assert_eq!(format_age(15 * DAY), "2w");
+        assert_eq!(format_age(334 * DAY), "11mo");
+        assert_eq!(format_age(400 * DAY), "1y");
+    }
+
+    #[test]
+    fn test_parse_line_porcelain_author_times() {
+        let blame = "\
+0123456789012345678901234567890123456789 1 1 1
+author A
+author-time 1600000000
+author-tz +0000
+filename a.txt
+\tauthor-time 1
+0123456789012345678901234567890123456789 2 2
+author A
+author-time 1600000001
+filename a.txt
+\tsecond line
+";
+        assert_eq!(
+            parse_line_porcelain_author_times(blame),
+            vec![1600000000, 1600000001]
+        );
+    }
+
+    #[test]
+    fn test_annotate_age() {
+        let config = make_config_from_args(&["--annotate-age"]);
+        let calls = std::rc::Rc::new(std::cell::Cell::new(0));
+        let output = run_delta_with_machine(ANNOTATE_AGE_DIFF, &config, |machine| {
+            machine.age_annotator = Some(AgeAnnotator::new(
+                Box::new(StubBlame {
+                    calls: calls.clone(),
+                }),
+                NOW,
+            ));
+        });
+        let output = strip_ansi_codes(&output);
+        let lines: Vec<&str> = output.lines().collect();
+        // Unchanged lines show the age, changed lines are padded; the file is blamed once.
+        assert!(lines.contains(&"  1y a"));
+        assert!(lines.contains(&"     b"));
+        assert!(lines.contains(&"     B"));
+        assert!(lines.contains(&"  3d c"));
+        assert!(lines.contains(&" now d"));
+        assert!(lines.contains(&"     x"));
+        assert!(lines.contains(&"     y"));
+        assert_eq!(calls.get(), 2);
+    }
+
+    struct StubAuthors {
+        calls: std::rc::Rc<std::cell::RefCell<Vec<(String, String)>>>,
+    }
+
+    impl AuthorSource for StubAuthors {
+        fn authors(&mut self, revision: &str, path: &str) -> Option<Vec<String>> {
+            self.calls
+                .borrow_mut()
+                .push((revision.to_string(), path.to_string()));
+            (path == "a.txt").then(|| vec!["Ann".to_string(), "Bartholomew Smith".to_string()])
+        }
+    }
+
+    #[test]
+    fn test_parse_line_porcelain_authors() {
+        let blame = "\
+0123456789012345678901234567890123456789 1 1 1
+author Ann
+author-mail <ann@example.com>
+filename a.txt
+\tauthor Bob
+";
+        assert_eq!(parse_line_porcelain_authors(blame), vec!["Ann"]);
+    }
+
+    #[test]
+    fn test_blame_removed() {
+        let config = make_config_from_args(&["--blame-removed"]);
+        let calls = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let output = run_delta_with_machine(BLAME_REMOVED_LOG, &config, |machine| {
+            machine.removed_line_blamer = Some(RemovedLineBlamer::new(
+                Box::new(StubAuthors {
+                    calls: calls.clone(),
+                }),
+                "HEAD".to_string(),
+            ));
+        });
+        let output = strip_ansi_codes(&output);
+        let lines: Vec<&str> = output.lines().collect();
+        // Removed lines show the author, other lines are padded.
+        assert!(lines.contains(&"Ann         a"));
+        assert!(lines.contains(&"Bartholome… b"));
+        assert!(lines.contains(&"            B"));
+        assert!(lines.contains(&"            x"));
+        assert!(lines.contains(&"            y"));
+        // Each file is blamed once, in the parent of its commit.
+        assert_eq!(
+            *calls.borrow(),
+            [
+                ("94907c0f^".to_string(), "a.txt".to_string()),
+                ("94907c0f^".to_string(), "b.txt".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_old_revision() {
+        let repo_dir = std::env::temp_dir().join("delta__test_get_old_revision");
+        let _ = std::fs::remove_dir_all(&repo_dir);
+        let repo = git2::Repository::init(&repo_dir).unwrap();
+        let signature = git2::Signature::now("A", "a@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let commit = |message: &str, parents: &[&git2::Commit], update_ref: Option<&str>| {
+            let id = repo
+                .commit(update_ref, &signature, &signature, message, &tree, parents)
+                .unwrap();
+            repo.find_commit(id).unwrap()
+        };
+        let base = commit("base", &[], Some("HEAD"));
+        let main = commit("main", &[&base], Some("HEAD"));
+        let topic = commit("topic", &[&base], None);
+        repo.branch("topic", &topic, false).unwrap();
+
+        let old_revision = |args: &[&str]| {
+            get_old_revision(
+                &repo,
+                &args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>(),
+            )
+            .unwrap_or_default()
+        };
+        let [base, main] = [base.id(), main.id()].map(|id| id.to_string());
+        assert_eq!(old_revision(&[]), "");
+        assert_eq!(old_revision(&["a.txt"]), "");
+        assert_eq!(
+            old_revision(&["topic", "HEAD", "a.txt"]),
+            topic.id().to_string()
+        );
+        assert_eq!(old_revision(&["HEAD~1..topic"]), base);
+        assert_eq!(old_revision(&["..topic"]), main);
+        assert_eq!(old_revision(&["HEAD...topic"]), base);
+
+        std::fs::remove_dir_all(&repo_dir).unwrap();
+    }
+
+    const BLAME_REMOVED_LOG: &str = "\
+commit 94907c0f
+Author: Dan Davison <dandavison7@gmail.com>
+Date:   Thu May 14 11:13:17 2020 -0400
+
+    Change a and b
+
+diff --git a/a.txt b/a.txt
+index 1111111..2222222 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,1 +1,0 @@
+-a
+@@ -2,1 +1,1 @@
+-b
++B
+diff --git a/b.txt b/b.txt
+index 3333333..4444444 100644
+--- a/b.txt
++++ b/b.txt
+@@ -1,2 +1,1 @@
+ x
+-y
+";
+
+    const ANNOTATE_AGE_DIFF: &str = "\
+diff --git a/a.txt b/a.txt
+index 1111111..2222222 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ a
+-b
++B
+@@ -3,2 +3,2 @@
+ c
+ d
+diff --git a/b.txt b/b.txt
+index 3333333..4444444 100644
+--- a/b.txt
++++ b/b.txt
+@@ -1,1 +1,1 @@
+ x
+ y
+";
+}
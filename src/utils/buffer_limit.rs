@@ -0,0 +1,94 @@
+use std::io::BufRead;
+
+use bytelines::ByteLines;
+
+use crate::config::Config;
+
+/// A limit on the number of bytes buffered by a feature which needs all of the input or output
+/// before writing any of it (--toc, --minimap, --stat-group-by-dir, --line-numbers-zero-pad,
+/// --log-show-files, --sort-files, --group-by-category, --hunk-header-show-position,
+/// --collapse-eol-only, --syntax-highlight-max-file-bytes and --grep-show-counts).
+/// Once the limit is exceeded, a notice is written to stderr, and the feature is expected to give
+/// up and write the rest as it is produced.
+pub struct BufferLimit {
+    feature: &'static str,
+    limit: Option<usize>,
+    n_bytes: usize,
+    exceeded: bool,
+}
+
+impl BufferLimit {
+    pub fn new(feature: &'static str, config: &Config) -> Self {
+        Self {
+            feature,
+            limit: config.buffer_limit_bytes,
+            n_bytes: 0,
+            exceeded: false,
+        }
+    }
+
+    /// Count `n_bytes` more bytes as buffered, and return whether the limit has been exceeded.
+    pub fn add(&mut self, n_bytes: usize) -> bool {
+        self.n_bytes += n_bytes;
+        if let Some(limit) = self
+            .limit
+            .filter(|&limit| !self.exceeded && self.n_bytes > limit)
+        {
+            self.exceeded = true;
+            eprintln!(
+                "delta: more than {limit} bytes would be buffered for --{} (see \
+                 --buffer-limit-bytes); writing the rest without it.",
+                self.feature
+            );
+        }
+        self.exceeded
+    }
+
+    /// Count the buffer as emptied. Once exceeded, the limit stays exceeded.
+    pub fn reset(&mut self) {
+        self.n_bytes = 0;
+    }
+
+    pub fn is_exceeded(&self) -> bool {
+        self.exceeded
+    }
+}
+
+/// Read all of the input, for a feature which needs it before writing any output. The lines are
+/// returned newline-terminated, as they would be read from the input. If `limit` is exceeded,
+/// reading stops after the line exceeding it.
+pub fn read_input<I>(lines: &mut ByteLines<I>, limit: &mut BufferLimit) -> Vec<u8>
+where
+    I: BufRead,
+{
+    let mut input = Vec::new();
+    while let Some(Ok(line)) = lines.next() {
+        input.extend_from_slice(line);
+        input.push(b'\n');
+        if limit.add(line.len() + 1) {
+            break;
+        }
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::make_config_from_args;
+
+    #[test]
+    fn test_buffer_limit() {
+        let config = make_config_from_args(&["--buffer-limit-bytes=10"]);
+        let mut limit = BufferLimit::new("toc", &config);
+        assert!(!limit.add(6));
+        assert!(!limit.add(4));
+        assert!(limit.add(1));
+        assert!(limit.is_exceeded());
+        assert!(limit.add(0));
+
+        let config = make_config_from_args(&[]);
+        let mut limit = BufferLimit::new("toc", &config);
+        assert!(!limit.add(usize::MAX));
+    }
+}
@@ -0,0 +1,214 @@
+use std::fmt;
+use std::io::Write;
+
+use crate::delta::State;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// A hash of the content of a diff: the paths of its files, the lines of its hunks, and the header
+/// lines of changes without hunks, but not their styling (used by --digest). It is written to its
+/// own writer, standard error by default, rather than with the diff.
+pub struct Digest {
+    hash: u64,
+    file_pair: Option<(String, String)>,
+    writer: Box<dyn Write>,
+}
+
+impl Default for Digest {
+    fn default() -> Self {
+        Self::new(Box::new(std::io::stderr()))
+    }
+}
+
+impl Digest {
+    pub fn new(writer: Box<dyn Write>) -> Self {
+        Self {
+            hash: FNV_OFFSET_BASIS,
+            file_pair: None,
+            writer,
+        }
+    }
+
+    /// Add the current line of the diff, which is in `state`, to the digest. Lines other than
+    /// hunk headers, hunk lines and header lines reporting a change (`is_change_header_line`) are
+    /// not part of the content.
+    pub fn add_line(
+        &mut self,
+        state: &State,
+        minus_file: &str,
+        plus_file: &str,
+        line: &str,
+        is_change_header_line: bool,
+    ) {
+        if !is_change_header_line
+            && !matches!(
+                state,
+                State::HunkHeader(..)
+                    | State::HunkMinus(..)
+                    | State::HunkPlus(..)
+                    | State::HunkZero(..)
+            )
+        {
+            return;
+        }
+        if self
+            .file_pair
+            .as_ref()
+            .map(|(minus, plus)| (minus.as_str(), plus.as_str()))
+            != Some((minus_file, plus_file))
+        {
+            self.file_pair = Some((minus_file.to_string(), plus_file.to_string()));
+            for field in ["\0files", minus_file, plus_file] {
+                self.update(field.as_bytes());
+            }
+        }
+        self.update(line.as_bytes());
+    }
+
+    /// Write the digest, on a line of its own.
+    pub fn finish(&mut self) -> std::io::Result<()> {
+        let digest = self.to_string();
+        writeln!(self.writer, "{digest}")?;
+        self.writer.flush()
+    }
+
+    // FNV-1a, of the bytes followed by a newline. Unlike the hasher of the standard library, it
+    // gives the same hash in every build of delta.
+    fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes.iter().chain(b"\n") {
+            self.hash ^= u64::from(*byte);
+            self.hash = self.hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:016x}", self.hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::{BufReader, Write};
+    use std::rc::Rc;
+
+    use bytelines::ByteLines;
+
+    use super::Digest;
+    use crate::delta::StateMachine;
+    use crate::tests::integration_test_utils::{make_config_from_args, run_delta_with_machine};
+
+    const DIFF: &str = "\
+commit 1111111111111111111111111111111111111111
+Author: A <a@example.com>
+
+    Change a
+
+diff --git a/a.txt b/a.txt
+index 1111111..2222222 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ a
+-b
++c
+";
+
+    fn digest(args: &[&str], input: &str) -> String {
+        let config = make_config_from_args(&[args, &["--digest"]].concat());
+        let mut writer = Vec::new();
+        let mut machine = StateMachine::new(&mut writer, &config);
+        machine
+            .consume(ByteLines::new(BufReader::new(input.as_bytes())))
+            .unwrap();
+        machine.digest.as_ref().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_digest_is_stable_across_styles() {
+        let expected = digest(&[], DIFF);
+        assert_eq!(expected.len(), 16);
+        for args in [
+            &["--side-by-side"][..],
+            &["--line-numbers", "--minus-style=red"],
+            &["--color-only"],
+        ] {
+            assert_eq!(digest(args, DIFF), expected, "{:?}", args);
+        }
+        // Nor does it depend on the colors of the input, or on lines other than those of hunks.
+        let colored = DIFF
+            .replace("-b\n", "\x1b[31m-b\x1b[m\n")
+            .replace("index 1111111..2222222", "index 3333333..4444444")
+            .replace("Change a", "Change a again");
+        assert_eq!(digest(&[], &colored), expected);
+    }
+
+    #[test]
+    fn test_digest_changes_with_content() {
+        let expected = digest(&[], DIFF);
+        for input in [
+            DIFF.replace("+c\n", "+d\n"),
+            DIFF.replace("a.txt", "b.txt"),
+            DIFF.replace("@@ -1,2 +1,2 @@", "@@ -2,2 +2,2 @@"),
+            DIFF.replace(" a\n-b\n", "-b\n a\n"),
+        ] {
+            assert_ne!(digest(&[], &input), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_digest_includes_changes_without_hunks() {
+        let mode_change = "\
+diff --git a/a.sh b/a.sh
+old mode 100644
+new mode 100755
+";
+        let rename = "\
+diff --git a/a.txt b/b.txt
+similarity index 100%
+rename from a.txt
+rename to b.txt
+";
+        let empty = digest(&[], "");
+        for input in [mode_change, rename] {
+            assert_ne!(digest(&[], input), empty, "{}", input);
+        }
+        assert_ne!(
+            digest(&[], mode_change),
+            digest(&[], &mode_change.replace("100755", "120000"))
+        );
+        assert_ne!(
+            digest(&[], rename),
+            digest(&[], &rename.replace("b.txt", "c.txt"))
+        );
+    }
+
+    // A writer whose output can be read once it has been given away.
+    #[derive(Clone, Default)]
+    struct SharedWriter(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedWriter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_digest_is_written_to_its_writer() {
+        let config = make_config_from_args(&["--digest"]);
+        let digest_writer = SharedWriter::default();
+        let output = run_delta_with_machine(DIFF, &config, |machine| {
+            machine.digest = Some(Digest::new(Box::new(digest_writer.clone())));
+        });
+        let expected = format!("{}\n", digest(&[], DIFF));
+        assert_eq!(String::from_utf8(digest_writer.0.take()).unwrap(), expected);
+        assert!(!output.contains(expected.trim()));
+    }
+}
@@ -0,0 +1,148 @@
+use std::borrow::Cow;
+
+use crate::fatal;
+
+/// The encoding assumed for input lines which are not valid UTF-8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Decode UTF-16 if a byte order mark is found, otherwise Latin-1.
+    Auto,
+    /// Do not transcode, replace invalid sequences by U+FFFD.
+    Utf8,
+    Latin1,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    pub fn from_option(encoding: Option<&str>) -> Self {
+        match encoding {
+            Some("auto") | None => Encoding::Auto,
+            Some("utf-8") => Encoding::Utf8,
+            Some("latin-1") => Encoding::Latin1,
+            Some("utf-16le") => Encoding::Utf16Le,
+            Some("utf-16be") => Encoding::Utf16Be,
+            _ => fatal(
+                "Invalid option for assume-encoding: Expected \"auto\", \"utf-8\", \"latin-1\", \
+                 \"utf-16le\" or \"utf-16be\".",
+            ),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Auto => "auto",
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Latin1 => "Latin-1",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+        }
+    }
+}
+
+/// Decode a line which is not valid UTF-8, or a line of a UTF-16 file. Return the decoded line,
+/// and the encoding it was transcoded from, if any.
+pub fn decode_non_utf8_line(line: &[u8], assumed: Encoding) -> (Cow<'_, str>, Option<Encoding>) {
+    let encoding = match assumed {
+        Encoding::Auto => detect_encoding(line),
+        encoding => encoding,
+    };
+    let decoded = match encoding {
+        Encoding::Utf8 | Encoding::Auto => return (String::from_utf8_lossy(line), None),
+        Encoding::Latin1 => line.iter().map(|&b| b as char).collect(),
+        Encoding::Utf16Le | Encoding::Utf16Be => decode_utf16_line(line, encoding),
+    };
+    (Cow::from(decoded), Some(encoding))
+}
+
+/// Look for a UTF-16 byte order mark, either at the start of a line or following a diff line
+/// marker ('+', '-', or ' '). Otherwise assume Latin-1, as any byte sequence is valid Latin-1.
+pub fn detect_encoding(line: &[u8]) -> Encoding {
+    let content = split_diff_marker(line).1;
+    if content.starts_with(&[0xff, 0xfe]) {
+        Encoding::Utf16Le
+    } else if content.starts_with(&[0xfe, 0xff]) {
+        Encoding::Utf16Be
+    } else {
+        Encoding::Latin1
+    }
+}
+
+fn split_diff_marker(line: &[u8]) -> (&[u8], &[u8]) {
+    match line.first() {
+        Some(b'+') | Some(b'-') | Some(b' ') => line.split_at(1),
+        _ => line.split_at(0),
+    }
+}
+
+// The diff marker preceding the content is ASCII, so only the content is UTF-16. Since the input
+// was split into lines at '\n' bytes, the other byte of the UTF-16 newline is left over: at the
+// start of the content (little-endian), or at its end (big-endian).
+fn decode_utf16_line(line: &[u8], encoding: Encoding) -> String {
+    let (marker, mut content) = split_diff_marker(line);
+    let little_endian = encoding == Encoding::Utf16Le;
+    if content.len() % 2 == 1 {
+        content = if little_endian && content[0] == 0 {
+            &content[1..]
+        } else {
+            &content[..content.len() - 1]
+        };
+    }
+    let units = content.chunks_exact(2).map(|pair| {
+        if little_endian {
+            u16::from_le_bytes([pair[0], pair[1]])
+        } else {
+            u16::from_be_bytes([pair[0], pair[1]])
+        }
+    });
+    let mut decoded = String::from_utf8_lossy(marker).into_owned();
+    decoded.extend(
+        char::decode_utf16(units)
+            .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+            .filter(|c| *c != '\u{feff}'),
+    );
+    decoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_latin_1() {
+        assert_eq!(
+            decode_non_utf8_line(b"+caf\xe9 cr\xe8me", Encoding::Auto),
+            (Cow::from("+café crème"), Some(Encoding::Latin1))
+        );
+        assert_eq!(
+            decode_non_utf8_line(b"+caf\xe9", Encoding::Latin1),
+            (Cow::from("+café"), Some(Encoding::Latin1))
+        );
+    }
+
+    #[test]
+    fn test_decode_utf8_lossy() {
+        assert_eq!(
+            decode_non_utf8_line(b"+caf\xe9", Encoding::Utf8),
+            (Cow::from("+caf\u{fffd}"), None)
+        );
+    }
+
+    #[test]
+    fn test_decode_utf16() {
+        // "hé\n" as UTF-16LE with BOM, split at the '\n' byte.
+        assert_eq!(
+            decode_non_utf8_line(b"+\xff\xfeh\x00\xe9\x00", Encoding::Auto),
+            (Cow::from("+hé"), Some(Encoding::Utf16Le))
+        );
+        // The following line starts with the second byte of the UTF-16LE newline.
+        assert_eq!(
+            decode_non_utf8_line(b"-\x00h\x00\xe9\x00", Encoding::Utf16Le),
+            (Cow::from("-hé"), Some(Encoding::Utf16Le))
+        );
+        assert_eq!(
+            decode_non_utf8_line(b" \xfe\xff\x00h\x00\xe9\x00", Encoding::Auto),
+            (Cow::from(" hé"), Some(Encoding::Utf16Be))
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use syntect::parsing::SyntaxSet;
+
+use crate::fatal;
+
+/// A command formatting the files of a language (given by --format-command).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatCommand {
+    // The file extensions, or file names, of the language.
+    pub extensions: Vec<String>,
+    pub command: Vec<String>,
+}
+
+/// Parse --format-command, a semicolon-separated list of entries of the form LANG=COMMAND, where
+/// LANG is the name or a file extension of a language of `syntax_set`.
+pub fn parse_format_commands(
+    format_command: Option<&str>,
+    syntax_set: &SyntaxSet,
+) -> Vec<FormatCommand> {
+    let mut format_commands = Vec::new();
+    for entry_str in format_command.iter().flat_map(|s| s.split(';')) {
+        if entry_str.trim().is_empty() {
+            continue;
+        }
+        let invalid_entry = |reason: &str| -> ! {
+            fatal(format!(
+                "Invalid format-command entry: {}. {reason}",
+                entry_str.trim()
+            ))
+        };
+        let (lang, command_str) = entry_str
+            .split_once('=')
+            .unwrap_or_else(|| invalid_entry("Expected LANG=COMMAND."));
+        let syntax = syntax_set
+            .find_syntax_by_token(lang.trim())
+            .unwrap_or_else(|| invalid_entry("Unknown language (see --list-languages)."));
+        let command = match shell_words::split(command_str) {
+            Ok(command) if !command.is_empty() => command,
+            _ => invalid_entry("Expected LANG=COMMAND."),
+        };
+        format_commands.push(FormatCommand {
+            extensions: syntax.file_extensions.clone(),
+            command,
+        });
+    }
+    format_commands
+}
+
+/// Return `contents` of the file at `path` formatted by the command of its language, if any. The
+/// contents are returned unchanged if the command fails or changes the number of lines.
+pub fn format_file(format_commands: &[FormatCommand], path: &Path, contents: String) -> String {
+    match get_command(format_commands, path) {
+        Some(command) => format_text(command, &contents).unwrap_or(contents),
+        None => contents,
+    }
+}
+
+fn get_command<'a>(format_commands: &'a [FormatCommand], path: &Path) -> Option<&'a [String]> {
+    let file_name = path.file_name()?.to_str()?;
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    format_commands
+        .iter()
+        .find(|format_command| {
+            format_command
+                .extensions
+                .iter()
+                .any(|ext| ext == file_name || Some(ext.as_str()) == extension)
+        })
+        .map(|format_command| format_command.command.as_slice())
+}
+
+// Return the text written by `command` given `text`, or None if it fails or changes the number
+// of lines.
+fn format_text(command: &[String], text: &str) -> Option<String> {
+    let program = grep_cli::resolve_binary(&command[0]).ok()?;
+    let mut child = Command::new(program)
+        .args(&command[1..])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    let mut stdin = child.stdin.take()?;
+    let input = text.to_string();
+    // Written from another thread, so that a command writing as it reads cannot block on a full
+    // pipe.
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let output = child.wait_with_output().ok()?;
+    let _ = writer.join();
+    if !output.status.success() {
+        return None;
+    }
+    let formatted = String::from_utf8_lossy(&output.stdout).into_owned();
+    (formatted.lines().count() == text.lines().count()).then_some(formatted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::make_config_from_args;
+
+    fn format(format_command: &str, path: &str) -> String {
+        let config = make_config_from_args(&["--format-command", format_command]);
+        format_file(
+            &config.format_commands,
+            Path::new(path),
+            "x = 1\ny = 2\n".to_string(),
+        )
+    }
+
+    #[test]
+    fn test_parse_format_commands() {
+        let config = make_config_from_args(&[
+            "--format-command",
+            "rust=rustfmt --emit stdout; py = sh -c 'black -q -'",
+        ]);
+        assert_eq!(config.format_commands.len(), 2);
+        assert!(config.format_commands[0]
+            .extensions
+            .contains(&"rs".to_string()));
+        assert_eq!(
+            config.format_commands[0].command,
+            ["rustfmt", "--emit", "stdout"]
+        );
+        assert!(config.format_commands[1]
+            .extensions
+            .contains(&"py".to_string()));
+        assert_eq!(
+            config.format_commands[1].command,
+            ["sh", "-c", "black -q -"]
+        );
+
+        let config = make_config_from_args(&[]);
+        assert!(config.format_commands.is_empty());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_files_of_a_language_are_formatted() {
+        assert_eq!(
+            format("python=sed s/=/:=/", "/repo/a.py"),
+            "x := 1\ny := 2\n"
+        );
+        // The command of another language is not used.
+        assert_eq!(format("rust=sed s/=/:=/", "/repo/a.py"), "x = 1\ny = 2\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_formatting_which_changes_lines_is_not_used() {
+        // The command removes a line, fails, or does not exist.
+        assert_eq!(format("rust=sed 1d", "a.rs"), "x = 1\ny = 2\n");
+        assert_eq!(format("rust=sh -c 'exit 1'", "a.rs"), "x = 1\ny = 2\n");
+        assert_eq!(
+            format("rust=delta-no-such-formatter", "a.rs"),
+            "x = 1\ny = 2\n"
+        );
+    }
+}
@@ -0,0 +1,356 @@
+use std::path::Path;
+
+use regex::Regex;
+
+use crate::config::Config;
+use crate::utils;
+
+/// Find the function context of hunks whose header lacks it (used by --compute-function-context).
+/// Files assigned a diff driver with a function-context regex by .gitattributes use that regex,
+/// i.e. `*.tex diff=tex` in .gitattributes, and `diff.tex.xfuncname` (or `diff.tex.funcname`)
+/// in git config. Other files use the built-in regex of their language, if there is one. As git
+/// does, the lines preceding the hunk are searched for one matching the regex, in the new version
+/// of the file given by the blob id of its "index" line. That is read from the repository, or
+/// else from the working tree, if the file there is that version.
+#[derive(Default)]
+pub struct FunctionContextFinder {
+    // The repository, discovered on first use.
+    repo: Option<Option<git2::Repository>>,
+    // The file and blob last looked up, and its lines if it has a funcname regex.
+    file: Option<((String, String), Option<FileWithFuncname>)>,
+}
+
+struct FileWithFuncname {
+    lines: Vec<String>,
+    regexes: FuncnameRegexes,
+}
+
+impl FunctionContextFinder {
+    /// Return the function context of the hunk of `path`, a file in `language` whose new version
+    /// has the (abbreviated) blob id `blob_id`, starting at `line_number`.
+    pub fn find(
+        &mut self,
+        path: &str,
+        blob_id: &str,
+        language: &str,
+        line_number: usize,
+        config: &Config,
+    ) -> Option<String> {
+        let key = (path.to_string(), blob_id.to_string());
+        if self.file.as_ref().map(|(file, _)| file) != Some(&key) {
+            let file = self.read_file_with_funcname(path, blob_id, language, config);
+            self.file = Some((key, file));
+        }
+        let FileWithFuncname { lines, regexes } = self.file.as_ref()?.1.as_ref()?;
+        lines
+            .get(..line_number.checked_sub(1)?.min(lines.len()))?
+            .iter()
+            .rev()
+            .find_map(|line| regexes.function_context(line))
+    }
+
+    fn read_file_with_funcname(
+        &mut self,
+        path: &str,
+        blob_id: &str,
+        language: &str,
+        config: &Config,
+    ) -> Option<FileWithFuncname> {
+        let absolute_path = utils::path::absolute_path(path, config)?;
+        let regexes = match self.get_diff_driver_funcname(&absolute_path, config) {
+            Some(funcname) => FuncnameRegexes::parse(&funcname)?,
+            None => FuncnameRegexes::parse(get_builtin_funcname(language)?)?,
+        };
+        let contents = self
+            .read_blob(blob_id, config)
+            .or_else(|| read_working_tree_blob(&absolute_path, blob_id))?;
+        let lines = String::from_utf8_lossy(&contents)
+            .lines()
+            .map(str::to_string)
+            .collect();
+        Some(FileWithFuncname { lines, regexes })
+    }
+
+    fn get_repo(&mut self, config: &Config) -> Option<&git2::Repository> {
+        self.repo
+            .get_or_insert_with(|| {
+                git2::Repository::discover(config.cwd_of_delta_process.as_ref()?).ok()
+            })
+            .as_ref()
+    }
+
+    // Return the contents of the blob with the given abbreviated id, if it is in the repository.
+    fn read_blob(&mut self, blob_id: &str, config: &Config) -> Option<Vec<u8>> {
+        let object = self
+            .get_repo(config)?
+            .find_object_by_prefix(blob_id, Some(git2::ObjectType::Blob))
+            .ok()?;
+        Some(object.as_blob()?.content().to_vec())
+    }
+
+    // Return the funcname setting of the diff driver assigned to the file by .gitattributes.
+    fn get_diff_driver_funcname(
+        &mut self,
+        absolute_path: &Path,
+        config: &Config,
+    ) -> Option<String> {
+        let repo = self.get_repo(config)?;
+        let relative_path = absolute_path.strip_prefix(repo.workdir()?).ok()?;
+        let driver = repo
+            .get_attr(relative_path, "diff", git2::AttrCheckFlags::FILE_THEN_INDEX)
+            .ok()??;
+        let git_config = config.git_config()?;
+        git_config
+            .get::<String>(&format!("diff.{driver}.xfuncname"))
+            .or_else(|| git_config.get::<String>(&format!("diff.{driver}.funcname")))
+    }
+}
+
+/// Return the built-in funcname setting of a language, named as by --list-languages (used by
+/// --compute-function-context). These match the definitions nested in other definitions, which
+/// git's default of a line starting with a letter misses.
+fn get_builtin_funcname(language: &str) -> Option<&'static str> {
+    let funcname = match language {
+        "Bourne Again Shell (bash)" => {
+            r"^[\t ]*((function[\t ]+)?[A-Za-z_][A-Za-z0-9_:.-]*[\t ]*\(\).*|function[\t ]+[A-Za-z_].*)$"
+        }
+        "Elixir" => r"^[\t ]*((defp?|defmacrop?|defmodule|defprotocol|defimpl)[\t ].*)$",
+        "Go" => r"^((func|type)[\t ].*)$",
+        "JavaScript" | "JavaScript (Babel)" | "TypeScript" | "TypeScriptReact" => {
+            r"^[\t ]*((export[\t ]+)?(default[\t ]+)?(async[\t ]+)?(function\*?|class)[\t ].*)$"
+        }
+        "Lua" => r"^[\t ]*((local[\t ]+)?function[\t ].*)$",
+        "Markdown" => r"^(#{1,6}[\t ].*)$",
+        "Python" => r"^[\t ]*((async[\t ]+)?(def|class)[\t ].*)$",
+        "Ruby" => r"^[\t ]*((class|module|def)[\t ].*)$",
+        "Rust" => {
+            r#"^[\t ]*((pub(\([^)]*\))?[\t ]+)?((async|const|unsafe|extern[\t ]+"[^"]*")[\t ]+)*(fn|struct|enum|union|trait|impl|mod|macro_rules!)[\t <].*)$"#
+        }
+        _ => return None,
+    };
+    Some(funcname)
+}
+
+// Return the contents of the working tree version of a file, if it is the blob with the given
+// abbreviated id.
+fn read_working_tree_blob(path: &Path, blob_id: &str) -> Option<Vec<u8>> {
+    let contents = std::fs::read(path).ok()?;
+    let oid = git2::Oid::hash_object(git2::ObjectType::Blob, &contents).ok()?;
+    (!blob_id.is_empty() && oid.to_string().starts_with(blob_id)).then_some(contents)
+}
+
+/// The regexes of a `funcname` setting: one per line, where a leading '!' negates a regex.
+pub struct FuncnameRegexes(Vec<(bool, Regex)>);
+
+impl FuncnameRegexes {
+    pub fn parse(funcname: &str) -> Option<Self> {
+        let regexes = funcname
+            .lines()
+            .map(|pattern| match pattern.strip_prefix('!') {
+                Some(pattern) => Regex::new(pattern).map(|regex| (true, regex)),
+                None => Regex::new(pattern).map(|regex| (false, regex)),
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        Some(Self(regexes))
+    }
+
+    /// Return the function context given by `line`, if it matches. As in git, this is the first
+    /// capture group if there is one and the whole line otherwise, truncated to 80 bytes. A line
+    /// is not a match if the first regex it matches is negated.
+    pub fn function_context(&self, line: &str) -> Option<String> {
+        let (negated, caps) = self
+            .0
+            .iter()
+            .find_map(|(negated, regex)| Some((*negated, regex.captures(line)?)))?;
+        if negated {
+            return None;
+        }
+        let context = caps.get(1).map_or(line, |m| m.as_str()).trim_end();
+        let mut end = context.len().min(80);
+        while !context.is_char_boundary(end) {
+            end -= 1;
+        }
+        Some(context[..end].to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils;
+
+    #[test]
+    fn test_funcname_regexes() {
+        let regexes = FuncnameRegexes::parse("!^= Draft\n^= (.*)$\n^(\\[.*\\])").unwrap();
+        assert_eq!(
+            regexes.function_context("= Overview"),
+            Some("Overview".to_string())
+        );
+        assert_eq!(regexes.function_context("= Draft notes"), None);
+        assert_eq!(
+            regexes.function_context("[section]  "),
+            Some("[section]".to_string())
+        );
+        assert_eq!(regexes.function_context("text"), None);
+    }
+
+    #[test]
+    fn test_builtin_funcname_regexes() {
+        let function_context = |language: &str, line: &str| {
+            FuncnameRegexes::parse(get_builtin_funcname(language).unwrap())
+                .unwrap()
+                .function_context(line)
+        };
+        assert_eq!(
+            function_context("Python", "    def method(self):"),
+            Some("def method(self):".to_string())
+        );
+        assert_eq!(function_context("Python", "    return 1"), None);
+        assert_eq!(
+            function_context("Rust", "    pub(crate) async fn run() {"),
+            Some("pub(crate) async fn run() {".to_string())
+        );
+        assert_eq!(
+            function_context("Rust", "impl<T> Trait for S<T> {"),
+            Some("impl<T> Trait for S<T> {".to_string())
+        );
+        assert_eq!(function_context("Rust", "    let fnord = 1;"), None);
+        assert_eq!(
+            function_context("JavaScript (Babel)", "  export async function f() {"),
+            Some("export async function f() {".to_string())
+        );
+        assert_eq!(
+            function_context("Bourne Again Shell (bash)", "  cleanup() {"),
+            Some("cleanup() {".to_string())
+        );
+        assert_eq!(get_builtin_funcname("Plain Text"), None);
+    }
+
+    fn blob_id(contents: &str) -> String {
+        git2::Oid::hash_object(git2::ObjectType::Blob, contents.as_bytes())
+            .unwrap()
+            .to_string()[..7]
+            .to_string()
+    }
+
+    #[test]
+    fn test_hunk_header_function_context_computed_for_language() {
+        let dir = std::env::temp_dir()
+            .join("delta__test_hunk_header_function_context_computed_for_language");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let contents = "class A:\n    def first(self):\n        pass\n\n    def second(self):\n        one\n        two\n        three\n";
+        fs::write(dir.join("a.py"), contents).unwrap();
+        fs::write(dir.join("a.txt"), contents).unwrap();
+
+        let hunk_header = |args: &[&str], file: &str, blob_id: &str| {
+            let mut config = integration_test_utils::make_config_from_args(args);
+            config.cwd_of_delta_process = Some(dir.canonicalize().unwrap());
+            // Git's default regex only matches lines starting with a letter: "class A:".
+            let input = format!(
+                "\
+diff --git a/{file} b/{file}
+index 1111111..{blob_id} 100644
+--- a/{file}
++++ b/{file}
+@@ -7,2 +7,2 @@
+         two
+-        3
++        three
+"
+            );
+            let output = strip_ansi_codes(&integration_test_utils::run_delta(&input, &config));
+            output
+                .lines()
+                .find(|line| line.starts_with("7:"))
+                .unwrap()
+                .to_string()
+        };
+        let blob_id = blob_id(contents);
+        assert_eq!(
+            hunk_header(&["--compute-function-context"], "a.py", &blob_id),
+            "7: def second(self): │"
+        );
+        assert_eq!(hunk_header(&[], "a.py", &blob_id), "7: │");
+        // The working tree file is not the new version of the file.
+        assert_eq!(
+            hunk_header(&["--compute-function-context"], "a.py", "2222222"),
+            "7: │"
+        );
+        // There is no built-in regex for plain text.
+        assert_eq!(
+            hunk_header(&["--compute-function-context"], "a.txt", &blob_id),
+            "7: │"
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_hunk_header_function_context_from_gitattributes_diff_driver() {
+        let repo_dir = std::env::temp_dir()
+            .join("delta__test_hunk_header_function_context_from_gitattributes_diff_driver");
+        let _ = fs::remove_dir_all(&repo_dir);
+        let repo = git2::Repository::init(&repo_dir).unwrap();
+        fs::write(repo_dir.join(".gitattributes"), "*.doc diff=doc\n").unwrap();
+        let doc_contents = "= Introduction\ntext\n\n= Usage\none\ntwo\nthree\nfour\n";
+        let txt_contents = "= Introduction\none\ntwo\nthree\n";
+        // The new version of a.doc is read from the repository, not from the working tree.
+        repo.blob(doc_contents.as_bytes()).unwrap();
+        fs::write(repo_dir.join("a.doc"), "= Working tree\n").unwrap();
+        fs::write(repo_dir.join("a.txt"), txt_contents).unwrap();
+
+        let git_config_contents = br#"
+[diff "doc"]
+    xfuncname = "^= (.*)$"
+"#;
+        let git_config_path =
+            "delta__test_hunk_header_function_context_from_gitattributes_diff_driver.gitconfig";
+        let mut config = integration_test_utils::make_config_from_args_and_git_config(
+            &["--compute-function-context"],
+            Some(git_config_contents),
+            Some(git_config_path),
+        );
+        config.cwd_of_delta_process = Some(repo_dir.canonicalize().unwrap());
+
+        let hunk_header = |file: &str, blob_id: &str, fragment: &str| {
+            let input = format!(
+                "\
+diff --git a/{file} b/{file}
+index 1111111..{blob_id} 100644
+--- a/{file}
++++ b/{file}
+@@ -6,2 +6,2 @@{fragment}
+ two
+-3
++three
+"
+            );
+            let output = strip_ansi_codes(&integration_test_utils::run_delta(&input, &config));
+            output
+                .lines()
+                .find(|line| line.starts_with("6:"))
+                .unwrap()
+                .to_string()
+        };
+        // The fragment is found with the regex of the file's diff driver.
+        assert_eq!(
+            hunk_header("a.doc", &blob_id(doc_contents), ""),
+            "6: Usage │"
+        );
+        // A fragment provided by git is kept.
+        assert_eq!(
+            hunk_header("a.doc", &blob_id(doc_contents), " = Introduction"),
+            "6: = Introduction │"
+        );
+        // Files without a diff driver, and without a built-in regex, are unaffected.
+        assert_eq!(hunk_header("a.txt", &blob_id(txt_contents), ""), "6: │");
+
+        fs::remove_file(git_config_path).unwrap();
+        fs::remove_dir_all(&repo_dir).unwrap();
+    }
+}
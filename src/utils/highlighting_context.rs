@@ -0,0 +1,89 @@
+use std::path::{Path, PathBuf};
+
+use crate::utils::trailing_context::FileSource;
+
+/// Supply the lines of the new version of a file preceding each of its hunks, to be highlighted
+/// before the hunk (used by --stateful-highlighting). Each file is read once, and the
+/// highlighters carry on from one hunk of a file to the next, so that only the lines between the
+/// two hunks are supplied.
+pub struct HighlightingContext {
+    source: Box<dyn FileSource>,
+    // The file of the last hunk, its lines if it could be read, and the number of the line
+    // following that hunk in the new version of the file.
+    file: Option<(PathBuf, Option<Vec<String>>, usize)>,
+}
+
+impl HighlightingContext {
+    pub fn new(source: Box<dyn FileSource>) -> Self {
+        Self { source, file: None }
+    }
+
+    /// Return whether the highlighters continue from the last hunk, and the lines they have yet
+    /// to be given before the hunk of `file` spanning `plus_length` lines from `plus_line_number`
+    /// in its new version. If they do not continue, they are to start afresh.
+    pub fn lines_before_hunk(
+        &mut self,
+        file: Option<&Path>,
+        plus_line_number: usize,
+        plus_length: usize,
+    ) -> (bool, &[String]) {
+        let Some(file) = file else {
+            self.file = None;
+            return (false, &[]);
+        };
+        let (first_line_number, lines) = match self.file.take() {
+            Some((last_file, lines, next_line_number)) if last_file == file => (
+                Some(next_line_number).filter(|&n| n <= plus_line_number && lines.is_some()),
+                lines,
+            ),
+            _ => (None, self.source.read_lines(file)),
+        };
+        let lines = &self
+            .file
+            .insert((file.to_path_buf(), lines, plus_line_number + plus_length))
+            .1;
+        let lines = lines.as_deref().unwrap_or_default();
+        let end = plus_line_number.saturating_sub(1).min(lines.len());
+        let start = first_line_number
+            .map_or(0, |n| n.saturating_sub(1))
+            .min(end);
+        (first_line_number.is_some(), &lines[start..end])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::StubFiles;
+
+    #[test]
+    fn test_lines_before_hunk() {
+        let mut context = HighlightingContext::new(Box::new(StubFiles {
+            path: "a.txt",
+            lines: &["1", "2", "3", "4", "5", "6", "7", "8"],
+        }));
+        let a = Some(Path::new("/repo/a.txt"));
+        assert_eq!(
+            context.lines_before_hunk(a, 3, 2),
+            (false, &["1", "2"].map(String::from)[..])
+        );
+        // The next hunk of the file continues after the last one.
+        assert_eq!(
+            context.lines_before_hunk(a, 7, 1),
+            (true, &["5", "6"].map(String::from)[..])
+        );
+        // A hunk preceding the last one starts afresh.
+        assert_eq!(
+            context.lines_before_hunk(a, 2, 1),
+            (false, &["1"].map(String::from)[..])
+        );
+        // A file which cannot be read supplies no lines.
+        let b = Some(Path::new("/repo/b.txt"));
+        assert_eq!(context.lines_before_hunk(b, 3, 1), (false, &[][..]));
+        assert_eq!(context.lines_before_hunk(b, 5, 1), (false, &[][..]));
+        assert_eq!(
+            context.lines_before_hunk(a, 3, 1),
+            (false, &["1", "2"].map(String::from)[..])
+        );
+    }
+}
@@ -0,0 +1,88 @@
+use std::io::{self, Write};
+
+/// A writer which indents every non-empty line written to it by a number of spaces (used with
+/// --indent).
+pub struct IndentingWriter<'a> {
+    writer: &'a mut dyn Write,
+    indent: String,
+    at_line_start: bool,
+}
+
+impl<'a> IndentingWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write, indent: usize) -> Self {
+        Self {
+            writer,
+            indent: " ".repeat(indent),
+            at_line_start: true,
+        }
+    }
+}
+
+impl Write for IndentingWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        for line in buf.split_inclusive(|&byte| byte == b'\n') {
+            if self.at_line_start && line != b"\n" {
+                self.writer.write_all(self.indent.as_bytes())?;
+            }
+            self.writer.write_all(line)?;
+            self.at_line_start = line.ends_with(b"\n");
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_indenting_writer() {
+        let mut output = Vec::new();
+        let mut writer = IndentingWriter::new(&mut output, 2);
+        write!(writer, "a\n\nb").unwrap();
+        writeln!(writer, "c").unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "  a\n\n  bc\n");
+    }
+
+    #[test]
+    fn test_indent_option() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ a
+-b
++c
+";
+        for args in [&["--width=40"][..], &["--width=40", "--line-numbers"][..]] {
+            let plain = DeltaTest::with_args(args).with_input(input);
+            let indented =
+                DeltaTest::with_args(&[args, &["--indent=4"]].concat()).with_input(input);
+            assert_eq!(
+                plain.output.lines().count(),
+                indented.output.lines().count()
+            );
+            for (plain, indented) in plain.output.lines().zip(indented.output.lines()) {
+                if plain.is_empty() {
+                    assert_eq!(indented, "");
+                } else {
+                    assert_eq!(&indented[..4], "    ");
+                }
+                // Decorations are narrowed to keep the total width.
+                if !plain.is_empty() && plain.chars().all(|c| c == '─') {
+                    assert_eq!(
+                        indented.trim_start().chars().count() + 4,
+                        plain.chars().count()
+                    );
+                }
+            }
+        }
+    }
+}
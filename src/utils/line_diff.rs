@@ -0,0 +1,244 @@
+use std::cmp::min;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edit {
+    Keep,
+    Delete,
+    Insert,
+}
+
+/// Return the unified diff of the lines of `old` and `new`, with `context` lines of context
+/// around each change, or an empty string if they are equal.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+) -> String {
+    // The newlines are kept, so that a change to the newline at the end of the file is a change.
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let edits = diff_lines(&old_lines, &new_lines);
+    if edits.iter().all(|&edit| edit == Edit::Keep) {
+        return String::new();
+    }
+
+    // The ranges of the edits of the hunks: each change with the context around it, merging
+    // those which overlap or touch.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (i, _) in edits
+        .iter()
+        .enumerate()
+        .filter(|(_, &edit)| edit != Edit::Keep)
+    {
+        let (start, end) = (i.saturating_sub(context), min(i + 1 + context, edits.len()));
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    // The numbers of the old and new lines preceding each edit.
+    let mut positions = Vec::with_capacity(edits.len() + 1);
+    let (mut old_pos, mut new_pos) = (0, 0);
+    for edit in &edits {
+        positions.push((old_pos, new_pos));
+        match edit {
+            Edit::Keep => {
+                old_pos += 1;
+                new_pos += 1;
+            }
+            Edit::Delete => old_pos += 1,
+            Edit::Insert => new_pos += 1,
+        }
+    }
+    positions.push((old_pos, new_pos));
+
+    let mut diff = format!("--- {old_label}\n+++ {new_label}\n");
+    for (start, end) in hunks {
+        let (old_start, new_start) = positions[start];
+        let (old_end, new_end) = positions[end];
+        diff.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            format_range(old_start, old_end - old_start),
+            format_range(new_start, new_end - new_start)
+        ));
+        for (edit, &(old_pos, new_pos)) in edits[start..end].iter().zip(&positions[start..end]) {
+            let (prefix, line) = match edit {
+                Edit::Keep => (' ', old_lines[old_pos]),
+                Edit::Delete => ('-', old_lines[old_pos]),
+                Edit::Insert => ('+', new_lines[new_pos]),
+            };
+            diff.push(prefix);
+            diff.push_str(line);
+            if !line.ends_with('\n') {
+                diff.push_str("\n\\ No newline at end of file\n");
+            }
+        }
+    }
+    diff
+}
+
+// Format the range of a hunk header as git does: an empty range is given by the line preceding
+// it, and the length of a range of one line is omitted.
+fn format_range(start: usize, n_lines: usize) -> String {
+    match n_lines {
+        0 => format!("{start},0"),
+        1 => format!("{}", start + 1),
+        _ => format!("{},{n_lines}", start + 1),
+    }
+}
+
+// Return a shortest sequence of edits turning `old` into `new`, found with Myers' algorithm
+// after removing the lines which they start and end with in common.
+fn diff_lines<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    let n_prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let n_suffix = old[n_prefix..]
+        .iter()
+        .rev()
+        .zip(new[n_prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut edits = vec![Edit::Keep; n_prefix];
+    edits.extend(myers(
+        &old[n_prefix..old.len() - n_suffix],
+        &new[n_prefix..new.len() - n_suffix],
+    ));
+    edits.extend(vec![Edit::Keep; n_suffix]);
+    edits
+}
+
+fn myers<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+    let (n, m) = (old.len() as isize, new.len() as isize);
+    let max = n + m;
+    // v[k + offset] is the furthest x reached on diagonal k = x - y.
+    let offset = max + 1;
+    let mut v = vec![0_isize; (2 * max + 3) as usize];
+    // The part of v used by each step d, i.e. diagonals -d - 1 to d + 1, before the step.
+    let mut trace = Vec::new();
+    'steps: for d in 0..=max {
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let i = (k + offset) as usize;
+            let mut x = if k == -d || (k != d && v[i - 1] < v[i + 1]) {
+                v[i + 1]
+            } else {
+                v[i - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && old[x as usize] == new[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[i] = x;
+            if x >= n && y >= m {
+                break 'steps;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let get = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = get(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Keep);
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            edits.push(if x == prev_x {
+                Edit::Insert
+            } else {
+                Edit::Delete
+            });
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_lines() {
+        use Edit::*;
+        let diff = |old: &str, new: &str| {
+            let (old, new): (Vec<char>, Vec<char>) = (old.chars().collect(), new.chars().collect());
+            diff_lines(&old, &new)
+        };
+        assert_eq!(diff("", ""), vec![]);
+        assert_eq!(diff("abc", "abc"), vec![Keep, Keep, Keep]);
+        assert_eq!(diff("", "ab"), vec![Insert, Insert]);
+        assert_eq!(diff("ab", ""), vec![Delete, Delete]);
+        assert_eq!(diff("abc", "axc"), vec![Keep, Delete, Insert, Keep]);
+        assert_eq!(
+            diff("abcabba", "cbabac"),
+            vec![Delete, Delete, Keep, Insert, Keep, Keep, Delete, Keep, Insert]
+        );
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let new = "1\ntwo\n3\n4\n5\n6\n7\n8\n9\n10\neleven\n";
+        assert_eq!(
+            unified_diff(old, new, "old", "new", 1),
+            "\
+--- old
++++ new
+@@ -1,3 +1,3 @@
+ 1
+-2
++two
+ 3
+@@ -10 +10,2 @@
+ 10
++eleven
+"
+        );
+        // With more context, the hunks are merged.
+        assert!(unified_diff(old, new, "old", "new", 4).contains("@@ -1,10 +1,11 @@\n"));
+        assert_eq!(unified_diff(old, old, "old", "new", 3), "");
+    }
+
+    #[test]
+    fn test_unified_diff_of_empty_and_unterminated_files() {
+        assert_eq!(
+            unified_diff("", "a\nb", "old", "new", 3),
+            "\
+--- old
++++ new
+@@ -0,0 +1,2 @@
++a
++b
+\\ No newline at end of file
+"
+        );
+        assert_eq!(
+            unified_diff("a\n", "a", "old", "new", 3),
+            "\
+--- old
++++ new
+@@ -1 +1 @@
+-a
++a
+\\ No newline at end of file
+"
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use std::fmt;
+
+use crate::delta::State;
+
+/// A hunk whose lines on the new side of the file do not match its header.
+#[derive(Debug, PartialEq, Eq)]
+pub struct LineNumberMismatch {
+    pub file: String,
+    // The number of the hunk in the file, starting at 1.
+    pub hunk_number: usize,
+    pub hunk_header: String,
+    // The first line number and the number of lines of the new side declared by the hunk header.
+    pub declared: (usize, usize),
+    // The number of lines of the new side which were numbered.
+    pub n_rendered: usize,
+}
+
+impl fmt::Display for LineNumberMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (start, n_lines) = self.declared;
+        write!(
+            f,
+            "delta: line numbers do not match the hunk header: file={} hunk={} header=\"{}\" \
+             declared_start={} declared_lines={} rendered_lines={}",
+            self.file, self.hunk_number, self.hunk_header, start, n_lines, self.n_rendered
+        )
+    }
+}
+
+// The hunk being checked: its file, number and header, and its first line number and number of
+// lines on the new side.
+struct Hunk {
+    file: String,
+    number: usize,
+    header: String,
+    declared: (usize, usize),
+}
+
+/// Checks that the line numbers which delta gives the new side of each hunk end where its header
+/// says they do (used by --validate-line-numbers).
+#[derive(Default)]
+pub struct LineNumberValidator {
+    hunk: Option<Hunk>,
+    // The file of the last hunk, and the number of hunks seen in it.
+    file: String,
+    n_hunks_of_file: usize,
+    pub mismatches: Vec<LineNumberMismatch>,
+}
+
+impl LineNumberValidator {
+    /// Add the current line of the diff, which is in `state`. If it ends a hunk, check the hunk,
+    /// given `next_plus_line_number`, the line number delta would give the next line of the new
+    /// side, and return the mismatch if there is one.
+    pub fn add_line(
+        &mut self,
+        state: &State,
+        minus_file: &str,
+        plus_file: &str,
+        next_plus_line_number: usize,
+    ) -> Option<&LineNumberMismatch> {
+        match state {
+            State::HunkMinus(..)
+            | State::HunkPlus(..)
+            | State::HunkZero(..)
+            | State::HunkMinusWrapped
+            | State::HunkPlusWrapped
+            | State::HunkZeroWrapped => None,
+            State::HunkHeader(_, parsed_hunk_header, line, _) => {
+                let is_mismatch = self.check_hunk(next_plus_line_number);
+                let file = if plus_file == "/dev/null" {
+                    minus_file
+                } else {
+                    plus_file
+                };
+                if file != self.file {
+                    self.file = file.to_string();
+                    self.n_hunks_of_file = 0;
+                }
+                self.n_hunks_of_file += 1;
+                self.hunk =
+                    parsed_hunk_header
+                        .line_numbers_and_hunk_lengths
+                        .last()
+                        .map(|&declared| Hunk {
+                            file: file.to_string(),
+                            number: self.n_hunks_of_file,
+                            header: line.clone(),
+                            declared,
+                        });
+                self.mismatches.last().filter(|_| is_mismatch)
+            }
+            _ => self.finish(next_plus_line_number),
+        }
+    }
+
+    /// Check the current hunk, if any, which has ended with the diff.
+    pub fn finish(&mut self, next_plus_line_number: usize) -> Option<&LineNumberMismatch> {
+        if self.check_hunk(next_plus_line_number) {
+            self.mismatches.last()
+        } else {
+            None
+        }
+    }
+
+    fn check_hunk(&mut self, next_plus_line_number: usize) -> bool {
+        let Some(hunk) = self.hunk.take() else {
+            return false;
+        };
+        let (start, n_lines) = hunk.declared;
+        let n_rendered = next_plus_line_number.saturating_sub(start);
+        if n_rendered == n_lines {
+            return false;
+        }
+        self.mismatches.push(LineNumberMismatch {
+            file: hunk.file,
+            hunk_number: hunk.number,
+            hunk_header: hunk.header,
+            declared: hunk.declared,
+            n_rendered,
+        });
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::BufReader;
+
+    use bytelines::ByteLines;
+
+    use super::LineNumberMismatch;
+    use crate::delta::StateMachine;
+    use crate::tests::integration_test_utils::make_config_from_args;
+
+    fn mismatches(args: &[&str], input: &str) -> Vec<LineNumberMismatch> {
+        let config = make_config_from_args(&[args, &["--validate-line-numbers"]].concat());
+        let mut writer = Vec::new();
+        let mut machine = StateMachine::new(&mut writer, &config);
+        machine
+            .consume(ByteLines::new(BufReader::new(input.as_bytes())))
+            .unwrap();
+        machine.line_number_validator.take().unwrap().mismatches
+    }
+
+    const DIFF: &str = "\
+diff --git a/a.txt b/a.txt
+index 1111111..2222222 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,3 +1,3 @@
+ a
+-b
++c
+ d
+@@ -10,2 +10,3 @@ fn f()
+ x
++y
+ z
+diff --git a/b.txt b/b.txt
+index 1111111..2222222 100644
+--- a/b.txt
++++ b/b.txt
+@@ -1 +1 @@
+-a
++b
+";
+
+    #[test]
+    fn test_consistent_hunks_pass() {
+        for args in [&[][..], &["--side-by-side"], &["--line-numbers"]] {
+            assert_eq!(mismatches(args, DIFF), vec![], "{:?}", args);
+        }
+    }
+
+    #[test]
+    fn test_inconsistent_hunk_is_reported() {
+        // The second hunk of a.txt declares 4 lines on the new side but has 3.
+        let input = DIFF.replace("@@ -10,2 +10,3 @@", "@@ -10,2 +10,4 @@");
+        let expected = LineNumberMismatch {
+            file: "a.txt".to_string(),
+            hunk_number: 2,
+            hunk_header: "@@ -10,2 +10,4 @@ fn f()".to_string(),
+            declared: (10, 4),
+            n_rendered: 3,
+        };
+        assert_eq!(
+            expected.to_string(),
+            "delta: line numbers do not match the hunk header: file=a.txt hunk=2 \
+             header=\"@@ -10,2 +10,4 @@ fn f()\" declared_start=10 declared_lines=4 \
+             rendered_lines=3"
+        );
+        assert_eq!(mismatches(&[], &input), vec![expected]);
+
+        // A hunk ending the input is checked too.
+        let input = DIFF.replace("@@ -1 +1 @@", "@@ -1 +1,2 @@");
+        let mismatches = mismatches(&[], &input);
+        assert_eq!(mismatches.len(), 1);
+        assert_eq!(
+            (mismatches[0].file.as_str(), mismatches[0].n_rendered),
+            ("b.txt", 1)
+        );
+    }
+}
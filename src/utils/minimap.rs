@@ -0,0 +1,299 @@
+use std::io::{self, Write};
+
+use ansi_term::Color;
+
+use crate::ansi::{self, ANSI_CSI_CLEAR_TO_EOL};
+use crate::cli;
+use crate::config::Config;
+use crate::style::Style;
+use crate::utils::buffer_limit::BufferLimit;
+
+/// The number of columns taken by the minimap: a space and the column of cells.
+pub const MINIMAP_WIDTH: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LineKind {
+    Minus,
+    Plus,
+    Other,
+}
+
+// A color identifying removed or added lines in the output, and whether it is a background color.
+type ColorKey = (Color, bool);
+
+/// A writer which collects everything written to it, and writes it followed by a column giving an
+/// overview of the whole diff (used with --minimap). Each cell of the column summarizes several
+/// lines of output. Lines are recognized as removed or added lines by the colors of the minus
+/// and plus styles.
+pub struct MinimapWriter<'a> {
+    writer: &'a mut dyn Write,
+    output: Vec<u8>,
+    width: Option<usize>,
+    height: usize,
+    minus_colors: Vec<ColorKey>,
+    plus_colors: Vec<ColorKey>,
+    minus_cell_style: ansi_term::Style,
+    plus_cell_style: ansi_term::Style,
+    limit: BufferLimit,
+}
+
+impl<'a> MinimapWriter<'a> {
+    pub fn new(writer: &'a mut dyn Write, config: &Config) -> Self {
+        Self {
+            writer,
+            output: Vec::new(),
+            width: match config.decorations_width {
+                cli::Width::Fixed(width) => Some(width + config.indent),
+                cli::Width::Variable => None,
+            },
+            height: config.available_terminal_height.max(1),
+            minus_colors: color_keys(&[
+                config.minus_style,
+                config.minus_emph_style,
+                config.minus_non_emph_style,
+            ]),
+            plus_colors: color_keys(&[
+                config.plus_style,
+                config.plus_emph_style,
+                config.plus_non_emph_style,
+            ]),
+            minus_cell_style: cell_style(config.minus_emph_style),
+            plus_cell_style: cell_style(config.plus_emph_style),
+            limit: BufferLimit::new("minimap", config),
+        }
+    }
+
+    /// Write everything written so far, with the minimap to the right of its first lines. Once
+    /// --buffer-limit-bytes is exceeded, everything has already been written, without a minimap.
+    pub fn finish(self) -> io::Result<()> {
+        if self.limit.is_exceeded() {
+            return self.writer.flush();
+        }
+        let output = String::from_utf8_lossy(&self.output);
+        let lines: Vec<&str> = output.lines().collect();
+        let kinds: Vec<LineKind> = lines.iter().map(|line| self.line_kind(line)).collect();
+        let cells = summarize(&kinds, self.height);
+        let width = self.width.unwrap_or_else(|| {
+            lines
+                .iter()
+                .map(|line| ansi::measure_text_width(line))
+                .max()
+                .unwrap_or(0)
+        });
+        for (i, line) in lines.iter().enumerate() {
+            match cells.get(i) {
+                Some(kind) => {
+                    let padding = " ".repeat(width.saturating_sub(ansi::measure_text_width(line)));
+                    // A background color extending to the end of the line is instead extended to
+                    // the minimap, by writing the padding in place of the clear-to-EOL sequence.
+                    let line = if line.contains(ANSI_CSI_CLEAR_TO_EOL) {
+                        line.replacen(ANSI_CSI_CLEAR_TO_EOL, &padding, 1)
+                    } else {
+                        format!("{line}{padding}")
+                    };
+                    writeln!(self.writer, "{} {}", line, self.paint_cell(*kind))?;
+                }
+                None => writeln!(self.writer, "{line}")?,
+            }
+        }
+        self.writer.flush()
+    }
+
+    fn line_kind(&self, line: &str) -> LineKind {
+        let has_color = |keys: &[ColorKey]| {
+            ansi::parse_style_sections(line).iter().any(|(style, _)| {
+                keys.iter().any(|&(color, is_background)| {
+                    if is_background {
+                        style.background == Some(color)
+                    } else {
+                        style.foreground == Some(color)
+                    }
+                })
+            })
+        };
+        if has_color(&self.minus_colors) {
+            LineKind::Minus
+        } else if has_color(&self.plus_colors) {
+            LineKind::Plus
+        } else {
+            LineKind::Other
+        }
+    }
+
+    fn paint_cell(&self, kind: LineKind) -> String {
+        match kind {
+            LineKind::Minus => self.minus_cell_style.paint("█").to_string(),
+            LineKind::Plus => self.plus_cell_style.paint("█").to_string(),
+            LineKind::Other => ansi_term::Style::new().dimmed().paint("│").to_string(),
+        }
+    }
+}
+
+impl Write for MinimapWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.limit.is_exceeded() {
+            return self.writer.write(buf);
+        }
+        self.output.extend_from_slice(buf);
+        if self.limit.add(buf.len()) {
+            // Write what was collected without a minimap.
+            self.writer.write_all(&std::mem::take(&mut self.output))?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// The colors of the styles, preferring background colors. The foreground colors of syntax
+// highlighted styles vary from line to line, so they do not identify anything.
+fn color_keys(styles: &[Style]) -> Vec<ColorKey> {
+    styles
+        .iter()
+        .filter_map(|style| match style.ansi_term_style.background {
+            Some(color) => Some((color, true)),
+            None if style.is_syntax_highlighted => None,
+            None => style.ansi_term_style.foreground.map(|color| (color, false)),
+        })
+        .collect()
+}
+
+// A cell is painted in the background color of the style, as a foreground color.
+fn cell_style(style: Style) -> ansi_term::Style {
+    let style = style.ansi_term_style;
+    match style.background.or(style.foreground) {
+        Some(color) => ansi_term::Style::new().fg(color),
+        None => ansi_term::Style::new(),
+    }
+}
+
+/// Group the lines into at most `height` cells of equal size, each of the kind of line occurring
+/// most often in it. Ties are resolved in favor of removed, then added lines.
+fn summarize(kinds: &[LineKind], height: usize) -> Vec<LineKind> {
+    let lines_per_cell = kinds.len().div_ceil(height).max(1);
+    kinds
+        .chunks(lines_per_cell)
+        .map(|chunk| {
+            let count = |kind| chunk.iter().filter(|&&k| k == kind).count();
+            let (n_minus, n_plus, n_other) = (
+                count(LineKind::Minus),
+                count(LineKind::Plus),
+                count(LineKind::Other),
+            );
+            if n_minus >= n_plus && n_minus >= n_other {
+                LineKind::Minus
+            } else if n_plus >= n_other {
+                LineKind::Plus
+            } else {
+                LineKind::Other
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineKind::*;
+    use super::*;
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_summarize() {
+        let kinds = [Other, Minus, Minus, Plus, Other, Other, Plus, Plus];
+        assert_eq!(summarize(&kinds, 8), kinds);
+        assert_eq!(summarize(&kinds, 4), [Minus, Minus, Other, Plus]);
+        assert_eq!(summarize(&kinds, 3), [Minus, Other, Plus]);
+        assert_eq!(summarize(&kinds, 1), [Plus]);
+        assert_eq!(summarize(&[], 4), []);
+    }
+
+    #[test]
+    fn test_minimap() {
+        let mut input = String::from(
+            "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,20 +1,20 @@
+",
+        );
+        for i in 0..20 {
+            input.push_str(&format!("-a{i}\n"));
+        }
+        for i in 0..20 {
+            input.push_str(&format!("+b{i}\n"));
+        }
+        let output = DeltaTest::with_args(&["--minimap"]).with_input(&input);
+        let lines: Vec<&str> = output.raw_output.lines().collect();
+        // The 47 lines of output are summarized by 24 cells of 2 lines each. The first 7 lines
+        // are the file and hunk headers, followed by 20 removed lines, and 20 added lines.
+        assert_eq!(lines.len(), 47);
+        let cells: Vec<String> = lines
+            .iter()
+            .take(24)
+            .map(|line| {
+                assert_eq!(strip_ansi_codes(line).chars().count(), 43);
+                ansi::explain_ansi(line.rsplit(' ').next().unwrap(), false)
+            })
+            .collect();
+        for cell in &cells[0..3] {
+            assert_eq!(cell, "(dim normal)│");
+        }
+        // Ties are resolved in favor of removed lines.
+        for cell in &cells[3..14] {
+            assert_eq!(cell, "(124)█");
+        }
+        for cell in &cells[14..24] {
+            assert_eq!(cell, "(28)█");
+        }
+        // The background color of removed and added lines extends to the minimap.
+        assert_eq!(
+            ansi::explain_ansi(lines[7], false),
+            format!("(normal 52)a0(normal 52){}(normal) (124)█", " ".repeat(39))
+        );
+        // Lines below the minimap are not changed.
+        assert_eq!(
+            lines[24],
+            "\x1b[48;5;52ma17\x1b[0m\x1b[48;5;52m\x1b[0K\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_minimap_past_buffer_limit() {
+        let mut input = String::from("--- a.txt\n+++ b.txt\n@@ -1,20 +1,20 @@\n");
+        for i in 0..20 {
+            input.push_str(&format!("-a{i}\n+b{i}\n"));
+        }
+        let with_minimap = DeltaTest::with_args(&["--minimap"])
+            .with_input(&input)
+            .raw_output;
+        assert!(with_minimap.contains('█'));
+        // The diff is written without the minimap once the limit is exceeded.
+        let output = DeltaTest::with_args(&["--minimap", "--buffer-limit-bytes=500"])
+            .with_input(&input)
+            .raw_output;
+        assert!(!output.contains('█'));
+        assert_eq!(output.lines().count(), with_minimap.lines().count());
+        assert!(output.ends_with("b19\x1b[0m\x1b[48;5;22m\x1b[0K\x1b[0m\n"));
+    }
+
+    #[test]
+    fn test_minimap_is_not_shown_by_default() {
+        let output = DeltaTest::with_args(&[])
+            .with_input(
+                "\
+--- a.txt
++++ b.txt
+@@ -1 +1 @@
+-a
++b
+",
+            )
+            .output;
+        assert!(!output.contains('█'));
+    }
+}
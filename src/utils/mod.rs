@@ -1,13 +1,30 @@
 #[cfg(not(tarpaulin_include))]
 pub mod bat;
+pub mod blame;
+pub mod buffer_limit;
+pub mod digest;
+pub mod encoding;
+pub mod format_command;
+pub mod function_context;
 pub mod git;
 pub mod helpwrap;
+pub mod highlighting_context;
+pub mod indent;
+pub mod line_diff;
+pub mod line_number_validator;
+pub mod minimap;
+pub mod moved_blocks;
+pub mod null_input;
 pub mod path;
 pub mod process;
 pub mod regex_replacement;
 pub mod round_char_boundary;
+pub mod sort_files;
 pub mod syntect;
 pub mod tabs;
+pub mod text_diff;
+pub mod trailing_context;
+pub mod whitespace;
 pub mod workarounds;
 
 // Use the most (even overly) strict ordering. Atomics are not used in hot loops so
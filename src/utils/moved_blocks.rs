@@ -0,0 +1,155 @@
+use crate::minusplus::{MinusPlus, MinusPlusIndex};
+
+// The number of alphanumeric characters a block must have to be considered moved, as for git's
+// --color-moved.
+const MIN_ALNUM_CHARS: usize = 20;
+
+/// A run of consecutive removed or added lines of a file, starting at line number `start`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    pub start: usize,
+    pub lines: Vec<String>,
+}
+
+impl Block {
+    /// The number of the last line of the block.
+    pub fn end(&self) -> usize {
+        self.start + self.lines.len() - 1
+    }
+}
+
+/// Collects the removed and added lines of a file, and finds the blocks which were moved within
+/// it (used by --show-moves-paired).
+#[derive(Default)]
+pub struct MovedBlocks {
+    blocks: MinusPlus<Vec<Block>>,
+}
+
+impl MovedBlocks {
+    /// Add a removed or added line, which has had its diff prefix removed.
+    pub fn push(&mut self, side: MinusPlusIndex, line_number: usize, line: &str) {
+        let blocks = &mut self.blocks[side];
+        match blocks.last_mut() {
+            Some(block) if block.end() + 1 == line_number => block.lines.push(line.to_string()),
+            _ => blocks.push(Block {
+                start: line_number,
+                lines: vec![line.to_string()],
+            }),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks[MinusPlusIndex::Minus].is_empty()
+            && self.blocks[MinusPlusIndex::Plus].is_empty()
+    }
+
+    /// Return each removed block which was added elsewhere with identical lines, paired with the
+    /// added block, and forget the lines collected so far.
+    pub fn take_moves(&mut self) -> Vec<(Block, Block)> {
+        let MinusPlus {
+            minus: removed,
+            plus: mut added,
+        } = std::mem::take(&mut self.blocks);
+        let mut moves = Vec::new();
+        for removed_block in removed {
+            let n_alnum_chars: usize = removed_block
+                .lines
+                .iter()
+                .map(|line| line.chars().filter(|c| c.is_alphanumeric()).count())
+                .sum();
+            if n_alnum_chars < MIN_ALNUM_CHARS {
+                continue;
+            }
+            if let Some(i) = added
+                .iter()
+                .position(|added_block| added_block.lines == removed_block.lines)
+            {
+                moves.push((removed_block, added.remove(i)));
+            }
+        }
+        moves
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::minusplus::MinusPlusIndex::{Minus, Plus};
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    #[test]
+    fn test_take_moves() {
+        let mut moved_blocks = MovedBlocks::default();
+        for (i, line) in ["fn moved_function() {", "    call_the_body();", "}"]
+            .iter()
+            .enumerate()
+        {
+            moved_blocks.push(Minus, 3 + i, line);
+            moved_blocks.push(Plus, 20 + i, line);
+        }
+        // Removed lines which were not added again, and a short block which was.
+        moved_blocks.push(Minus, 10, "gone();");
+        moved_blocks.push(Minus, 12, "x");
+        moved_blocks.push(Plus, 30, "x");
+        let moves = moved_blocks.take_moves();
+        assert_eq!(moves.len(), 1);
+        let (removed, added) = &moves[0];
+        assert_eq!((removed.start, removed.end()), (3, 5));
+        assert_eq!((added.start, added.end()), (20, 22));
+        assert_eq!(removed.lines, added.lines);
+        assert!(moved_blocks.is_empty());
+    }
+
+    #[test]
+    fn test_show_moves_paired() {
+        let input = "\
+diff --git a/a.rs b/a.rs
+index 223ca50..367a6f6 100644
+--- a/a.rs
++++ b/a.rs
+@@ -1,4 +1,1 @@
+-fn moved_function() {
+-    call_the_body();
+-}
+ fn stays() {}
+@@ -10,1 +7,4 @@
+ fn other() {}
++fn moved_function() {
++    call_the_body();
++}
+diff --git a/b.rs b/b.rs
+index 223ca50..367a6f6 100644
+--- a/b.rs
++++ b/b.rs
+@@ -1 +1 @@
+-a
++b
+";
+        let output = DeltaTest::with_args(&["--show-moves-paired", "--width=60"])
+            .with_input(input)
+            .output;
+        let lines: Vec<&str> = output.lines().collect();
+        let header = lines
+            .iter()
+            .position(|line| line.starts_with("moved, identical:"))
+            .unwrap();
+        assert_eq!(lines[header], "moved, identical: a.rs 1-3 ⟶  8-10");
+        assert_eq!(
+            &lines[header + 1..header + 4],
+            [
+                " 1 │ fn moved_function() {     8 │ fn moved_function() {",
+                " 2 │     call_the_body();      9 │     call_the_body();",
+                " 3 │ }                        10 │ }",
+            ]
+        );
+        // The paired view follows the hunks of its file.
+        assert_eq!(
+            lines[header - 3..header],
+            ["fn moved_function() {", "    call_the_body();", "}"]
+        );
+        assert!(lines[header + 4..].iter().any(|line| line.contains("b.rs")));
+
+        let output = DeltaTest::with_args(&[]).with_input(input).output;
+        assert!(!output.contains("moved, identical:"));
+    }
+}
@@ -0,0 +1,169 @@
+use lazy_static::lazy_static;
+use regex::bytes::Regex;
+
+// The status of a record of `git diff --name-status -z` or `git diff --raw -z` output, which is
+// followed by two paths for a rename or copy, and by one otherwise.
+lazy_static! {
+    static ref NULL_DELIMITED_STATUS_REGEX: Regex = Regex::new(
+        r"^(?::[0-7]{6} [0-7]{6} [0-9a-f]+(?:\.\.\.)? [0-9a-f]+(?:\.\.\.)? )?([ACDMRTUX])[0-9]*$"
+    )
+    .unwrap();
+}
+
+/// Whether the input is NUL-delimited (see --null-input).
+pub enum NullInput {
+    // No line has been read yet.
+    Undecided,
+    No,
+    Yes(NullDelimitedRecords),
+}
+
+impl NullInput {
+    /// Decide by the first line of the input, unless decided already: the input is NUL-delimited
+    /// if it starts with a NUL-terminated status of a record.
+    pub fn decide(&mut self, first_line: &[u8]) {
+        if let NullInput::Undecided = self {
+            let starts_with_record = first_line
+                .split(|&byte| byte == b'\0')
+                .next()
+                .filter(|_| first_line.contains(&b'\0'))
+                .is_some_and(|field| NULL_DELIMITED_STATUS_REGEX.is_match(field));
+            *self = if starts_with_record {
+                NullInput::Yes(NullDelimitedRecords::default())
+            } else {
+                NullInput::No
+            };
+        }
+    }
+}
+
+/// Collects the NUL-terminated fields of the input, and turns each record of a status followed by
+/// its paths into a line in which they are separated by tabs, as in the output of git without -z.
+#[derive(Default)]
+pub struct NullDelimitedRecords {
+    // Whether a line has been added.
+    started: bool,
+    // The bytes read since the last NUL.
+    partial_field: Vec<u8>,
+    // The fields of the current record.
+    fields: Vec<Vec<u8>>,
+}
+
+impl NullDelimitedRecords {
+    /// Add a line of input, which was newline-terminated, and return the lines of the records it
+    /// completes.
+    pub fn push(&mut self, line: &[u8]) -> Vec<Vec<u8>> {
+        if self.started {
+            // The newline ending the previous line is part of a field.
+            self.partial_field.push(b'\n');
+        }
+        self.started = true;
+        let mut lines = Vec::new();
+        let mut fields = line.split(|&byte| byte == b'\0').peekable();
+        while let Some(field) = fields.next() {
+            self.partial_field.extend_from_slice(field);
+            if fields.peek().is_some() {
+                self.fields.push(std::mem::take(&mut self.partial_field));
+                lines.extend(self.take_record());
+            }
+        }
+        lines
+    }
+
+    /// Return the line of the fields of an incomplete record at the end of the input, if any.
+    pub fn finish(&mut self) -> Option<Vec<u8>> {
+        if !self.partial_field.is_empty() {
+            self.fields.push(std::mem::take(&mut self.partial_field));
+        }
+        (!self.fields.is_empty()).then(|| std::mem::take(&mut self.fields).join(&b'\t'))
+    }
+
+    // Take the line of the current record if it is complete. A field which is not a status is a
+    // line by itself.
+    fn take_record(&mut self) -> Option<Vec<u8>> {
+        let n_paths = match NULL_DELIMITED_STATUS_REGEX.captures(&self.fields[0]) {
+            Some(caps) if matches!(&caps[1], b"R" | b"C") => 2,
+            Some(_) => 1,
+            None => 0,
+        };
+        (self.fields.len() > n_paths).then(|| std::mem::take(&mut self.fields).join(&b'\t'))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::DeltaTest;
+
+    fn push_all(input: &[u8]) -> Vec<String> {
+        let mut records = NullDelimitedRecords::default();
+        let mut lines: Vec<Vec<u8>> = input
+            .split(|&byte| byte == b'\n')
+            .flat_map(|line| records.push(line))
+            .collect();
+        lines.extend(records.finish());
+        lines
+            .into_iter()
+            .map(|line| String::from_utf8(line).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_null_delimited_records() {
+        assert_eq!(
+            push_all(b"M\0src/a.rs\0R097\0old.rs\0new\nname.rs\0A\0b.rs\0"),
+            ["M\tsrc/a.rs", "R097\told.rs\tnew\nname.rs", "A\tb.rs"]
+        );
+        assert_eq!(
+            push_all(b":100644 100644 bcd1234 0123456 M\0src/a.rs\0D\0gone"),
+            [":100644 100644 bcd1234 0123456 M\tsrc/a.rs", "D\tgone"]
+        );
+    }
+
+    #[test]
+    fn test_null_input_is_decided_by_first_line() {
+        for (first_line, expected) in [
+            (&b"M\0src/a.rs\0"[..], true),
+            (b":100644 100644 bcd1234 0123456 M\0src/a.rs\0", true),
+            (b"M\tsrc/a.rs", false),
+            (b"diff --git a/a\0 b/a", false),
+        ] {
+            let mut null_input = NullInput::Undecided;
+            null_input.decide(first_line);
+            assert_eq!(
+                matches!(null_input, NullInput::Yes(_)),
+                expected,
+                "{first_line:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_null_input_name_status() {
+        let input = "M\0src/delta.rs\0R097\0src/old.rs\0src/new\nname.rs\0A\0src/new.rs\0";
+        DeltaTest::with_args(&["--status-map=M=modified:yellow, R=renamed"])
+            .explain_ansi()
+            .with_input(input)
+            .expect_after_skip(
+                0,
+                "
+(yellow)modified(normal)\tsrc/delta.rs
+renamed (97%)\tsrc/old.rs\tsrc/new
+name.rs
+A\tsrc/new.rs
+",
+            );
+    }
+
+    #[test]
+    fn test_null_input_option() {
+        // Without the option, input which does not start with a record is left as it is.
+        let input = "src/a.rs\0src/b.rs\0";
+        let output = DeltaTest::with_args(&[]).with_input(input).output;
+        assert_eq!(output, "src/a.rs\0src/b.rs\0\n");
+        let output = DeltaTest::with_args(&["--null-input"])
+            .with_input(input)
+            .output;
+        assert_eq!(output, "src/a.rs\nsrc/b.rs\n");
+    }
+}
@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::path::{Component, Path, PathBuf};
 
 use crate::config::Config;
@@ -11,8 +12,16 @@ pub fn absolute_path(relative_path: &str, config: &Config) -> Option<PathBuf> {
         &config.cwd_of_user_shell_process,
         calling_process().paths_in_input_are_relative_to_cwd() || config.relative_paths,
     ) {
-        // Note that if we were invoked by git then cwd_of_delta_process == repo_root
-        (Some(cwd_of_delta_process), _, false) => Some(cwd_of_delta_process.join(relative_path)),
+        // Paths in the output of git are relative to the repo root. Note that if we were invoked
+        // by git then cwd_of_delta_process == repo_root, but not if git output was piped to delta.
+        // Paths in two-file mode and in the output of `diff -u` are relative to the cwd.
+        (Some(cwd_of_delta_process), _, false) => Some(
+            match &config.repo_root {
+                Some(repo_root) if calling_process().is_git() => repo_root,
+                _ => cwd_of_delta_process,
+            }
+            .join(relative_path),
+        ),
         (_, Some(cwd_of_user_shell_process), true) => {
             Some(cwd_of_user_shell_process.join(relative_path))
         }
@@ -47,6 +56,30 @@ pub fn relativize_path_maybe(path: &mut String, config: &Config) {
     }
 }
 
+/// Shorten each directory of `path` to its first character (two for a hidden directory), keeping
+/// the file name whole (used with --path-compress-dirs).
+pub fn compress_dirs(path: &str) -> Cow<'_, str> {
+    let Some((dirs, file_name)) = path.rsplit_once('/') else {
+        return Cow::from(path);
+    };
+    let mut compressed: Vec<&str> = dirs
+        .split('/')
+        .map(|dir| match dir {
+            "." | ".." => dir,
+            _ => {
+                let n_chars = if dir.starts_with('.') { 2 } else { 1 };
+                let end = dir
+                    .char_indices()
+                    .nth(n_chars)
+                    .map_or(dir.len(), |(i, _)| i);
+                &dir[..end]
+            }
+        })
+        .collect();
+    compressed.push(file_name);
+    Cow::from(compressed.join("/"))
+}
+
 /// Return current working directory of the user's shell process. I.e. the directory which they are
 /// in when delta exits. This is the directory relative to which the file paths in delta output are
 /// constructed if they are using either (a) delta's relative-paths option or (b) git's --relative
@@ -116,3 +149,64 @@ pub fn fake_delta_cwd_for_tests() -> PathBuf {
         PathBuf::from(r"C:\fake\delta\cwd")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::env::DeltaEnv;
+    use crate::tests::integration_test_utils::{
+        make_config_from_args, make_options_from_args_and_git_config_with_custom_env,
+    };
+    use crate::utils::process::tests::FakeParentArgs;
+
+    // Make a config as if delta was run in the fake cwd, inside the repo at `repo_root`.
+    fn make_config_in_repo(repo_root: &Path) -> Config {
+        Config::from(make_options_from_args_and_git_config_with_custom_env(
+            DeltaEnv {
+                repo_root: Some(repo_root.to_path_buf()),
+                ..DeltaEnv::default()
+            },
+            &[],
+            None,
+            None,
+        ))
+    }
+
+    #[test]
+    fn test_absolute_path_is_relative_to_repo_root() {
+        let _args = FakeParentArgs::for_scope("git diff");
+        let config = make_config_from_args(&[]);
+        assert_eq!(
+            absolute_path("src/a.rs", &config),
+            Some(fake_delta_cwd_for_tests().join("src/a.rs"))
+        );
+        let config = make_config_in_repo(&fake_delta_cwd_for_tests().join("../repo"));
+        assert_eq!(
+            absolute_path("src/a.rs", &config),
+            Some(normalize_path(
+                fake_delta_cwd_for_tests().join("../repo/src/a.rs")
+            ))
+        );
+    }
+
+    #[test]
+    fn test_absolute_path_is_relative_to_cwd_outside_git() {
+        let config = make_config_in_repo(&fake_delta_cwd_for_tests().join(".."));
+        // Two-file mode run from a subdirectory of the repo, and the output of `diff -u`.
+        for parent in ["delta a.rs b.rs", "diff -u a.rs b.rs"] {
+            let _args = FakeParentArgs::for_scope(parent);
+            assert_eq!(
+                absolute_path("b.rs", &config),
+                Some(fake_delta_cwd_for_tests().join("b.rs"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_compress_dirs() {
+        assert_eq!(compress_dirs("src/handlers/foo/bar.rs"), "s/h/f/bar.rs");
+        assert_eq!(compress_dirs("bar.rs"), "bar.rs");
+        assert_eq!(compress_dirs("/usr/lib/bar.rs"), "/u/l/bar.rs");
+        assert_eq!(compress_dirs("../.config/ünï/bar.rs"), "../.c/ü/bar.rs");
+    }
+}
@@ -42,12 +42,27 @@ impl CallingProcess {
             _ => false,
         }
     }
+
+    /// Whether the calling process is a git command, as opposed to e.g. `diff` or a grep tool.
+    pub fn is_git(&self) -> bool {
+        matches!(
+            self,
+            CallingProcess::GitDiff(_)
+                | CallingProcess::GitShow(_, _)
+                | CallingProcess::GitLog(_)
+                | CallingProcess::GitReflog(_)
+                | CallingProcess::GitBlame(_)
+                | CallingProcess::GitGrep(_)
+        )
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CommandLine {
     pub long_options: HashSet<String>,
     pub short_options: HashSet<String>,
+    // The arguments which are not options, e.g. the revisions and paths of `git diff`.
+    pub args: Vec<String>,
     pub last_arg: Option<String>,
 }
 
@@ -128,6 +143,32 @@ fn determine_calling_process() -> CallingProcess {
         .unwrap_or(CallingProcess::None)
 }
 
+/// Whether delta was started by another delta process (used by --guard-nested).
+pub fn is_nested_delta() -> bool {
+    has_delta_parent(ProcInfo::new())
+}
+
+fn has_delta_parent<P>(mut info: P) -> bool
+where
+    P: ProcessInterface,
+{
+    let mut pid = info.my_pid();
+    // Wrappers such as sudo may be between the two delta processes.
+    while let Some(parent) = info.parent_process(pid) {
+        let name = parent
+            .cmd()
+            .first()
+            .and_then(|cmd| Path::new(cmd).file_stem())
+            .and_then(|name| name.to_str());
+        match name {
+            Some("delta") => return true,
+            Some("sudo") | Some("doas") => pid = parent.pid(),
+            _ => return false,
+        }
+    }
+    false
+}
+
 // Return value of `extract_args(args: &[String]) -> ProcessArgs<T>` function which is
 // passed to `calling_process_cmdline()`.
 #[derive(Debug, PartialEq, Eq)]
@@ -236,11 +277,13 @@ fn is_git_binary(git: &str) -> bool {
 fn parse_command_line<'a>(args: impl Iterator<Item = &'a str>) -> CommandLine {
     let mut long_options = HashSet::new();
     let mut short_options = HashSet::new();
+    let mut non_option_args = Vec::new();
     let mut last_arg = None;
     let mut after_double_dash = false;
 
     for s in args {
         if after_double_dash {
+            non_option_args.push(s.to_string());
             last_arg = Some(s);
         } else if s == "--" {
             after_double_dash = true;
@@ -249,6 +292,7 @@ fn parse_command_line<'a>(args: impl Iterator<Item = &'a str>) -> CommandLine {
         } else if let Some(suffix) = s.strip_prefix('-') {
             short_options.extend(suffix.chars().map(|c| format!("-{c}")));
         } else {
+            non_option_args.push(s.to_string());
             last_arg = Some(s);
         }
     }
@@ -256,6 +300,7 @@ fn parse_command_line<'a>(args: impl Iterator<Item = &'a str>) -> CommandLine {
     CommandLine {
         long_options,
         short_options,
+        args: non_option_args,
         last_arg: last_arg.map(|s| s.to_string()),
     }
 }
@@ -759,6 +804,31 @@ pub mod tests {
         arg1.iter().map(|&s| s.to_owned()).collect()
     }
 
+    #[test]
+    fn test_has_delta_parent() {
+        assert!(has_delta_parent(MockProcInfo::with(&[
+            (2, 100, "/usr/bin/delta --side-by-side", Some(1)),
+            (3, 100, "delta", Some(2)),
+        ])));
+        assert!(has_delta_parent(MockProcInfo::with(&[
+            (2, 100, "delta", Some(1)),
+            (3, 100, "sudo delta", Some(2)),
+            (4, 100, "delta", Some(3)),
+        ])));
+        assert!(!has_delta_parent(MockProcInfo::with(&[
+            (2, 100, "git diff", Some(1)),
+            (3, 100, "delta", Some(2)),
+        ])));
+        assert!(!has_delta_parent(MockProcInfo::with(&[
+            (2, 100, "delta", Some(1)),
+            (3, 100, "git diff", Some(2)),
+            (4, 100, "delta", Some(3)),
+        ])));
+        assert!(!has_delta_parent(MockProcInfo::with(&[(
+            3, 100, "delta", None
+        )])));
+    }
+
     #[test]
     fn test_process_testing() {
         {
@@ -768,6 +838,7 @@ pub mod tests {
                 Some(CallingProcess::GitBlame(CommandLine {
                     long_options: [].into(),
                     short_options: [].into(),
+                    args: vec!["hello".into()],
                     last_arg: Some("hello".into())
                 }))
             );
@@ -779,6 +850,7 @@ pub mod tests {
                 Some(CallingProcess::GitBlame(CommandLine {
                     long_options: [].into(),
                     short_options: [].into(),
+                    args: vec!["world.txt".into()],
                     last_arg: Some("world.txt".into())
                 }))
             );
@@ -790,6 +862,7 @@ pub mod tests {
                 Some(CallingProcess::GitBlame(CommandLine {
                     long_options: [].into(),
                     short_options: [].into(),
+                    args: vec!["hello".into(), "world.txt".into()],
                     last_arg: Some("world.txt".into())
                 }))
             );
@@ -805,6 +878,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec!["do.not.panic".into()],
                 last_arg: Some("do.not.panic".into())
             }))
         );
@@ -844,6 +918,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec!["once".into()],
                 last_arg: Some("once".into())
             }))
         );
@@ -853,6 +928,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec!["twice".into()],
                 last_arg: Some("twice".into())
             }))
         );
@@ -873,6 +949,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec!["once".into()],
                 last_arg: Some("once".into())
             }))
         );
@@ -887,6 +964,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec!["once".into()],
                 last_arg: Some("once".into())
             }))
         );
@@ -915,6 +993,7 @@ pub mod tests {
         let no_options_command_line = CommandLine {
             long_options: [].into(),
             short_options: [].into(),
+            args: vec!["hello.txt".into()],
             last_arg: Some("hello.txt".to_string()),
         };
         let parent = MockProcInfo::with(&[
@@ -947,6 +1026,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec!["--not.an.argument".into()],
                 last_arg: Some("--not.an.argument".to_string()),
             }))
         );
@@ -961,6 +1041,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: ["--help.txt".into()].into(),
                 short_options: [].into(),
+                args: vec![],
                 last_arg: None,
             }))
         );
@@ -975,6 +1056,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec![],
                 last_arg: None,
             }))
         );
@@ -997,6 +1079,7 @@ pub mod tests {
         let expected_result = Some(CallingProcess::GitBlame(CommandLine {
             long_options: set(&["--incremental", "--color-by-age", "--since", "--contents"]),
             short_options: set(&["-f", "-n", "-b", "-t", "-M", "-C", "-2"]),
+            args: vec!["annotation.txt".into(), "hello.txt".into()],
             last_arg: Some("hello.txt".to_string()),
         }));
 
@@ -1032,6 +1115,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec!["src/main.rs".into()],
                 last_arg: Some("src/main.rs".into())
             }))
         );
@@ -1054,6 +1138,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: set(&["--correct"]),
                 short_options: [].into(),
+                args: vec!["src/main.abc".into()],
                 last_arg: Some("src/main.abc".into())
             }))
         );
@@ -1071,6 +1156,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec!["src/main.def".into()],
                 last_arg: Some("src/main.def".into())
             }))
         );
@@ -1094,6 +1180,7 @@ pub mod tests {
             Some(CallingProcess::GitBlame(CommandLine {
                 long_options: [].into(),
                 short_options: [].into(),
+                args: vec!["src/main.this".into()],
                 last_arg: Some("src/main.this".into())
             }))
         );
@@ -1110,6 +1197,7 @@ pub mod tests {
         let empty_command_line = CommandLine {
             long_options: [].into(),
             short_options: [].into(),
+            args: vec!["pattern".into(), "hello.txt".into()],
             last_arg: Some("hello.txt".to_string()),
         };
         let parent = MockProcInfo::with(&[
@@ -1155,6 +1243,7 @@ pub mod tests {
         let expected_result = Some(CallingProcess::GitGrep(CommandLine {
             long_options: set(&["--function-context", "--show-function", "--foo"]),
             short_options: set(&["-a", "-b", "-n", "-W"]),
+            args: vec!["pattern".into(), "hello.txt".into()],
             last_arg: Some("hello.txt".to_string()),
         }));
 
@@ -0,0 +1,152 @@
+use std::cmp::Reverse;
+
+use crate::ansi;
+use crate::config::{Config, SortFiles};
+use crate::handlers::file_category::get_file_category;
+use crate::handlers::toc::get_changed_files;
+
+/// Return the input with the files of each commit in the order given by --sort-files, grouped by
+/// category with --group-by-category. A file starts with a `diff` line, and runs until the next
+/// file or commit; the lines which are not part of a file keep their place.
+pub fn sort_files(input: &[u8], config: &Config) -> Vec<u8> {
+    let mut output = Vec::with_capacity(input.len());
+    // The files since the last line which is not part of a file.
+    let mut files = Vec::new();
+    let mut file_start = None;
+    let mut offset = 0;
+    for line in input.split_inclusive(|&byte| byte == b'\n') {
+        let text = ansi::strip_ansi_codes(&String::from_utf8_lossy(line));
+        let text = text.trim_end_matches('\n');
+        let is_diff_line = text.starts_with("diff ");
+        if is_diff_line || config.commit_regex.is_match(text) {
+            if let Some(start) = file_start.take() {
+                files.push(&input[start..offset]);
+            }
+            if is_diff_line {
+                file_start = Some(offset);
+            } else {
+                write_sorted_files(&mut output, &mut files, config);
+            }
+        }
+        if file_start.is_none() {
+            output.extend_from_slice(line);
+        }
+        offset += line.len();
+    }
+    if let Some(start) = file_start {
+        files.push(&input[start..]);
+    }
+    write_sorted_files(&mut output, &mut files, config);
+    output
+}
+
+fn write_sorted_files(output: &mut Vec<u8>, files: &mut Vec<&[u8]>, config: &Config) {
+    match config.sort_files {
+        SortFiles::Path => files.sort_by_cached_key(|file| get_changed_files(file).pop()),
+        SortFiles::Changes => files.sort_by_cached_key(|file| Reverse(count_changed_lines(file))),
+        SortFiles::None => {}
+    }
+    if config.group_by_category {
+        // The sort is stable, so that the files of each category keep the order given above.
+        files.sort_by_cached_key(|file| get_file_category(file));
+    }
+    for file in files.drain(..) {
+        output.extend_from_slice(file);
+    }
+}
+
+// Return the number of removed and added lines in the hunks of a file.
+fn count_changed_lines(file: &[u8]) -> usize {
+    String::from_utf8_lossy(file)
+        .lines()
+        .map(ansi::strip_ansi_codes)
+        .skip_while(|line| !line.starts_with("@@"))
+        .filter(|line| line.starts_with('-') || line.starts_with('+'))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::integration_test_utils::{make_config_from_args, DeltaTest};
+
+    fn make_file(path: &str, n_changes: usize) -> String {
+        let mut file = format!(
+            "diff --git a/{path} b/{path}\nindex 1111111..2222222 100644\n--- a/{path}\n+++ b/{path}\n@@ -1,{n_changes} +0,0 @@\n"
+        );
+        for _ in 0..n_changes {
+            file.push_str("-removed\n");
+        }
+        file
+    }
+
+    fn sorted_files(args: &[&str], input: &str) -> Vec<String> {
+        let config = make_config_from_args(args);
+        let output = sort_files(input.as_bytes(), &config);
+        get_changed_files(&output)
+    }
+
+    #[test]
+    fn test_sort_files() {
+        let input = [
+            make_file("b.txt", 1),
+            make_file("a.txt", 3),
+            make_file("c.txt", 2),
+        ]
+        .concat();
+        assert_eq!(
+            sorted_files(&["--sort-files=path"], &input),
+            ["a.txt", "b.txt", "c.txt"]
+        );
+        assert_eq!(
+            sorted_files(&["--sort-files=changes"], &input),
+            ["a.txt", "c.txt", "b.txt"]
+        );
+        // Files with as many changes keep their order.
+        let input = [make_file("b.txt", 1), make_file("a.txt", 1)].concat();
+        assert_eq!(
+            sorted_files(&["--sort-files=changes"], &input),
+            ["b.txt", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn test_sort_files_of_each_commit() {
+        let input = [
+            "commit 1111111111111111111111111111111111111111\nAuthor: A <a@example.com>\n\n    one\n\n",
+            &make_file("d.txt", 1),
+            &make_file("c.txt", 1),
+            "commit 2222222222222222222222222222222222222222\nAuthor: A <a@example.com>\n\n    two\n\n",
+            &make_file("b.txt", 1),
+            &make_file("a.txt", 1),
+        ]
+        .concat();
+        let config = make_config_from_args(&["--sort-files=path"]);
+        let output = String::from_utf8(sort_files(input.as_bytes(), &config)).unwrap();
+        let expected = [
+            "commit 1111111111111111111111111111111111111111\nAuthor: A <a@example.com>\n\n    one\n\n",
+            &make_file("c.txt", 1),
+            &make_file("d.txt", 1),
+            "commit 2222222222222222222222222222222222222222\nAuthor: A <a@example.com>\n\n    two\n\n",
+            &make_file("a.txt", 1),
+            &make_file("b.txt", 1),
+        ]
+        .concat();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_sort_files_output() {
+        let input = [make_file("b.txt", 1), make_file("a.txt", 2)].concat();
+        let file_positions = |args: &[&str]| {
+            let output = DeltaTest::with_args(args).with_input(&input).output;
+            (output.find("a.txt").unwrap(), output.find("b.txt").unwrap())
+        };
+        let (a, b) = file_positions(&["--sort-files=none"]);
+        assert!(b < a);
+        let (a, b) = file_positions(&["--sort-files=path"]);
+        assert!(a < b);
+        let (a, b) = file_positions(&["--sort-files=changes"]);
+        assert!(a < b);
+    }
+}
@@ -17,6 +17,17 @@ impl TabCfg {
     pub fn replace(&self) -> bool {
         !self.replacement.is_empty()
     }
+
+    /// The tab expansion of text whose width must be known to align it, such as the text of file
+    /// and hunk headers and of the line-number gutter: tabs are expanded as in the diff content, or
+    /// as a single space if they are passed through.
+    pub fn for_decorations(&self) -> Self {
+        if self.replace() {
+            self.clone()
+        } else {
+            TabCfg::new(1)
+        }
+    }
 }
 
 /// Expand tabs as spaces.
@@ -61,4 +72,14 @@ pub mod tests {
         // ensure non-ascii chars were removed:
         assert!(utf8_prefix.len() - result.len() > n);
     }
+
+    #[test]
+    fn test_expand_for_decorations() {
+        let line = "fn\tfoo(";
+        assert_eq!(
+            expand(line, &TabCfg::new(4).for_decorations()),
+            "fn    foo("
+        );
+        assert_eq!(expand(line, &TabCfg::new(0).for_decorations()), "fn foo(");
+    }
 }
@@ -0,0 +1,98 @@
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+
+/// A source of text diffs of files which git considers binary.
+pub trait TextDiffSource {
+    /// Return the diff, treating their contents as text, of the blobs with the given (possibly
+    /// abbreviated) ids, or None if it cannot be computed.
+    fn text_diff(&mut self, minus_blob_id: &str, plus_blob_id: &str) -> Option<String>;
+}
+
+/// Diff blobs by running `git diff --text`.
+pub struct GitTextDiff;
+
+impl TextDiffSource for GitTextDiff {
+    fn text_diff(&mut self, minus_blob_id: &str, plus_blob_id: &str) -> Option<String> {
+        let git_path = grep_cli::resolve_binary("git").ok()?;
+        let output = Command::new(git_path)
+            .args(["diff", "--text", "--no-color", "--no-ext-diff"])
+            .args([minus_blob_id, plus_blob_id])
+            .stderr(Stdio::null())
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Return a source of text diffs using git, if requested and delta is running in a repository.
+pub fn from_config(config: &Config) -> Option<Box<dyn TextDiffSource>> {
+    if !config.force_text {
+        return None;
+    }
+    git2::Repository::discover(config.cwd_of_delta_process.as_ref()?).ok()?;
+    Some(Box::new(GitTextDiff))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::{
+        make_config_from_args, run_delta_with_machine, StubTextDiff,
+    };
+
+    const DIFF: &str = "\
+diff --git a/data.txt b/data.txt
+index 1111111..2222222 100644
+Binary files a/data.txt and b/data.txt differ
+diff --git a/image.png b/image.png
+index 3333333..4444444 100644
+Binary files a/image.png and b/image.png differ
+";
+
+    fn run(args: &[&str]) -> String {
+        let config = make_config_from_args(args);
+        let output = run_delta_with_machine(DIFF, &config, |machine| {
+            if config.force_text {
+                machine.text_diff_source = Some(Box::new(StubTextDiff {
+                    blob_ids: ("1111111", "2222222"),
+                    text_diff: "\
+diff --git a/1111111 b/2222222
+index 1111111..2222222 100644
+--- a/1111111
++++ b/2222222
+@@ -1,2 +1,2 @@
+ a\x00
+-b
++c
+",
+                }));
+            }
+        });
+        strip_ansi_codes(&output)
+    }
+
+    #[test]
+    fn test_force_text() {
+        let output = run(&["--force-text"]);
+        let lines: Vec<&str> = output.lines().collect();
+        // The hunks of the text diff are shown under the header of the file.
+        assert!(lines.contains(&"data.txt"));
+        let hunk = lines.iter().position(|line| *line == "b").unwrap();
+        assert_eq!(lines[hunk - 1], "a\x00");
+        assert_eq!(lines[hunk + 1], "c");
+        assert!(!output.contains("data.txt (binary file)"));
+        // A file whose text diff cannot be computed is shown as binary.
+        assert!(output.contains("image.png (binary file)"));
+    }
+
+    #[test]
+    fn test_force_text_is_off_by_default() {
+        let output = run(&[]);
+        assert!(output.contains("data.txt (binary file)"));
+        assert!(output.contains("image.png (binary file)"));
+    }
+}
@@ -0,0 +1,124 @@
+use std::path::Path;
+
+use crate::config::Config;
+
+/// A source of the current contents of files.
+pub trait FileSource {
+    /// Return the lines of the file at `path`, or None if it cannot be read.
+    fn read_lines(&mut self, path: &Path) -> Option<Vec<String>>;
+}
+
+/// Read files from the working tree.
+pub struct WorkingTree;
+
+impl FileSource for WorkingTree {
+    fn read_lines(&mut self, path: &Path) -> Option<Vec<String>> {
+        let contents = std::fs::read(path).ok()?;
+        Some(
+            String::from_utf8_lossy(&contents)
+                .lines()
+                .map(str::to_string)
+                .collect(),
+        )
+    }
+}
+
+/// Supply the lines following the last hunk of a file (used by --show-trailing-context).
+pub struct TrailingContext {
+    source: Box<dyn FileSource>,
+    n_lines: usize,
+}
+
+impl TrailingContext {
+    pub fn new(source: Box<dyn FileSource>, n_lines: usize) -> Self {
+        Self { source, n_lines }
+    }
+
+    /// Return a reader of the working tree, if requested and delta is running in a repository.
+    pub fn from_config(config: &Config) -> Option<Self> {
+        if config.show_trailing_context == 0 {
+            return None;
+        }
+        git2::Repository::discover(config.cwd_of_delta_process.as_ref()?).ok()?;
+        Some(Self::new(
+            Box::new(WorkingTree),
+            config.show_trailing_context,
+        ))
+    }
+
+    /// Return the lines of `file` starting at `line_number`, at most as many as requested.
+    pub fn lines_from(&mut self, file: &Path, line_number: usize) -> Vec<String> {
+        let lines = self.source.read_lines(file).unwrap_or_default();
+        lines
+            .into_iter()
+            .skip(line_number.saturating_sub(1))
+            .take(self.n_lines)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ansi::strip_ansi_codes;
+    use crate::tests::integration_test_utils::{
+        make_config_from_args, run_delta_with_machine, StubFiles,
+    };
+
+    const DIFF: &str = "\
+diff --git a/a.txt b/a.txt
+index 1111111..2222222 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1,2 +1,2 @@
+ a
+-b
++B
+@@ -3,1 +3,1 @@
+-x
++c
+diff --git a/b.txt b/b.txt
+index 3333333..4444444 100644
+--- a/b.txt
++++ b/b.txt
+@@ -1,1 +1,1 @@
+-y
++Y
+";
+
+    fn run(args: &[&str]) -> Vec<String> {
+        let config = make_config_from_args(args);
+        let output = run_delta_with_machine(DIFF, &config, |machine| {
+            let files = StubFiles {
+                path: "a.txt",
+                lines: &["a", "B", "c", "d", "e", "f", "g"],
+            };
+            machine.trailing_context = Some(TrailingContext::new(Box::new(files), 2));
+        });
+        strip_ansi_codes(&output)
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_show_trailing_context() {
+        let lines = run(&["--show-trailing-context=2"]);
+        // The lines following the last hunk of a.txt are appended to it.
+        let c = lines.iter().position(|line| line == "c").unwrap();
+        assert_eq!(lines[c - 1], "x");
+        assert_eq!(lines[c + 1..c + 3], ["d", "e"]);
+        assert_eq!(lines[c + 3], "");
+        assert!(lines[c + 4].starts_with("b.txt"));
+        // b.txt cannot be read, so nothing is appended to it.
+        assert_eq!(lines.last().unwrap(), "Y");
+        assert!(!lines.contains(&"f".to_string()));
+    }
+
+    #[test]
+    fn test_show_trailing_context_line_numbers() {
+        let lines = run(&["--show-trailing-context=2", "--line-numbers"]);
+        assert!(lines.iter().any(|line| line.ends_with("4 │d")));
+        assert!(lines.iter().any(|line| line.ends_with("5 │e")));
+    }
+}
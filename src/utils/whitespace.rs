@@ -0,0 +1,87 @@
+use std::ops::Range;
+
+use crate::utils::tabs::TabCfg;
+
+// The number of spaces which git's indent-with-non-tab rule takes could be a tab.
+const GIT_TAB_WIDTH: usize = 8;
+
+/// Return the byte ranges of the whitespace errors of `line`, which has had its diff prefix
+/// removed but not its tabs expanded, as `git diff --check` reports them (used with --check). The
+/// ranges are those of the line once its tabs are expanded according to `tab_cfg`.
+pub fn whitespace_errors(line: &str, tab_cfg: &TabCfg) -> Vec<Range<usize>> {
+    let line = line.trim_end_matches('\n');
+    let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+    let mut errors = Vec::new();
+    // space-before-tab: spaces in the indentation which are followed by a tab.
+    if let (Some(space), Some(tab)) = (indent.find(' '), indent.rfind('\t')) {
+        if space < tab {
+            errors.push(space..tab + 1);
+        }
+    }
+    // indent-with-non-tab: spaces ending the indentation which could be a tab.
+    let spaces_start = indent.rfind('\t').map_or(0, |tab| tab + 1);
+    if indent.len() - spaces_start >= GIT_TAB_WIDTH {
+        errors.push(spaces_start..indent.len());
+    }
+    // blank-at-eol: trailing whitespace.
+    let content_end = line
+        .trim_end_matches(|c: char| c.is_ascii_whitespace())
+        .len();
+    if content_end < line.len() {
+        errors.push(content_end..line.len());
+    }
+
+    errors.sort_by_key(|range| range.start);
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in errors {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    // Each tab before an offset becomes tab_cfg.width() bytes.
+    let expand = |offset: usize| {
+        if tab_cfg.replace() {
+            offset + line[..offset].matches('\t').count() * (tab_cfg.width() - 1)
+        } else {
+            offset
+        }
+    };
+    merged
+        .into_iter()
+        .map(|range| expand(range.start)..expand(range.end))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whitespace_errors() {
+        let no_tabs = TabCfg::new(0);
+        assert!(whitespace_errors("\tfoo bar\n", &no_tabs).is_empty());
+        assert!(whitespace_errors("    foo\n", &no_tabs).is_empty());
+        // Trailing whitespace.
+        assert_eq!(whitespace_errors("foo \t \n", &no_tabs), vec![3..6]);
+        assert_eq!(whitespace_errors("  \n", &no_tabs), vec![0..2]);
+        // Space before tab.
+        assert_eq!(whitespace_errors("\t \tfoo\n", &no_tabs), vec![1..3]);
+        assert_eq!(whitespace_errors("  \t foo\n", &no_tabs), vec![0..3]);
+        // Indent with non-tab.
+        assert_eq!(whitespace_errors("        foo\n", &no_tabs), vec![0..8]);
+        assert_eq!(whitespace_errors("\t         foo\n", &no_tabs), vec![1..10]);
+        // All three, the first two of which overlap.
+        assert_eq!(
+            whitespace_errors(" \t        foo \n", &no_tabs),
+            vec![0..10, 13..14]
+        );
+    }
+
+    #[test]
+    fn test_whitespace_errors_with_expanded_tabs() {
+        let tab_cfg = TabCfg::new(4);
+        assert_eq!(whitespace_errors("\t \tfoo\n", &tab_cfg), vec![4..9]);
+        assert_eq!(whitespace_errors("\tfoo\t\n", &tab_cfg), vec![7..11]);
+    }
+}
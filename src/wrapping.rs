@@ -416,6 +416,7 @@ pub fn wrap_minusplus_block<'c: 'a, 'a>(
         wrapinfo_iter: &mut ItWrap,
         line_width: usize,
         fill_style: &Style,
+        symbol_style: &Style,
         errhint: &'a str,
     ) -> (usize, usize)
     where
@@ -436,20 +437,19 @@ pub fn wrap_minusplus_block<'c: 'a, 'a>(
             must_wrap,
             line_width,
             &config.null_syntect_style,
-            &Some(config.wrap_config.inline_hint_syntect_style),
+            &Some(SyntectStyle::from_delta_style(*symbol_style)),
         );
 
-        // TODO: Why is the background color set to white when
-        // ansi_term_style.background is None?
-        let inline_hint_style = if config
-            .inline_hint_style
-            .ansi_term_style
-            .background
-            .is_some()
-        {
-            Some(config.inline_hint_style)
-        } else {
-            None
+        // The symbols are painted on the background of the line, unless their style has one.
+        let symbol_style = Style {
+            ansi_term_style: ansi_term::Style {
+                background: symbol_style
+                    .ansi_term_style
+                    .background
+                    .or(fill_style.ansi_term_style.background),
+                ..symbol_style.ansi_term_style
+            },
+            ..*symbol_style
         };
 
         let (start2, extended_to2) = wrap_if_too_long(
@@ -461,7 +461,7 @@ pub fn wrap_minusplus_block<'c: 'a, 'a>(
             must_wrap,
             line_width,
             fill_style,
-            &inline_hint_style,
+            &Some(symbol_style),
         );
 
         // The underlying text is the same for the style and diff, so
@@ -490,6 +490,7 @@ pub fn wrap_minusplus_block<'c: 'a, 'a>(
                 &mut wrapinfo[$side],
                 line_width[$side],
                 &fill_style[$side],
+                &config.wrap_symbol_style[$side],
                 $errhint,
             )
         }};
@@ -1206,4 +1207,47 @@ index 223ca50..e69de29 100644
                 );
         }
     }
+
+    #[test]
+    fn test_wrap_symbol_styles() {
+        let input = "\
+diff --git a/a.txt b/a.txt
+index 223ca50..367a6f6 100644
+--- a/a.txt
++++ b/a.txt
+@@ -1 +1 @@
+-aaaa bbbb cccc dddd eeee ffff gggg hhhh iiii
++aaaa bbbb cccc dddd eeee ffff gggg hhhh jjjj
+";
+        let args = [
+            "--side-by-side",
+            "--width=50",
+            "--wrap-left-symbol=@",
+            "--minus-style=red",
+            "--plus-style=green 22",
+        ];
+        // The symbols are painted on the background of the line.
+        DeltaTest::with_args(
+            &[
+                &args[..],
+                &[
+                    "--wrap-symbol-minus-style=yellow",
+                    "--wrap-symbol-plus-style=bold cyan",
+                ],
+            ]
+            .concat(),
+        )
+        .explain_ansi()
+        .with_input(input)
+        .expect_contains(
+            "(red)aaaa bbbb cccc ddd(yellow)@(blue)│(28)  1 (blue)│(green 22)aaaa bbbb cccc ddd(bold cyan)@(normal)",
+        );
+        // By default, both sides are painted in inline-hint-style.
+        DeltaTest::with_args(&[&args[..], &["--inline-hint-style=magenta"]].concat())
+            .explain_ansi()
+            .with_input(input)
+            .expect_contains(
+                "(red)aaaa bbbb cccc ddd(purple)@(blue)│(28)  1 (blue)│(green 22)aaaa bbbb cccc ddd(purple)@(normal)",
+            );
+    }
 }